@@ -0,0 +1,51 @@
+#!/bin/sh
+# Sets the desktop wallpaper to match the weather, for `weather hook`:
+#
+#   weather hook --script contrib/hooks/set-wallpaper.sh
+#
+# Images are looked up in $WALLPAPER_DIR (default ~/Pictures/weather) by
+# condition and time of day, falling back to the condition alone and then
+# to default.jpg:
+#
+#   rain-night.jpg  rain.jpg  clear-day.jpg  clear-night.jpg  default.jpg
+#
+# Conditions are clear, clouds, drizzle, rain, snow, thunderstorm, fog and
+# other.
+set -eu
+
+dir="${WALLPAPER_DIR:-$HOME/Pictures/weather}"
+
+image=""
+for name in "$WEATHER_CONDITION-$WEATHER_DAYTIME" "$WEATHER_CONDITION" default; do
+    for ext in jpg jpeg png; do
+        if [ -f "$dir/$name.$ext" ]; then
+            image="$dir/$name.$ext"
+            break 2
+        fi
+    done
+done
+if [ -z "$image" ]; then
+    echo "No wallpaper for $WEATHER_CONDITION in $dir" >&2
+    exit 1
+fi
+
+case "$(uname -s)" in
+Darwin)
+    osascript -e 'on run argv' \
+        -e 'tell application "System Events" to tell every desktop to set picture to (item 1 of argv)' \
+        -e 'end run' "$image"
+    ;;
+*)
+    if command -v gsettings >/dev/null 2>&1 && [ -n "${DBUS_SESSION_BUS_ADDRESS:-}" ]; then
+        gsettings set org.gnome.desktop.background picture-uri "file://$image"
+        gsettings set org.gnome.desktop.background picture-uri-dark "file://$image"
+    elif command -v swww >/dev/null 2>&1; then
+        swww img "$image"
+    elif command -v feh >/dev/null 2>&1; then
+        feh --bg-fill "$image"
+    else
+        echo "Found no way to set the wallpaper: install gsettings, swww or feh" >&2
+        exit 1
+    fi
+    ;;
+esac
@@ -0,0 +1,22 @@
+#!/bin/sh
+# Recolours every kitty window to suit the weather and the time of day, for
+# `weather hook`:
+#
+#   weather hook --script contrib/hooks/terminal-theme.sh
+#
+# Needs `allow_remote_control yes` (or `socket-only` with `listen_on`) in
+# kitty.conf. Themes are kitty colour files in $KITTY_THEME_DIR (default
+# ~/.config/kitty/weather), looked up like the wallpapers of
+# set-wallpaper.sh: e.g. rain-night.conf, then rain.conf, then night.conf
+# or day.conf.
+set -eu
+
+dir="${KITTY_THEME_DIR:-$HOME/.config/kitty/weather}"
+
+for name in "$WEATHER_CONDITION-$WEATHER_DAYTIME" "$WEATHER_CONDITION" "$WEATHER_DAYTIME"; do
+    if [ -f "$dir/$name.conf" ]; then
+        exec kitty @ set-colors --all --configured "$dir/$name.conf"
+    fi
+done
+echo "No kitty theme for $WEATHER_CONDITION ($WEATHER_DAYTIME) in $dir" >&2
+exit 1
@@ -0,0 +1,7 @@
+fn main() {
+    // Used by `weather self-update` to pick the release asset for this platform.
+    println!(
+        "cargo:rustc-env=TARGET={}",
+        std::env::var("TARGET").unwrap()
+    );
+}
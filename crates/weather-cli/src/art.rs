@@ -0,0 +1,154 @@
+//! `--art`: the current condition drawn in plain ASCII, as wttr.in does,
+//! beside the card. Each picture has a mask of the same shape giving the
+//! colour of each character, so the art stays aligned with or without
+//! colour and renders on terminals without emoji.
+
+use crate::render;
+use colored::Colorize;
+use weather_core::model::Conditions;
+
+/// Columns of every picture.
+pub const WIDTH: usize = 13;
+
+/// Columns between the picture and the card.
+const GAP: usize = 2;
+
+/// Five lines of art and their colour masks: `y` sun and lightning, `b`
+/// rain, `w` snow, `d` fog; anything else is left uncoloured.
+type Picture = [(&'static str, &'static str); 5];
+
+const SUNNY: Picture = [
+    ("    \\   /    ", "    y   y    "),
+    ("     .-.     ", "     yyy     "),
+    ("  - (   ) -  ", "  y y   y y  "),
+    ("     `-'     ", "     yyy     "),
+    ("    /   \\    ", "    y   y    "),
+];
+
+const MOON: Picture = [
+    ("      .--.   ", "      yyyy   "),
+    ("    .' .-'   ", "    yy yyy   "),
+    ("   (  (      ", "   y  y      "),
+    ("    '. '-.   ", "    yy yyy   "),
+    ("      '--'   ", "      yyyy   "),
+];
+
+const PARTLY_CLOUDY: Picture = [
+    ("   \\  /      ", "   y  y      "),
+    (" _ /\"\".-.    ", " y yyy       "),
+    ("   \\_(   ).  ", "   yy        "),
+    ("   /(___(__) ", "   y         "),
+    ("             ", "             "),
+];
+
+const CLOUDY: Picture = [
+    ("             ", "             "),
+    ("     .--.    ", "             "),
+    ("  .-(    ).  ", "             "),
+    (" (___.__)__) ", "             "),
+    ("             ", "             "),
+];
+
+const FOG: Picture = [
+    ("             ", "             "),
+    (" _ - _ - _ - ", " d d d d d d "),
+    ("  _ - _ - _  ", "  d d d d d  "),
+    (" _ - _ - _ - ", " d d d d d d "),
+    ("             ", "             "),
+];
+
+const DRIZZLE: Picture = [
+    ("     .-.     ", "             "),
+    ("    (   ).   ", "             "),
+    ("   (___(__)  ", "             "),
+    ("    ' ' ' '  ", "    b b b b  "),
+    ("   ' ' ' '   ", "   b b b b   "),
+];
+
+const RAIN: Picture = [
+    ("     .-.     ", "             "),
+    ("    (   ).   ", "             "),
+    ("   (___(__)  ", "             "),
+    ("  ,',',',',  ", "  bbbbbbbbb  "),
+    ("  ,',',',',  ", "  bbbbbbbbb  "),
+];
+
+const SNOW: Picture = [
+    ("     .-.     ", "             "),
+    ("    (   ).   ", "             "),
+    ("   (___(__)  ", "             "),
+    ("    *  *  *  ", "    w  w  w  "),
+    ("   *  *  *   ", "   w  w  w   "),
+];
+
+const THUNDERSTORM: Picture = [
+    ("     .-.     ", "             "),
+    ("    (   ).   ", "             "),
+    ("   (___(__)  ", "             "),
+    ("  ,'7',7,',  ", "  bbybbybbb  "),
+    ("  ,',/,'/',  ", "  bbbybbybb  "),
+];
+
+const UNKNOWN: Picture = [
+    ("    .-.      ", "             "),
+    ("     __)     ", "             "),
+    ("    (        ", "             "),
+    ("     `-'     ", "             "),
+    ("      *      ", "             "),
+];
+
+/// The picture of `current`'s condition: partly cloudy by day while the
+/// clouds cover half the sky or less.
+fn picture(current: &Conditions, night: bool) -> &'static Picture {
+    match current.condition.kind.as_str() {
+        "thunderstorm" => &THUNDERSTORM,
+        "drizzle" => &DRIZZLE,
+        "rain" => &RAIN,
+        "snow" => &SNOW,
+        "fog" => &FOG,
+        "clear" if night => &MOON,
+        "clear" => &SUNNY,
+        "clouds" if !night && current.cloud_cover_percent <= 50 => &PARTLY_CLOUDY,
+        "clouds" => &CLOUDY,
+        _ => &UNKNOWN,
+    }
+}
+
+/// The lines of the picture of `current`'s condition, coloured.
+pub fn draw(current: &Conditions, night: bool) -> Vec<String> {
+    picture(current, night)
+        .iter()
+        .map(|(line, mask)| {
+            line.chars()
+                .zip(mask.chars())
+                .map(|(c, colour)| {
+                    let c = c.to_string();
+                    match colour {
+                        'y' => c.bright_yellow().to_string(),
+                        'b' => c.bright_blue().to_string(),
+                        'w' => c.bright_white().bold().to_string(),
+                        'd' => c.dimmed().to_string(),
+                        _ => c,
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// `art` to the left of `rows`, level with the first line inside the
+/// card's border; above them when both do not fit in `columns`.
+pub fn beside(art: &[String], rows: &[String], columns: Option<usize>) -> Vec<String> {
+    let card_width = rows.first().map_or(0, |row| render::display_width(row));
+    if columns.is_some_and(|columns| WIDTH + GAP + card_width > columns) {
+        return art.iter().chain(rows).cloned().collect();
+    }
+    let blank = " ".repeat(WIDTH + GAP);
+    rows.iter()
+        .enumerate()
+        .map(|(i, row)| match i.checked_sub(1).and_then(|i| art.get(i)) {
+            Some(line) => format!("{}{}{}", line, " ".repeat(GAP), row),
+            None => format!("{}{}", blank, row),
+        })
+        .collect()
+}
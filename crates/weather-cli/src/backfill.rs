@@ -0,0 +1,147 @@
+//! `weather db backfill`: fills the observation log with hourly history from
+//! the Open-Meteo archive, a month per request.
+//!
+//! Progress is kept per city in the data directory after every stored month,
+//! so an interrupted run picks up where it stopped when started again with
+//! the same `--from`.
+
+use crate::{cache, platform, storage::Storage};
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use weather_providers::geocode::Place;
+use weather_providers::openmeteo::{self, ArchiveError};
+
+/// Days the archive trails behind today; later hours are still empty.
+pub const ARCHIVE_DELAY_DAYS: i64 = 6;
+
+/// Pause between requests, well inside the free tier's per-minute limit.
+const PAGE_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Waits after a rate-limit response, doubling each time; the run gives up
+/// after the last one.
+const BACKOFF_SECS: [u64; 4] = [60, 120, 240, 480];
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Progress {
+    from: NaiveDate,
+    /// Last day stored.
+    through: NaiveDate,
+}
+
+pub struct Backfill<'a> {
+    pub city: &'a str,
+    pub place: &'a Place,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// Ignore saved progress and start again at `from`.
+    pub restart: bool,
+}
+
+pub async fn run(
+    client: &reqwest::Client,
+    backfill: &Backfill<'_>,
+    storage: &dyn Storage,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let progress_path =
+        progress_file(backfill.city).ok_or("Cannot determine the data directory")?;
+    let mut start = backfill.from;
+    match read_progress(&progress_path) {
+        Some(progress) if !backfill.restart && progress.from == backfill.from => {
+            start = progress.through + Duration::days(1);
+            if start <= backfill.to {
+                println!("Resuming after {}", progress.through);
+            }
+        }
+        Some(progress) if !backfill.restart => println!(
+            "Earlier backfill from {} (through {}) ignored: --from differs",
+            progress.from, progress.through
+        ),
+        _ => {}
+    }
+    if start > backfill.to {
+        println!("Nothing to do: already stored through {}", backfill.to);
+        return Ok(());
+    }
+    println!(
+        "Backfilling {} from {} through {} into {}",
+        backfill.place.english_name(),
+        start,
+        backfill.to,
+        storage.describe()
+    );
+
+    let mut first = true;
+    while start <= backfill.to {
+        let end = month_end(start).min(backfill.to);
+        if !first {
+            tokio::time::sleep(PAGE_DELAY).await;
+        }
+        first = false;
+
+        let body = fetch_page(client, backfill.place, start, end).await?;
+        let archive = body.parse()?;
+        let reports = openmeteo::reports(backfill.place, &archive);
+        storage.append(&reports).await.map_err(|e| e.to_string())?;
+        write_progress(
+            &progress_path,
+            &Progress {
+                from: backfill.from,
+                through: end,
+            },
+        )?;
+        println!("{}: {} hours", start.format("%Y-%m"), reports.len());
+        start = end + Duration::days(1);
+    }
+    Ok(())
+}
+
+async fn fetch_page(
+    client: &reqwest::Client,
+    place: &Place,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<openmeteo::ArchiveBody, Box<dyn std::error::Error>> {
+    let mut backoff = BACKOFF_SECS.iter();
+    loop {
+        match openmeteo::archive(client, place.lat, place.lon, start, end).await {
+            Err(ArchiveError::RateLimited) => {
+                let secs = backoff.next().ok_or(
+                    "Open-Meteo keeps refusing requests; progress is saved, run again later",
+                )?;
+                eprintln!("Rate limited; retrying in {} s", secs);
+                tokio::time::sleep(std::time::Duration::from_secs(*secs)).await;
+            }
+            result => return Ok(result?),
+        }
+    }
+}
+
+/// The last day of the month `date` falls in.
+fn month_end(date: NaiveDate) -> NaiveDate {
+    let (year, month) = match date.month() {
+        12 => (date.year() + 1, 1),
+        month => (date.year(), month + 1),
+    };
+    NaiveDate::from_ymd_opt(year, month, 1).expect("first of a month is valid") - Duration::days(1)
+}
+
+fn progress_file(city: &str) -> Option<PathBuf> {
+    platform::dir(platform::Dir::Data).map(|dir| {
+        dir.join("backfill")
+            .join(format!("{}.json", cache::slug(city)))
+    })
+}
+
+fn read_progress(path: &Path) -> Option<Progress> {
+    let bytes = std::fs::read(path).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+fn write_progress(path: &Path, progress: &Progress) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, serde_json::to_vec(progress)?)?;
+    Ok(())
+}
@@ -0,0 +1,56 @@
+//! Audible cues for `--bell` and `--sound`, for terminals left open without
+//! a desktop notification daemon, e.g. over SSH, where the local terminal
+//! still rings the bell.
+
+use crate::speak::find_executable;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Plays the file in `WEATHER_SOUND`; the player only handles WAV.
+const WINDOWS_PLAYER: &str = "(New-Object Media.SoundPlayer $env:WEATHER_SOUND).PlaySync()";
+
+/// Sound players tried in order, with the arguments that precede the file.
+const PLAYERS: &[(&str, &[&str])] = &[
+    ("afplay", &[]),
+    ("paplay", &[]),
+    ("pw-play", &[]),
+    ("aplay", &["-q"]),
+    ("termux-media-player", &["play"]),
+    (
+        "powershell",
+        &["-NoProfile", "-NonInteractive", "-Command", WINDOWS_PLAYER],
+    ),
+];
+
+/// Rings the terminal bell. Written to standard error so that it reaches
+/// the terminal when standard output is piped.
+pub fn ring() {
+    let mut stderr = std::io::stderr();
+    // Nothing to report if the terminal is gone.
+    stderr.write_all(b"\x07").ok();
+    stderr.flush().ok();
+}
+
+/// Plays the sound file at `path` with the first player found on `PATH`.
+pub fn play(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let (name, program, args) = PLAYERS
+        .iter()
+        .find_map(|(name, args)| find_executable(name).map(|path| (*name, path, *args)))
+        .ok_or(
+            "No sound player found (tried afplay, paplay, pw-play, aplay, termux-media-player)",
+        )?;
+
+    let mut command = Command::new(&program);
+    command.args(args).stdout(Stdio::null());
+    if name == "powershell" {
+        command.env("WEATHER_SOUND", path);
+    } else {
+        command.arg(path);
+    }
+    let status = command.status()?;
+    if !status.success() {
+        return Err(format!("{} exited with {}", program.display(), status).into());
+    }
+    Ok(())
+}
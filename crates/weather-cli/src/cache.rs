@@ -0,0 +1,304 @@
+//! The provider's last response per location, kept in the cache directory
+//! so that repeating a lookup within `[cache] weather_ttl` costs no API call
+//! (`--no-cache` skips it), and so status line widgets can render without
+//! touching the network. Responses are always in metric units and
+//! converted for display, so the units asked for do not key the cache; the
+//! provider does.
+//!
+//! Long-running processes keep report files of their own, with a [`Memory`]
+//! LRU in front, so a refresh inside the TTL costs neither a disk read nor
+//! JSON parsing.
+
+use crate::{platform, saved, Query};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use weather_core::model::Report;
+use weather_providers::geocode::Place;
+
+/// Where the server's disk tier keeps the latest report for `city`.
+fn report_path(city: &str) -> Option<PathBuf> {
+    platform::dir(platform::Dir::Cache)
+        .map(|dir| dir.join("reports").join(format!("{}.json", slug(city))))
+}
+
+/// A saved location's files are named after its alias and where it is, so
+/// saving the alias elsewhere does not show the old place.
+fn saved_name(location: &saved::Location) -> String {
+    format!(
+        "{}-{:.4},{:.4}",
+        slug(location.label()),
+        location.place.lat,
+        location.place.lon
+    )
+}
+
+pub fn read_report(path: &Path) -> Option<Report> {
+    let bytes = std::fs::read(path).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// Writes `report` to `path` via a rename, so readers never see a
+/// half-written file.
+pub fn write_report(path: &Path, report: &Report) -> Result<(), Box<dyn std::error::Error>> {
+    write_json(path, report)
+}
+
+/// A provider's current conditions for a place, as fetched.
+#[derive(Serialize, Deserialize)]
+pub struct Response {
+    pub place: Place,
+    pub report: Report,
+}
+
+/// Where `provider`'s latest response for `query` is kept. The current
+/// location is not cached: the device may have moved since.
+pub fn response_path(provider: &str, query: &Query) -> Option<PathBuf> {
+    let name = match query {
+        Query::City(city) => slug(city),
+        Query::Choice { name, index } => format!("{}-{}", slug(name), index),
+        Query::Here(_) => return None,
+        Query::Coordinates { lat, lon } => format!("{:.4},{:.4}", lat, lon),
+        Query::Saved(location) => saved_name(location),
+    };
+    platform::dir(platform::Dir::Cache).map(|dir| {
+        dir.join("responses")
+            .join(provider)
+            .join(format!("{}.json", name))
+    })
+}
+
+/// The response at `path` and its age, if it was written less than `ttl`
+/// ago.
+pub fn read_response(path: &Path, ttl: Duration) -> Option<(Response, Duration)> {
+    let age = age(path)?;
+    if age >= ttl {
+        return None;
+    }
+    let bytes = std::fs::read(path).ok()?;
+    Some((serde_json::from_slice(&bytes).ok()?, age))
+}
+
+pub fn write_response(path: &Path, response: &Response) -> Result<(), Box<dyn std::error::Error>> {
+    write_json(path, response)
+}
+
+fn write_json(path: &Path, value: &impl Serialize) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let staged = path.with_extension("json.tmp");
+    std::fs::write(&staged, serde_json::to_vec_pretty(value)?)?;
+    std::fs::rename(&staged, path)?;
+    Ok(())
+}
+
+/// File-name-safe form of a city query: "São Paulo, BR" -> "são-paulo-br".
+pub fn slug(city: &str) -> String {
+    city.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Hit and miss counts of one cache.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct Stats {
+    pub memory_hits: u64,
+    /// Misses in memory answered from the disk cache.
+    pub disk_hits: u64,
+    pub misses: u64,
+}
+
+struct Entry<V> {
+    value: V,
+    stored: Instant,
+    /// Tick of the last read or write, for least-recently-used eviction.
+    used: u64,
+}
+
+struct MemoryState<V> {
+    entries: HashMap<String, Entry<V>>,
+    tick: u64,
+    stats: Stats,
+}
+
+/// An in-memory LRU whose entries expire `ttl` after they were stored.
+pub struct Memory<V> {
+    capacity: usize,
+    ttl: Duration,
+    state: Mutex<MemoryState<V>>,
+}
+
+impl<V: Clone> Memory<V> {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Memory {
+            capacity: capacity.max(1),
+            ttl,
+            state: Mutex::new(MemoryState {
+                entries: HashMap::new(),
+                tick: 0,
+                stats: Stats::default(),
+            }),
+        }
+    }
+
+    /// The fresh value for `key`, counting a memory hit. Misses are counted
+    /// by the caller, which may still find the value on disk.
+    pub fn get(&self, key: &str) -> Option<V> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.tick += 1;
+        let tick = state.tick;
+        let entry = state.entries.get_mut(key)?;
+        if entry.stored.elapsed() >= self.ttl {
+            state.entries.remove(key);
+            return None;
+        }
+        entry.used = tick;
+        let value = entry.value.clone();
+        state.stats.memory_hits += 1;
+        Some(value)
+    }
+
+    pub fn insert(&self, key: &str, value: V) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.tick += 1;
+        let used = state.tick;
+        if !state.entries.contains_key(key) && state.entries.len() >= self.capacity {
+            let oldest = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                state.entries.remove(&oldest);
+            }
+        }
+        let entry = Entry {
+            value,
+            stored: Instant::now(),
+            used,
+        };
+        state.entries.insert(key.to_string(), entry);
+    }
+
+    fn record(&self, update: impl FnOnce(&mut Stats)) {
+        update(&mut self.state.lock().unwrap_or_else(|e| e.into_inner()).stats);
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).stats
+    }
+}
+
+/// TTLs and size of the in-memory tier, per endpoint.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    /// Entries kept per endpoint.
+    pub capacity: usize,
+    /// How long a current weather report is reused, e.g. "10min", here and
+    /// by repeated lookups on the command line.
+    #[serde(deserialize_with = "crate::config::duration")]
+    pub weather_ttl: Duration,
+    /// How long a geocoded place is reused.
+    #[serde(deserialize_with = "crate::config::duration")]
+    pub geocode_ttl: Duration,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        // OpenWeatherMap refreshes current conditions about every 10
+        // minutes; places do not move.
+        Settings {
+            capacity: 256,
+            weather_ttl: Duration::from_secs(600),
+            geocode_ttl: Duration::from_secs(24 * 3600),
+        }
+    }
+}
+
+/// Current weather and geocoding results for a long-running process:
+/// memory first, then the report files, then the caller fetches.
+pub struct TwoTier {
+    weather: Memory<Report>,
+    places: Memory<Place>,
+}
+
+impl TwoTier {
+    pub fn new(settings: &Settings) -> Self {
+        TwoTier {
+            weather: Memory::new(settings.capacity, settings.weather_ttl),
+            places: Memory::new(settings.capacity, settings.geocode_ttl),
+        }
+    }
+
+    /// The cached report for `city`, from memory or else from a report file
+    /// written within the weather TTL.
+    pub fn report(&self, city: &str) -> Option<Report> {
+        let key = slug(city);
+        if let Some(report) = self.weather.get(&key) {
+            return Some(report);
+        }
+        let from_disk = report_path(city)
+            .filter(|path| is_fresh(path, self.weather.ttl))
+            .and_then(|path| read_report(&path));
+        match from_disk {
+            Some(report) => {
+                self.weather.record(|stats| stats.disk_hits += 1);
+                self.weather.insert(&key, report.clone());
+                Some(report)
+            }
+            None => {
+                self.weather.record(|stats| stats.misses += 1);
+                None
+            }
+        }
+    }
+
+    /// Stores a freshly fetched report in memory and on disk.
+    pub fn store_report(&self, city: &str, report: &Report) {
+        self.weather.insert(&slug(city), report.clone());
+        if let Some(path) = report_path(city) {
+            // The disk tier is an optimisation; failing to write it is not
+            // an error for the lookup.
+            write_report(&path, report).ok();
+        }
+    }
+
+    pub fn place(&self, city: &str) -> Option<Place> {
+        let place = self.places.get(&slug(city));
+        if place.is_none() {
+            self.places.record(|stats| stats.misses += 1);
+        }
+        place
+    }
+
+    pub fn store_place(&self, city: &str, place: &Place) {
+        self.places.insert(&slug(city), place.clone());
+    }
+
+    /// Hit and miss counts by endpoint.
+    pub fn stats(&self) -> HashMap<String, Stats> {
+        HashMap::from([
+            ("weather".to_string(), self.weather.stats()),
+            ("geocode".to_string(), self.places.stats()),
+        ])
+    }
+}
+
+/// Whether the file at `path` was modified less than `ttl` ago.
+fn is_fresh(path: &Path, ttl: Duration) -> bool {
+    age(path).is_some_and(|age| age < ttl)
+}
+
+/// Time since the file at `path` was modified.
+fn age(path: &Path) -> Option<Duration> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+}
@@ -0,0 +1,97 @@
+//! Dates in a second calendar, shown next to the Gregorian ones for users
+//! who plan by another calendar (`calendar = "hijri"` in the config).
+//!
+//! Conversions come from ICU4X; month names are transliterated to Latin
+//! script so they line up with the rest of the output.
+
+use chrono::{Datelike, NaiveDate};
+use icu_calendar::cal::{Hebrew, Hijri, Persian};
+use icu_calendar::types::{LeapStatus, MonthInfo};
+use icu_calendar::Date;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Calendar {
+    /// Gregorian dates only.
+    #[default]
+    Gregorian,
+    /// The Umm al-Qura Hijri calendar of Saudi Arabia.
+    Hijri,
+    /// The Solar Hijri calendar of Iran and Afghanistan.
+    Persian,
+    Hebrew,
+}
+
+const HIJRI_MONTHS: [&str; 12] = [
+    "Muharram",
+    "Safar",
+    "Rabi' al-Awwal",
+    "Rabi' al-Thani",
+    "Jumada al-Ula",
+    "Jumada al-Akhirah",
+    "Rajab",
+    "Sha'ban",
+    "Ramadan",
+    "Shawwal",
+    "Dhu al-Qa'dah",
+    "Dhu al-Hijjah",
+];
+
+const PERSIAN_MONTHS: [&str; 12] = [
+    "Farvardin",
+    "Ordibehesht",
+    "Khordad",
+    "Tir",
+    "Mordad",
+    "Shahrivar",
+    "Mehr",
+    "Aban",
+    "Azar",
+    "Dey",
+    "Bahman",
+    "Esfand",
+];
+
+const HEBREW_MONTHS: [&str; 12] = [
+    "Tishrei", "Heshvan", "Kislev", "Tevet", "Shevat", "Adar", "Nisan", "Iyar", "Sivan", "Tammuz",
+    "Av", "Elul",
+];
+
+impl Calendar {
+    /// `date` in this calendar, e.g. `25 Rabi' al-Thani 1448 AH`, or `None`
+    /// for the Gregorian calendar or a date outside the supported range.
+    pub fn format(self, date: NaiveDate) -> Option<String> {
+        let iso = Date::try_new_iso(date.year(), date.month() as u8, date.day() as u8).ok()?;
+        let (day, month, year, era) = match self {
+            Calendar::Gregorian => return None,
+            Calendar::Hijri => {
+                let date = iso.to_calendar(Hijri::new_umm_al_qura());
+                let month = HIJRI_MONTHS[month_index(date.month())].to_string();
+                (date.day_of_month().0, month, date.era_year().year, "AH")
+            }
+            Calendar::Persian => {
+                let date = iso.to_calendar(Persian::new());
+                let month = PERSIAN_MONTHS[month_index(date.month())].to_string();
+                (date.day_of_month().0, month, date.era_year().year, "AP")
+            }
+            Calendar::Hebrew => {
+                let date = iso.to_calendar(Hebrew::new());
+                let info = date.month();
+                // Leap years repeat Adar: the leap month is Adar I, and the
+                // regular Adar after it becomes Adar II.
+                let month = match info.leap_status() {
+                    LeapStatus::Leap => "Adar I".to_string(),
+                    LeapStatus::Base => "Adar II".to_string(),
+                    _ => HEBREW_MONTHS[month_index(info)].to_string(),
+                };
+                (date.day_of_month().0, month, date.era_year().year, "AM")
+            }
+        };
+        Some(format!("{} {} {} {}", day, month, year, era))
+    }
+}
+
+fn month_index(month: MonthInfo) -> usize {
+    usize::from(month.number().clamp(1, 12)) - 1
+}
@@ -0,0 +1,108 @@
+//! Microclimate calibration: fixed offsets added to the provider's values
+//! for a location, e.g. for a yard reliably colder than the airport the
+//! provider reports from.
+//!
+//! ```toml
+//! [calibration."Berlin"]
+//! temp_offset = -1.5
+//! humidity_offset = 4
+//! ```
+//!
+//! Offsets can be set by hand or learned from a personal weather station
+//! with `weather db calibrate <location>`, which averages the differences
+//! recorded in the observation log. They change what is displayed only:
+//! JSON output and the log keep the provider's values, so that learning
+//! never feeds on its own corrections.
+
+use crate::station;
+use serde::Deserialize;
+use weather_core::model::{Conditions, Report};
+
+/// Fewest recorded station readings an offset is learned from.
+pub const MIN_SAMPLES: usize = 6;
+
+/// Offsets for one location, added to the provider's values.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Calibration {
+    /// In °C.
+    pub temp_offset: f64,
+    /// In percentage points.
+    pub humidity_offset: f64,
+    /// In hPa.
+    pub pressure_offset: f64,
+}
+
+impl Calibration {
+    pub fn is_zero(&self) -> bool {
+        *self == Calibration::default()
+    }
+
+    /// Adds the offsets to `current`'s temperatures, humidity and pressure.
+    pub fn apply(&self, current: &mut Conditions) {
+        current.temperature_c += self.temp_offset;
+        current.feels_like_c += self.temp_offset;
+        current.temperature_min_c += self.temp_offset;
+        current.temperature_max_c += self.temp_offset;
+        current.humidity_percent = (current.humidity_percent as f64 + self.humidity_offset)
+            .clamp(0.0, 100.0)
+            .round() as i32;
+        current.pressure_hpa = (current.pressure_hpa as f64 + self.pressure_offset).round() as i32;
+    }
+}
+
+/// One offset learned from the log.
+#[derive(Debug, Clone)]
+pub struct Learned {
+    /// Config key, e.g. `temp_offset`.
+    pub key: &'static str,
+    pub label: &'static str,
+    /// Mean of station minus provider.
+    pub offset: f64,
+    /// Standard deviation of the differences; large when the offset
+    /// depends on the weather rather than the place.
+    pub spread: f64,
+    pub samples: usize,
+}
+
+/// Offsets learned from recorded reports that carry station readings.
+/// Fields with fewer than [`MIN_SAMPLES`] readings are left out.
+pub fn learn(reports: &[Report]) -> Vec<Learned> {
+    const FIELDS: [(&str, &str, &str); 3] = [
+        ("temperature_c", "temp_offset", "Temperature"),
+        ("humidity_percent", "humidity_offset", "Humidity"),
+        ("pressure_hpa", "pressure_offset", "Pressure"),
+    ];
+    FIELDS
+        .iter()
+        .filter_map(|&(field, key, label)| {
+            let differences: Vec<f64> = reports
+                .iter()
+                .filter_map(|report| {
+                    let local = report.local.as_ref()?;
+                    station::compare(local, &report.current)
+                        .into_iter()
+                        .find(|comparison| comparison.field == field)
+                        .map(|comparison| comparison.difference())
+                })
+                .collect();
+            if differences.len() < MIN_SAMPLES {
+                return None;
+            }
+            let n = differences.len() as f64;
+            let offset = differences.iter().sum::<f64>() / n;
+            let variance = differences
+                .iter()
+                .map(|d| (d - offset).powi(2))
+                .sum::<f64>()
+                / n;
+            Some(Learned {
+                key,
+                label,
+                offset,
+                spread: variance.sqrt(),
+                samples: differences.len(),
+            })
+        })
+        .collect()
+}
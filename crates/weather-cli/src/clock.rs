@@ -0,0 +1,239 @@
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use std::time::Duration;
+
+/// Source of "now" for everything that renders data relative to the current
+/// time, so output can be reproduced for an arbitrary moment.
+pub trait Clock {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The real wall clock.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock frozen at a fixed instant, used by `--as-of`.
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+/// Parses an `--as-of` value: RFC 3339 (`2024-06-01T08:00:00+02:00`), a UTC
+/// `YYYY-MM-DD HH:MM` date-time, or Unix seconds.
+pub fn parse_timestamp(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Ok(datetime.with_timezone(&Utc));
+    }
+    if let Ok(naive) = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M") {
+        return Ok(Utc.from_utc_datetime(&naive));
+    }
+    if let Ok(seconds) = value.parse::<i64>() {
+        if let Some(datetime) = Utc.timestamp_opt(seconds, 0).single() {
+            return Ok(datetime);
+        }
+    }
+    Err(format!(
+        "Invalid timestamp '{}': expected RFC 3339, 'YYYY-MM-DD HH:MM' or Unix seconds",
+        value
+    ))
+}
+
+/// [`parse_duration`] as a clap validator, for arguments whose value is
+/// optional, which structopt cannot give a custom parser.
+pub fn validate_duration(value: String) -> Result<(), String> {
+    parse_duration(&value).map(drop)
+}
+
+/// Parses a duration such as `45min`, `1h30m`, `90s`, `500ms`, `2h` or
+/// `7d`; a bare number is minutes.
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let invalid = || {
+        format!(
+            "Invalid duration '{}': expected e.g. 45min, 1h30m or 90s",
+            value
+        )
+    };
+    if let Ok(minutes) = value.parse::<u64>() {
+        return Ok(Duration::from_secs(minutes * 60));
+    }
+
+    let mut millis = 0;
+    let mut rest = value.trim();
+    if rest.is_empty() {
+        return Err(invalid());
+    }
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(invalid)?;
+        let amount: u64 = rest[..digits].parse().map_err(|_| invalid())?;
+        rest = &rest[digits..];
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let scale = match &rest[..unit_len] {
+            "ms" => 1,
+            "s" | "sec" | "secs" => 1000,
+            "m" | "min" | "mins" => 60_000,
+            "h" | "hr" | "hrs" | "hour" | "hours" => 3_600_000,
+            "d" | "day" | "days" => 86_400_000,
+            _ => return Err(invalid()),
+        };
+        millis += amount * scale;
+        rest = &rest[unit_len..];
+    }
+    Ok(Duration::from_millis(millis))
+}
+
+/// Whether it is daytime at `now`, given one day's sunrise and sunset. Only
+/// the time of day is compared, so the answer stays sensible when `now` is on
+/// a different day than the sun times.
+pub fn is_daytime(now: i64, sunrise: i64, sunset: i64) -> bool {
+    let day_length = sunset - sunrise;
+    (now - sunrise).rem_euclid(86_400) < day_length
+}
+
+/// A length of time in the largest fitting units, e.g. "40s", "35m",
+/// "2h 10m" or "3d".
+pub fn span(seconds: i64) -> String {
+    let minutes = seconds / 60;
+    if seconds < 60 {
+        format!("{}s", seconds)
+    } else if minutes < 60 {
+        format!("{}m", minutes)
+    } else if minutes < 48 * 60 {
+        format!("{}h {}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}d", minutes / (24 * 60))
+    }
+}
+
+/// Describes `timestamp` relative to `now`, e.g. "in 2h 10m" or "35m ago".
+pub fn relative(now: i64, timestamp: i64) -> String {
+    let difference = timestamp - now;
+    if difference.abs() < 60 {
+        return "now".to_string();
+    }
+    let span = span(difference.abs());
+
+    if difference > 0 {
+        format!("in {}", span)
+    } else {
+        format!("{} ago", span)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        parse_timestamp(value).unwrap()
+    }
+
+    #[test]
+    fn fixed_clock_stays_put() {
+        let clock: Box<dyn Clock> = Box::new(FixedClock(at("2024-06-01 08:00")));
+        assert_eq!(clock.now(), at("2024-06-01 08:00"));
+        assert_eq!(clock.now(), clock.now());
+    }
+
+    #[test]
+    fn parses_each_timestamp_form() {
+        let expected = Utc.with_ymd_and_hms(2024, 6, 1, 6, 0, 0).unwrap();
+        assert_eq!(at("2024-06-01T08:00:00+02:00"), expected);
+        assert_eq!(at("2024-06-01T06:00:00Z"), expected);
+        assert_eq!(at("2024-06-01 06:00"), expected);
+        assert_eq!(at("1717221600"), expected);
+    }
+
+    #[test]
+    fn rejects_other_timestamps() {
+        for value in ["", "yesterday", "2024-06-01", "01/06/2024 06:00"] {
+            assert!(parse_timestamp(value).is_err(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("45"), Ok(Duration::from_secs(45 * 60)));
+        assert_eq!(parse_duration("45min"), Ok(Duration::from_secs(45 * 60)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("7d"), Ok(Duration::from_secs(7 * 86_400)));
+        for value in ["", "h", "10 parsecs", "1.5h"] {
+            assert!(parse_duration(value).is_err(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn daytime_between_sunrise_and_sunset() {
+        let sunrise = at("2024-06-01 03:45").timestamp();
+        let sunset = at("2024-06-01 19:25").timestamp();
+        assert!(is_daytime(
+            at("2024-06-01 12:00").timestamp(),
+            sunrise,
+            sunset
+        ));
+        assert!(!is_daytime(
+            at("2024-06-01 02:00").timestamp(),
+            sunrise,
+            sunset
+        ));
+        assert!(!is_daytime(
+            at("2024-06-01 22:00").timestamp(),
+            sunrise,
+            sunset
+        ));
+    }
+
+    #[test]
+    fn daytime_with_sun_times_of_another_day() {
+        // As with --as-of a day after the report was fetched.
+        let sunrise = at("2024-06-01 03:45").timestamp();
+        let sunset = at("2024-06-01 19:25").timestamp();
+        assert!(is_daytime(
+            at("2024-06-02 12:00").timestamp(),
+            sunrise,
+            sunset
+        ));
+        assert!(!is_daytime(
+            at("2024-06-02 23:00").timestamp(),
+            sunrise,
+            sunset
+        ));
+        assert!(!is_daytime(
+            at("2024-05-31 01:00").timestamp(),
+            sunrise,
+            sunset
+        ));
+    }
+
+    #[test]
+    fn spans_in_the_largest_fitting_units() {
+        assert_eq!(span(40), "40s");
+        assert_eq!(span(35 * 60), "35m");
+        assert_eq!(span(2 * 3600 + 10 * 60), "2h 10m");
+        assert_eq!(span(47 * 3600), "47h 0m");
+        assert_eq!(span(3 * 86_400), "3d");
+    }
+
+    #[test]
+    fn relative_to_the_clock() {
+        let clock = FixedClock(at("2024-06-01 17:15"));
+        let now = clock.now().timestamp();
+        let sunset = at("2024-06-01 19:25").timestamp();
+        let sunrise = at("2024-06-01 03:45").timestamp();
+        assert_eq!(relative(now, sunset), "in 2h 10m");
+        assert_eq!(relative(now, sunrise), "13h 30m ago");
+        assert_eq!(relative(now, now + 30), "now");
+    }
+}
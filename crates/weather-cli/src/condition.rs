@@ -0,0 +1,170 @@
+//! Conditions for `weather notify`, such as `temp>40 or rain`: comparisons
+//! of a field with a number, named as in `weather check`, and words for
+//! what is falling or the state of the sky, joined with `and` and `or`
+//! (`and` binding tighter).
+//!
+//! Unlike threshold expressions, `>` and `<` are strict; `>=` and `<=`
+//! include the limit. Values are in metric units.
+
+use crate::thresholds;
+use std::str::FromStr;
+use weather_core::model::Conditions;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Above,
+    AtLeast,
+    Below,
+    AtMost,
+}
+
+impl Op {
+    fn holds(self, value: f64, limit: f64) -> bool {
+        match self {
+            Op::Above => value > limit,
+            Op::AtLeast => value >= limit,
+            Op::Below => value < limit,
+            Op::AtMost => value <= limit,
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Op::Above => ">",
+            Op::AtLeast => ">=",
+            Op::Below => "<",
+            Op::AtMost => "<=",
+        }
+    }
+}
+
+/// Words for the current conditions, from the condition kind and the
+/// precipitation of the last hour.
+const SKY_WORDS: &[&str] = &["rain", "drizzle", "snow", "storm", "fog", "clear", "cloudy"];
+
+#[derive(Debug, Clone)]
+enum Test {
+    Compare { field: String, op: Op, limit: f64 },
+    Sky(&'static str),
+}
+
+impl Test {
+    /// What about `current` passes the test, e.g. `temperature 41.2 > 40`;
+    /// `None` when it fails or the field has no value.
+    fn passed(&self, current: &Conditions) -> Option<String> {
+        match self {
+            Test::Compare { field, op, limit } => {
+                let value = thresholds::field_value(current, field)?;
+                op.holds(value, *limit)
+                    .then(|| format!("{} {:.1} {} {}", field, value, op.symbol(), limit))
+            }
+            Test::Sky(word) => {
+                let kind = current.condition.kind.as_str();
+                let fell = |amount: Option<f64>| amount.is_some_and(|amount| amount > 0.0);
+                let now = match *word {
+                    "rain" => {
+                        matches!(kind, "rain" | "drizzle" | "thunderstorm") || fell(current.rain_mm)
+                    }
+                    "drizzle" => kind == "drizzle",
+                    "snow" => kind == "snow" || fell(current.snow_mm),
+                    "storm" => kind == "thunderstorm",
+                    "fog" => kind == "fog",
+                    "clear" => kind == "clear",
+                    _ => kind == "clouds",
+                };
+                now.then(|| word.to_string())
+            }
+        }
+    }
+}
+
+impl FromStr for Test {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "Invalid condition '{}': expected e.g. 'temp>40', 'wind>=10' or one of {}",
+                s,
+                SKY_WORDS.join(", ")
+            )
+        };
+        let Some(split) = s.find(['<', '>']) else {
+            let word = s.trim().to_lowercase();
+            return match word.as_str() {
+                "thunderstorm" => Ok(Test::Sky("storm")),
+                _ => SKY_WORDS
+                    .iter()
+                    .find(|known| **known == word)
+                    .map(|known| Test::Sky(known))
+                    .ok_or_else(invalid),
+            };
+        };
+        let (field, rest) = s.split_at(split);
+        let (op, limit) = match (rest.strip_prefix(">"), rest.strip_prefix("<")) {
+            (Some(limit), _) => match limit.strip_prefix('=') {
+                Some(limit) => (Op::AtLeast, limit),
+                None => (Op::Above, limit),
+            },
+            (_, Some(limit)) => match limit.strip_prefix('=') {
+                Some(limit) => (Op::AtMost, limit),
+                None => (Op::Below, limit),
+            },
+            _ => return Err(invalid()),
+        };
+        let field = thresholds::field_name(field);
+        thresholds::check_field(&field)?;
+        let limit = limit.trim().parse().map_err(|_| invalid())?;
+        Ok(Test::Compare { field, op, limit })
+    }
+}
+
+/// Alternatives, each matching when all of its tests pass.
+#[derive(Debug, Clone)]
+pub struct Condition {
+    any: Vec<Vec<Test>>,
+}
+
+impl Condition {
+    /// What about `current` matches, from the first alternative that does.
+    pub fn matches(&self, current: &Conditions) -> Option<Vec<String>> {
+        self.any
+            .iter()
+            .find_map(|all| all.iter().map(|test| test.passed(current)).collect())
+    }
+}
+
+impl FromStr for Condition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Words are separated by spaces, which comparisons may contain too:
+        // `temp > 40` is rejoined.
+        let mut any = vec![vec![String::new()]];
+        for word in s.split_whitespace() {
+            match word.to_lowercase().as_str() {
+                "or" => any.push(vec![String::new()]),
+                "and" => any.last_mut().unwrap().push(String::new()),
+                _ => any.last_mut().unwrap().last_mut().unwrap().push_str(word),
+            }
+        }
+        let any = any
+            .into_iter()
+            .map(|all| {
+                all.iter()
+                    .map(|test| {
+                        if test.is_empty() {
+                            Err(format!(
+                                "Invalid condition '{}': expected a test on each side of `and` and `or`",
+                                s
+                            ))
+                        } else {
+                            test.parse()
+                        }
+                    })
+                    .collect::<Result<Vec<Test>, String>>()
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Condition { any })
+    }
+}
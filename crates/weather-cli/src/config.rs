@@ -0,0 +1,244 @@
+//! The optional `config.toml` in the config directory (see `weather paths`).
+//!
+//! ```toml
+//! version = 1  # format of this file; older ones are migrated on load
+//! city = "Berlin"  # used when no location is given; --city etc. override
+//! units = "imperial"  # or "metric" or "standard"; --units overrides
+//! locale = "de-DE"  # --locale overrides, this overrides $LANG
+//! lang = "de"  # descriptions and labels; --lang overrides (labels default to the locale's)
+//! api_key = "…"  # OPEN_WEATHER_MAP_API overrides
+//! calendar = "hijri"  # also shown next to dates: hijri, persian or hebrew
+//!
+//! [metrics]
+//! card = ["dew_point", "heat_index"]
+//! json = ["dew_point", "cloud_base"]
+//!
+//! [thresholds.wind_gust]
+//! warn = 15
+//! critical = 25
+//!
+//! [storage]
+//! backend = "sqlite"
+//! record = true
+//!
+//! [freshness]
+//! stale_after = "90min"
+//!
+//! [cache]
+//! weather_ttl = "5min"
+//! geocode_ttl = "7d"
+//!
+//! [retry]  # of timeouts, refused connections, 429 and 5xx answers
+//! retries = 3  # --retries overrides; 0 disables
+//! delay = "1s"  # before the first retry, doubled after; --retry-delay overrides
+//! jitter = false
+//!
+//! [week]  # defaults follow the locale's region
+//! first_day = "saturday"
+//! weekend = ["friday", "saturday"]
+//!
+//! [stations]
+//! "Berlin" = "netatmo:70:ee:50:00:00:01"
+//! here = "ecowitt:192.168.1.50"
+//!
+//! [calibration."Berlin"]  # see `weather db calibrate`
+//! temp_offset = -1.5
+//!
+//! [alerts]  # CAP feeds read by --alerts as well as the built-in sources
+//! cap_feeds = ["https://alerts.example.gov/cap/feed.xml"]
+//!
+//! [surf.spots."Fistral"]  # for `weather surf`
+//! lat = 50.4167
+//! lon = -5.1
+//! facing = 300  # the way the beach faces, out to sea, in degrees
+//!
+//! [card]
+//! layout = "short"  # --layout overrides
+//!
+//! [card.layouts]  # "---" is a rule; metric keys show that metric
+//! short = ["header", "condition", "temperature", "---", "wind", "dew_point"]
+//!
+//! [tui.keys]  # replaces the default keys of the actions named
+//! page_down = ["ctrl-d", "pagedown"]
+//! quit = ["q"]
+//! ```
+
+use crate::{
+    cache, calendar, calibration, clock, layout, migrate, platform, storage, surf, thresholds, tui,
+    units,
+};
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use weather_core::metrics;
+use weather_providers::{pws, retry};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Location used when none is given on the command line.
+    pub city: Option<String>,
+    pub units: units::Units,
+    /// Locale tag, e.g. `de-DE`, used when `--locale` is not given.
+    pub locale: Option<String>,
+    /// Language of OpenWeatherMap's descriptions and of the card's labels,
+    /// used when `--lang` is not given.
+    pub lang: Option<String>,
+    /// OpenWeatherMap API key, used when `OPEN_WEATHER_MAP_API` is not set.
+    pub api_key: Option<String>,
+    /// A calendar to show dates in alongside the Gregorian one.
+    pub calendar: calendar::Calendar,
+    pub metrics: MetricsConfig,
+    pub thresholds: thresholds::Thresholds,
+    pub storage: storage::Settings,
+    pub freshness: FreshnessConfig,
+    /// How long lookups are reused, on disk and in `weather serve`.
+    pub cache: cache::Settings,
+    pub retry: RetryConfig,
+    pub week: WeekConfig,
+    /// Personal weather station shown with a location when `--pws` is not
+    /// given, by the name passed to `--city` (`here` for `--here`).
+    #[serde(deserialize_with = "stations")]
+    pub stations: BTreeMap<String, pws::Station>,
+    /// Offsets added to displayed conditions, by location name as for
+    /// `stations`.
+    pub calibration: BTreeMap<String, calibration::Calibration>,
+    pub alerts: AlertsConfig,
+    pub surf: surf::Settings,
+    pub tui: tui::Settings,
+    pub card: layout::Settings,
+}
+
+impl Config {
+    /// The OpenWeatherMap API key: from the environment (including `.env`
+    /// files), else from the config file.
+    pub fn api_key(&self) -> Option<String> {
+        std::env::var("OPEN_WEATHER_MAP_API")
+            .ok()
+            .or_else(|| self.api_key.clone())
+    }
+}
+
+/// Derived metrics shown in each output, by calculator key.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricsConfig {
+    pub card: Vec<String>,
+    pub json: Vec<String>,
+}
+
+/// Overrides of the locale's first day of the week and weekend days.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WeekConfig {
+    pub first_day: Option<chrono::Weekday>,
+    pub weekend: Option<Vec<chrono::Weekday>>,
+}
+
+/// Extra sources for `--alerts`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AlertsConfig {
+    /// URLs of Common Alerting Protocol messages or Atom or RSS feeds of
+    /// them, e.g. a national weather service's.
+    pub cap_feeds: Vec<String>,
+}
+
+/// When an observation is old enough to warn about.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FreshnessConfig {
+    /// Age from which output is marked stale, e.g. "90min".
+    #[serde(deserialize_with = "duration")]
+    pub stale_after: std::time::Duration,
+}
+
+impl Default for FreshnessConfig {
+    fn default() -> Self {
+        // OpenWeatherMap refreshes roughly every 10 minutes; an hour means
+        // the station has stopped reporting.
+        FreshnessConfig {
+            stale_after: std::time::Duration::from_secs(3600),
+        }
+    }
+}
+
+/// How requests that failed transiently are retried.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetryConfig {
+    pub retries: u32,
+    /// Delay before the first retry, e.g. "500ms".
+    #[serde(deserialize_with = "duration")]
+    pub delay: std::time::Duration,
+    /// Wait a random 50–100% of each delay.
+    pub jitter: bool,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        let policy = retry::Policy::default();
+        RetryConfig {
+            retries: policy.retries,
+            delay: policy.base_delay,
+            jitter: policy.jitter,
+        }
+    }
+}
+
+pub(crate) fn duration<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<std::time::Duration, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    clock::parse_duration(&text).map_err(serde::de::Error::custom)
+}
+
+fn stations<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BTreeMap<String, pws::Station>, D::Error> {
+    BTreeMap::<String, String>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(name, station)| Ok((name, station.parse().map_err(serde::de::Error::custom)?)))
+        .collect()
+}
+
+/// Default location of the config file.
+pub fn default_path() -> Option<PathBuf> {
+    platform::dir(platform::Dir::Config).map(|dir| dir.join("config.toml"))
+}
+
+/// Loads and validates the config at `path`, or the default location. A
+/// missing default file yields the default config; a missing explicit
+/// file is an error.
+pub fn load(path: Option<&Path>) -> Result<Config, String> {
+    let (path, explicit) = match path {
+        Some(path) => (path.to_path_buf(), true),
+        None => match default_path() {
+            Some(path) => (path, false),
+            None => return Ok(Config::default()),
+        },
+    };
+
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound && !explicit => {
+            return Ok(Config::default())
+        }
+        Err(e) => return Err(format!("Cannot read {}: {}", path.display(), e)),
+    };
+    let mut table: toml::Table =
+        toml::from_str(&text).map_err(|e| format!("Invalid config {}: {}", path.display(), e))?;
+    migrate::config(&path, &mut table)?;
+    let config: Config = table
+        .try_into()
+        .map_err(|e| format!("Invalid config {}: {}", path.display(), e))?;
+
+    metrics::validate(&config.metrics.card)
+        .and_then(|_| metrics::validate(&config.metrics.json))
+        .and_then(|_| thresholds::validate(&config.thresholds))
+        .and_then(|_| storage::validate(&config.storage))
+        .and_then(|_| layout::validate(&config.card))
+        .and_then(|_| surf::validate(&config.surf))
+        .map_err(|e| format!("Invalid config {}: {}", path.display(), e))?;
+    Ok(config)
+}
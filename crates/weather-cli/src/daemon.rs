@@ -0,0 +1,144 @@
+//! `weather daemon`: keeps the latest weather for the device's location in a
+//! state file that status bars, trays and other tools can read cheaply.
+
+use crate::location::{self, Source};
+use crate::storage::Storage;
+use crate::{cache, hook, http_client, platform, report};
+use chrono::Local;
+use futures_util::{Stream, StreamExt};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::time::Duration;
+use weather_core::geo;
+use weather_core::model::Report;
+use weather_providers::client::{Cancel, ChangeFilter, WeatherClient, WeatherUpdate};
+use weather_providers::geocode::Place;
+
+pub struct DaemonOptions {
+    pub source: Source,
+    pub interval: Duration,
+    /// Distance in metres the device must move before the active location
+    /// is re-resolved.
+    pub geofence_m: f64,
+    /// Observation log each refresh is appended to.
+    pub storage: Option<Box<dyn Storage>>,
+    /// Script run after each refresh, for `weather hook`.
+    pub hook: Option<PathBuf>,
+}
+
+/// Path of the file holding the most recent report.
+pub fn state_file() -> Option<PathBuf> {
+    platform::dir(platform::Dir::Cache).map(|dir| dir.join("current.json"))
+}
+
+pub async fn run(
+    options: DaemonOptions,
+    api_key: String,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let state_path = state_file().ok_or("Cannot determine the cache directory")?;
+    println!(
+        "Refreshing every {}s, switching location after {:.0} m of movement; writing {}",
+        options.interval.as_secs(),
+        options.geofence_m,
+        state_path.display()
+    );
+
+    let client = WeatherClient::with_http(http_client()?, api_key);
+    let mut active: Option<Place> = None;
+    let mut updates: Option<Pin<Box<dyn Stream<Item = WeatherUpdate>>>> = None;
+    let mut ticker = tokio::time::interval(options.interval);
+    loop {
+        let next_update = async {
+            match updates.as_mut() {
+                Some(updates) => updates.next().await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            _ = ticker.tick() => match follow(&options, &client, &mut active).await {
+                // Every new observation is written, so the state file
+                // never looks older than the provider's data.
+                Ok(Some(place)) => {
+                    updates = Some(Box::pin(client.watch_with(
+                        place,
+                        options.interval,
+                        ChangeFilter::NewObservation,
+                        Cancel::default(),
+                    )))
+                }
+                Ok(None) => {}
+                Err(e) => log_failure(&*e),
+            },
+            Some(update) = next_update => match update {
+                WeatherUpdate::Report { report, .. } => {
+                    if let Err(e) = save(&options, &report, &state_path).await {
+                        log_failure(&*e);
+                    }
+                    if let Some(script) = &options.hook {
+                        if let Err(e) = hook::run(script, &report).await {
+                            log("hook failed", &*e);
+                        }
+                    }
+                }
+                WeatherUpdate::Error(e) => log_failure(&e),
+            },
+            _ = tokio::signal::ctrl_c() => {
+                println!("Stopping");
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn log_failure(error: &dyn std::fmt::Display) {
+    log("refresh failed", error);
+}
+
+fn log(what: &str, error: &dyn std::fmt::Display) {
+    eprintln!("[{}] {}: {}", Local::now().format("%H:%M:%S"), what, error);
+}
+
+/// Reads the device position and, when it has left the geofence around the
+/// active location, resolves and returns the new place to watch.
+async fn follow(
+    options: &DaemonOptions,
+    client: &WeatherClient,
+    active: &mut Option<Place>,
+) -> Result<Option<Place>, Box<dyn std::error::Error>> {
+    let position = location::current_position(&options.source).await?;
+
+    let moved = active
+        .as_ref()
+        .map(|place| geo::distance_m(place.lat, place.lon, position.lat, position.lon));
+    if moved.is_some_and(|distance| distance <= options.geofence_m) {
+        return Ok(None);
+    }
+    let place = client
+        .reverse(position.lat, position.lon, &report::cancel())
+        .await?;
+    println!(
+        "[{}] active location: {}{}",
+        Local::now().format("%H:%M:%S"),
+        place.english_name(),
+        moved.map_or_else(String::new, |d| format!(" (moved {:.1} km)", d / 1000.0))
+    );
+    // The timezone comes with every weather response, so switching places
+    // re-resolves it as a matter of course.
+    *active = Some(place.clone());
+    Ok(Some(place))
+}
+
+async fn save(
+    options: &DaemonOptions,
+    report: &Report,
+    state_path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    cache::write_report(state_path, report)?;
+    if let Some(storage) = &options.storage {
+        storage
+            .append(std::slice::from_ref(report))
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
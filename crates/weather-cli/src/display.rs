@@ -0,0 +1,1182 @@
+//! The terminal display of a report: the card of current conditions with
+//! the sections the options add to it, and the one-line and simple forms.
+
+use crate::render::{self, Card};
+use crate::DisplayOptions;
+use crate::{art, calibration, clock, glossary, hyperlocal, layout, recommend, roads, station};
+use crate::{format_temperature, get_weather_ascii, get_weather_emoji, local_datetime};
+use chrono::{DateTime, Timelike, Utc};
+use colored::Colorize;
+use std::io::IsTerminal;
+use weather_core::{astro, atmosphere, metrics, model, wind};
+use weather_providers::geocode::Place;
+use weather_providers::river;
+
+pub fn display_weather(
+    report: &model::Report,
+    place: &Place,
+    calibration: Option<&calibration::Calibration>,
+    options: &DisplayOptions,
+) {
+    let now = options.clock.now().timestamp();
+    let mut card = Card::new();
+    for section in &options.layout {
+        card_section(&mut card, section, report, place, calibration, now, options);
+    }
+
+    println!();
+    if options.art {
+        let night = is_night(report, options);
+        let art = art::draw(&report.current, night);
+        let columns = crossterm::terminal::size()
+            .ok()
+            .filter(|_| std::io::stdout().is_terminal())
+            .map(|(columns, _)| columns as usize);
+        for line in art::beside(&art, &card.rows(), columns) {
+            println!("{}", line);
+        }
+    } else {
+        card.print();
+    }
+
+    if options.astro {
+        display_astro(report, options);
+    }
+    if options.performance {
+        display_performance(report, options);
+    }
+    if options.ballistics {
+        display_ballistics(report, options);
+    }
+    if options.explain {
+        display_explanations(&report.current, options);
+    }
+}
+
+/// Adds the lines of one section of the card layout.
+fn card_section(
+    card: &mut Card,
+    section: &layout::Section,
+    report: &model::Report,
+    place: &Place,
+    calibration: Option<&calibration::Calibration>,
+    now: i64,
+    options: &DisplayOptions,
+) {
+    use crate::layout::Section;
+    match section {
+        Section::Header => {
+            let name = match place.native_name() {
+                Some(native) => {
+                    format!("{} ({})", place.english_name(), render::isolate(native))
+                }
+                None => place.english_name().to_string(),
+            };
+            card.line(format!(
+                "{}{} {}, {}",
+                options.icon("🌍"),
+                options.catalog.text("card.weather_in").bright_green(),
+                name.bold(),
+                report.location.country.bold()
+            ));
+        }
+        Section::Time => {
+            for line in context_header(report, now, options) {
+                card.line(line);
+            }
+        }
+        Section::Condition => {
+            let condition = &report.current.condition;
+            card.line(format!(
+                "{} {} ({})",
+                condition_icon(report, options),
+                condition_name(&condition.kind).bold(),
+                condition.description
+            ));
+        }
+        Section::Temperature => {
+            let temp = format_temperature(report.current.temperature_c, options);
+            let feels_like = format_temperature(report.current.feels_like_c, options);
+            card.line(format!(
+                "{}{}",
+                options.icon("🌡️"),
+                options.catalog.format(
+                    "card.temperature",
+                    &[
+                        (
+                            "temp",
+                            &options.highlight(&report.current, "temperature", &temp, |t| {
+                                t.bright_yellow()
+                            })
+                        ),
+                        (
+                            "feels_like",
+                            &options.highlight(&report.current, "feels_like", &feels_like, |t| t
+                                .normal())
+                        ),
+                    ]
+                )
+            ));
+        }
+        Section::MinMax => {
+            let temp_min = format_temperature(report.current.temperature_min_c, options);
+            let temp_max = format_temperature(report.current.temperature_max_c, options);
+            card.line(format!(
+                "{}{}",
+                options.icon("📊"),
+                options
+                    .catalog
+                    .format("card.min_max", &[("min", &temp_min), ("max", &temp_max)])
+            ));
+        }
+        Section::Humidity => card.line(labelled(
+            options.icon("💧"),
+            "card.humidity",
+            options.highlight(
+                &report.current,
+                "humidity",
+                &format!("{}%", report.current.humidity_percent),
+                |t| t.normal(),
+            ),
+            options,
+        )),
+        Section::Pressure => card.line(labelled(
+            options.icon("🔄"),
+            "card.pressure",
+            options.highlight(
+                &report.current,
+                "pressure",
+                &options.quantity(options.units.pressure(report.current.pressure_hpa as f64)),
+                |t| t.normal(),
+            ),
+            options,
+        )),
+        Section::Wind => {
+            card.line(format!(
+                "{}{}",
+                options.icon("💨"),
+                options.catalog.format(
+                    "card.wind",
+                    &[
+                        (
+                            "speed",
+                            &options.highlight(
+                                &report.current,
+                                "wind_speed",
+                                &options
+                                    .quantity(options.units.speed(report.current.wind_speed_ms)),
+                                |t| t.normal()
+                            )
+                        ),
+                        (
+                            "compass",
+                            options
+                                .catalog
+                                .compass(report.current.wind_direction_deg as f64)
+                        ),
+                        ("degrees", &report.current.wind_direction_deg.to_string()),
+                    ]
+                )
+            ));
+            if let Some(heading) = options.heading {
+                card.line(heading_line(&report.current, heading, options));
+            }
+        }
+        Section::Gusts => {
+            if let Some(gust) = report.current.wind_gust_ms {
+                card.line(labelled(
+                    options.icon("🌬️"),
+                    "card.gusts",
+                    options.highlight(
+                        &report.current,
+                        "wind_gust",
+                        &options.quantity(options.units.speed(gust)),
+                        |t| t.normal(),
+                    ),
+                    options,
+                ));
+            }
+        }
+        Section::Visibility => card.line(labelled(
+            options.icon("👁️"),
+            "card.visibility",
+            options.highlight(
+                &report.current,
+                "visibility",
+                &options.quantity(options.units.distance(report.current.visibility_m as f64)),
+                |t| t.normal(),
+            ),
+            options,
+        )),
+        Section::Cloudiness => card.line(labelled(
+            options.icon("☁️"),
+            "card.cloudiness",
+            options.highlight(
+                &report.current,
+                "cloudiness",
+                &format!("{}%", report.current.cloud_cover_percent),
+                |t| t.normal(),
+            ),
+            options,
+        )),
+        Section::Metrics => {
+            metric_lines(card, &options.config.metrics.card, &report.current, options)
+        }
+        Section::Metric(key) => {
+            metric_lines(card, std::slice::from_ref(key), &report.current, options)
+        }
+        Section::Precipitation => {
+            if let Some(rain) = report.current.rain_mm {
+                let amount = options.highlight(
+                    &report.current,
+                    "rain_1h",
+                    &options.quantity(options.units.precipitation(rain)),
+                    |t| t.normal(),
+                );
+                card.line(format!(
+                    "{}{}",
+                    options.icon("🌧️"),
+                    options.catalog.format("card.rain", &[("amount", &amount)])
+                ));
+            }
+            if let Some(snow) = report.current.snow_mm {
+                let amount = options.quantity(options.units.precipitation(snow));
+                card.line(format!(
+                    "{}{}",
+                    options.icon("🌨️"),
+                    options.catalog.format("card.snow", &[("amount", &amount)])
+                ));
+            }
+        }
+        Section::Sunrise => card.line(format!(
+            "{}{}",
+            options.icon("🌅"),
+            options.catalog.format(
+                "card.sunrise",
+                &[(
+                    "time",
+                    &sun_event(sun_times(report).0, now, report, options)
+                )]
+            )
+        )),
+        Section::Sunset => card.line(format!(
+            "{}{}",
+            options.icon("🌇"),
+            options.catalog.format(
+                "card.sunset",
+                &[(
+                    "time",
+                    &sun_event(sun_times(report).1, now, report, options)
+                )]
+            )
+        )),
+        Section::Calibration => {
+            if let Some(calibration) = calibration {
+                card.line(
+                    format!(
+                        "Calibrated for this location: {}",
+                        calibration_summary(calibration, options)
+                    )
+                    .dimmed()
+                    .to_string(),
+                );
+            }
+        }
+        Section::Separator => card.rule(),
+    }
+}
+
+/// A card line: `icon`, then the message `key` with `value` in its
+/// `{value}`.
+fn labelled(icon: String, key: &str, value: String, options: &DisplayOptions) -> String {
+    format!(
+        "{}{}",
+        icon,
+        options.catalog.format(key, &[("value", &value)])
+    )
+}
+
+/// A line for each of the derived metrics `keys`.
+fn metric_lines(
+    card: &mut Card,
+    keys: &[String],
+    current: &model::Conditions,
+    options: &DisplayOptions,
+) {
+    let locale = &options.locale;
+    for (calculator, value) in metrics::compute(current, keys) {
+        let value = match calculator.unit {
+            metrics::Unit::Temperature => format_temperature(value, options),
+            metrics::Unit::Fixed("m") => {
+                let (value, _, unit) = options.units.height(value);
+                locale.quantity(value, calculator.precision, unit)
+            }
+            metrics::Unit::Fixed(unit) => locale.quantity(value, calculator.precision, unit),
+            metrics::Unit::None => locale.number(value, calculator.precision),
+        };
+        card.line(format!(
+            "{}{}: {}",
+            options.icon(calculator.icon),
+            calculator.label,
+            options.highlight(current, calculator.key, &value, |t| t.normal())
+        ));
+    }
+}
+
+/// Today's sunrise and sunset. When the provider gives neither, they are
+/// computed locally, which also tells polar day and night apart (`None`
+/// when the sun doesn't rise or set).
+fn sun_times(report: &model::Report) -> (Option<i64>, Option<i64>) {
+    let current = &report.current;
+    if let (Some(sunrise), Some(sunset)) = (current.sunrise, current.sunset) {
+        return (Some(sunrise.timestamp()), Some(sunset.timestamp()));
+    }
+    let location = &report.location;
+    let date =
+        local_datetime(report.observed_at.timestamp(), location.utc_offset_seconds).date_naive();
+    let times = astro::sun_times(
+        location.latitude,
+        location.longitude,
+        date,
+        location.utc_offset_seconds,
+    );
+    (times.sunrise, times.sunset)
+}
+
+/// "06:12 (in 2h 5m)", or a note when the event doesn't happen today.
+fn sun_event(
+    time: Option<i64>,
+    now: i64,
+    report: &model::Report,
+    options: &DisplayOptions,
+) -> String {
+    match time {
+        Some(time) => format!(
+            "{} ({})",
+            options
+                .locale
+                .time(&local_datetime(time, report.location.utc_offset_seconds)),
+            clock::relative(now, time)
+        ),
+        None if is_night(report, options) => options.catalog.text("card.polar_night").to_string(),
+        None => options.catalog.text("card.midnight_sun").to_string(),
+    }
+}
+
+/// The place's elevation and where it comes from: `--elevation`, else the
+/// provider's ground-level pressure; `None` when neither gives it.
+fn elevation(report: &model::Report, options: &DisplayOptions) -> Option<(f64, &'static str)> {
+    let current = &report.current;
+    match (options.elevation, current.station_pressure_hpa) {
+        (Some(elevation), _) => Some((elevation, "given")),
+        (None, Some(ground)) => Some((
+            atmosphere::elevation_m(
+                f64::from(current.pressure_hpa),
+                ground,
+                current.temperature_c,
+            ),
+            "from the ground-level pressure",
+        )),
+        (None, None) => None,
+    }
+}
+
+/// Prints the `--performance` section: how dense the air is, and the
+/// altitudes that follow from it, from the report's derived metrics.
+fn display_performance(report: &model::Report, options: &DisplayOptions) {
+    let locale = &options.locale;
+    let derived = |key: &str| report.derived.get(key).copied();
+    let height = |metres: f64| {
+        let (value, _, unit) = options.units.height(metres);
+        locale.quantity(value, 0, unit)
+    };
+
+    let mut card = Card::new();
+    card.line(format!(
+        "{}{}",
+        options.icon("🏔️"),
+        "Performance".bright_green()
+    ));
+    card.line(match elevation(report, options) {
+        Some((elevation, source)) => format!("Elevation: {} ({})", height(elevation), source),
+        None => format!(
+            "Elevation: {}",
+            "unknown, sea level assumed (give --elevation)".yellow()
+        ),
+    });
+    if let Some(density) = derived("air_density") {
+        card.line(format!(
+            "{}Air density: {} ({} of standard)",
+            options.icon("🌬️"),
+            locale.quantity(density, 3, "kg/m³"),
+            locale.quantity(density / atmosphere::SEA_LEVEL_DENSITY * 100.0, 1, "%")
+        ));
+    }
+    if let Some(altitude) = derived("pressure_altitude") {
+        card.line(format!("Pressure altitude: {}", height(altitude)));
+    }
+    if let Some(altitude) = derived("density_altitude") {
+        card.line(format!("Density altitude: {}", height(altitude).bold()));
+    }
+    card.print();
+}
+
+/// The wind along and across `heading`, for the card's wind section, e.g.
+/// "Heading 270°: headwind 3.1 m/s, crosswind 1.2 m/s from the left".
+fn heading_line(current: &model::Conditions, heading: f64, options: &DisplayOptions) -> String {
+    let components = wind::components(
+        current.wind_speed_ms,
+        f64::from(current.wind_direction_deg),
+        heading,
+    );
+    let along = if components.head >= 0.0 {
+        format!(
+            "headwind {}",
+            options.quantity(options.units.speed(components.head))
+        )
+        .bright_red()
+    } else {
+        format!(
+            "tailwind {}",
+            options.quantity(options.units.speed(-components.head))
+        )
+        .bright_green()
+    };
+    format!(
+        "Heading {:.0}°: {}, crosswind {} from the {}",
+        heading,
+        along,
+        options.quantity(options.units.speed(components.cross.abs())),
+        if components.cross >= 0.0 {
+            "right"
+        } else {
+            "left"
+        }
+    )
+}
+
+/// Prints the `--ballistics` section: the conditions a ballistic solver
+/// asks for, and the wind across the line of fire given with `--heading`.
+fn display_ballistics(report: &model::Report, options: &DisplayOptions) {
+    let locale = &options.locale;
+    let current = &report.current;
+    let station = current.station_pressure_hpa;
+
+    let mut card = Card::new();
+    card.line(format!(
+        "{}{}",
+        options.icon("🎯"),
+        "Ballistics".bright_green()
+    ));
+    card.line(format!(
+        "Temperature: {}",
+        format_temperature(current.temperature_c, options)
+    ));
+    card.line(format!(
+        "Station pressure: {}{}",
+        options.quantity(
+            options
+                .units
+                .pressure(station.unwrap_or(f64::from(report.current.pressure_hpa)))
+        ),
+        if station.is_none() {
+            " (sea level assumed; give --elevation)"
+                .yellow()
+                .to_string()
+        } else {
+            String::new()
+        }
+    ));
+    card.line(format!(
+        "Humidity: {}",
+        locale.quantity(f64::from(current.humidity_percent), 0, "%")
+    ));
+    if let Some(&altitude) = report.derived.get("density_altitude") {
+        let (altitude, _, unit) = options.units.height(altitude);
+        card.line(format!(
+            "Density altitude: {}",
+            locale.quantity(altitude, 0, unit).bold()
+        ));
+    }
+    let from = f64::from(report.current.wind_direction_deg);
+    match options.heading {
+        Some(heading) => {
+            let components = wind::components(report.current.wind_speed_ms, from, heading);
+            let side = if components.cross >= 0.0 {
+                "right"
+            } else {
+                "left"
+            };
+            card.line(format!(
+                "Wind: {} from {} o'clock",
+                options.quantity(options.units.speed(report.current.wind_speed_ms)),
+                wind::clock(from, heading)
+            ));
+            card.line(format!(
+                "Crosswind: {} from the {}",
+                options
+                    .quantity(options.units.speed(components.cross.abs()))
+                    .bold(),
+                side
+            ));
+            card.line(format!(
+                "{}: {}",
+                if components.head >= 0.0 {
+                    "Headwind"
+                } else {
+                    "Tailwind"
+                },
+                options.quantity(options.units.speed(components.head.abs()))
+            ));
+        }
+        None => card.line(format!(
+            "Wind: {} from {:.0}° {}",
+            options.quantity(options.units.speed(report.current.wind_speed_ms)),
+            from,
+            "(give --heading for the crosswind)".dimmed()
+        )),
+    }
+    card.print();
+}
+
+fn display_astro(report: &model::Report, options: &DisplayOptions) {
+    let locale = &options.locale;
+    let (lat, lon) = (report.location.latitude, report.location.longitude);
+    let now = options.clock.now().timestamp();
+    let date = local_datetime(now, report.location.utc_offset_seconds).date_naive();
+    let time = |t: Option<i64>| {
+        t.map_or_else(
+            || "—".to_string(),
+            |t| locale.time(&local_datetime(t, report.location.utc_offset_seconds)),
+        )
+    };
+
+    let mut card = Card::new();
+    card.line(format!(
+        "{}{} ({})",
+        options.icon("🔭"),
+        "Sun & Moon".bright_green(),
+        options.date(&local_datetime(now, report.location.utc_offset_seconds))
+    ));
+
+    let sun = astro::sun_times(lat, lon, date, report.location.utc_offset_seconds);
+    card.line(format!(
+        "{}Solar elevation: {}",
+        options.icon("📐"),
+        locale.quantity(astro::solar_elevation(lat, lon, now), 1, "°")
+    ));
+    card.line(format!(
+        "{}Solar noon: {}",
+        options.icon("🌞"),
+        time(Some(sun.solar_noon))
+    ));
+    if let (Some(rise), Some(set)) = (sun.sunrise, sun.sunset) {
+        let minutes = (set - rise) / 60;
+        card.line(format!(
+            "{}Day length: {}h {}m",
+            options.icon("⏳"),
+            minutes / 60,
+            minutes % 60
+        ));
+    }
+    for (label, twilight, altitude) in [
+        ("Civil", sun.civil, astro::CIVIL_ALTITUDE),
+        ("Nautical", sun.nautical, astro::NAUTICAL_ALTITUDE),
+        (
+            "Astronomical",
+            sun.astronomical,
+            astro::ASTRONOMICAL_ALTITUDE,
+        ),
+    ] {
+        let span = match (twilight.dawn, twilight.dusk) {
+            (Some(dawn), Some(dusk)) => format!("{} – {}", time(Some(dawn)), time(Some(dusk))),
+            // The sun never gets that far below the horizon, or never that
+            // far above it.
+            _ if astro::solar_elevation(lat, lon, sun.solar_noon) > altitude => {
+                "lasts all night".to_string()
+            }
+            _ => "sun stays below".to_string(),
+        };
+        card.line(format!(
+            "{}{} twilight: {}",
+            options.icon("🌆"),
+            label,
+            span
+        ));
+    }
+
+    let phase = astro::moon_phase(now);
+    card.line(format!(
+        "{}Moon: {} ({} illuminated)",
+        options.icon(moon_emoji(phase.age)),
+        options.catalog.moon_phase(phase.name),
+        locale.quantity(phase.illumination * 100.0, 0, "%")
+    ));
+    let moon = astro::moon_times(lat, lon, date, report.location.utc_offset_seconds);
+    card.line(format!(
+        "{}Moonrise: {}  Moonset: {}",
+        options.icon("🌙"),
+        time(moon.moonrise),
+        time(moon.moonset)
+    ));
+
+    card.print();
+}
+
+fn moon_emoji(age: f64) -> &'static str {
+    const PHASES: [&str; 8] = ["🌑", "🌒", "🌓", "🌔", "🌕", "🌖", "🌗", "🌘"];
+    PHASES[((age * 8.0).round() as usize) % 8]
+}
+
+pub fn display_river(report: &river::RiverReport, options: &DisplayOptions) {
+    let locale = &options.locale;
+    let now = options.clock.now().timestamp();
+    let mut card = Card::new();
+
+    let name = match &report.river {
+        Some(river) => format!("{} at {}", river, report.station),
+        None => report.station.clone(),
+    };
+    card.line(format!(
+        "{}{} {}",
+        options.icon("🏞️"),
+        "River".bright_green(),
+        name.bold()
+    ));
+
+    match report.level {
+        Some(level) => {
+            let trend = match report.trend {
+                Some(change) if change.abs() >= 0.01 => format!(
+                    ", {} {} in 2h",
+                    if change > 0.0 { "rising" } else { "falling" },
+                    locale.quantity(change.abs(), 2, &report.unit)
+                ),
+                Some(_) => ", steady".to_string(),
+                None => String::new(),
+            };
+            let observed = report
+                .observed_at
+                .map_or_else(String::new, |t| format!(" ({})", clock::relative(now, t)));
+            card.line(format!(
+                "{}Level: {}{}{}",
+                options.icon("📏"),
+                locale.quantity(level, 2, &report.unit),
+                trend,
+                observed
+            ));
+            if let Some(high) = report.typical_high {
+                let note = locale.quantity(high, 2, &report.unit);
+                card.line(if level > high {
+                    format!("Above the typical range (up to {})", note)
+                        .bright_red()
+                        .to_string()
+                } else {
+                    format!("Typical range up to {}", note)
+                });
+            }
+        }
+        None => card.line("Level: no recent reading".to_string()),
+    }
+
+    if report.warnings.is_empty() {
+        card.line(format!("{}No flood warnings in force", options.icon("✅")));
+    }
+    for warning in &report.warnings {
+        card.line(format!(
+            "{}{}: {}",
+            options.icon("⚠️"),
+            warning.severity.bright_red().bold(),
+            warning.area
+        ));
+    }
+
+    card.print();
+}
+
+/// Width the text of an alert is wrapped to, and the most lines of it
+/// shown; `--json` has it in full.
+const ALERT_WIDTH: usize = 60;
+const ALERT_LINES: usize = 8;
+
+/// Prints the alerts in force at the place, worst first, and how many
+/// more the feed has for other parts of the surrounding area.
+pub fn display_alerts(
+    alerts: &[model::Alert],
+    elsewhere: usize,
+    report: &model::Report,
+    options: &DisplayOptions,
+) {
+    let mut card = Card::new();
+    let title = if alerts.is_empty() {
+        "Alerts".bright_green()
+    } else {
+        "Alerts".bright_red().bold()
+    };
+    card.line(format!("{}{}", options.icon("🚨"), title));
+
+    if alerts.is_empty() {
+        card.line(format!("{}No alerts in force here", options.icon("✅")));
+    }
+    let now = local_datetime(
+        options.clock.now().timestamp(),
+        report.location.utc_offset_seconds,
+    );
+    // Times today go without the date.
+    let when = |time: DateTime<Utc>| {
+        let time = local_datetime(time.timestamp(), report.location.utc_offset_seconds);
+        if time.date_naive() == now.date_naive() {
+            options.locale.time(&time)
+        } else {
+            format!("{} {}", options.date(&time), options.locale.time(&time))
+        }
+    };
+    for (i, alert) in alerts.iter().enumerate() {
+        if i > 0 {
+            card.rule();
+        }
+        let event = match alert.severity {
+            model::Severity::Extreme | model::Severity::Severe => alert.event.bright_red().bold(),
+            model::Severity::Moderate => alert.event.yellow().bold(),
+            _ => alert.event.bold(),
+        };
+        // An alert already in effect only needs its end.
+        let from = alert
+            .effective
+            .filter(|effective| effective.timestamp() > now.timestamp())
+            .map_or_else(String::new, |effective| {
+                format!(" from {}", when(effective))
+            });
+        let until = alert
+            .expires
+            .map_or_else(String::new, |expires| format!(" until {}", when(expires)));
+        card.line(format!("{}{}{}{}", options.icon("⚠️"), event, from, until));
+        if let Some(detail) = alert
+            .headline
+            .as_deref()
+            .or(Some(&alert.area))
+            .filter(|d| !d.is_empty())
+        {
+            card.line(detail.to_string());
+        }
+        if let Some(description) = &alert.description {
+            let mut lines = render::wrap(description, ALERT_WIDTH);
+            if lines.len() > ALERT_LINES {
+                lines.truncate(ALERT_LINES);
+                lines[ALERT_LINES - 1].push_str(if options.ascii { " ..." } else { " …" });
+            }
+            for line in lines {
+                card.line(line.dimmed().to_string());
+            }
+        }
+        let issuer = match &alert.sender {
+            Some(sender) => format!("{} via {}", sender, alert_source(&alert.source)),
+            None => alert_source(&alert.source).to_string(),
+        };
+        card.line(issuer.dimmed().to_string());
+    }
+    if elsewhere > 0 {
+        card.line(
+            format!(
+                "{} more for other parts of the area, not covering this location",
+                elsewhere
+            )
+            .dimmed()
+            .to_string(),
+        );
+    }
+
+    card.print();
+}
+
+/// Display name of an alert feed.
+fn alert_source(source: &str) -> &str {
+    match source {
+        "nws" => "the National Weather Service",
+        "meteoalarm" => "MeteoAlarm",
+        "openweathermap" => "OpenWeatherMap",
+        "cap" => "a CAP feed",
+        other => other,
+    }
+}
+
+/// Prints which station the provider answered with and how far it is from
+/// the point asked for, which explains most differences from a thermometer
+/// at home.
+pub fn display_source(report: &model::Report, place: &Place, options: &DisplayOptions) {
+    let source = &report.source;
+    let mut card = Card::new();
+    card.line(format!(
+        "{}{}",
+        options.icon("📡"),
+        "Data source".bright_green()
+    ));
+    card.line(format!("Provider: {}", provider_name(&source.provider)));
+    match (&source.name, &source.station) {
+        (Some(name), Some(id)) => card.line(format!(
+            "Station: {} (id {})",
+            render::isolate(name).bold(),
+            id
+        )),
+        (Some(name), None) => card.line(format!("Station: {}", render::isolate(name).bold())),
+        (None, Some(id)) => card.line(format!("Station: {}", id)),
+        (None, None) => {}
+    }
+    match (source.latitude, source.longitude) {
+        (Some(lat), Some(lon)) => {
+            let distance = weather_core::geo::distance_m(place.lat, place.lon, lat, lon);
+            card.line(format!("Position: {:.4}, {:.4}", lat, lon));
+            card.line(format!(
+                "Requested: {:.4}, {:.4} ({} away)",
+                place.lat,
+                place.lon,
+                options.quantity(options.units.distance(distance))
+            ));
+        }
+        _ => card.line(format!("Requested: {:.4}, {:.4}", place.lat, place.lon)),
+    }
+    card.line(
+        "Values are for the station or model grid point, not the exact address"
+            .dimmed()
+            .to_string(),
+    );
+    card.print();
+}
+
+/// Display name of a report's provider.
+fn provider_name(provider: &str) -> &str {
+    match provider {
+        "openweathermap" => "OpenWeatherMap",
+        "open-meteo" => "Open-Meteo",
+        other => other,
+    }
+}
+
+/// Prints the `--hyperlocal` estimates with their spread over the grid
+/// points, next to the provider's single-point values.
+pub fn display_hyperlocal(
+    hyperlocal: &model::Hyperlocal,
+    current: &model::Conditions,
+    options: &DisplayOptions,
+) {
+    let locale = &options.locale;
+    let (coldest, warmest) = hyperlocal::spread(hyperlocal, |point| point.temperature_c);
+    let (driest, wettest) = hyperlocal::spread(hyperlocal, |point| point.precipitation_mm);
+
+    let mut card = Card::new();
+    card.line(format!(
+        "{}{} ({} grid points)",
+        options.icon("📍"),
+        "Hyperlocal".bright_green(),
+        hyperlocal.points.len()
+    ));
+    card.line(format!(
+        "Temperature: {} (spread {} to {}, provider {})",
+        format_temperature(hyperlocal.temperature_c, options).bold(),
+        format_temperature(coldest, options),
+        format_temperature(warmest, options),
+        format_temperature(current.temperature_c, options)
+    ));
+    card.line(format!(
+        "Precipitation: {} in the last hour (spread {} to {})",
+        options
+            .quantity(options.units.precipitation(hyperlocal.precipitation_mm))
+            .bold(),
+        {
+            let (driest, decimals, _) = options.units.precipitation(driest);
+            locale.number(driest, decimals)
+        },
+        options.quantity(options.units.precipitation(wettest))
+    ));
+    card.line(
+        "Weighted by distance; a wide spread means conditions change quickly nearby"
+            .dimmed()
+            .to_string(),
+    );
+    card.print();
+}
+
+/// Prints the `--pws` readings next to the provider's, flagging values
+/// that differ by more than the usual spread.
+pub fn display_station(
+    local: &model::LocalObservation,
+    current: &model::Conditions,
+    options: &DisplayOptions,
+) {
+    let (locale, units) = (&options.locale, options.units);
+    let mut card = Card::new();
+    let observed = local.observed_at.map_or_else(String::new, |t| {
+        format!(
+            " ({})",
+            clock::relative(options.clock.now().timestamp(), t.timestamp())
+        )
+    });
+    card.line(format!(
+        "{}{} {}{}",
+        options.icon("🏡"),
+        "Your station".bright_green(),
+        local.source.bold(),
+        observed.dimmed()
+    ));
+
+    let comparisons = station::compare(local, current);
+    for comparison in &comparisons {
+        let (station, provider, difference) = match comparison.field {
+            "temperature_c" => (
+                format_temperature(comparison.station, options),
+                format_temperature(comparison.provider, options),
+                options.quantity(units.temperature_difference(comparison.difference())),
+            ),
+            "humidity_percent" => (
+                locale.quantity(comparison.station, 0, "%"),
+                locale.quantity(comparison.provider, 0, "%"),
+                locale.quantity(comparison.difference(), 0, "%"),
+            ),
+            "pressure_hpa" => (
+                options.quantity(units.pressure(comparison.station)),
+                options.quantity(units.pressure(comparison.provider)),
+                options.quantity(units.pressure(comparison.difference())),
+            ),
+            _ => (
+                options.quantity(units.speed(comparison.station)),
+                options.quantity(units.speed(comparison.provider)),
+                options.quantity(units.speed(comparison.difference())),
+            ),
+        };
+        let sign = if comparison.difference() > 0.0 {
+            "+"
+        } else {
+            ""
+        };
+        let line = format!(
+            "{}: {} (provider {}, {}{})",
+            comparison.label, station, provider, sign, difference
+        );
+        card.line(if comparison.discrepant {
+            format!("{}{}", options.icon("⚠️"), line.yellow().bold())
+        } else {
+            line
+        });
+    }
+    if let Some(gust) = local.wind_gust_ms {
+        card.line(format!("Gusts: {}", options.quantity(units.speed(gust))));
+    }
+    if let Some(rate) = local.rain_rate_mm_h.filter(|rate| *rate > 0.0) {
+        card.line(format!("Rain rate: {}", locale.quantity(rate, 1, "mm/h")));
+    }
+    if comparisons.is_empty() {
+        card.line(
+            "The station reports nothing to compare"
+                .dimmed()
+                .to_string(),
+        );
+    } else if comparisons.iter().any(|c| c.discrepant) {
+        card.line(
+            "Large differences: check the sensor's siting or trust the station for here"
+                .dimmed()
+                .to_string(),
+        );
+    }
+    card.print();
+}
+
+pub fn display_roads(advisory: &roads::Advisory, report: &model::Report, options: &DisplayOptions) {
+    let risk = |assessment: &roads::Assessment| {
+        let label = assessment.risk.label();
+        let label = match assessment.risk {
+            roads::IceRisk::High => label.bright_red().bold(),
+            roads::IceRisk::Moderate => label.yellow().bold(),
+            roads::IceRisk::Low => label.green(),
+        };
+        format!("{} — {}", label, assessment.reason)
+    };
+
+    let mut card = Card::new();
+    card.line(format!(
+        "{}{}",
+        options.icon("🚗"),
+        "Road conditions".bright_green()
+    ));
+    card.line(format!("Ice risk now: {}", risk(&advisory.now)));
+    match &advisory.overnight {
+        Some(overnight) => card.line(format!(
+            "Overnight: {} ({})",
+            risk(overnight),
+            options.locale.time(&local_datetime(
+                overnight.at,
+                report.location.utc_offset_seconds
+            ))
+        )),
+        None => card.line("Overnight: no forecast available".dimmed().to_string()),
+    }
+    card.print();
+}
+
+pub fn display_simple(report: &model::Report, options: &DisplayOptions) {
+    let (temp, _, unit) = options.units.temperature(report.current.temperature_c);
+    let icon = condition_icon(report, options);
+    let recommendation = recommend::recommend(&report.current);
+
+    println!();
+    println!("  {}", icon);
+    println!();
+    for row in render::big_text(&format!("{}{}", temp.round() as i64, unit)) {
+        println!(
+            "  {}",
+            options.highlight(&report.current, "temperature", &row, |t| t.bright_yellow())
+        );
+    }
+    println!();
+    println!("  {}", recommendation.sentence().bold());
+}
+
+/// `--output compact`: e.g. `☀️ 21.3°C London (feels 19°)`.
+pub fn compact_line(report: &model::Report, place: &Place, options: &DisplayOptions) -> String {
+    let (feels_like, _, unit) = options.units.temperature(report.current.feels_like_c);
+    // Kelvin are not degrees.
+    let degrees = if unit == "K" { "\u{a0}K" } else { "°" };
+    let temperature = format_temperature(report.current.temperature_c, options);
+    format!(
+        "{} {} {} (feels {}{})",
+        condition_icon(report, options),
+        options.highlight(&report.current, "temperature", &temperature, |t| t.normal()),
+        place.english_name(),
+        options.locale.number(feels_like, 0),
+        degrees
+    )
+}
+
+fn display_explanations(current: &model::Conditions, options: &DisplayOptions) {
+    println!("\n{}", "What these numbers mean".bold());
+    for entry in glossary::ENTRIES {
+        if let Some(context) = glossary::context(entry, current, options) {
+            println!(
+                "  {} {}",
+                format!("{}:", entry.title).bright_cyan(),
+                context
+            );
+        }
+    }
+}
+
+/// The non-zero offsets of `calibration`, e.g. `temperature -1.5 °C`.
+fn calibration_summary(calibration: &calibration::Calibration, options: &DisplayOptions) -> String {
+    let locale = &options.locale;
+    [
+        (
+            calibration.temp_offset,
+            "temperature",
+            options.quantity(
+                options
+                    .units
+                    .temperature_difference(calibration.temp_offset),
+            ),
+        ),
+        (
+            calibration.humidity_offset,
+            "humidity",
+            locale.quantity(calibration.humidity_offset, 0, "%"),
+        ),
+        (
+            calibration.pressure_offset,
+            "pressure",
+            options.quantity(options.units.pressure(calibration.pressure_offset)),
+        ),
+    ]
+    .into_iter()
+    .filter(|(offset, _, _)| *offset != 0.0)
+    .map(|(offset, label, text)| {
+        let sign = if offset > 0.0 { "+" } else { "" };
+        format!("{} {}{}", label, sign, text)
+    })
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+pub fn condition_icon(report: &model::Report, options: &DisplayOptions) -> &'static str {
+    let condition = &report.current.condition.kind;
+    let night = is_night(report, options);
+    if options.ascii {
+        get_weather_ascii(condition, night)
+    } else {
+        get_weather_emoji(condition, night)
+    }
+}
+
+/// A condition kind as a heading: "Thunderstorm".
+pub fn condition_name(kind: &str) -> String {
+    let mut chars = kind.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// The greeting and local time at the place, and when the data was
+/// observed: "Observed 12m ago at 14:03:00 local".
+fn context_header(report: &model::Report, now: i64, options: &DisplayOptions) -> [String; 2] {
+    let catalog = &options.catalog;
+    let local = local_datetime(now, report.location.utc_offset_seconds);
+    let greeting = match local.hour() {
+        5..=11 => "header.morning",
+        12..=17 => "header.afternoon",
+        18..=21 => "header.evening",
+        _ => "header.night",
+    };
+    let (icon, period) = if is_night(report, options) {
+        ("🌙", "header.night_indicator")
+    } else {
+        ("☀️", "header.day_indicator")
+    };
+    let time = options.locale.time(&local);
+    let date = options.locale.date(&local);
+    let local_time = catalog.format(
+        "header.local_time",
+        &[
+            ("greeting", catalog.text(greeting)),
+            ("time", &time),
+            ("date", &date),
+            ("period", catalog.text(period)),
+        ],
+    );
+
+    let observed_time = options.locale.time(&local_datetime(
+        report.observed_at.timestamp(),
+        report.location.utc_offset_seconds,
+    ));
+    let age = now - report.observed_at.timestamp();
+    let observed = if age < 60 {
+        catalog.format("header.observed_now", &[("time", &observed_time)])
+    } else {
+        catalog.format(
+            "header.observed",
+            &[("ago", &clock::span(age)), ("time", &observed_time)],
+        )
+    };
+    let observed = if options.is_stale(age) {
+        format!(
+            "{}{}",
+            options.icon("⚠️"),
+            catalog.format("header.stale", &[("observed", &observed)])
+        )
+        .yellow()
+        .bold()
+        .to_string()
+    } else {
+        observed.dimmed().to_string()
+    };
+    [format!("{}{}", options.icon(icon), local_time), observed]
+}
+
+fn is_night(report: &model::Report, options: &DisplayOptions) -> bool {
+    let now = options.clock.now().timestamp();
+    match (report.current.sunrise, report.current.sunset) {
+        (Some(sunrise), Some(sunset)) => {
+            !clock::is_daytime(now, sunrise.timestamp(), sunset.timestamp())
+        }
+        _ => {
+            let location = &report.location;
+            astro::solar_elevation(location.latitude, location.longitude, now) < -0.833
+        }
+    }
+}
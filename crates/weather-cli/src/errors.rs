@@ -0,0 +1,249 @@
+//! Error reports: what failed, the chain of causes behind it, and a hint
+//! at the likely fix.
+//!
+//! On a terminal the chain is drawn as a tree; piped or with
+//! `WEATHER_ASCII=1` it stays on one line, so logs remain greppable. API
+//! keys in request URLs are masked either way.
+//!
+//! The exit code tells scripts what kind of failure it was; see
+//! [`exit_code`].
+//!
+//! ```text
+//! Error: Failed to look up London
+//!   ├─▶ error sending request for url (https://api.openweathermap.org/…)
+//!   ╰─▶ dns error: failed to lookup address information
+//!   help: Is your network up? The server's name could not be resolved.
+//! ```
+
+use crate::platform;
+use colored::Colorize;
+use std::error::Error as StdError;
+use std::fmt;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+use weather_providers::WeatherError;
+
+/// Set by `--ascii`: no tree and no arrows.
+static PLAIN: AtomicBool = AtomicBool::new(false);
+
+pub type Error = Box<dyn StdError>;
+
+/// An error together with what was being attempted when it happened.
+#[derive(Debug)]
+pub struct Context {
+    message: String,
+    source: Error,
+}
+
+impl fmt::Display for Context {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl StdError for Context {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&*self.source)
+    }
+}
+
+pub trait ResultExt<T> {
+    /// Wraps the error with `message`, keeping it as the cause.
+    fn context(self, message: impl Into<String>) -> Result<T, Error>;
+
+    /// [`ResultExt::context`] with a message only built on failure.
+    fn with_context(self, message: impl FnOnce() -> String) -> Result<T, Error>;
+}
+
+impl<T, E: Into<Error>> ResultExt<T> for Result<T, E> {
+    fn context(self, message: impl Into<String>) -> Result<T, Error> {
+        self.with_context(|| message.into())
+    }
+
+    fn with_context(self, message: impl FnOnce() -> String) -> Result<T, Error> {
+        self.map_err(|e| {
+            Box::new(Context {
+                message: message(),
+                source: e.into(),
+            }) as Error
+        })
+    }
+}
+
+/// The messages of `error` and its causes, outermost first. Many errors
+/// (reqwest's among them) repeat their cause after a colon; that copy is
+/// dropped so each cause is shown once.
+pub fn chain(error: &(dyn StdError + 'static)) -> Vec<String> {
+    let mut messages = Vec::new();
+    let mut current = Some(error);
+    while let Some(error) = current {
+        let message = error.to_string();
+        current = error.source();
+        // Wrappers that show their cause's message, such as
+        // `WeatherError::Network`, leave it to the cause.
+        if current.is_some_and(|cause| cause.to_string() == message) {
+            continue;
+        }
+        let message = match current.map(|cause| format!(": {}", cause)) {
+            Some(suffix) if message.ends_with(&suffix) => {
+                message[..message.len() - suffix.len()].to_string()
+            }
+            _ => message,
+        };
+        let message = redact(&message);
+        if messages.last() != Some(&message) {
+            messages.push(message);
+        }
+    }
+    messages
+}
+
+/// Query parameters carrying credentials, as they appear in request URLs.
+const SECRET_PARAMETERS: [&str; 4] = ["appid=", "key=", "apiKey=", "applicationKey="];
+
+/// `message` with the values of credential parameters in URLs hidden.
+fn redact(message: &str) -> String {
+    let mut redacted = String::with_capacity(message.len());
+    let mut rest = message;
+    while let Some(start) = rest.find(['?', '&']) {
+        redacted.push_str(&rest[..=start]);
+        rest = &rest[start + 1..];
+        if let Some(name) = SECRET_PARAMETERS.iter().find(|p| rest.starts_with(*p)) {
+            let end = rest.find(['&', ')', ' ']).unwrap_or(rest.len());
+            redacted.push_str(name);
+            redacted.push_str("***");
+            rest = &rest[end..];
+        }
+    }
+    redacted.push_str(rest);
+    redacted
+}
+
+/// A likely fix for `error`, judged from its causes.
+pub fn hint(error: &(dyn StdError + 'static)) -> Option<&'static str> {
+    let mut current = Some(error);
+    while let Some(error) = current {
+        if let Some(e) = error.downcast_ref::<reqwest::Error>() {
+            if e.is_timeout() {
+                return Some("The service did not answer in time. Try again shortly.");
+            }
+            if e.is_connect() {
+                return Some(if chain(e).iter().any(|m| m.contains("dns error")) {
+                    "Is your network up? The server's name could not be resolved."
+                } else {
+                    "Is your network up? Check any proxy or firewall in between."
+                });
+            }
+            if e.is_decode() {
+                return Some(UNEXPECTED_RESPONSE);
+            }
+        }
+        if error.is::<serde_json::Error>() && error.source().is_none() {
+            return Some(UNEXPECTED_RESPONSE);
+        }
+        if let Some(e) = error.downcast_ref::<std::io::Error>() {
+            if e.kind() == std::io::ErrorKind::PermissionDenied {
+                return Some("Check the permissions of the file or directory.");
+            }
+        }
+        match error.downcast_ref::<WeatherError>() {
+            Some(WeatherError::InvalidApiKey { .. }) => {
+                return Some("Check the API key, e.g. OPEN_WEATHER_MAP_API in your .env file.")
+            }
+            Some(WeatherError::RateLimited { .. }) => {
+                return Some("The service is rate limiting requests. Wait a minute and try again.")
+            }
+            _ => {}
+        }
+        current = error.source();
+    }
+    None
+}
+
+const UNEXPECTED_RESPONSE: &str =
+    "The service answered in an unexpected format; it may have changed its API.";
+
+/// The exit code of errors of no other kind: a bad command line, config or
+/// file. Codes 1 to 3 are answers, not failures: the severity for
+/// `--exit-status` and the Nagios check, and the "no" of `will-it-rain`
+/// and `notify`.
+pub const OTHER: i32 = 10;
+
+/// The exit code for `error`, from the first of its causes that is a
+/// [`WeatherError`], else [`OTHER`].
+pub fn exit_code(error: &(dyn StdError + 'static)) -> i32 {
+    let mut current = Some(error);
+    while let Some(error) = current {
+        if let Some(e) = error.downcast_ref::<WeatherError>() {
+            return match e {
+                WeatherError::CityNotFound(_) | WeatherError::NoData(_) => 4,
+                WeatherError::InvalidApiKey { .. } => 5,
+                WeatherError::RateLimited { .. } => 6,
+                WeatherError::Network(_) => 7,
+                WeatherError::Decode(_) => 8,
+                WeatherError::Status { .. } => 9,
+                WeatherError::Middleware(_) | WeatherError::Cancelled(_) => OTHER,
+            };
+        }
+        current = error.source();
+    }
+    OTHER
+}
+
+/// [`print`]s `error` and exits with its [`exit_code`].
+pub fn exit(error: impl Into<Error>) -> ! {
+    let error = error.into();
+    let code = exit_code(&*error);
+    print(error);
+    std::process::exit(code)
+}
+
+/// Exits for a command line clap rejected: successfully for `--help` and
+/// `--version`, else with clap's message and [`OTHER`].
+pub fn exit_usage(error: structopt::clap::Error) -> ! {
+    use structopt::clap::ErrorKind;
+    match error.kind {
+        ErrorKind::HelpDisplayed | ErrorKind::VersionDisplayed => error.exit(),
+        _ => {
+            eprintln!("{}", error.message);
+            std::process::exit(OTHER)
+        }
+    }
+}
+
+/// Keeps reports to plain ASCII, as for `--ascii`.
+pub fn set_plain(plain: bool) {
+    PLAIN.store(plain, Ordering::Relaxed);
+}
+
+/// Prints `error` with its causes and a hint to stderr.
+pub fn print(error: impl Into<Error>) {
+    let error = error.into();
+    let messages = chain(&*error);
+    let hint = hint(&*error);
+    let unicode = !PLAIN.load(Ordering::Relaxed) && platform::supports_emoji();
+    let graphical = unicode && std::io::stderr().is_terminal();
+
+    let (first, causes) = messages.split_first().expect("chain includes the error");
+    if graphical {
+        eprintln!("{} {}", "Error:".bright_red(), first);
+        for (i, cause) in causes.iter().enumerate() {
+            let branch = if i + 1 == causes.len() {
+                "╰─▶"
+            } else {
+                "├─▶"
+            };
+            eprintln!("  {} {}", branch.dimmed(), cause);
+        }
+    } else {
+        let mut line = first.clone();
+        for cause in causes {
+            line.push_str(if unicode { " → " } else { ": " });
+            line.push_str(cause);
+        }
+        eprintln!("{} {}", "Error:".bright_red(), line);
+    }
+    if let Some(hint) = hint {
+        eprintln!("  {} {}", "help:".cyan(), hint);
+    }
+}
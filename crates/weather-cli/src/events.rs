@@ -0,0 +1,85 @@
+//! `weather events`: the upcoming events of an iCalendar file, each with
+//! the forecast where and when it happens. The place is the event's `GEO`
+//! coordinates, else its location looked up by name; the forecast is taken
+//! at the middle of the event, or of its first day when it runs longer.
+
+use crate::gpx::{self, Leg};
+use crate::ics::Event;
+use serde::Serialize;
+use weather_providers::route::{Point, PointForecast};
+
+/// The longest stretch of an event its forecast is taken over, in seconds.
+const LONGEST_S: i64 = 86_400;
+
+/// Names to look an event's location up by, most specific first: the
+/// whole text, then without its leading parts, as in `City Park,
+/// Springfield` and `Springfield`.
+pub fn place_names(location: &str) -> Vec<String> {
+    let parts: Vec<&str> = location
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect();
+    (0..parts.len()).map(|i| parts[i..].join(", ")).collect()
+}
+
+/// The weather over an event. Temperatures are in °C, wind in m/s.
+#[derive(Debug, Clone, Serialize)]
+pub struct EventWeather {
+    pub temperature_c: f64,
+    /// 0.0 to 1.0.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub precipitation_probability: Option<f64>,
+    /// Over the hour.
+    pub precipitation_mm: f64,
+    pub condition: &'static str,
+    pub wind_speed_ms: f64,
+    pub wind_from_deg: f64,
+}
+
+/// An event with where it was found and its forecast. Times are Unix
+/// timestamps.
+#[derive(Debug, Clone, Serialize)]
+pub struct Annotated {
+    pub summary: String,
+    pub location: String,
+    pub all_day: bool,
+    pub start: i64,
+    pub end: i64,
+    /// The place the location was found as; `None` when it wasn't.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub place: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weather: Option<EventWeather>,
+}
+
+/// The weather over `event` at `point`, from the forecast there, with
+/// when the event starts and ends; the weather is `None` beyond the
+/// forecast.
+pub fn weather(
+    event: &Event,
+    point: Point,
+    forecast: &PointForecast,
+) -> (i64, i64, Option<EventWeather>) {
+    let offset = forecast.utc_offset_seconds;
+    let (start, end) = (event.start.timestamp(offset), event.end_timestamp(offset));
+    let leg = Leg {
+        from_s: 0,
+        to_s: (end - start).clamp(0, LONGEST_S),
+        from_m: 0.0,
+        to_m: 0.0,
+        lat: point.lat,
+        lon: point.lon,
+        elevation_m: point.elevation,
+        heading_deg: None,
+    };
+    let weather = gpx::weather(&leg, start, forecast).map(|w| EventWeather {
+        temperature_c: w.temperature_c,
+        precipitation_probability: w.precipitation_probability,
+        precipitation_mm: w.precipitation_mm,
+        condition: w.condition,
+        wind_speed_ms: w.wind_speed_ms,
+        wind_from_deg: w.wind_from_deg,
+    });
+    (start, end, weather)
+}
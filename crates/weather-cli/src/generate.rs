@@ -0,0 +1,106 @@
+//! `weather generate`: packaging artifacts produced from the CLI definition
+//! itself, so they never drift from the actual flags.
+
+use crate::Opt;
+use std::fs;
+use std::path::Path;
+use structopt::clap::Shell;
+use structopt::StructOpt;
+
+const BIN_NAME: &str = "weather";
+
+/// Writes shell completion scripts for `shells` (all supported shells when
+/// empty) into `out`.
+pub fn completions(out: &Path, shells: &[Shell]) -> std::io::Result<()> {
+    fs::create_dir_all(out)?;
+    let shells = if shells.is_empty() {
+        vec![
+            Shell::Bash,
+            Shell::Zsh,
+            Shell::Fish,
+            Shell::PowerShell,
+            Shell::Elvish,
+        ]
+    } else {
+        shells.to_vec()
+    };
+
+    for shell in shells {
+        Opt::clap().gen_completions(BIN_NAME, shell, out);
+    }
+    println!("Wrote completions to {}", out.display());
+    Ok(())
+}
+
+/// Writes a `weather.1` man page into `out`, built from the long help text.
+pub fn man(out: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    fs::create_dir_all(out)?;
+
+    let mut help = Vec::new();
+    Opt::clap().write_long_help(&mut help)?;
+    let help = String::from_utf8_lossy(&help);
+
+    let mut page = String::new();
+    page.push_str(&format!(
+        ".TH WEATHER 1 \"\" \"weather {}\" \"User Commands\"\n",
+        env!("CARGO_PKG_VERSION")
+    ));
+    page.push_str(".SH NAME\nweather \\- a weather CLI application\n");
+    page.push_str(".SH SYNOPSIS\n.B weather\n[\\fIOPTIONS\\fR] [\\fISUBCOMMAND\\fR]\n");
+    page.push_str(".SH DESCRIPTION\n");
+    page.push_str(
+        "Shows current weather, summaries and forecasts for a city. Without \\fB\\-\\-city\\fR \
+         it runs interactively. The OpenWeatherMap API key is read from the \
+         \\fBOPEN_WEATHER_MAP_API\\fR environment variable or a \\fI.env\\fR file.\n",
+    );
+
+    // Turn the FLAGS/OPTIONS/SUBCOMMANDS sections of the help into tagged
+    // paragraphs: one `.TP` per flag or subcommand, followed by its text.
+    let mut section: Option<&str> = None;
+    for line in help.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if !line.starts_with(' ') && trimmed.ends_with(':') {
+            let heading = trimmed.trim_end_matches(':');
+            // The usage line is already covered by SYNOPSIS.
+            section = (heading != "USAGE").then_some(heading);
+            if let Some(heading) = section {
+                page.push_str(&format!(".SH {}\n", heading));
+            }
+            continue;
+        }
+        let Some(section) = section else {
+            continue;
+        };
+
+        if trimmed.starts_with('-') || section == "SUBCOMMANDS" {
+            let (term, description) = trimmed.split_once("  ").unwrap_or((trimmed, ""));
+            page.push_str(&format!(".TP\n\\fB{}\\fR\n", roff_escape(term.trim())));
+            if !description.trim().is_empty() {
+                page.push_str(&roff_escape(description.trim()));
+                page.push('\n');
+            }
+        } else {
+            page.push_str(&roff_escape(trimmed));
+            page.push('\n');
+        }
+    }
+
+    page.push_str(".SH ENVIRONMENT\n.TP\n.B OPEN_WEATHER_MAP_API\nOpenWeatherMap API key.\n");
+
+    let path = out.join("weather.1");
+    fs::write(&path, page)?;
+    println!("Wrote {}", path.display());
+    Ok(())
+}
+
+fn roff_escape(line: &str) -> String {
+    let escaped = line.replace('\\', "\\\\").replace('-', "\\-");
+    if escaped.starts_with('.') || escaped.starts_with('\'') {
+        format!("\\&{}", escaped)
+    } else {
+        escaped
+    }
+}
@@ -0,0 +1,239 @@
+use crate::{format_temperature, DisplayOptions};
+use weather_core::metrics::dew_point;
+use weather_core::model::Conditions;
+
+/// A glossary entry describing one of the displayed weather metrics.
+pub struct Entry {
+    pub key: &'static str,
+    pub aliases: &'static [&'static str],
+    pub title: &'static str,
+    pub description: &'static str,
+    pub link: &'static str,
+}
+
+pub const ENTRIES: &[Entry] = &[
+    Entry {
+        key: "temperature",
+        aliases: &["temp"],
+        title: "Temperature",
+        description: "Air temperature measured in the shade about 2 m above the ground.",
+        link: "https://en.wikipedia.org/wiki/Temperature",
+    },
+    Entry {
+        key: "feels-like",
+        aliases: &["feels_like", "apparent", "wind-chill", "heat-index"],
+        title: "Feels like",
+        description: "How warm or cold it feels on skin: wind makes cold air feel colder \
+                      (wind chill) and humidity makes warm air feel hotter (heat index).",
+        link: "https://en.wikipedia.org/wiki/Apparent_temperature",
+    },
+    Entry {
+        key: "dew-point",
+        aliases: &["dew_point", "dewpoint"],
+        title: "Dew point",
+        description: "The temperature to which air must cool for dew to form. It measures \
+                      how much moisture is in the air, and is a better guide to how muggy \
+                      it feels than relative humidity.",
+        link: "https://en.wikipedia.org/wiki/Dew_point",
+    },
+    Entry {
+        key: "humidity",
+        aliases: &["relative-humidity", "rh"],
+        title: "Relative humidity",
+        description: "How close the air is to saturation, as a percentage. 100% means the \
+                      air holds as much water vapour as it can at the current temperature.",
+        link: "https://en.wikipedia.org/wiki/Humidity",
+    },
+    Entry {
+        key: "pressure",
+        aliases: &["air-pressure", "barometer"],
+        title: "Air pressure",
+        description: "The weight of the air above, reduced to sea level so places can be \
+                      compared. High pressure usually brings settled weather, low pressure \
+                      brings clouds, wind and rain.",
+        link: "https://en.wikipedia.org/wiki/Atmospheric_pressure",
+    },
+    Entry {
+        key: "pressure-tendency",
+        aliases: &["pressure_tendency", "tendency"],
+        title: "Pressure tendency",
+        description: "How pressure changed over the last three hours. Falling pressure \
+                      often means an approaching storm; rising pressure means clearing \
+                      weather. It takes observations over time, so a single reading can't \
+                      show it.",
+        link: "https://en.wikipedia.org/wiki/Atmospheric_pressure#Pressure_tendency",
+    },
+    Entry {
+        key: "wind",
+        aliases: &["wind-speed", "direction"],
+        title: "Wind",
+        description: "Average wind speed over the last few minutes, and the compass \
+                      direction it blows from (0° = from the north, 90° = from the east).",
+        link: "https://en.wikipedia.org/wiki/Beaufort_scale",
+    },
+    Entry {
+        key: "gust",
+        aliases: &["gusts"],
+        title: "Wind gusts",
+        description: "Brief peaks in wind speed lasting a few seconds. Gusts are what knock \
+                      over bins and bikes, so they matter more than the average.",
+        link: "https://en.wikipedia.org/wiki/Wind_gust",
+    },
+    Entry {
+        key: "visibility",
+        aliases: &[],
+        title: "Visibility",
+        description: "The distance at which objects can be clearly seen. Fog, mist, haze, \
+                      heavy rain and snow reduce it.",
+        link: "https://en.wikipedia.org/wiki/Visibility",
+    },
+    Entry {
+        key: "cloudiness",
+        aliases: &["clouds", "cloud-cover"],
+        title: "Cloudiness",
+        description: "The share of the sky covered by clouds.",
+        link: "https://en.wikipedia.org/wiki/Cloud_cover",
+    },
+];
+
+/// Finds an entry by key or alias, ignoring case.
+pub fn lookup(name: &str) -> Option<&'static Entry> {
+    let name = name.to_lowercase().replace(' ', "-");
+    ENTRIES
+        .iter()
+        .find(|entry| entry.key == name || entry.aliases.contains(&name.as_str()))
+}
+
+/// Describes what the current value of the entry's metric means, if the
+/// metric can be judged from a single observation.
+pub fn context(entry: &Entry, current: &Conditions, options: &DisplayOptions) -> Option<String> {
+    let context = match entry.key {
+        "temperature" => {
+            let feel = match current.temperature_c {
+                t if t < 0.0 => "below freezing — watch for ice",
+                t if t < 10.0 => "cold — a warm coat is a good idea",
+                t if t < 18.0 => "cool — bring a jacket",
+                t if t < 25.0 => "mild and comfortable",
+                t if t < 32.0 => "warm",
+                _ => "hot — drink plenty of water and avoid the midday sun",
+            };
+            format!(
+                "It is {} now, which is {}.",
+                format_temperature(current.temperature_c, options),
+                feel
+            )
+        }
+        "feels-like" => {
+            let difference = current.feels_like_c - current.temperature_c;
+            let reason = if difference <= -1.0 {
+                "colder than the thermometer says, mostly because of the wind"
+            } else if difference >= 1.0 {
+                "warmer than the thermometer says, mostly because of the humidity"
+            } else {
+                "about the same as the actual temperature"
+            };
+            format!(
+                "It feels like {}: {}.",
+                format_temperature(current.feels_like_c, options),
+                reason
+            )
+        }
+        "dew-point" => {
+            let dew = dew_point(current.temperature_c, current.humidity_percent as f64);
+            let feel = match dew {
+                d if d < 10.0 => "dry",
+                d if d < 16.0 => "comfortable",
+                d if d < 18.0 => "slightly humid",
+                d if d < 21.0 => "muggy",
+                d if d < 24.0 => "oppressive",
+                _ => "extremely humid and uncomfortable",
+            };
+            format!(
+                "The dew point is {}, which feels {}.",
+                format_temperature(dew, options),
+                feel
+            )
+        }
+        "humidity" => {
+            let feel = match current.humidity_percent {
+                h if h < 30 => "dry — skin and throat may feel it",
+                h if h <= 60 => "comfortable",
+                h if h <= 80 => "humid",
+                _ => "very humid — fog or rain is likely",
+            };
+            format!(
+                "Humidity is {}%, which is {}.",
+                current.humidity_percent, feel
+            )
+        }
+        "pressure" => {
+            let feel = match current.pressure_hpa {
+                p if p < 1000 => "low — expect unsettled weather",
+                p if p <= 1020 => "normal",
+                _ => "high — expect settled weather",
+            };
+            format!(
+                "Pressure is {}, which is {}.",
+                options.quantity(options.units.pressure(current.pressure_hpa as f64)),
+                feel
+            )
+        }
+        "wind" => format!(
+            "{} is a {} on the Beaufort scale.",
+            options.quantity(options.units.speed(current.wind_speed_ms)),
+            beaufort_description(current.wind_speed_ms)
+        ),
+        "gust" => {
+            let gust = current.wind_gust_ms?;
+            format!(
+                "Gusts reach {}, a {} at their peak.",
+                options.quantity(options.units.speed(gust)),
+                beaufort_description(gust)
+            )
+        }
+        "visibility" => {
+            let km = current.visibility_m as f64 / 1000.0;
+            let feel = match km {
+                v if v < 1.0 => "fog — drive with low-beam lights",
+                v if v < 4.0 => "mist or haze",
+                v if v < 10.0 => "moderate",
+                _ => "good",
+            };
+            format!(
+                "Visibility is {}, which is {}.",
+                options.quantity(options.units.distance(current.visibility_m as f64)),
+                feel
+            )
+        }
+        "cloudiness" => {
+            let feel = match current.cloud_cover_percent {
+                c if c < 12 => "clear",
+                c if c < 37 => "a few clouds",
+                c if c < 62 => "partly cloudy",
+                c if c < 87 => "mostly cloudy",
+                _ => "overcast",
+            };
+            format!("Cloud cover is {}%: {}.", current.cloud_cover_percent, feel)
+        }
+        _ => return None,
+    };
+    Some(context)
+}
+
+fn beaufort_description(speed: f64) -> &'static str {
+    match speed {
+        s if s < 0.5 => "calm",
+        s if s < 1.6 => "light air",
+        s if s < 3.4 => "light breeze",
+        s if s < 5.5 => "gentle breeze",
+        s if s < 8.0 => "moderate breeze",
+        s if s < 10.8 => "fresh breeze",
+        s if s < 13.9 => "strong breeze",
+        s if s < 17.2 => "near gale",
+        s if s < 20.8 => "gale",
+        s if s < 24.5 => "strong gale",
+        s if s < 28.5 => "storm",
+        s if s < 32.7 => "violent storm",
+        _ => "hurricane-force wind",
+    }
+}
@@ -0,0 +1,320 @@
+//! `weather gpx`: the weather along a GPX track at the times each part of
+//! it will be reached. The track's timestamps give the pace; `--start`
+//! moves them to begin at another time. The track is cut into legs of
+//! equal time, each forecast at its middle point, at that point's
+//! elevation, with the hourly forecast interpolated to the minute.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc, Weekday};
+use serde::Serialize;
+use std::str::FromStr;
+use weather_core::{geo, wind};
+use weather_providers::openmeteo;
+use weather_providers::route::{Point, PointForecast};
+
+/// Legs shorter than this, in metres, have no direction of travel.
+const STATIONARY_M: f64 = 10.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct TrackPoint {
+    pub lat: f64,
+    pub lon: f64,
+    pub elevation: Option<f64>,
+    pub time: DateTime<Utc>,
+}
+
+/// The timed points of the tracks in a GPX document, in order.
+pub fn parse(xml: &str) -> Result<Vec<TrackPoint>, String> {
+    let document = roxmltree::Document::parse(xml).map_err(|e| format!("Not GPX: {}", e))?;
+    let mut points = Vec::new();
+    for (i, node) in document
+        .descendants()
+        .filter(|node| node.has_tag_name("trkpt"))
+        .enumerate()
+    {
+        let coordinate = |name| node.attribute(name).and_then(|v| v.parse::<f64>().ok());
+        let (Some(lat), Some(lon)) = (coordinate("lat"), coordinate("lon")) else {
+            return Err(format!("Track point {} has no valid lat and lon", i + 1));
+        };
+        let time = child(node, "time")
+            .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+            .ok_or_else(|| {
+                format!(
+                    "Track point {} has no <time>; the track needs timestamps for its pace \
+                     (route planners can export a timed track)",
+                    i + 1
+                )
+            })?
+            .with_timezone(&Utc);
+        if points
+            .last()
+            .is_some_and(|last: &TrackPoint| last.time > time)
+        {
+            return Err(format!(
+                "Track point {} is timed before the one before it",
+                i + 1
+            ));
+        }
+        points.push(TrackPoint {
+            lat,
+            lon,
+            elevation: child(node, "ele").and_then(|ele| ele.parse().ok()),
+            time,
+        });
+    }
+    if points.len() < 2 {
+        return Err("The GPX file has fewer than two track points (<trkpt>)".to_string());
+    }
+    if points[0].time == points[points.len() - 1].time {
+        return Err("The track's points are all timed the same".to_string());
+    }
+    Ok(points)
+}
+
+/// The text of `node`'s child element `name`.
+fn child<'a>(node: roxmltree::Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.children()
+        .find(|child| child.has_tag_name(name))
+        .and_then(|child| child.text())
+        .map(str::trim)
+}
+
+/// The day of `--start`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Day {
+    Today,
+    Tomorrow,
+    Weekday(Weekday),
+    Date(NaiveDate),
+}
+
+/// `--start`: a local time such as `08:00` or `8`, optionally on a day
+/// such as `sat`, `tomorrow` or `2024-06-22`. Without a day it is the next time the clock shows it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Start {
+    day: Option<Day>,
+    time: NaiveTime,
+}
+
+impl FromStr for Start {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "Invalid start '{}': expected a time such as 08:00, with a day before it \
+                 if not the next (e.g. 'sat 08:00', 'tomorrow 7:30', '2024-06-22 08:00')",
+                s
+            )
+        };
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let (day, time) = match words[..] {
+            [time] => (None, time),
+            [day, time] => {
+                let day = match day.to_lowercase().as_str() {
+                    "today" => Day::Today,
+                    "tomorrow" => Day::Tomorrow,
+                    _ => match NaiveDate::parse_from_str(day, "%Y-%m-%d") {
+                        Ok(date) => Day::Date(date),
+                        Err(_) => Day::Weekday(day.parse().map_err(|_| invalid())?),
+                    },
+                };
+                (Some(day), time)
+            }
+            _ => return Err(invalid()),
+        };
+        let time = NaiveTime::parse_from_str(time, "%H:%M")
+            .ok()
+            .or_else(|| NaiveTime::from_hms_opt(time.parse().ok()?, 0, 0))
+            .ok_or_else(invalid)?;
+        Ok(Start { day, time })
+    }
+}
+
+impl Start {
+    /// The moment meant at a place `offset` seconds ahead of UTC, `now`.
+    pub fn resolve(&self, now: DateTime<Utc>, offset: i32) -> DateTime<Utc> {
+        let local_now = (now + Duration::seconds(offset as i64)).naive_utc();
+        let today = local_now.date();
+        let passed = local_now.time() > self.time;
+        let date = match self.day {
+            None if passed => today + Duration::days(1),
+            None | Some(Day::Today) => today,
+            Some(Day::Tomorrow) => today + Duration::days(1),
+            Some(Day::Date(date)) => date,
+            Some(Day::Weekday(weekday)) => {
+                let ahead = (weekday.num_days_from_monday() as i64
+                    - today.weekday().num_days_from_monday() as i64)
+                    .rem_euclid(7);
+                let ahead = if ahead == 0 && passed { 7 } else { ahead };
+                today + Duration::days(ahead)
+            }
+        };
+        date.and_time(self.time).and_utc() - Duration::seconds(offset as i64)
+    }
+}
+
+/// A stretch of the track covering equal time, with where it is at its
+/// middle.
+#[derive(Debug, Clone, Serialize)]
+pub struct Leg {
+    /// Seconds from the start of the track.
+    pub from_s: i64,
+    pub to_s: i64,
+    /// Distance along the track, in metres.
+    pub from_m: f64,
+    pub to_m: f64,
+    pub lat: f64,
+    pub lon: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elevation_m: Option<f64>,
+    /// The direction of travel from the start of the leg to its end;
+    /// `None` when it stays put.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heading_deg: Option<f64>,
+}
+
+impl Leg {
+    pub fn point(&self) -> Point {
+        Point {
+            lat: self.lat,
+            lon: self.lon,
+            elevation: self.elevation_m,
+        }
+    }
+}
+
+/// Where the track is a number of seconds after its start: position,
+/// elevation and distance covered.
+struct Position {
+    lat: f64,
+    lon: f64,
+    elevation: Option<f64>,
+    distance_m: f64,
+}
+
+/// The track cut into legs of `every` seconds, the last one shorter.
+pub fn legs(points: &[TrackPoint], every: i64) -> Vec<Leg> {
+    let mut covered = vec![0.0];
+    for pair in points.windows(2) {
+        let step = geo::distance_m(pair[0].lat, pair[0].lon, pair[1].lat, pair[1].lon);
+        covered.push(covered[covered.len() - 1] + step);
+    }
+    let start = points[0].time;
+    let elapsed = |point: &TrackPoint| (point.time - start).num_seconds();
+    let total = points.last().map_or(0, elapsed);
+    let at = |seconds: i64| {
+        let i = points
+            .windows(2)
+            .position(|pair| elapsed(&pair[1]) >= seconds)
+            .unwrap_or(points.len() - 2);
+        let (a, b) = (&points[i], &points[i + 1]);
+        let span = elapsed(b) - elapsed(a);
+        let t = if span > 0 {
+            (seconds - elapsed(a)) as f64 / span as f64
+        } else {
+            0.0
+        };
+        let lerp = |x: f64, y: f64| x + (y - x) * t;
+        Position {
+            lat: lerp(a.lat, b.lat),
+            lon: lerp(a.lon, b.lon),
+            elevation: a.elevation.zip(b.elevation).map(|(x, y)| lerp(x, y)),
+            distance_m: lerp(covered[i], covered[i + 1]),
+        }
+    };
+    (0..total)
+        .step_by(every.max(1) as usize)
+        .map(|from_s| {
+            let to_s = (from_s + every).min(total);
+            let (from, middle, to) = (at(from_s), at((from_s + to_s) / 2), at(to_s));
+            Leg {
+                from_s,
+                to_s,
+                from_m: from.distance_m,
+                to_m: to.distance_m,
+                lat: middle.lat,
+                lon: middle.lon,
+                elevation_m: middle.elevation,
+                heading_deg: (to.distance_m - from.distance_m >= STATIONARY_M)
+                    .then(|| geo::bearing_deg(from.lat, from.lon, to.lat, to.lon)),
+            }
+        })
+        .collect()
+}
+
+/// The weather over a leg, at its middle. Temperatures are in °C, wind in
+/// m/s; times are Unix timestamps.
+#[derive(Debug, Clone, Serialize)]
+pub struct LegWeather {
+    #[serde(flatten)]
+    pub leg: Leg,
+    pub start: i64,
+    pub end: i64,
+    pub temperature_c: f64,
+    /// 0.0 to 1.0.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub precipitation_probability: Option<f64>,
+    /// Over the hour.
+    pub precipitation_mm: f64,
+    pub condition: &'static str,
+    pub wind_speed_ms: f64,
+    pub wind_from_deg: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wind_gust_ms: Option<f64>,
+    /// Against the direction of travel, negative for a tailwind.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub headwind_ms: Option<f64>,
+    /// Across it, positive from the right.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crosswind_ms: Option<f64>,
+}
+
+/// The weather over `leg` of a track started at `start`, from the forecast
+/// at its middle; `None` beyond the forecast.
+pub fn weather(leg: &Leg, start: i64, forecast: &PointForecast) -> Option<LegWeather> {
+    let hourly = &forecast.hourly;
+    let at = start + (leg.from_s + leg.to_s) / 2;
+    let i = hourly
+        .time
+        .windows(2)
+        .position(|pair| (pair[0]..pair[1]).contains(&at))?;
+    let t = (at - hourly.time[i]) as f64 / (hourly.time[i + 1] - hourly.time[i]) as f64;
+    let lerp = |series: &Vec<Option<f64>>| {
+        let (a, b) = (series.get(i).copied()??, series.get(i + 1).copied()??);
+        Some(a + (b - a) * t)
+    };
+    let nearest = if t < 0.5 { i } else { i + 1 };
+
+    // Directions are interpolated as vectors, so 350° and 10° make north.
+    let (speed_a, speed_b) = (
+        hourly.wind_speed_10m.get(i).copied()??,
+        hourly.wind_speed_10m.get(i + 1).copied()??,
+    );
+    let (from_a, from_b) = (
+        hourly.wind_direction_10m.get(i).copied()??.to_radians(),
+        hourly.wind_direction_10m.get(i + 1).copied()??.to_radians(),
+    );
+    let x = speed_a * from_a.sin() * (1.0 - t) + speed_b * from_b.sin() * t;
+    let y = speed_a * from_a.cos() * (1.0 - t) + speed_b * from_b.cos() * t;
+    let wind_speed_ms = speed_a + (speed_b - speed_a) * t;
+    let wind_from_deg = x.atan2(y).to_degrees().rem_euclid(360.0);
+    let components = leg
+        .heading_deg
+        .map(|heading| wind::components(wind_speed_ms, wind_from_deg, heading));
+
+    Some(LegWeather {
+        leg: leg.clone(),
+        start: start + leg.from_s,
+        end: start + leg.to_s,
+        temperature_c: lerp(&hourly.temperature_2m)?,
+        precipitation_probability: lerp(&hourly.precipitation_probability)
+            .map(|percent| percent / 100.0),
+        precipitation_mm: hourly.precipitation.get(nearest).copied()??,
+        condition: openmeteo::describe(hourly.weather_code.get(nearest).copied()??),
+        wind_speed_ms,
+        wind_from_deg,
+        wind_gust_ms: lerp(&hourly.wind_gusts_10m),
+        headwind_ms: components.map(|c| c.head),
+        crosswind_ms: components.map(|c| c.cross),
+    })
+}
@@ -0,0 +1,37 @@
+//! `weather history`: the readings recorded in the observation log (see
+//! [`storage`](crate::storage)) with a summary of them. Recording is
+//! opt-in:
+//!
+//! ```toml
+//! [storage]
+//! backend = "sqlite"
+//! record = true
+//! ```
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use weather_core::model::Report;
+
+/// Statistics over recorded readings. Temperatures are in °C.
+#[derive(Debug, Clone, Serialize)]
+pub struct Summary {
+    pub readings: usize,
+    pub first: DateTime<Utc>,
+    pub last: DateTime<Utc>,
+    pub min_temperature_c: f64,
+    pub max_temperature_c: f64,
+    pub mean_temperature_c: f64,
+}
+
+/// The summary of `reports`; `None` when there are none.
+pub fn summarize(reports: &[Report]) -> Option<Summary> {
+    let temperatures = reports.iter().map(|report| report.current.temperature_c);
+    Some(Summary {
+        readings: reports.len(),
+        first: reports.iter().map(|report| report.observed_at).min()?,
+        last: reports.iter().map(|report| report.observed_at).max()?,
+        min_temperature_c: temperatures.clone().fold(f64::INFINITY, f64::min),
+        max_temperature_c: temperatures.clone().fold(f64::NEG_INFINITY, f64::max),
+        mean_temperature_c: temperatures.sum::<f64>() / reports.len() as f64,
+    })
+}
@@ -0,0 +1,136 @@
+//! `weather hook --script FILE`: runs `weather daemon` and calls a script
+//! after each refresh, to follow the weather with a wallpaper, a keyboard's
+//! RGB lighting or a terminal theme. `contrib/hooks` has examples.
+//!
+//! The script gets the conditions in environment variables, in metric
+//! units whatever `--units` says:
+//!
+//! | Variable                 | Example                       |
+//! |--------------------------|-------------------------------|
+//! | `WEATHER_CONDITION`      | `clear`, `clouds`, `drizzle`, `rain`, `snow`, `thunderstorm`, `fog` or `other` |
+//! | `WEATHER_DAYTIME`        | `day` or `night`              |
+//! | `WEATHER_DESCRIPTION`    | `light rain`                  |
+//! | `WEATHER_TEMPERATURE_C`  | `12.4`                        |
+//! | `WEATHER_FEELS_LIKE_C`   | `10.9`                        |
+//! | `WEATHER_HUMIDITY`       | `81` (percent)                |
+//! | `WEATHER_WIND_MS`        | `4.6`                         |
+//! | `WEATHER_CLOUD_COVER`    | `75` (percent)                |
+//! | `WEATHER_LOCATION`       | `Berlin`                      |
+//! | `WEATHER_COUNTRY`        | `DE`                          |
+//! | `WEATHER_OBSERVED_AT`    | `2024-03-01T09:50:00Z`        |
+//!
+//! and the whole report, as `--json` prints it, on standard input. A
+//! script that fails or outlasts [`TIMEOUT`] is reported and the daemon
+//! carries on.
+
+use crate::clock;
+use chrono::Utc;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use weather_core::astro;
+use weather_core::model::Report;
+
+/// How long a script may run before it is killed.
+pub const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Checks that `script` can be run, before the daemon starts.
+pub fn check(script: &Path) -> Result<(), String> {
+    let metadata = std::fs::metadata(script)
+        .map_err(|e| format!("Cannot use hook script {}: {}", script.display(), e))?;
+    if !metadata.is_file() {
+        return Err(format!("Hook script {} is not a file", script.display()));
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if metadata.permissions().mode() & 0o111 == 0 {
+            return Err(format!(
+                "Hook script {} is not executable; run `chmod +x {}`",
+                script.display(),
+                script.display()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Runs `script` with the conditions of `report`, waiting for it to finish.
+pub async fn run(script: &Path, report: &Report) -> Result<(), Box<dyn std::error::Error>> {
+    let mut command = tokio::process::Command::from(command(script, report));
+    command.kill_on_drop(true);
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Cannot run hook script {}: {}", script.display(), e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let json = serde_json::to_vec_pretty(report)?;
+        // A script that does not read its input closes the pipe early,
+        // which is no reason to fail.
+        tokio::spawn(async move { stdin.write_all(&json).await.ok() });
+    }
+    let status = tokio::time::timeout(TIMEOUT, child.wait())
+        .await
+        .map_err(|_| {
+            format!(
+                "Hook script {} did not finish within {}s",
+                script.display(),
+                TIMEOUT.as_secs()
+            )
+        })??;
+    if !status.success() {
+        return Err(format!("Hook script {} exited with {}", script.display(), status).into());
+    }
+    Ok(())
+}
+
+fn command(script: &Path, report: &Report) -> Command {
+    let current = &report.current;
+    let mut command = Command::new(script);
+    command
+        .stdin(Stdio::piped())
+        .env("WEATHER_CONDITION", &current.condition.kind)
+        .env(
+            "WEATHER_DAYTIME",
+            if is_daytime(report) { "day" } else { "night" },
+        )
+        .env("WEATHER_DESCRIPTION", &current.condition.description)
+        .env(
+            "WEATHER_TEMPERATURE_C",
+            format!("{:.1}", current.temperature_c),
+        )
+        .env(
+            "WEATHER_FEELS_LIKE_C",
+            format!("{:.1}", current.feels_like_c),
+        )
+        .env("WEATHER_HUMIDITY", current.humidity_percent.to_string())
+        .env("WEATHER_WIND_MS", format!("{:.1}", current.wind_speed_ms))
+        .env(
+            "WEATHER_CLOUD_COVER",
+            current.cloud_cover_percent.to_string(),
+        )
+        .env("WEATHER_LOCATION", &report.location.name)
+        .env("WEATHER_COUNTRY", &report.location.country)
+        .env(
+            "WEATHER_OBSERVED_AT",
+            report
+                .observed_at
+                .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        );
+    command
+}
+
+/// Whether the sun is up at the location now; without sun times, whether
+/// it is above the horizon.
+fn is_daytime(report: &Report) -> bool {
+    let now = Utc::now().timestamp();
+    match (report.current.sunrise, report.current.sunset) {
+        (Some(sunrise), Some(sunset)) => {
+            clock::is_daytime(now, sunrise.timestamp(), sunset.timestamp())
+        }
+        _ => {
+            astro::solar_elevation(report.location.latitude, report.location.longitude, now)
+                >= -0.833
+        }
+    }
+}
@@ -0,0 +1,294 @@
+//! `weather hourly`: the next 24 to 48 hours at a glance, a sparkline of
+//! the temperature above bars for the chance of precipitation. With
+//! `--heading`, a row of arrows shows the wind relative to the direction of
+//! travel, ahead being up.
+
+use crate::units::Units;
+use colored::Colorize;
+use serde::Serialize;
+use weather_core::wind;
+use weather_providers::forecast::Forecast;
+use weather_providers::onecall::OneCall;
+
+/// The most hours shown: One Call forecasts 48.
+pub const MAX_HOURS: usize = 48;
+
+/// An hour label every this many columns.
+const LABEL_EVERY: usize = 3;
+
+/// Width of the row titles before the columns.
+const GUTTER: usize = 6;
+
+const BLOCKS: [&str; 8] = ["▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"];
+const ASCII_LEVELS: [&str; 5] = ["_", ".", "-", "~", "^"];
+/// Where the wind blows towards, clockwise from straight ahead.
+const ARROWS: [&str; 8] = ["↑", "↗", "→", "↘", "↓", "↙", "←", "↖"];
+const ASCII_ARROWS: [&str; 8] = ["^", "/", ">", "\\", "v", "/", "<", "\\"];
+
+/// Wind below this many m/s is calm, whatever its direction.
+const CALM: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Hour {
+    pub start: i64,
+    pub temperature_c: f64,
+    /// Probability of precipitation, 0.0 to 1.0.
+    pub precipitation_probability: f64,
+    pub wind_speed_ms: f64,
+    /// Where the wind blows from, in degrees from north.
+    pub wind_from_deg: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wind_gust_ms: Option<f64>,
+    /// The wind against the `--heading`, negative for a tailwind.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub headwind_ms: Option<f64>,
+    /// The wind across the `--heading`, positive from the right.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crosswind_ms: Option<f64>,
+}
+
+pub fn from_onecall(onecall: &OneCall) -> Vec<Hour> {
+    onecall
+        .hourly
+        .iter()
+        .map(|hour| Hour {
+            start: hour.dt,
+            temperature_c: hour.temp,
+            precipitation_probability: hour.pop,
+            wind_speed_ms: hour.wind_speed,
+            wind_from_deg: f64::from(hour.wind_deg),
+            wind_gust_ms: hour.wind_gust,
+            headwind_ms: None,
+            crosswind_ms: None,
+        })
+        .collect()
+}
+
+/// Hours from the 3-hourly forecast, the temperature interpolated between
+/// entries and each entry's chance of precipitation and wind held for its
+/// three hours.
+pub fn from_forecast(forecast: &Forecast) -> Vec<Hour> {
+    let entries = &forecast.list;
+    entries
+        .iter()
+        .enumerate()
+        .flat_map(|(i, entry)| {
+            let next = entries.get(i + 1).map_or(entry.main.temp, |e| e.main.temp);
+            (0..3).map(move |step| Hour {
+                start: entry.dt + step * 3600,
+                temperature_c: entry.main.temp + (next - entry.main.temp) * step as f64 / 3.0,
+                precipitation_probability: entry.pop,
+                wind_speed_ms: entry.wind.speed,
+                wind_from_deg: f64::from(entry.wind.deg),
+                wind_gust_ms: entry.wind.gust,
+                headwind_ms: None,
+                crosswind_ms: None,
+            })
+        })
+        .collect()
+}
+
+/// The `count` hours from the one `now` falls in.
+pub fn upcoming(hours: &[Hour], now: i64, count: usize) -> Vec<Hour> {
+    let first_hour = now - now.rem_euclid(3600);
+    hours
+        .iter()
+        .filter(|hour| hour.start >= first_hour)
+        .take(count)
+        .copied()
+        .collect()
+}
+
+/// Splits each hour's wind along and across `heading`, in degrees.
+pub fn set_heading(hours: &mut [Hour], heading: f64) {
+    for hour in hours {
+        let components = wind::components(hour.wind_speed_ms, hour.wind_from_deg, heading);
+        hour.headwind_ms = Some(components.head);
+        hour.crosswind_ms = Some(components.cross);
+    }
+}
+
+/// The chart's lines: hour labels, the temperature sparkline and the
+/// precipitation bars, then the extremes. `hour_label` formats a column's
+/// start time. With a `heading`, the wind follows the precipitation.
+pub fn chart(
+    hours: &[Hour],
+    units: Units,
+    hour_label: impl Fn(i64) -> String,
+    heading: Option<f64>,
+    ascii: bool,
+) -> Vec<String> {
+    let Some((coldest, warmest)) = extremes(hours) else {
+        return vec!["No hourly forecast for this place".to_string()];
+    };
+
+    let mut labels = " ".repeat(GUTTER);
+    for (i, hour) in hours.iter().enumerate().step_by(LABEL_EVERY) {
+        labels.push_str(&" ".repeat((GUTTER + i).saturating_sub(labels.chars().count())));
+        labels.push_str(&hour_label(hour.start));
+    }
+
+    let (_, _, unit) = units.temperature(0.0);
+    let range = warmest.temperature_c - coldest.temperature_c;
+    let temperatures: String = hours
+        .iter()
+        .map(|hour| {
+            // A flat day sits in the middle rather than along the floor.
+            let level = if range < f64::EPSILON {
+                0.5
+            } else {
+                (hour.temperature_c - coldest.temperature_c) / range
+            };
+            if ascii {
+                ASCII_LEVELS[scale(level, ASCII_LEVELS.len())].to_string()
+            } else {
+                BLOCKS[scale(level, BLOCKS.len())]
+                    .bright_yellow()
+                    .to_string()
+            }
+        })
+        .collect();
+    let precipitation: String = hours
+        .iter()
+        .map(|hour| precipitation_cell(hour.precipitation_probability, ascii))
+        .collect();
+
+    let temperature = |hour: &Hour| {
+        let (value, _, unit) = units.temperature(hour.temperature_c);
+        format!("{:.0}{} at {}:00", value, unit, hour_label(hour.start))
+    };
+    let mut summary = format!(
+        "High {} · Low {}",
+        temperature(warmest),
+        temperature(coldest)
+    );
+    let wettest = first_max(hours, |hour| hour.precipitation_probability)
+        .filter(|hour| hour.precipitation_probability >= 0.05);
+    if let Some(hour) = wettest {
+        summary.push_str(&format!(
+            " · Rain likeliest at {}:00 ({:.0}%)",
+            hour_label(hour.start),
+            hour.precipitation_probability * 100.0
+        ));
+    }
+
+    let mut lines = vec![
+        labels,
+        format!("{:<GUTTER$}{}", unit, temperatures),
+        format!("{:<GUTTER$}{}", "Rain", precipitation),
+    ];
+    if let Some(heading) = heading {
+        let arrows: String = hours
+            .iter()
+            .map(|hour| wind_cell(hour, heading, ascii))
+            .collect();
+        lines.push(format!("{:<GUTTER$}{}", "Wind", arrows));
+    }
+    lines.push(summary);
+    if let Some(heading) = heading {
+        lines.push(wind_summary(hours, units, &hour_label, heading));
+    }
+    lines
+}
+
+/// An arrow for where the wind blows relative to `heading`, red against
+/// the traveller and green behind them. Calm leaves the column empty.
+fn wind_cell(hour: &Hour, heading: f64, ascii: bool) -> String {
+    if hour.wind_speed_ms < CALM {
+        return " ".to_string();
+    }
+    let towards = (hour.wind_from_deg + 180.0 - heading).rem_euclid(360.0);
+    let step = (towards / 45.0).round() as usize % 8;
+    let arrow = if ascii {
+        ASCII_ARROWS[step]
+    } else {
+        ARROWS[step]
+    };
+    if ascii {
+        return arrow.to_string();
+    }
+    let head = wind::components(hour.wind_speed_ms, hour.wind_from_deg, heading).head;
+    if head >= CALM {
+        arrow.bright_red().to_string()
+    } else if head <= -CALM {
+        arrow.bright_green().to_string()
+    } else {
+        arrow.to_string()
+    }
+}
+
+/// The strongest headwind and crosswind for `heading`, and when.
+fn wind_summary(
+    hours: &[Hour],
+    units: Units,
+    hour_label: impl Fn(i64) -> String,
+    heading: f64,
+) -> String {
+    let components =
+        |hour: &Hour| wind::components(hour.wind_speed_ms, hour.wind_from_deg, heading);
+    let speed = |ms: f64| {
+        let (value, _, unit) = units.speed(ms);
+        format!("{:.0} {}", value, unit)
+    };
+    let mut parts = Vec::new();
+    match first_max(hours, |hour| components(hour).head) {
+        Some(hour) if components(hour).head >= CALM => parts.push(format!(
+            "headwind up to {} at {}:00",
+            speed(components(hour).head),
+            hour_label(hour.start)
+        )),
+        _ => parts.push("no headwind".to_string()),
+    }
+    if let Some(hour) = first_max(hours, |hour| components(hour).cross.abs())
+        .filter(|hour| components(hour).cross.abs() >= CALM)
+    {
+        let cross = components(hour).cross;
+        parts.push(format!(
+            "crosswind up to {} from the {} at {}:00",
+            speed(cross.abs()),
+            if cross >= 0.0 { "right" } else { "left" },
+            hour_label(hour.start)
+        ));
+    }
+    format!("Heading {:.0}°: {}", heading, parts.join(", "))
+}
+
+/// The first coldest and the first warmest hour.
+fn extremes(hours: &[Hour]) -> Option<(&Hour, &Hour)> {
+    let coldest = first_max(hours, |hour| -hour.temperature_c)?;
+    let warmest = first_max(hours, |hour| hour.temperature_c)?;
+    Some((coldest, warmest))
+}
+
+/// The earliest hour with the highest `value`.
+fn first_max(hours: &[Hour], value: impl Fn(&Hour) -> f64) -> Option<&Hour> {
+    hours.iter().reduce(|best, hour| {
+        if value(hour) > value(best) {
+            hour
+        } else {
+            best
+        }
+    })
+}
+
+/// The step of `levels` that `fraction` (0.0 to 1.0) falls on.
+fn scale(fraction: f64, levels: usize) -> usize {
+    ((fraction * levels as f64) as usize).min(levels - 1)
+}
+
+/// A bar for the chance of precipitation; in ASCII, its tens as a digit
+/// (`#` for certain). Under 5% leaves the column empty.
+fn precipitation_cell(probability: f64, ascii: bool) -> String {
+    if probability < 0.05 {
+        return " ".to_string();
+    }
+    if ascii {
+        return match (probability * 10.0).round() as u32 {
+            10.. => "#".to_string(),
+            tens => tens.to_string(),
+        };
+    }
+    BLOCKS[scale(probability, BLOCKS.len())]
+        .bright_blue()
+        .to_string()
+}
@@ -0,0 +1,85 @@
+//! `--hyperlocal`: temperature and precipitation for the exact coordinates
+//! rather than the provider's nearest station or grid cell, which can be
+//! far off on coasts and in valleys. The corners of the 0.1° cell around
+//! the place are fetched and weighted by inverse distance.
+
+use futures_util::future::try_join_all;
+use weather_core::geo;
+use weather_core::model::{Conditions, GridPoint, Hyperlocal};
+use weather_providers::geocode::Place;
+use weather_providers::WeatherClient;
+
+/// Spacing of the sampled grid, about 11 km north to south.
+const GRID_DEG: f64 = 0.1;
+
+/// Interpolates the current conditions at `place` from the four corners
+/// of the grid cell it lies in.
+pub async fn interpolate(
+    client: &WeatherClient,
+    place: &Place,
+) -> Result<Hyperlocal, Box<dyn std::error::Error>> {
+    let south = (place.lat / GRID_DEG).floor() * GRID_DEG;
+    let west = (place.lon / GRID_DEG).floor() * GRID_DEG;
+    let corners = [
+        (south, west),
+        (south, west + GRID_DEG),
+        (south + GRID_DEG, west),
+        (south + GRID_DEG, west + GRID_DEG),
+    ];
+    let fetches = corners.iter().map(|&(lat, lon)| {
+        let corner = Place {
+            lat: lat.clamp(-90.0, 90.0),
+            lon: wrap_longitude(lon),
+            ..place.clone()
+        };
+        async move {
+            let report = client.current(&corner, &crate::report::cancel()).await?;
+            Ok::<_, Box<dyn std::error::Error>>(GridPoint::new(
+                corner.lat,
+                corner.lon,
+                geo::distance_m(place.lat, place.lon, corner.lat, corner.lon),
+                report.current.temperature_c,
+                precipitation(&report.current),
+            ))
+        }
+    });
+    let points = try_join_all(fetches).await?;
+
+    let estimate = |value: fn(&GridPoint) -> f64| {
+        let samples: Vec<_> = points
+            .iter()
+            .map(|point| (point.latitude, point.longitude, value(point)))
+            .collect();
+        geo::idw(place.lat, place.lon, &samples).unwrap_or_default()
+    };
+    Ok(Hyperlocal::new(
+        estimate(|point| point.temperature_c),
+        estimate(|point| point.precipitation_mm),
+        points,
+    ))
+}
+
+/// Lowest and highest of `value` over the grid points.
+pub fn spread(hyperlocal: &Hyperlocal, value: fn(&GridPoint) -> f64) -> (f64, f64) {
+    hyperlocal
+        .points
+        .iter()
+        .map(value)
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+            (min.min(v), max.max(v))
+        })
+}
+
+/// Rain and snow over the last hour.
+fn precipitation(current: &Conditions) -> f64 {
+    current.rain_mm.unwrap_or(0.0) + current.snow_mm.unwrap_or(0.0)
+}
+
+/// `lon` brought back into -180..180 after stepping across the antimeridian.
+fn wrap_longitude(lon: f64) -> f64 {
+    if lon > 180.0 {
+        lon - 360.0
+    } else {
+        lon
+    }
+}
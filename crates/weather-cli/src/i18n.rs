@@ -0,0 +1,128 @@
+//! Translations of generated text: compass points, moon phases, the
+//! summary templates and the card's labels.
+//!
+//! Messages live in TOML files, one per language, keyed by section and name
+//! (`[summary] high = "{text}; high of {temp}"` is `summary.high`). English
+//! is the reference and fallback; the languages in `locales/` are built in,
+//! and a `locales/<language>.toml` in the config directory adds a language
+//! or overrides single messages.
+
+use crate::platform;
+use std::collections::HashMap;
+
+const ENGLISH: &str = include_str!("../locales/en.toml");
+
+/// Translations shipped with the binary, by language code.
+const BUILT_IN: &[(&str, &str)] = &[
+    ("de", include_str!("../locales/de.toml")),
+    ("es", include_str!("../locales/es.toml")),
+    ("fr", include_str!("../locales/fr.toml")),
+    ("it", include_str!("../locales/it.toml")),
+    ("nl", include_str!("../locales/nl.toml")),
+    ("pt", include_str!("../locales/pt.toml")),
+];
+
+/// Compass point keys, clockwise from north in 22.5° steps.
+const COMPASS: [&str; 16] = [
+    "n", "nne", "ne", "ene", "e", "ese", "se", "sse", "s", "ssw", "sw", "wsw", "w", "wnw", "nw",
+    "nnw",
+];
+
+#[derive(Debug, Clone)]
+pub struct Catalog {
+    messages: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// The messages for `language`, layered over English.
+    pub fn load(language: &str) -> Result<Catalog, String> {
+        let mut catalog = Catalog {
+            messages: HashMap::new(),
+        };
+        catalog.merge(ENGLISH, "built-in en")?;
+        if let Some((_, text)) = BUILT_IN.iter().find(|(code, _)| *code == language) {
+            catalog.merge(text, &format!("built-in {}", language))?;
+        }
+
+        let custom = platform::dir(platform::Dir::Config)
+            .map(|dir| dir.join("locales").join(format!("{}.toml", language)));
+        if let Some(path) = custom {
+            match std::fs::read_to_string(&path) {
+                Ok(text) => catalog.merge(&text, &path.display().to_string())?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(format!("Cannot read {}: {}", path.display(), e)),
+            }
+        }
+        Ok(catalog)
+    }
+
+    fn merge(&mut self, text: &str, source: &str) -> Result<(), String> {
+        let table: toml::Table =
+            toml::from_str(text).map_err(|e| format!("Invalid translations {}: {}", source, e))?;
+        for (section, entries) in table {
+            let entries = entries.as_table().ok_or_else(|| {
+                format!(
+                    "Invalid translations {}: '{}' is not a section",
+                    source, section
+                )
+            })?;
+            for (name, message) in entries {
+                let message = message.as_str().ok_or_else(|| {
+                    format!(
+                        "Invalid translations {}: {}.{} is not a string",
+                        source, section, name
+                    )
+                })?;
+                self.messages
+                    .insert(format!("{}.{}", section, name), message.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// The message for `key`, or the key itself when no language has it.
+    pub fn text<'a>(&'a self, key: &'a str) -> &'a str {
+        self.messages.get(key).map_or(key, String::as_str)
+    }
+
+    /// The message for `key` with each `{name}` replaced by its value.
+    /// Placeholders without a value are kept as they are.
+    pub fn format(&self, key: &str, args: &[(&str, &str)]) -> String {
+        let mut message = String::new();
+        let mut rest = self.text(key);
+        while let Some(open) = rest.find('{') {
+            message.push_str(&rest[..open]);
+            rest = &rest[open..];
+            let value = rest.find('}').and_then(|close| {
+                let (_, value) = args.iter().find(|(name, _)| *name == &rest[1..close])?;
+                Some((close, value))
+            });
+            match value {
+                Some((close, value)) => {
+                    message.push_str(value);
+                    rest = &rest[close + 1..];
+                }
+                None => {
+                    message.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        message.push_str(rest);
+        message
+    }
+
+    /// The 16-point compass name of a bearing in degrees.
+    pub fn compass(&self, degrees: f64) -> &str {
+        let index = (degrees.rem_euclid(360.0) / 22.5).round() as usize % COMPASS.len();
+        self.messages
+            .get(&format!("compass.{}", COMPASS[index]))
+            .map_or(COMPASS[index], String::as_str)
+    }
+
+    /// The translated name of an [`astro::MoonPhase`](weather_core::astro::MoonPhase).
+    pub fn moon_phase<'a>(&'a self, name: &'a str) -> &'a str {
+        let key = format!("moon.{}", name.to_lowercase().replace(' ', "_"));
+        self.messages.get(&key).map_or(name, String::as_str)
+    }
+}
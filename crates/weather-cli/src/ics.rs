@@ -0,0 +1,375 @@
+//! iCalendar (`.ics`) files. Events are read for `weather events`: their
+//! title, where they are and when. Times with a `TZID`, or floating, are
+//! taken as local time wherever the event is, which is what they almost
+//! always mean; recurring events count only their first occurrence.
+//! Events are written, in UTC, for `weather wake`.
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+
+/// When an event starts or ends.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum When {
+    Utc(DateTime<Utc>),
+    /// Local time at the event.
+    Local(NaiveDateTime),
+    /// An all-day event's day.
+    Day(NaiveDate),
+}
+
+impl When {
+    /// Unix timestamp at a place `offset` seconds ahead of UTC; a day
+    /// starts at local midnight.
+    pub fn timestamp(&self, offset: i32) -> i64 {
+        let local = |naive: NaiveDateTime| naive.and_utc().timestamp() - offset as i64;
+        match *self {
+            When::Utc(time) => time.timestamp(),
+            When::Local(naive) => local(naive),
+            When::Day(date) => local(date.and_hms_opt(0, 0, 0).unwrap_or_default()),
+        }
+    }
+
+    fn parse(value: &str, date_only: bool) -> Option<When> {
+        if date_only || value.len() == 8 {
+            return NaiveDate::parse_from_str(value, "%Y%m%d")
+                .ok()
+                .map(When::Day);
+        }
+        let (value, utc) = match value.strip_suffix('Z') {
+            Some(value) => (value, true),
+            None => (value, false),
+        };
+        let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+        Some(if utc {
+            When::Utc(naive.and_utc())
+        } else {
+            When::Local(naive)
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub summary: String,
+    pub location: Option<String>,
+    /// Coordinates from the `GEO` property, as (lat, lon).
+    pub geo: Option<(f64, f64)>,
+    pub start: When,
+    pub end: Option<When>,
+}
+
+impl Event {
+    pub fn all_day(&self) -> bool {
+        matches!(self.start, When::Day(_))
+    }
+
+    /// The end, else an hour after a timed start or the end of an
+    /// all-day one's day, as RFC 5545 has it.
+    pub fn end_timestamp(&self, offset: i32) -> i64 {
+        match (self.end, self.start) {
+            (Some(end), _) => end.timestamp(offset),
+            (None, When::Day(day)) => When::Day(day + Duration::days(1)).timestamp(offset),
+            (None, start) => start.timestamp(offset) + 3600,
+        }
+    }
+}
+
+/// The events in an iCalendar document, in file order. Events without a
+/// start are left out.
+pub fn parse(text: &str) -> Result<Vec<Event>, String> {
+    // Long lines are folded onto continuation lines starting with a space
+    // or a tab.
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    if !lines
+        .first()
+        .is_some_and(|line| line.trim().eq_ignore_ascii_case("BEGIN:VCALENDAR"))
+    {
+        return Err("Not an iCalendar file: it does not start with BEGIN:VCALENDAR".to_string());
+    }
+
+    let mut events = Vec::new();
+    // Within a VEVENT: its properties so far, and how deep in components
+    // such as VALARM nested inside it.
+    let mut current: Option<(Properties, usize)> = None;
+    for line in &lines {
+        let Some((name, params, value)) = property(line) else {
+            continue;
+        };
+        match (name.as_str(), &mut current) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => {
+                current = Some((Properties::default(), 0));
+            }
+            ("BEGIN", Some((_, depth))) => *depth += 1,
+            ("END", Some((_, depth))) if *depth > 0 => *depth -= 1,
+            ("END", Some(_)) => {
+                if let Some((properties, _)) = current.take() {
+                    events.extend(properties.event());
+                }
+            }
+            (_, Some((properties, 0))) => properties.set(&name, &params, value),
+            _ => {}
+        }
+    }
+    Ok(events)
+}
+
+#[derive(Debug, Default)]
+struct Properties {
+    summary: Option<String>,
+    location: Option<String>,
+    geo: Option<(f64, f64)>,
+    start: Option<When>,
+    end: Option<When>,
+}
+
+impl Properties {
+    fn set(&mut self, name: &str, params: &str, value: &str) {
+        let date_only = params.to_ascii_uppercase().contains("VALUE=DATE")
+            && !params.to_ascii_uppercase().contains("VALUE=DATE-TIME");
+        match name {
+            "SUMMARY" => self.summary = Some(unescape(value)),
+            "LOCATION" => self.location = Some(unescape(value)).filter(|l| !l.is_empty()),
+            "GEO" => {
+                self.geo = value.split_once([';', ',']).and_then(|(lat, lon)| {
+                    Some((lat.trim().parse().ok()?, lon.trim().parse().ok()?))
+                })
+            }
+            "DTSTART" => self.start = When::parse(value.trim(), date_only),
+            "DTEND" => self.end = When::parse(value.trim(), date_only),
+            _ => {}
+        }
+    }
+
+    fn event(self) -> Option<Event> {
+        Some(Event {
+            summary: self
+                .summary
+                .filter(|summary| !summary.is_empty())
+                .unwrap_or_else(|| "(no title)".to_string()),
+            location: self.location,
+            geo: self.geo,
+            start: self.start?,
+            end: self.end,
+        })
+    }
+}
+
+/// A content line's name, upper case, its parameters and its value:
+/// `DTSTART;TZID=Europe/London:20240622T140000`. Colons inside quoted
+/// parameter values do not end the name.
+fn property(line: &str) -> Option<(String, String, &str)> {
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            quoted = !quoted;
+            None
+        }
+        ':' if !quoted => Some(i),
+        _ => None,
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let (name, params) = head.split_once(';').unwrap_or((head, ""));
+    Some((name.trim().to_ascii_uppercase(), params.to_string(), value))
+}
+
+/// A text value with its escapes undone; line breaks become commas.
+fn unescape(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match (c, c == '\\') {
+            (_, true) => match chars.next() {
+                Some('n' | 'N') => text.push_str(", "),
+                Some(escaped) => text.push(escaped),
+                None => {}
+            },
+            (c, false) => text.push(c),
+        }
+    }
+    text.trim().to_string()
+}
+
+/// An event to write.
+#[derive(Debug, Clone)]
+pub struct NewEvent {
+    /// Unique and stable, so importing again updates the event.
+    pub uid: String,
+    pub summary: String,
+    pub description: String,
+    pub start: DateTime<Utc>,
+    pub duration: Duration,
+    /// Whether to sound an alarm when the event starts.
+    pub alarm: bool,
+}
+
+/// `events` as an iCalendar document, stamped `now`.
+pub fn write(events: &[NewEvent], now: DateTime<Utc>) -> String {
+    let time = |t: DateTime<Utc>| t.format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//wather-app//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    for event in events {
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}", event.uid),
+            format!("DTSTAMP:{}", time(now)),
+            format!("DTSTART:{}", time(event.start)),
+            format!("DTEND:{}", time(event.start + event.duration)),
+            format!("SUMMARY:{}", escape(&event.summary)),
+            format!("DESCRIPTION:{}", escape(&event.description)),
+        ]);
+        if event.alarm {
+            lines.extend([
+                "BEGIN:VALARM".to_string(),
+                "ACTION:DISPLAY".to_string(),
+                format!("DESCRIPTION:{}", escape(&event.summary)),
+                "TRIGGER:PT0S".to_string(),
+                "END:VALARM".to_string(),
+            ]);
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold(line) + "\r\n").collect()
+}
+
+/// A text value with backslashes, `;`, `,` and line breaks escaped.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// A content line folded to at most 75 octets a line, without splitting
+/// a character.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// A calendar of the given content lines, CRLF-terminated.
+    fn calendar(lines: &[&str]) -> String {
+        let mut text = String::from("BEGIN:VCALENDAR\r\n");
+        for line in lines {
+            text.push_str(line);
+            text.push_str("\r\n");
+        }
+        text + "END:VCALENDAR\r\n"
+    }
+
+    fn naive(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn unfolds_continuation_lines() {
+        let text = calendar(&[
+            "BEGIN:VEVENT",
+            "SUMMARY:Picnic in the",
+            "  park",
+            "LOCATION:Regent's Park\\, Lon",
+            "\tdon",
+            "DTSTART:20240622T120000Z",
+            "END:VEVENT",
+        ]);
+        let events = parse(&text).unwrap();
+        assert_eq!(events[0].summary, "Picnic in the park");
+        assert_eq!(events[0].location.as_deref(), Some("Regent's Park, London"));
+    }
+
+    #[test]
+    fn reads_all_day_and_zoned_starts() {
+        let text = calendar(&[
+            "BEGIN:VEVENT",
+            "SUMMARY:Holiday",
+            "DTSTART;VALUE=DATE:20240622",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "SUMMARY:Match",
+            "DTSTART;TZID=\"Europe/London:GMT\":20240622T140000",
+            "DTEND;TZID=Europe/London:20240622T160000",
+            "END:VEVENT",
+        ]);
+        let events = parse(&text).unwrap();
+        let day = NaiveDate::from_ymd_opt(2024, 6, 22).unwrap();
+        assert_eq!(events[0].start, When::Day(day));
+        assert!(events[0].all_day());
+        // All day ends at the next local midnight.
+        assert_eq!(
+            events[0].end_timestamp(3600),
+            When::Day(day.succ_opt().unwrap()).timestamp(3600)
+        );
+        // The quoted colon does not end the parameters.
+        assert_eq!(events[1].start, When::Local(naive("2024-06-22 14:00")));
+        assert_eq!(events[1].end, Some(When::Local(naive("2024-06-22 16:00"))));
+        assert_eq!(events[1].start.timestamp(3600), 1_719_061_200);
+    }
+
+    #[test]
+    fn nested_alarms_keep_the_event_properties() {
+        let text = calendar(&[
+            "BEGIN:VEVENT",
+            "SUMMARY:Run",
+            "DTSTART:20240622T060000Z",
+            "BEGIN:VALARM",
+            "DESCRIPTION:Reminder",
+            "TRIGGER:-PT15M",
+            "END:VALARM",
+            "LOCATION:Hyde Park",
+            "END:VEVENT",
+        ]);
+        let events = parse(&text).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].summary, "Run");
+        assert_eq!(events[0].location.as_deref(), Some("Hyde Park"));
+    }
+
+    #[test]
+    fn rejects_other_documents() {
+        assert!(parse("BEGIN:VCARD\r\nEND:VCARD\r\n").is_err());
+        assert!(parse("").is_err());
+    }
+
+    #[test]
+    fn written_events_read_back() {
+        let start = Utc.with_ymd_and_hms(2024, 6, 22, 4, 30, 0).unwrap();
+        let event = NewEvent {
+            uid: "wake-2024-06-22@wather-app".to_string(),
+            summary: format!("Sunrise; wake up, {}", "and go outside ".repeat(6)),
+            description: "Sunrise at 04:43\nCivil dawn at 03:56".to_string(),
+            start,
+            duration: Duration::minutes(15),
+            alarm: true,
+        };
+        let text = write(std::slice::from_ref(&event), start);
+        assert!(text.lines().all(|line| line.len() <= 75));
+        let events = parse(&text).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].summary, event.summary.trim());
+        assert_eq!(events[0].start, When::Utc(start));
+        assert_eq!(events[0].end, Some(When::Utc(start + event.duration)));
+    }
+}
@@ -0,0 +1,137 @@
+//! InfluxDB line protocol, as also accepted by VictoriaMetrics and other
+//! TSDBs with an Influx-compatible write endpoint.
+
+use serde::Deserialize;
+use weather_core::model::Report;
+
+/// Where to write lines. With `bucket` the InfluxDB 2 API is used
+/// (`/api/v2/write`), otherwise the 1.x endpoint (`/write?db=`) that
+/// VictoriaMetrics also serves.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Target {
+    pub url: String,
+    pub token: Option<String>,
+    pub org: Option<String>,
+    pub bucket: Option<String>,
+    #[serde(default = "default_database")]
+    pub database: String,
+}
+
+fn default_database() -> String {
+    "weather".to_string()
+}
+
+/// The report as one `weather` measurement line, timestamped in seconds.
+pub fn line(report: &Report) -> String {
+    let current = &report.current;
+    let tags = [
+        ("location", report.location.name.as_str()),
+        ("country", report.location.country.as_str()),
+        ("provider", report.source.provider.as_str()),
+    ];
+    let mut fields = vec![
+        ("temperature_c", float(current.temperature_c)),
+        ("feels_like_c", float(current.feels_like_c)),
+        ("humidity_percent", integer(current.humidity_percent)),
+        ("pressure_hpa", integer(current.pressure_hpa)),
+        ("wind_speed_ms", float(current.wind_speed_ms)),
+        ("wind_direction_deg", integer(current.wind_direction_deg)),
+        ("visibility_m", integer(current.visibility_m)),
+        ("cloud_cover_percent", integer(current.cloud_cover_percent)),
+        ("condition", string(&current.condition.kind)),
+    ];
+    if let Some(gust) = current.wind_gust_ms {
+        fields.push(("wind_gust_ms", float(gust)));
+    }
+
+    let mut line = String::from("weather");
+    for (key, value) in tags.iter().filter(|(_, value)| !value.is_empty()) {
+        line.push_str(&format!(",{}={}", key, escape_tag(value)));
+    }
+    let fields = fields
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .chain(
+            report
+                .derived
+                .iter()
+                .map(|(key, value)| format!("{}={}", escape_tag(key), float(*value))),
+        )
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("{} {} {}", line, fields, report.observed_at.timestamp())
+}
+
+impl Target {
+    /// The write endpoint this target describes.
+    pub fn endpoint(&self) -> Endpoint {
+        let base = self.url.trim_end_matches('/');
+        let url = match &self.bucket {
+            Some(bucket) => format!(
+                "{}/api/v2/write?org={}&bucket={}",
+                base,
+                self.org.as_deref().unwrap_or_default(),
+                bucket
+            ),
+            None => format!("{}/write?db={}", base, self.database),
+        };
+        Endpoint {
+            url,
+            token: self.token.clone(),
+        }
+    }
+}
+
+/// A full write URL, e.g. `http://localhost:8086/api/v2/write?org=home&bucket=weather`
+/// or VictoriaMetrics' `http://localhost:8428/write`.
+#[derive(Debug, Clone)]
+pub struct Endpoint {
+    pub url: String,
+    pub token: Option<String>,
+}
+
+/// Posts `lines` to `endpoint`, with timestamps in seconds.
+pub async fn write(
+    client: &reqwest::Client,
+    endpoint: &Endpoint,
+    lines: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut request = client
+        .post(&endpoint.url)
+        .query(&[("precision", "s")])
+        .body(lines.to_string());
+    if let Some(token) = &endpoint.token {
+        request = request.header("Authorization", format!("Token {}", token));
+    }
+
+    let res = request.send().await?;
+    if !res.status().is_success() {
+        let status = res.status();
+        let detail = res.text().await.unwrap_or_default();
+        return Err(format!("Influx write failed: HTTP {} {}", status, detail.trim()).into());
+    }
+    Ok(())
+}
+
+/// Numbers without a suffix are floats in line protocol.
+fn float(value: f64) -> String {
+    value.to_string()
+}
+
+fn integer(value: i32) -> String {
+    format!("{}i", value)
+}
+
+fn string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Escapes commas, equals signs and spaces in tag keys and values.
+fn escape_tag(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace('=', "\\=")
+        .replace(' ', "\\ ")
+}
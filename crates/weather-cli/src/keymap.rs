@@ -0,0 +1,227 @@
+//! Keybindings for `--tui`. Each action has default keys; `[tui.keys]` in
+//! the config replaces those of the actions it names:
+//!
+//! ```toml
+//! [tui.keys]
+//! scroll_down = ["j", "down", "ctrl-n"]
+//! quit = ["q"]
+//! ```
+//!
+//! Keys are characters (`G` is shift-g), `ctrl-` or `alt-` followed by a
+//! character, or one of `space`, `enter`, `esc`, `tab`, `backtab`,
+//! `backspace`, `up`, `down`, `left`, `right`, `pageup`, `pagedown`,
+//! `home`, `end` and `f1` to `f12`.
+
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    ScrollDown,
+    ScrollUp,
+    PageDown,
+    PageUp,
+    Top,
+    Bottom,
+    NextDay,
+    PreviousDay,
+    /// Search for a place to add to the saved locations.
+    Search,
+    NextLocation,
+    PreviousLocation,
+    /// Cycle through metric, imperial and standard units.
+    ToggleUnits,
+    /// Save the dashboard as shown to a file.
+    Screenshot,
+    /// Show the next location beside the one shown, or stop doing so.
+    Split,
+    /// Exchange the two sides of the split view.
+    Swap,
+}
+
+impl Action {
+    /// Short description for the help line.
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::ScrollDown | Action::ScrollUp => "scroll",
+            Action::PageDown | Action::PageUp => "page",
+            Action::Top | Action::Bottom => "top/bottom",
+            Action::NextDay | Action::PreviousDay => "day",
+            Action::Search => "search",
+            Action::NextLocation | Action::PreviousLocation => "location",
+            Action::ToggleUnits => "units",
+            Action::Screenshot => "screenshot",
+            Action::Split => "split",
+            Action::Swap => "swap",
+        }
+    }
+}
+
+/// A key with the modifiers that matter for binding it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    const fn plain(code: KeyCode) -> Self {
+        Key {
+            code,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    const fn char(c: char) -> Self {
+        Key::plain(KeyCode::Char(c))
+    }
+
+    fn matches(&self, event: &KeyEvent) -> bool {
+        // Shift is already in the character: `G` arrives as shift-`G`.
+        let relevant = KeyModifiers::CONTROL | KeyModifiers::ALT;
+        self.code == event.code && self.modifiers == event.modifiers & relevant
+    }
+}
+
+impl FromStr for Key {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (modifiers, name) = if let Some(rest) = s.strip_prefix("ctrl-") {
+            (KeyModifiers::CONTROL, rest)
+        } else if let Some(rest) = s.strip_prefix("alt-") {
+            (KeyModifiers::ALT, rest)
+        } else {
+            (KeyModifiers::NONE, s)
+        };
+        let mut chars = name.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match name.to_ascii_lowercase().as_str() {
+                "space" => KeyCode::Char(' '),
+                "enter" => KeyCode::Enter,
+                "esc" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                f => match f.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => return Err(format!("Unknown key '{}'", s)),
+                },
+            },
+        };
+        Ok(Key { code, modifiers })
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            f.write_str("ctrl-")?;
+        } else if self.modifiers.contains(KeyModifiers::ALT) {
+            f.write_str("alt-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::Up => f.write_str("↑"),
+            KeyCode::Down => f.write_str("↓"),
+            KeyCode::Left => f.write_str("←"),
+            KeyCode::Right => f.write_str("→"),
+            KeyCode::PageUp => f.write_str("PgUp"),
+            KeyCode::PageDown => f.write_str("PgDn"),
+            KeyCode::BackTab => f.write_str("shift-tab"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            code => write!(f, "{:?}", code).map(|_| ()),
+        }
+    }
+}
+
+/// Which keys trigger which action.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: BTreeMap<Action, Vec<Key>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        use KeyCode::*;
+        let bindings = [
+            (Action::Quit, vec![Key::char('q'), Key::plain(Esc)]),
+            (Action::ScrollDown, vec![Key::char('j'), Key::plain(Down)]),
+            (Action::ScrollUp, vec![Key::char('k'), Key::plain(Up)]),
+            (Action::PageDown, vec![Key::plain(PageDown), Key::char(' ')]),
+            (Action::PageUp, vec![Key::plain(PageUp), Key::char('b')]),
+            (Action::Top, vec![Key::char('g'), Key::plain(Home)]),
+            (Action::Bottom, vec![Key::char('G'), Key::plain(End)]),
+            (
+                Action::NextDay,
+                vec![Key::char('l'), Key::plain(Right), Key::plain(Tab)],
+            ),
+            (
+                Action::PreviousDay,
+                vec![Key::char('h'), Key::plain(Left), Key::plain(BackTab)],
+            ),
+            (Action::Search, vec![Key::char('/')]),
+            (Action::NextLocation, vec![Key::char('n'), Key::char(']')]),
+            (
+                Action::PreviousLocation,
+                vec![Key::char('p'), Key::char('[')],
+            ),
+            (Action::ToggleUnits, vec![Key::char('u')]),
+            (Action::Screenshot, vec![Key::char('s')]),
+            (Action::Split, vec![Key::char('v')]),
+            (Action::Swap, vec![Key::char('x')]),
+        ];
+        Keymap {
+            bindings: bindings.into_iter().collect(),
+        }
+    }
+}
+
+impl Keymap {
+    /// The action bound to `event`, if any. Ctrl-C always quits.
+    pub fn action(&self, event: &KeyEvent) -> Option<Action> {
+        if event.code == KeyCode::Char('c') && event.modifiers.contains(KeyModifiers::CONTROL) {
+            return Some(Action::Quit);
+        }
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.iter().any(|key| key.matches(event)))
+            .map(|(action, _)| *action)
+    }
+
+    /// The first key bound to `action`, for the help line.
+    pub fn first(&self, action: Action) -> Option<Key> {
+        self.bindings.get(&action)?.first().copied()
+    }
+}
+
+impl<'de> Deserialize<'de> for Keymap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut keymap = Keymap::default();
+        for (action, keys) in BTreeMap::<Action, Vec<String>>::deserialize(deserializer)? {
+            let keys = keys
+                .iter()
+                .map(|key| key.parse())
+                .collect::<Result<Vec<Key>, _>>()
+                .map_err(serde::de::Error::custom)?;
+            keymap.bindings.insert(action, keys);
+        }
+        Ok(keymap)
+    }
+}
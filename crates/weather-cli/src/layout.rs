@@ -0,0 +1,148 @@
+//! Layouts of the weather card: which sections it shows, in which order.
+//! The built-in `default` layout is the usual card; `[card.layouts]` in
+//! the config defines others, chosen with `[card] layout` or `--layout`:
+//!
+//! ```toml
+//! [card]
+//! layout = "short"
+//!
+//! [card.layouts]
+//! short = ["header", "condition", "temperature", "---", "wind", "dew_point"]
+//! ```
+//!
+//! Sections are named as in [`Section`]; `---` draws a rule across the
+//! card, and the key of a derived metric (see `weather explain`) shows
+//! that metric on its own.
+
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use weather_core::metrics;
+
+/// Name of the layout used unless another is chosen.
+pub const DEFAULT: &str = "default";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Section {
+    /// The place's name and country.
+    Header,
+    /// Local time and how old the observation is.
+    Time,
+    Condition,
+    /// Temperature and how it feels.
+    Temperature,
+    MinMax,
+    Humidity,
+    Pressure,
+    Wind,
+    /// Wind gusts, when reported.
+    Gusts,
+    Visibility,
+    Cloudiness,
+    /// The derived metrics in `[metrics] card`.
+    Metrics,
+    /// Rain and snow in the last hour, when there was any.
+    Precipitation,
+    Sunrise,
+    Sunset,
+    /// Offsets from `[calibration]`, when the location has any.
+    Calibration,
+    /// A rule across the card (`---`).
+    Separator,
+    /// One derived metric, by key.
+    Metric(String),
+}
+
+/// The names of the sections other than metrics, as written in layouts.
+const NAMES: &[(&str, Section)] = &[
+    ("header", Section::Header),
+    ("time", Section::Time),
+    ("condition", Section::Condition),
+    ("temperature", Section::Temperature),
+    ("min_max", Section::MinMax),
+    ("humidity", Section::Humidity),
+    ("pressure", Section::Pressure),
+    ("wind", Section::Wind),
+    ("gusts", Section::Gusts),
+    ("visibility", Section::Visibility),
+    ("cloudiness", Section::Cloudiness),
+    ("metrics", Section::Metrics),
+    ("precipitation", Section::Precipitation),
+    ("sunrise", Section::Sunrise),
+    ("sunset", Section::Sunset),
+    ("calibration", Section::Calibration),
+    ("---", Section::Separator),
+];
+
+impl FromStr for Section {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((_, section)) = NAMES.iter().find(|(name, _)| *name == s) {
+            return Ok(section.clone());
+        }
+        match metrics::lookup(s) {
+            Some(calculator) => Ok(Section::Metric(calculator.key.to_string())),
+            None => Err(format!(
+                "Unknown card section '{}'. Available: {}, or a metric key",
+                s,
+                NAMES
+                    .iter()
+                    .map(|(name, _)| *name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Section {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// `[card]` in the config.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    /// The layout used when `--layout` is not given.
+    pub layout: Option<String>,
+    /// Layouts by name, besides [`DEFAULT`].
+    pub layouts: BTreeMap<String, Vec<Section>>,
+}
+
+impl Settings {
+    /// The sections of the layout called `name`.
+    pub fn sections(&self, name: &str) -> Result<Vec<Section>, String> {
+        if let Some(sections) = self.layouts.get(name) {
+            return Ok(sections.clone());
+        }
+        if name == DEFAULT {
+            return Ok(NAMES
+                .iter()
+                .map(|(_, section)| section.clone())
+                .filter(|section| *section != Section::Separator)
+                .collect());
+        }
+        let mut names: Vec<&str> = self.layouts.keys().map(String::as_str).collect();
+        if !self.layouts.contains_key(DEFAULT) {
+            names.insert(0, DEFAULT);
+        }
+        Err(format!(
+            "Unknown card layout '{}'. Available: {}",
+            name,
+            names.join(", ")
+        ))
+    }
+}
+
+/// Checks that `[card] layout` names a layout.
+pub fn validate(settings: &Settings) -> Result<(), String> {
+    match &settings.layout {
+        Some(name) => settings.sections(name).map(|_| ()),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,216 @@
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, Weekday};
+use std::env;
+
+/// Order of the day, month and year components in a short date.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DateOrder {
+    DayMonthYear,
+    MonthDayYear,
+    YearMonthDay,
+}
+
+/// Locale-dependent formatting rules for numbers, units and dates.
+#[derive(Debug, Clone)]
+pub struct Locale {
+    language: String,
+    decimal_separator: char,
+    date_separator: char,
+    date_order: DateOrder,
+    space_before_unit: bool,
+    hour12: bool,
+    week: Week,
+}
+
+/// Which day a week starts on and which days are the weekend.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Week {
+    pub first_day: Weekday,
+    pub weekend: Vec<Weekday>,
+}
+
+impl Week {
+    /// The week of a region (ISO 3166 code, lowercase), following CLDR.
+    /// Without a region, Monday to Sunday with a Saturday–Sunday weekend.
+    pub fn for_region(region: &str) -> Week {
+        use Weekday::*;
+        let first_day = match region {
+            "us" | "ca" | "mx" | "br" | "jp" | "kr" | "tw" | "hk" | "ph" | "in" | "za" | "au"
+            | "il" | "sa" | "pk" => Sun,
+            "ae" | "eg" | "qa" | "kw" | "bh" | "om" | "jo" | "dz" | "ly" | "sd" | "af" | "ir" => {
+                Sat
+            }
+            _ => Mon,
+        };
+        let weekend = match region {
+            "ae" | "sa" | "eg" | "qa" | "kw" | "bh" | "om" | "jo" | "dz" | "ly" | "sd" | "il"
+            | "ye" | "iq" | "sy" | "bd" => vec![Fri, Sat],
+            "ir" | "af" => vec![Fri],
+            "in" | "ug" => vec![Sun],
+            _ => vec![Sat, Sun],
+        };
+        Week { first_day, weekend }
+    }
+
+    pub fn is_weekend(&self, date: NaiveDate) -> bool {
+        self.weekend.contains(&date.weekday())
+    }
+
+    /// Whether `date` is the first day of its week.
+    pub fn starts_week(&self, date: NaiveDate) -> bool {
+        date.weekday() == self.first_day
+    }
+}
+
+impl Locale {
+    /// Builds a locale from a BCP 47 style tag such as `de-DE`, `en_US` or `fr`.
+    /// Returns `None` when the language is not known.
+    pub fn from_tag(tag: &str) -> Option<Locale> {
+        let normalized = tag
+            .split('.')
+            .next()
+            .unwrap_or(tag)
+            .replace('_', "-")
+            .to_lowercase();
+        let mut parts = normalized.split('-');
+        let language = parts.next().unwrap_or("");
+        let region = parts.next().unwrap_or("");
+
+        let (decimal_separator, date_separator, date_order, space_before_unit, hour12) =
+            match (language, region) {
+                ("en", "us") | ("en", "") => ('.', '/', DateOrder::MonthDayYear, false, true),
+                ("en", "ca") => ('.', '-', DateOrder::YearMonthDay, false, true),
+                ("en", "au") | ("en", "nz") | ("en", "in") => {
+                    ('.', '/', DateOrder::DayMonthYear, false, true)
+                }
+                ("en", _) => ('.', '/', DateOrder::DayMonthYear, false, false),
+                ("de", _)
+                | ("ru", _)
+                | ("pl", _)
+                | ("cs", _)
+                | ("fi", _)
+                | ("nb", _)
+                | ("no", _)
+                | ("da", _)
+                | ("tr", _)
+                | ("uk", _) => (',', '.', DateOrder::DayMonthYear, true, false),
+                ("fr", _) | ("es", _) | ("it", _) | ("pt", _) => {
+                    (',', '/', DateOrder::DayMonthYear, true, false)
+                }
+                ("nl", _) => (',', '-', DateOrder::DayMonthYear, true, false),
+                ("sv", _) | ("lt", _) => (',', '-', DateOrder::YearMonthDay, true, false),
+                ("ja", _) | ("zh", _) => ('.', '/', DateOrder::YearMonthDay, false, false),
+                ("ko", _) => ('.', '.', DateOrder::YearMonthDay, false, true),
+                ("hi", _) => ('.', '/', DateOrder::DayMonthYear, false, true),
+                _ => return None,
+            };
+
+        Some(Locale {
+            language: language.to_string(),
+            decimal_separator,
+            date_separator,
+            date_order,
+            space_before_unit,
+            hour12,
+            week: Week::for_region(match (language, region) {
+                // Without a region, the language's main one.
+                ("en", "") => "us",
+                ("ja", "") => "jp",
+                ("ko", "") => "kr",
+                ("hi", "") => "in",
+                _ => region,
+            }),
+        })
+    }
+
+    /// Picks the locale from an explicit tag, falling back to the usual POSIX
+    /// environment variables and finally to `en-US`.
+    pub fn resolve(tag: Option<&str>) -> Result<Locale, String> {
+        if let Some(tag) = tag {
+            return Locale::from_tag(tag).ok_or_else(|| format!("Unsupported locale '{}'", tag));
+        }
+
+        ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .filter(|value| !value.is_empty() && value != "C" && value != "POSIX")
+            .find_map(|value| Locale::from_tag(&value))
+            .map_or_else(|| Ok(Locale::default()), Ok)
+    }
+
+    /// The ISO 639 language code, e.g. `de`.
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    /// The locale's week, unless overridden by [`Locale::set_week`].
+    pub fn week(&self) -> &Week {
+        &self.week
+    }
+
+    /// Replaces the first day of the week and the weekend, e.g. from the
+    /// config file.
+    pub fn set_week(&mut self, first_day: Option<Weekday>, weekend: Option<Vec<Weekday>>) {
+        if let Some(first_day) = first_day {
+            self.week.first_day = first_day;
+        }
+        if let Some(weekend) = weekend {
+            self.week.weekend = weekend;
+        }
+    }
+
+    /// Formats a number with a fixed number of decimals and the locale's separator.
+    pub fn number(&self, value: f64, precision: usize) -> String {
+        let formatted = format!("{:.*}", precision, value);
+        if self.decimal_separator == '.' {
+            formatted
+        } else {
+            formatted.replace('.', &self.decimal_separator.to_string())
+        }
+    }
+
+    /// Formats a value followed by its unit, e.g. `23.5°C` or `23,5 °C`.
+    /// Unit words such as `m/s` or `km` are always separated by a space.
+    pub fn quantity(&self, value: f64, precision: usize, unit: &str) -> String {
+        let symbol = unit.starts_with('°') || unit == "%";
+        if symbol && !self.space_before_unit {
+            format!("{}{}", self.number(value, precision), unit)
+        } else {
+            format!("{}\u{a0}{}", self.number(value, precision), unit)
+        }
+    }
+
+    /// Formats the calendar date of `datetime` using the locale's ordering.
+    pub fn date(&self, datetime: &DateTime<FixedOffset>) -> String {
+        let sep = self.date_separator;
+        let pattern = match self.date_order {
+            DateOrder::DayMonthYear => format!("%d{sep}%m{sep}%Y"),
+            DateOrder::MonthDayYear => format!("%m{sep}%d{sep}%Y"),
+            DateOrder::YearMonthDay => format!("%Y{sep}%m{sep}%d"),
+        };
+        datetime.format(&pattern).to_string()
+    }
+
+    /// Formats the wall-clock time of `datetime` in 12- or 24-hour style.
+    pub fn time(&self, datetime: &DateTime<FixedOffset>) -> String {
+        if self.hour12 {
+            datetime.format("%-I:%M:%S %p").to_string()
+        } else {
+            datetime.format("%H:%M:%S").to_string()
+        }
+    }
+
+    /// As [`Locale::time`] without the seconds, e.g. for forecast steps.
+    pub fn short_time(&self, datetime: &DateTime<FixedOffset>) -> String {
+        if self.hour12 {
+            datetime.format("%-I:%M %p").to_string()
+        } else {
+            datetime.format("%H:%M").to_string()
+        }
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale::from_tag("en-US").expect("en-US is always supported")
+    }
+}
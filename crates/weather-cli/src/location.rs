@@ -0,0 +1,233 @@
+//! Device location sources for `--here`, the IP address lookup behind
+//! `--auto`, and the coordinates given with `--lat` and `--lon`.
+
+use crate::http_client;
+use serde::Deserialize;
+use std::env;
+use std::process::Command;
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use weather_providers::iplocation;
+
+const GPSD_DEFAULT_ADDR: &str = "127.0.0.1:2947";
+const GPSD_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where to read the device position from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    /// termux-location on Termux, otherwise a local gpsd.
+    Auto,
+    Termux,
+    /// A gpsd daemon at `host:port`.
+    Gpsd(String),
+    /// Where the public IP address appears to be, to the nearest city.
+    Ip,
+}
+
+impl FromStr for Source {
+    type Err = String;
+
+    /// Accepts `auto`, `termux`, `gpsd`, `gpsd:<host>:<port>` or `ip`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "auto" => Ok(Source::Auto),
+            "termux" => Ok(Source::Termux),
+            "ip" => Ok(Source::Ip),
+            "gpsd" => Ok(Source::Gpsd(GPSD_DEFAULT_ADDR.to_string())),
+            _ => match value.strip_prefix("gpsd:") {
+                Some(addr) if addr.contains(':') => Ok(Source::Gpsd(addr.to_string())),
+                _ => Err(format!(
+                    "Unknown location source '{}': expected auto, termux, gpsd, gpsd:<host>:<port> or ip",
+                    value
+                )),
+            },
+        }
+    }
+}
+
+/// Parses a `--lat` value: decimal degrees from -90 to 90.
+pub fn parse_latitude(value: &str) -> Result<f64, String> {
+    parse_degrees(value, 90.0, "Latitude")
+}
+
+/// Parses a `--lon` value: decimal degrees from -180 to 180.
+pub fn parse_longitude(value: &str) -> Result<f64, String> {
+    parse_degrees(value, 180.0, "Longitude")
+}
+
+fn parse_degrees(value: &str, limit: f64, what: &str) -> Result<f64, String> {
+    let degrees: f64 = value
+        .trim()
+        .parse()
+        .map_err(|_| format!("{} '{}' is not a number of degrees", what, value))?;
+    if !(-limit..=limit).contains(&degrees) {
+        return Err(format!(
+            "{} {} is out of range: expected -{} to {}",
+            what, degrees, limit, limit
+        ));
+    }
+    Ok(degrees)
+}
+
+/// A position reported by a location source.
+#[derive(Debug, Clone)]
+pub struct Position {
+    pub lat: f64,
+    pub lon: f64,
+    /// Horizontal accuracy in metres, when the source reports it.
+    pub accuracy: Option<f64>,
+    pub source: &'static str,
+    /// Where the source says the position is, for sources too coarse to
+    /// trust without checking, e.g. "London, England, GB".
+    pub place: Option<String>,
+}
+
+/// Reads the device position from `source`. Called for every lookup, so
+/// long-running modes follow a moving device.
+pub async fn current_position(source: &Source) -> Result<Position, Box<dyn std::error::Error>> {
+    match source {
+        Source::Termux => termux_location(),
+        Source::Gpsd(addr) => gpsd_position(addr).await,
+        Source::Ip => ip_position().await,
+        Source::Auto if is_termux() => termux_location(),
+        Source::Auto => gpsd_position(GPSD_DEFAULT_ADDR).await.map_err(|e| {
+            format!(
+                "No location source available (not on Termux, and gpsd at {} failed: {})",
+                GPSD_DEFAULT_ADDR, e
+            )
+            .into()
+        }),
+    }
+}
+
+/// Whether we are running inside the Termux app on Android.
+pub fn is_termux() -> bool {
+    env::var_os("TERMUX_VERSION").is_some()
+        || env::var("PREFIX").is_ok_and(|prefix| prefix.contains("com.termux"))
+}
+
+#[derive(Debug, Deserialize)]
+struct TermuxLocation {
+    latitude: f64,
+    longitude: f64,
+    accuracy: Option<f64>,
+}
+
+/// Asks Termux:API for a single fix, preferring GPS and falling back to the
+/// (faster, coarser) network provider.
+fn termux_location() -> Result<Position, Box<dyn std::error::Error>> {
+    let mut last_error = String::new();
+    for provider in ["gps", "network"] {
+        let output = match Command::new("termux-location")
+            .args(["-p", provider, "-r", "once"])
+            .output()
+        {
+            Ok(output) => output,
+            Err(e) => {
+                return Err(format!(
+                    "Failed to run termux-location ({}). Install it with `pkg install termux-api` \
+                     and the Termux:API app.",
+                    e
+                )
+                .into())
+            }
+        };
+
+        if !output.status.success() || output.stdout.is_empty() {
+            last_error = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            continue;
+        }
+
+        match serde_json::from_slice::<TermuxLocation>(&output.stdout) {
+            Ok(fix) => {
+                return Ok(Position {
+                    lat: fix.latitude,
+                    lon: fix.longitude,
+                    accuracy: fix.accuracy,
+                    source: "termux-location",
+                    place: None,
+                })
+            }
+            Err(e) => last_error = e.to_string(),
+        }
+    }
+
+    Err(format!(
+        "termux-location returned no position{}",
+        if last_error.is_empty() {
+            String::new()
+        } else {
+            format!(": {}", last_error)
+        }
+    )
+    .into())
+}
+
+/// Looks the public IP address up. No accuracy is given: the position is a
+/// city centre, or the provider's idea of one.
+async fn ip_position() -> Result<Position, Box<dyn std::error::Error>> {
+    let location = iplocation::locate(&http_client()?).await?;
+    Ok(Position {
+        lat: location.latitude,
+        lon: location.longitude,
+        accuracy: None,
+        source: "ipapi.co",
+        place: Some(location.label()),
+    })
+}
+
+/// A gpsd time-position-velocity report; only the fields we use.
+#[derive(Debug, Deserialize)]
+struct GpsdReport {
+    class: String,
+    #[serde(default)]
+    mode: u8,
+    lat: Option<f64>,
+    lon: Option<f64>,
+    /// Estimated horizontal position error in metres.
+    eph: Option<f64>,
+    epx: Option<f64>,
+    epy: Option<f64>,
+}
+
+/// Connects to gpsd, enables JSON watch mode and waits for the first report
+/// with a 2D or 3D fix.
+async fn gpsd_position(addr: &str) -> Result<Position, Box<dyn std::error::Error>> {
+    let read_fix = async {
+        let mut stream = TcpStream::connect(addr).await?;
+        stream
+            .write_all(b"?WATCH={\"enable\":true,\"json\":true};\n")
+            .await?;
+
+        let mut lines = BufReader::new(stream).lines();
+        while let Some(line) = lines.next_line().await? {
+            let Ok(report) = serde_json::from_str::<GpsdReport>(&line) else {
+                continue;
+            };
+            if report.class != "TPV" || report.mode < 2 {
+                continue;
+            }
+            if let (Some(lat), Some(lon)) = (report.lat, report.lon) {
+                let accuracy = report.eph.or(match (report.epx, report.epy) {
+                    (Some(x), Some(y)) => Some(x.max(y)),
+                    _ => None,
+                });
+                return Ok(Position {
+                    lat,
+                    lon,
+                    accuracy,
+                    source: "gpsd",
+                    place: None,
+                });
+            }
+        }
+        Err::<Position, Box<dyn std::error::Error>>("gpsd closed the connection".into())
+    };
+
+    match tokio::time::timeout(GPSD_TIMEOUT, read_fix).await {
+        Ok(result) => result,
+        Err(_) => Err(format!("no GPS fix from gpsd within {}s", GPSD_TIMEOUT.as_secs()).into()),
+    }
+}
@@ -0,0 +1,4375 @@
+mod art;
+mod backfill;
+mod bell;
+mod cache;
+mod calendar;
+mod calibration;
+mod clock;
+mod condition;
+mod config;
+mod crash;
+mod daemon;
+mod display;
+mod errors;
+mod events;
+mod generate;
+mod glossary;
+mod gpx;
+mod history;
+mod hook;
+mod hourly;
+mod hyperlocal;
+mod i18n;
+mod ics;
+mod influx;
+mod keymap;
+mod layout;
+mod locale;
+mod location;
+mod migrate;
+mod mountain;
+mod nagios;
+mod notify;
+mod outlook;
+mod platform;
+mod recommend;
+mod render;
+mod report;
+mod roads;
+mod safe_window;
+mod saved;
+mod screenshot;
+mod server;
+mod solar;
+mod speak;
+mod station;
+mod storage;
+mod summary;
+mod surf;
+mod thresholds;
+mod timeline;
+mod tokens;
+mod tui;
+mod units;
+mod update;
+mod wake;
+mod widget;
+mod wind_window;
+
+use chrono::{DateTime, FixedOffset, Local, TimeZone, Utc};
+use clock::{Clock, FixedClock, SystemClock};
+use colored::{ColoredString, Colorize};
+use dotenv::dotenv;
+use errors::ResultExt;
+use futures_util::future::join_all;
+use futures_util::StreamExt;
+use locale::Locale;
+use render::Card;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::{Arc, Mutex};
+use structopt::StructOpt;
+use weather_core::model;
+use weather_providers::client::{Cancel, ChangeFilter, WeatherUpdate};
+use weather_providers::geocode::{self, Place};
+use weather_providers::provider::{self, WeatherProvider};
+use weather_providers::{
+    air, forecast, marine, onecall, openmeteo, pws, river, route, tides, WeatherClient,
+    WeatherError,
+};
+
+#[derive(StructOpt, Debug)]
+#[structopt(
+    name = "weather",
+    about = "A weather CLI application",
+    after_help = "EXIT CODES:\n    1  --exit-status warning, or \"no\" from will-it-rain and notify\n    2, 3  --exit-status critical and nagios results\n    4  no such place, or no weather for it\n    5  invalid API key\n    6  rate limited\n    7  network error\n    8  unexpected response\n    9  other HTTP error status\n    10  other errors"
+)]
+struct Opt {
+    /// Location, e.g. London or London,GB; repeat to show several, fetched concurrently
+    #[structopt(short, long, number_of_values = 1)]
+    city: Vec<String>,
+
+    /// Units to show values in: metric, imperial or standard (kelvin); JSON stays metric
+    #[structopt(long, value_name = "UNITS", possible_values = &["metric", "imperial", "standard"])]
+    units: Option<units::Units>,
+
+    /// Shorthand for --units imperial
+    #[structopt(short, long, conflicts_with = "units")]
+    fahrenheit: bool,
+
+    /// Shorthand for --units metric, e.g. when the config file sets imperial units
+    #[structopt(long, conflicts_with_all = &["fahrenheit", "units"])]
+    metric: bool,
+
+    /// Which of the places matching the --city name to use, as numbered in interactive mode
+    #[structopt(long, value_name = "N", parse(try_from_str = parse_select))]
+    select: Option<usize>,
+
+    /// Country code narrowing the --city name down, e.g. US (unless the name has one, as in London,GB)
+    #[structopt(long, value_name = "CODE", parse(try_from_str = parse_country))]
+    country: Option<String>,
+
+    /// Use the device's current location (termux-location on Android, else gpsd)
+    #[structopt(long, conflicts_with = "city")]
+    here: bool,
+
+    /// Detect the approximate location from the public IP address (the default when no location is given and input is not a terminal)
+    #[structopt(long, conflicts_with_all = &["city", "here", "location", "lat"])]
+    auto: bool,
+
+    /// Location source for the current position: auto, termux, gpsd, gpsd:<host>:<port> or ip
+    #[structopt(long, value_name = "SOURCE", conflicts_with = "city")]
+    location: Option<location::Source>,
+
+    /// Latitude of the place in decimal degrees, with --lon (e.g. for a trailhead)
+    #[structopt(
+        long,
+        allow_hyphen_values = true,
+        requires = "lon",
+        conflicts_with_all = &["city", "here", "location"],
+        parse(try_from_str = location::parse_latitude)
+    )]
+    lat: Option<f64>,
+
+    /// Longitude of the place in decimal degrees, with --lat
+    #[structopt(
+        long,
+        allow_hyphen_values = true,
+        requires = "lat",
+        parse(try_from_str = location::parse_longitude)
+    )]
+    lon: Option<f64>,
+
+    /// Source of current conditions: openweathermap or open-meteo (needs no API key)
+    #[structopt(long, value_name = "NAME", default_value = "openweathermap")]
+    provider: Provider,
+
+    /// Locale used for numbers and dates, e.g. `de-DE` (defaults to $LANG)
+    #[structopt(long)]
+    locale: Option<String>,
+
+    /// Language of OpenWeatherMap's weather descriptions and of the card's labels, e.g. `de` or `zh_cn` (labels default to the locale's language)
+    #[structopt(long, value_name = "CODE", parse(try_from_str = parse_language))]
+    lang: Option<String>,
+
+    /// Print the weather as JSON (see `weather schema`)
+    #[structopt(long)]
+    json: bool,
+
+    /// Explain what each value on the card means
+    #[structopt(long)]
+    explain: bool,
+
+    /// Card layout from [card.layouts] in the config (default: [card] layout, else "default")
+    #[structopt(long, value_name = "NAME")]
+    layout: Option<String>,
+
+    /// Use plain ASCII instead of emoji icons (automatic on terminals without emoji)
+    #[structopt(long)]
+    ascii: bool,
+
+    /// Draw the current condition as ASCII art beside the card, like wttr.in
+    #[structopt(long)]
+    art: bool,
+
+    /// Show only an icon, a big temperature and a friendly sentence
+    #[structopt(long)]
+    simple: bool,
+
+    /// Print a one or two sentence summary of today's weather instead of the card
+    #[structopt(long)]
+    summary: bool,
+
+    /// Read a short summary aloud using the system text-to-speech engine
+    #[structopt(long)]
+    speak: bool,
+
+    /// Write the spoken summary to a file (`-` for stdout) for an external TTS tool
+    #[structopt(long, parse(from_os_str), value_name = "FILE")]
+    speak_file: Option<PathBuf>,
+
+    /// River gauge to show with the weather: usgs:<site> or ea:<station>
+    #[structopt(long, value_name = "STATION", env = "WEATHER_RIVER_STATION")]
+    river: Option<river::Station>,
+
+    /// Show weather alerts in force at the location (NWS in the US, MeteoAlarm in Europe, else OpenWeatherMap One Call), plus any CAP feeds in [alerts] in the config
+    #[structopt(long)]
+    alerts: bool,
+
+    /// Show only the alerts, or with --json only the array of them; with --exit-status, exit 1 while any are in force and 2 for severe or extreme ones
+    #[structopt(long, conflicts_with_all = &["watch", "tui", "compact", "summary", "simple", "influx"])]
+    alerts_only: bool,
+
+    /// Personal weather station to compare with the provider: ecowitt:<host>, weewx:<url>, weewx:mqtt://<host>/<topic>, netatmo[:<mac>] or ambient[:<mac>]
+    #[structopt(long, value_name = "STATION", env = "WEATHER_PWS")]
+    pws: Option<pws::Station>,
+
+    /// Add a road ice risk advisory for now and the coming night
+    #[structopt(long)]
+    roads: bool,
+
+    /// Send a desktop notification when overnight conditions favour icy roads
+    #[structopt(long)]
+    notify_ice: bool,
+
+    /// Add sun and moon times, solar elevation and moon phase, computed offline
+    #[structopt(long)]
+    astro: bool,
+
+    /// Add air density, pressure altitude and density altitude, for pilots and athletes
+    #[structopt(long)]
+    performance: bool,
+
+    /// Add what long-range shooters dial in: temperature, station pressure, humidity, density altitude and crosswind
+    #[structopt(long)]
+    ballistics: bool,
+
+    /// Direction of travel or fire in degrees from north, to split the wind into headwind and crosswind in the card, --ballistics and `hourly` (e.g. 270)
+    #[structopt(long, value_name = "DEGREES", parse(try_from_str = parse_heading))]
+    heading: Option<f64>,
+
+    /// Elevation of the place in metres, for --performance (default: from the provider's ground-level pressure)
+    #[structopt(long, value_name = "METRES", allow_hyphen_values = true)]
+    elevation: Option<f64>,
+
+    /// Add where the data comes from: the provider's station, its position and distance
+    #[structopt(long)]
+    source_info: bool,
+
+    /// Interpolate temperature and precipitation for the exact coordinates from the surrounding grid points
+    #[structopt(long)]
+    hyperlocal: bool,
+
+    /// Add a timeline of conditions and precipitation for the next 24 hours
+    #[structopt(long)]
+    today: bool,
+
+    /// Fail instead of showing an observation older than this (e.g. 30min)
+    #[structopt(long, value_name = "DURATION", parse(try_from_str = clock::parse_duration))]
+    max_age: Option<std::time::Duration>,
+
+    /// Exit with 1 when a configured threshold reaches warn and 2 when critical
+    #[structopt(long)]
+    exit_status: bool,
+
+    /// Send a desktop notification when a configured threshold is breached
+    #[structopt(long)]
+    notify: bool,
+
+    /// Ring the terminal bell when a configured threshold is breached or an alert is in force (under --watch, only when new)
+    #[structopt(long)]
+    bell: bool,
+
+    /// Play this sound file when --bell would ring, with afplay, paplay, aplay or similar
+    #[structopt(long, parse(from_os_str), value_name = "FILE")]
+    sound: Option<PathBuf>,
+
+    /// Retries of requests that failed transiently: timeouts, refused connections, 429 and 5xx (default 2, or [retry] in the config)
+    #[structopt(long, value_name = "N")]
+    retries: Option<u32>,
+
+    /// Delay before the first retry, doubled for each one after (e.g. 500ms)
+    #[structopt(long, value_name = "DURATION", parse(try_from_str = clock::parse_duration))]
+    retry_delay: Option<std::time::Duration>,
+
+    /// Config file to use instead of config.toml in the config directory
+    #[structopt(long, parse(from_os_str), value_name = "FILE", env = "WEATHER_CONFIG")]
+    config: Option<PathBuf>,
+
+    /// Render relative times as if it were this moment (RFC 3339, 'YYYY-MM-DD HH:MM' UTC or Unix seconds)
+    #[structopt(long, value_name = "TIMESTAMP", parse(try_from_str = clock::parse_timestamp))]
+    as_of: Option<DateTime<Utc>>,
+
+    /// Output format: compact (one line for tmux, i3blocks or polybar), lualine or starship-module (status segments from the cache), or influx (line protocol)
+    #[structopt(long, alias = "format", value_name = "FORMAT")]
+    output: Option<Output>,
+
+    /// With --output influx, post to this write URL instead of printing (e.g. http://localhost:8428/write)
+    #[structopt(long, value_name = "URL", env = "WEATHER_INFLUX_URL")]
+    influx_url: Option<String>,
+
+    /// Token sent with --influx-url
+    #[structopt(
+        long,
+        value_name = "TOKEN",
+        env = "WEATHER_INFLUX_TOKEN",
+        hide_env_values = true
+    )]
+    influx_token: Option<String>,
+
+    /// With a status-line --output, fetch and update the cache first instead of only reading it
+    #[structopt(long, requires = "output")]
+    refresh: bool,
+
+    /// Fetch the current conditions even if a lookup within [cache] weather_ttl is cached
+    #[structopt(long)]
+    no_cache: bool,
+
+    /// Check every INTERVAL (e.g. 30s, 5min; default [cache] weather_ttl) and redraw the screen for each new observation, highlighting changes
+    #[structopt(
+        long,
+        value_name = "INTERVAL",
+        validator = clock::validate_duration,
+        conflicts_with_all = &["json", "output", "exit-status"]
+    )]
+    watch: Option<Option<String>>,
+
+    /// Open a full-screen dashboard of conditions and forecast for this and the saved locations (keys in [tui.keys] in the config)
+    #[structopt(long, conflicts_with_all = &["json", "output", "watch", "simple", "summary", "exit-status"])]
+    tui: bool,
+
+    /// With --tui, write the dashboard to FILE instead of opening it (.svg image, .txt, or ANSI text)
+    #[structopt(long, parse(from_os_str), value_name = "FILE", requires = "tui")]
+    screenshot: Option<PathBuf>,
+
+    /// Show every location saved with `weather save`, fetched concurrently
+    #[structopt(long, conflicts_with_all = &["city", "here", "auto", "location", "lat", "watch", "tui"])]
+    favorites: bool,
+
+    #[structopt(subcommand)]
+    cmd: Option<Command>,
+}
+
+#[derive(StructOpt, Debug)]
+enum Command {
+    /// Explain a weather metric such as dew-point or pressure (lists all without a field)
+    Explain { field: Option<String> },
+    /// Print the JSON schema of the `--json` output
+    Schema,
+    /// Show where configuration, cache and data files are stored
+    Paths,
+    /// Serve weather lookups over HTTP (OpenAPI document at /openapi.json)
+    Serve {
+        /// Address to listen on
+        #[structopt(long, default_value = "127.0.0.1:8080")]
+        bind: std::net::SocketAddr,
+
+        /// Require this bearer token on API requests
+        #[structopt(long, env = "WEATHER_SERVE_TOKEN", hide_env_values = true)]
+        token: Option<String>,
+
+        /// Requests per minute allowed per client address (0 for unlimited)
+        #[structopt(long, default_value = "60")]
+        rate_limit: u32,
+
+        /// Allow browser requests from this origin (repeatable, `*` for any)
+        #[structopt(long = "cors-origin", value_name = "ORIGIN")]
+        cors_origins: Vec<String>,
+    },
+    /// Keep the current location's weather fresh in a state file for status bars and trays
+    Daemon {
+        #[structopt(flatten)]
+        refresh: RefreshArgs,
+    },
+    /// Run the daemon and call a script with the conditions after each refresh, e.g. to set a wallpaper or theme
+    Hook {
+        /// Executable run with the conditions in WEATHER_* variables and the report as JSON on stdin
+        #[structopt(long, parse(from_os_str), value_name = "FILE")]
+        script: PathBuf,
+
+        #[structopt(flatten)]
+        refresh: RefreshArgs,
+    },
+    /// Exit non-zero unless a running `weather serve` reports ready (for container HEALTHCHECK)
+    Healthcheck {
+        /// Readiness endpoint to query
+        #[structopt(long, default_value = "http://127.0.0.1:8080/readyz")]
+        url: String,
+    },
+    /// Show the forecast for the coming days
+    Forecast {
+        /// Place to look up (defaults to --city or the current location)
+        location: Option<String>,
+
+        /// Number of days to show, today included (1 to 5)
+        #[structopt(long, default_value = "5")]
+        days: usize,
+
+        /// Show what changed since the previous forecast fetched for the place
+        #[structopt(long)]
+        diff: bool,
+
+        /// Only show weekend days (per the locale, or [week] in the config)
+        #[structopt(long)]
+        weekend: bool,
+    },
+    /// Chart the temperature and chance of precipitation hour by hour
+    Hourly {
+        /// Place to look up (defaults to --city or the current location)
+        location: Option<String>,
+
+        /// Number of hours to show (1 to 48)
+        #[structopt(long, default_value = "24")]
+        hours: usize,
+    },
+    /// Forecast sunshine and solar irradiance hour by hour, clouds accounted for (from Open-Meteo)
+    Solar {
+        /// Place to look up (defaults to --city or the current location)
+        location: Option<String>,
+
+        /// Number of days to show, today included (1 to 16)
+        #[structopt(long, default_value = "2")]
+        days: u32,
+
+        /// Only each day's hours of sunshine and total radiation
+        #[structopt(long)]
+        sunshine_hours: bool,
+    },
+    /// Show the next high and low tides near a coastal location
+    Tides {
+        /// Place to look up (defaults to --city or the current location)
+        location: Option<String>,
+    },
+    /// Rate the surf at dawn, midday and dusk from the swell and the wind off the beach (spots under [surf.spots] in the config)
+    Surf {
+        /// Spot configured under [surf.spots], else a place to look up, with --facing (defaults to --city)
+        spot: Option<String>,
+
+        /// The way the beach faces, out to sea, in degrees or as a compass point (e.g. 270, WSW); needed for a place that is not a configured spot
+        #[structopt(long, value_name = "DIRECTION", parse(try_from_str = surf::parse_facing))]
+        facing: Option<f64>,
+
+        /// Number of days to rate, today included (1 to 7)
+        #[structopt(long, default_value = "2")]
+        days: u32,
+    },
+    /// Show the forecast at the summit, halfway up and at the base of a
+    /// mountain, with the freezing level
+    Mountain {
+        /// Peak to look up (defaults to --city)
+        peak: Option<String>,
+
+        /// Summit elevation in metres, instead of the terrain at the peak's coordinates
+        #[structopt(long, value_name = "METRES")]
+        summit: Option<f64>,
+
+        /// Base elevation in metres, instead of the lowest terrain within 7.5 km
+        #[structopt(long, value_name = "METRES")]
+        base: Option<f64>,
+
+        /// Number of days to show, today included (1 to 16)
+        #[structopt(long, default_value = "3")]
+        days: u32,
+    },
+    /// Show the weather along a timed GPX track, leg by leg, at the time and
+    /// elevation each is reached
+    Gpx {
+        /// GPX file with a track whose points have timestamps
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+
+        /// Set off at this local time instead of the track's own (e.g. 08:00, 'sat 08:00', 'tomorrow 7:30')
+        #[structopt(long, value_name = "WHEN")]
+        start: Option<gpx::Start>,
+
+        /// Length of each leg in time (e.g. 30min, 2h)
+        #[structopt(long, value_name = "DURATION", default_value = "1h", parse(try_from_str = clock::parse_duration))]
+        every: std::time::Duration,
+    },
+    /// Show the upcoming events of an iCalendar file with the forecast
+    /// where and when each happens
+    Events {
+        /// iCalendar (.ics) file, such as one exported from a calendar app
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+    },
+    /// Print wake-up alarms relative to sunrise as an iCalendar series or
+    /// crontab lines
+    Wake {
+        /// Place to look up (defaults to --city or the current location)
+        location: Option<String>,
+
+        /// Time from sunrise to the alarm, negative for before (e.g. -30min, 15min)
+        #[structopt(long, value_name = "DURATION", default_value = "0", allow_hyphen_values = true, parse(try_from_str = wake::parse_offset))]
+        offset: i64,
+
+        /// Number of days of alarms, today included
+        #[structopt(long, default_value = "7")]
+        days: u32,
+
+        /// Print crontab lines, in this machine's time zone, instead of an iCalendar file
+        #[structopt(long)]
+        cron: bool,
+
+        /// Command the --cron lines run
+        #[structopt(long, value_name = "COMMAND", default_value = "notify-send 'Wake up'")]
+        command: String,
+    },
+    /// Show the air quality index and pollutant concentrations
+    Air {
+        /// Place to look up (defaults to --city or the current location)
+        location: Option<String>,
+    },
+    /// Compare locations in one table: a row each for now, a column each for the forecast days
+    Compare {
+        /// Places to compare, two or more, in the order shown
+        #[structopt(required = true, min_values = 2)]
+        cities: Vec<String>,
+
+        /// Number of forecast days to show, today included (0 to 5)
+        #[structopt(long, default_value = "5")]
+        days: usize,
+    },
+    /// Find dry gaps between showers and thunderstorms in the hours ahead
+    DryWindows {
+        /// Place to look up (defaults to --city or the current location)
+        location: Option<String>,
+
+        /// Shortest gap worth reporting, in minutes
+        #[structopt(long, default_value = "20")]
+        min: i64,
+
+        /// How many hours ahead to search
+        #[structopt(long, default_value = "12")]
+        hours: i64,
+    },
+    /// Find the hours ahead when the wind suits kiting, paragliding or sailing
+    WindWindow {
+        /// Place to look up (defaults to --city or the current location)
+        location: Option<String>,
+
+        /// Lightest wind that will do, in knots
+        #[structopt(long, default_value = "12", parse(try_from_str = parse_knots))]
+        min: f64,
+
+        /// Strongest wind that is still safe, in knots
+        #[structopt(long, default_value = "25", parse(try_from_str = parse_knots))]
+        max: f64,
+
+        /// Directions the wind must blow from, clockwise, in degrees or compass points (e.g. 180-240, S-WSW, 300-30)
+        #[structopt(long, value_name = "FROM-TO")]
+        dir: Option<wind_window::Sector>,
+
+        /// Shortest window worth reporting, in hours
+        #[structopt(long, default_value = "1")]
+        length: i64,
+
+        /// How many hours ahead to search (1 to 120; beyond 48 from the 3-hourly forecast)
+        #[structopt(long, default_value = "48")]
+        hours: i64,
+    },
+    /// Answer whether it will rain during a time window (exit 0: yes, 1: no; errors as below)
+    WillItRain {
+        /// Place to look up (defaults to --city or the current location)
+        location: Option<String>,
+
+        /// When the window starts, from now (e.g. 45min, 1h30m)
+        #[structopt(long = "in", value_name = "DURATION", default_value = "0", parse(try_from_str = clock::parse_duration))]
+        after: std::time::Duration,
+
+        /// How long the window lasts
+        #[structopt(long = "for", value_name = "DURATION", default_value = "30min", parse(try_from_str = clock::parse_duration))]
+        lasting: std::time::Duration,
+
+        /// Chance of precipitation, in percent, from which the answer is yes
+        #[structopt(long, default_value = "50")]
+        threshold: u8,
+    },
+    /// Send a desktop notification when the conditions match, e.g. from cron (exit 0: notified, 1: no match; errors as below)
+    Notify {
+        /// Place to look up (defaults to --city or the current location)
+        location: Option<String>,
+
+        /// The place, as with `weather --city` (for crontab lines reading `weather notify --city Delhi --if ...`)
+        #[structopt(short, long, conflicts_with = "location")]
+        city: Option<String>,
+
+        /// Conditions such as 'temp>40 or rain', 'wind>=10 and clouds<50': fields as in `weather check` (metric), or rain, drizzle, snow, storm, fog, clear, cloudy
+        #[structopt(long = "if", value_name = "CONDITIONS")]
+        condition: condition::Condition,
+    },
+    /// Nagios/Icinga plugin: exit 0/1/2 for OK/WARNING/CRITICAL (3 when unknown) with perfdata
+    Check {
+        /// Place to look up (defaults to --city or the current location)
+        location: Option<String>,
+
+        /// Warning threshold such as 'temp>35' or 'visibility<1000' (repeatable)
+        #[structopt(long, value_name = "EXPR")]
+        warn: Vec<String>,
+
+        /// Critical threshold in the same form (repeatable)
+        #[structopt(long, value_name = "EXPR")]
+        crit: Vec<String>,
+    },
+    /// Save a location under an alias such as home, then show it with `weather home`
+    Save {
+        /// One word naming the location, also taken wherever a place is
+        #[structopt(parse(try_from_str = parse_alias))]
+        alias: String,
+
+        /// Place to look up (defaults to --city or the current location)
+        location: Option<String>,
+    },
+    /// List or remove the locations saved with `weather save`
+    Saved(SavedCommand),
+    /// Sign in to a cloud weather station account used with --pws
+    Auth(AuthCommand),
+    /// Show the readings recorded in the observation log, with the lowest,
+    /// highest and mean temperature
+    History {
+        /// Only readings for this location name, as recorded (defaults to
+        /// --city, else every location)
+        location: Option<String>,
+
+        /// The location name, as with `weather --city`
+        #[structopt(short, long, conflicts_with = "location")]
+        city: Option<String>,
+
+        /// Only readings from this long ago onwards (e.g. 7d, 12h)
+        #[structopt(long, value_name = "DURATION", parse(try_from_str = clock::parse_duration))]
+        since: Option<std::time::Duration>,
+    },
+    /// Work with the observation log configured under [storage]
+    Db(DbCommand),
+    /// Generate packaging artifacts such as the man page and shell completions
+    Generate(GenerateCommand),
+    /// Update the binary to the latest GitHub release
+    SelfUpdate {
+        /// Only report whether an update is available
+        #[structopt(long)]
+        check_only: bool,
+    },
+    // `weather <alias>`: a saved location in place of a subcommand.
+    #[structopt(external_subcommand)]
+    Other(Vec<String>),
+}
+
+// How often `weather daemon` and `weather hook` refresh, and where. (A doc
+// comment here would replace their help text.)
+#[derive(StructOpt, Debug)]
+struct RefreshArgs {
+    /// Seconds between refreshes
+    #[structopt(long, default_value = "600")]
+    interval: u64,
+
+    /// Metres of movement after which the active location is re-resolved
+    #[structopt(long, default_value = "5000")]
+    geofence: f64,
+}
+
+#[derive(StructOpt, Debug)]
+enum DbCommand {
+    /// Print recorded reports as JSON lines, oldest first
+    Export {
+        /// Only reports for this location name
+        location: Option<String>,
+
+        /// Only reports from this long ago onwards (e.g. 7d, 12h)
+        #[structopt(long, value_name = "DURATION", parse(try_from_str = clock::parse_duration))]
+        since: Option<std::time::Duration>,
+    },
+    /// Fill the log with hourly history from the Open-Meteo archive,
+    /// resuming an interrupted run
+    Backfill {
+        city: String,
+
+        /// First day to import (YYYY-MM-DD)
+        #[structopt(long, value_name = "DATE")]
+        from: chrono::NaiveDate,
+
+        /// Last day to import; defaults to the newest day in the archive
+        #[structopt(long, value_name = "DATE")]
+        to: Option<chrono::NaiveDate>,
+
+        /// Start again at --from instead of resuming
+        #[structopt(long)]
+        restart: bool,
+    },
+    /// Learn calibration offsets for a location from the station readings
+    /// in the log
+    Calibrate {
+        /// Location name, as recorded
+        location: String,
+
+        /// Only reports from this long ago onwards (e.g. 30d)
+        #[structopt(long, value_name = "DURATION", parse(try_from_str = clock::parse_duration))]
+        since: Option<std::time::Duration>,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+enum SavedCommand {
+    /// List the saved locations and their aliases
+    List,
+    /// Forget a saved location, by alias or name
+    Remove { alias: String },
+}
+
+#[derive(StructOpt, Debug)]
+enum AuthCommand {
+    /// Store a refresh token from your app's token generator at dev.netatmo.com,
+    /// read from standard input
+    Netatmo,
+}
+
+#[derive(StructOpt, Debug)]
+enum GenerateCommand {
+    /// Write the weather.1 man page
+    Man {
+        /// Output directory
+        #[structopt(long, parse(from_os_str))]
+        out: PathBuf,
+    },
+    /// Write shell completion scripts
+    Completions {
+        /// Output directory
+        #[structopt(long, parse(from_os_str))]
+        out: PathBuf,
+
+        /// Only generate for this shell (repeatable; default: all)
+        #[structopt(long, possible_values = &structopt::clap::Shell::variants())]
+        shell: Vec<structopt::clap::Shell>,
+    },
+}
+
+/// What to fetch the weather for.
+enum Query {
+    /// The best match for a name.
+    City(String),
+    /// Another of the places matching a name, numbered from 1 in the order
+    /// the provider ranks them (`--select`, or picked interactively).
+    Choice { name: String, index: usize },
+    /// The device's current position, or the IP address's.
+    Here(location::Source),
+    /// A position given with `--lat` and `--lon`.
+    Coordinates { lat: f64, lon: f64 },
+    /// A location saved with `weather save`, which needs no lookup.
+    Saved(saved::Location),
+}
+
+impl std::fmt::Display for Query {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Query::City(city) => write!(f, "'{}'", city),
+            Query::Choice { name, index } => write!(f, "'{}' (match {})", name, index),
+            Query::Here(_) => write!(f, "your location"),
+            Query::Coordinates { lat, lon } => write!(f, "{:.4}, {:.4}", lat, lon),
+            Query::Saved(location) => write!(f, "'{}'", location.label()),
+        }
+    }
+}
+
+/// Formats selected with `--output`.
+#[derive(Debug, Clone, Copy)]
+enum Output {
+    Widget(widget::Format),
+    /// A single line with the place, fetched like the card.
+    Compact,
+    Influx,
+}
+
+impl std::str::FromStr for Output {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "influx" => Ok(Output::Influx),
+            "compact" => Ok(Output::Compact),
+            _ => s.parse().map(Output::Widget).map_err(|_| {
+                format!(
+                    "Unknown output format '{}'. Use compact, lualine, starship-module or influx",
+                    s
+                )
+            }),
+        }
+    }
+}
+
+/// Backends selectable with `--provider`. Forecasts, tides and the other
+/// subcommands still read OpenWeatherMap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Provider {
+    OpenWeatherMap,
+    OpenMeteo,
+}
+
+impl std::str::FromStr for Provider {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "openweathermap" | "owm" => Ok(Provider::OpenWeatherMap),
+            "open-meteo" | "openmeteo" => Ok(Provider::OpenMeteo),
+            _ => Err(format!(
+                "Unknown provider '{}'. Use openweathermap or open-meteo",
+                s
+            )),
+        }
+    }
+}
+
+/// State of `--watch` between refreshes.
+struct Watch {
+    interval: std::time::Duration,
+    /// Conditions shown by the previous refresh.
+    previous: Mutex<Option<model::Conditions>>,
+    /// Threshold breaches and alerts at the previous refresh, so that
+    /// `--bell` rings only for new ones.
+    events: Mutex<Vec<String>>,
+}
+
+/// Settings that control how weather data is rendered.
+struct DisplayOptions {
+    provider: Provider,
+    units: units::Units,
+    locale: Locale,
+    json: bool,
+    influx: bool,
+    compact: bool,
+    /// Where `--output influx` writes instead of standard output.
+    influx_endpoint: Option<influx::Endpoint>,
+    ascii: bool,
+    art: bool,
+    explain: bool,
+    simple: bool,
+    summary: bool,
+    speak: bool,
+    speak_file: Option<PathBuf>,
+    astro: bool,
+    performance: bool,
+    ballistics: bool,
+    /// Direction of travel or fire given with `--heading`, in degrees.
+    heading: Option<f64>,
+    /// Elevation given with `--elevation`, in metres.
+    elevation: Option<f64>,
+    today: bool,
+    source_info: bool,
+    hyperlocal: bool,
+    /// Always fetch rather than reuse a cached response.
+    no_cache: bool,
+    watch: Option<Watch>,
+    /// Observations older than this are an error.
+    max_age: Option<std::time::Duration>,
+    river: Option<river::Station>,
+    alerts: bool,
+    /// Only the alerts are shown, and they set the exit status.
+    alerts_only: bool,
+    pws: Option<pws::Station>,
+    roads: bool,
+    notify_ice: bool,
+    notify: bool,
+    bell: bool,
+    sound: Option<PathBuf>,
+    config: config::Config,
+    /// `--lang`, or `lang` in the config.
+    lang: Option<String>,
+    /// Translations of generated text, in the `--lang` or the locale's
+    /// language.
+    catalog: i18n::Catalog,
+    clock: Box<dyn Clock>,
+    /// Sections of the weather card, from `--layout` or the config.
+    layout: Vec<layout::Section>,
+}
+
+#[tokio::main]
+async fn main() {
+    if let Err(e) = run().await {
+        errors::exit(e);
+    }
+}
+
+async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    platform::enable_ansi();
+    dotenv().ok();
+    if let Some(config_dir) = platform::dir(platform::Dir::Config) {
+        dotenv::from_path(config_dir.join(".env")).ok();
+    }
+
+    let mut opt =
+        Opt::from_iter_safe(std::env::args_os()).unwrap_or_else(|e| errors::exit_usage(e));
+    // `weather home --json`: the flags around the alias of a saved location
+    // are parsed as if it were not there.
+    let alias = match &opt.cmd {
+        Some(Command::Other(args)) => {
+            let argv: Vec<std::ffi::OsString> = std::env::args_os().collect();
+            let at = argv.len() - args.len();
+            let alias = args[0].clone();
+            opt = Opt::from_iter_safe(argv[..at].iter().chain(&argv[at + 1..]))
+                .unwrap_or_else(|e| errors::exit_usage(e));
+            Some(alias)
+        }
+        _ => None,
+    };
+    crash::install(opt.config.clone());
+    errors::set_plain(opt.ascii);
+
+    let config = match config::load(opt.config.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            errors::exit(e);
+        }
+    };
+    // Flags override the config file's defaults.
+    weather_providers::retry::set_policy(weather_providers::retry::Policy {
+        retries: opt.retries.unwrap_or(config.retry.retries),
+        base_delay: opt.retry_delay.unwrap_or(config.retry.delay),
+        jitter: config.retry.jitter,
+    });
+    let located = opt.here || opt.auto || opt.location.is_some() || opt.lat.is_some();
+    let alias = alias.map(|alias| alias_query(&alias, &opt, located));
+    if opt.city.is_empty() && !located && alias.is_none() && !opt.favorites {
+        opt.city = config.city.iter().cloned().collect();
+    }
+    let locale_tag = opt.locale.as_deref().or(config.locale.as_deref());
+    let mut locale = match Locale::resolve(locale_tag) {
+        Ok(locale) => locale,
+        Err(e) => {
+            errors::exit(e);
+        }
+    };
+    let lang = match (&opt.lang, config.lang.as_deref().map(parse_language)) {
+        (Some(lang), _) => Some(lang.clone()),
+        (None, Some(Err(e))) => errors::exit(format!("Invalid lang in the config: {}", e)),
+        (None, lang) => lang.and_then(Result::ok),
+    };
+    if let Some(lang) = &lang {
+        weather_providers::openweathermap::set_language(lang);
+    }
+    // Labels in `zh_cn` are those of `zh`.
+    let language = lang
+        .as_deref()
+        .and_then(|lang| lang.split('_').next())
+        .unwrap_or(locale.language());
+    let catalog = match i18n::Catalog::load(language) {
+        Ok(catalog) => catalog,
+        Err(e) => {
+            errors::exit(e);
+        }
+    };
+    locale.set_week(config.week.first_day, config.week.weekend.clone());
+    if let Some(dir) = platform::dir(platform::Dir::Cache) {
+        if let Err(e) = migrate::cache(&dir) {
+            eprintln!("{} {}", "Cache:".yellow(), e);
+        }
+    }
+    if matches!(opt.output, Some(Output::Compact)) && !std::io::stdout().is_terminal() {
+        // Status bars show escape codes literally.
+        colored::control::set_override(false);
+    }
+    let layout_name = opt.layout.as_deref().or(config.card.layout.as_deref());
+    let layout = match config.card.sections(layout_name.unwrap_or(layout::DEFAULT)) {
+        Ok(layout) => layout,
+        Err(e) => {
+            errors::exit(e);
+        }
+    };
+    let options = DisplayOptions {
+        provider: opt.provider,
+        units: match (opt.units, opt.fahrenheit, opt.metric) {
+            (Some(units), _, _) => units,
+            (None, true, _) => units::Units::Imperial,
+            (None, _, true) => units::Units::Metric,
+            (None, false, false) => config.units,
+        },
+        locale,
+        json: opt.json,
+        influx: matches!(opt.output, Some(Output::Influx)),
+        compact: matches!(opt.output, Some(Output::Compact)),
+        influx_endpoint: opt.influx_url.clone().map(|url| influx::Endpoint {
+            url,
+            token: opt.influx_token.clone(),
+        }),
+        ascii: opt.ascii || !platform::supports_emoji(),
+        art: opt.art,
+        explain: opt.explain,
+        simple: opt.simple,
+        summary: opt.summary,
+        speak: opt.speak,
+        speak_file: opt.speak_file.clone(),
+        astro: opt.astro,
+        performance: opt.performance,
+        ballistics: opt.ballistics,
+        heading: opt.heading,
+        elevation: opt.elevation,
+        today: opt.today,
+        source_info: opt.source_info,
+        hyperlocal: opt.hyperlocal,
+        // Watching shows each refresh as fetched.
+        no_cache: opt.no_cache || opt.watch.is_some(),
+        watch: opt.watch.as_ref().map(|interval| Watch {
+            // Validated when the arguments were parsed.
+            interval: interval
+                .as_deref()
+                .and_then(|interval| clock::parse_duration(interval).ok())
+                .unwrap_or(config.cache.weather_ttl)
+                .max(std::time::Duration::from_secs(1)),
+            previous: Mutex::new(None),
+            events: Mutex::new(Vec::new()),
+        }),
+        max_age: opt.max_age,
+        river: opt.river.clone(),
+        alerts: opt.alerts || opt.alerts_only,
+        alerts_only: opt.alerts_only,
+        pws: opt.pws.clone(),
+        roads: opt.roads,
+        notify_ice: opt.notify_ice,
+        notify: opt.notify,
+        bell: opt.bell,
+        sound: opt.sound.clone(),
+        config,
+        lang,
+        catalog,
+        clock: match opt.as_of {
+            Some(instant) => Box::new(FixedClock(instant)),
+            None => Box::new(SystemClock),
+        },
+        layout,
+    };
+
+    let query = alias.or_else(|| flag_query(&opt));
+    if let (Some(Output::Widget(format)), None) = (opt.output, &opt.cmd) {
+        let query = query.unwrap_or(Query::Here(location::Source::Auto));
+        print_widget(format, &query, opt.refresh, &options).await;
+        return Ok(());
+    }
+
+    match &opt.cmd {
+        Some(Command::Explain { field }) => {
+            if let Err(e) = explain(
+                field.as_deref(),
+                opt.city.first().map(String::as_str),
+                &options,
+            )
+            .await
+            {
+                errors::exit(e);
+            }
+            return Ok(());
+        }
+        Some(Command::Paths) => {
+            for (label, kind) in [
+                ("Config", platform::Dir::Config),
+                ("Cache", platform::Dir::Cache),
+                ("Data", platform::Dir::Data),
+            ] {
+                let path = platform::dir(kind)
+                    .map_or_else(|| "(unknown)".to_string(), |p| p.display().to_string());
+                println!("{:<7} {}", format!("{}:", label), path);
+            }
+            if let Some(path) = opt.config.clone().or_else(config::default_path) {
+                println!("Config file: {}", path.display());
+            }
+            if let Ok(storage) = storage::open(&options.config.storage) {
+                println!("Observation log: {}", storage.describe());
+            }
+            if let Some(path) = tokens::path() {
+                println!("Tokens: {}", path.display());
+            }
+            if let Some(path) = saved::path() {
+                println!("Saved locations: {}", path.display());
+            }
+            return Ok(());
+        }
+        Some(Command::Schema) => {
+            print!("{}", model::SCHEMA);
+            return Ok(());
+        }
+        Some(Command::Serve {
+            bind,
+            token,
+            rate_limit,
+            cors_origins,
+        }) => {
+            let serve_options = server::ServeOptions {
+                bind: *bind,
+                token: token.clone(),
+                rate_limit: *rate_limit,
+                cors_origins: cors_origins.clone(),
+                cache: options.config.cache.clone(),
+            };
+            return server::serve(serve_options, require_api_key(&options.config)).await;
+        }
+        Some(Command::Generate(GenerateCommand::Man { out })) => {
+            generate::man(out)?;
+            return Ok(());
+        }
+        Some(Command::Generate(GenerateCommand::Completions { out, shell })) => {
+            generate::completions(out, shell)?;
+            return Ok(());
+        }
+        Some(Command::SelfUpdate { check_only }) => {
+            if let Err(e) = update::self_update(*check_only).await {
+                errors::exit(e);
+            }
+            return Ok(());
+        }
+        Some(Command::Daemon { refresh }) => {
+            let daemon_options = daemon_options(&opt, refresh, None, &options.config)?;
+            return daemon::run(daemon_options, require_api_key(&options.config)).await;
+        }
+        Some(Command::Hook { script, refresh }) => {
+            if let Err(e) = hook::check(script) {
+                errors::exit(e);
+            }
+            let daemon_options =
+                daemon_options(&opt, refresh, Some(script.clone()), &options.config)?;
+            return daemon::run(daemon_options, require_api_key(&options.config)).await;
+        }
+        Some(Command::Forecast {
+            location,
+            days,
+            diff,
+            weekend,
+        }) => {
+            let query = command_query(location, &opt, "forecast");
+            let api_key = require_api_key(&options.config);
+            if let Err(e) = show_forecast(&query, &api_key, *days, *diff, *weekend, &options).await
+            {
+                errors::exit(e);
+            }
+            return Ok(());
+        }
+        Some(Command::Hourly { location, hours }) => {
+            let query = command_query(location, &opt, "hourly");
+            let api_key = require_api_key(&options.config);
+            if let Err(e) = show_hourly(&query, &api_key, *hours, &options).await {
+                errors::exit(e);
+            }
+            return Ok(());
+        }
+        Some(Command::Solar {
+            location,
+            days,
+            sunshine_hours,
+        }) => {
+            let query = command_query(location, &opt, "solar");
+            // Only OpenWeatherMap's place search needs a key.
+            let api_key = options
+                .api_key()
+                .unwrap_or_else(|| require_api_key(&options.config));
+            if let Err(e) = show_solar(&query, &api_key, *days, *sunshine_hours, &options).await {
+                errors::exit(e);
+            }
+            return Ok(());
+        }
+        Some(Command::Tides { location }) => {
+            let query = command_query(location, &opt, "tides");
+            if let Err(e) = show_tides(&query, &require_api_key(&options.config), &options).await {
+                errors::exit(e);
+            }
+            return Ok(());
+        }
+        Some(Command::Surf { spot, facing, days }) => {
+            let configured = spot
+                .as_deref()
+                .and_then(|name| options.config.surf.find(name));
+            let result = match configured {
+                Some((name, spot)) => {
+                    let spot = surf::Spot {
+                        facing: facing.unwrap_or(spot.facing),
+                        ..*spot
+                    };
+                    show_surf(name, &spot, *days, &options).await
+                }
+                None => {
+                    let query = command_query(spot, &opt, "surf");
+                    let api_key = options
+                        .api_key()
+                        .unwrap_or_else(|| require_api_key(&options.config));
+                    surf_at(&query, *facing, &api_key, *days, &options).await
+                }
+            };
+            if let Err(e) = result {
+                errors::exit(e);
+            }
+            return Ok(());
+        }
+        Some(Command::Mountain {
+            peak,
+            summit,
+            base,
+            days,
+        }) => {
+            let query = command_query(peak, &opt, "mountain");
+            let api_key = options
+                .api_key()
+                .unwrap_or_else(|| require_api_key(&options.config));
+            if let Err(e) = show_mountain(&query, &api_key, *summit, *base, *days, &options).await {
+                errors::exit(e);
+            }
+            return Ok(());
+        }
+        Some(Command::Gpx { file, start, every }) => {
+            if let Err(e) = show_gpx(file, *start, *every, &options).await {
+                errors::exit(e);
+            }
+            return Ok(());
+        }
+        Some(Command::Events { file }) => {
+            let api_key = options
+                .api_key()
+                .unwrap_or_else(|| require_api_key(&options.config));
+            if let Err(e) = show_events(file, &api_key, &options).await {
+                errors::exit(e);
+            }
+            return Ok(());
+        }
+        Some(Command::Wake {
+            location,
+            offset,
+            days,
+            cron,
+            command,
+        }) => {
+            let query = command_query(location, &opt, "wake");
+            let api_key = options
+                .api_key()
+                .unwrap_or_else(|| require_api_key(&options.config));
+            if let Err(e) =
+                show_wake(&query, &api_key, *offset, *days, *cron, command, &options).await
+            {
+                errors::exit(e);
+            }
+            return Ok(());
+        }
+        Some(Command::Air { location }) => {
+            let query = command_query(location, &opt, "air");
+            if let Err(e) = show_air(&query, &require_api_key(&options.config), &options).await {
+                errors::exit(e);
+            }
+            return Ok(());
+        }
+        Some(Command::Compare { cities, days }) => {
+            let queries: Vec<Query> = cities.iter().map(|city| city_query(city, &opt)).collect();
+            let api_key = require_api_key(&options.config);
+            if let Err(e) = show_compare(&queries, &api_key, *days, &options).await {
+                errors::exit(e);
+            }
+            return Ok(());
+        }
+        Some(Command::DryWindows {
+            location,
+            min,
+            hours,
+        }) => {
+            let query = command_query(location, &opt, "dry-windows");
+            if let Err(e) = show_dry_windows(
+                &query,
+                &require_api_key(&options.config),
+                *min,
+                *hours,
+                &options,
+            )
+            .await
+            {
+                errors::exit(e);
+            }
+            return Ok(());
+        }
+        Some(Command::WindWindow {
+            location,
+            min,
+            max,
+            dir,
+            length,
+            hours,
+        }) => {
+            let query = command_query(location, &opt, "wind-window");
+            let criteria = wind_window::Criteria {
+                min_ms: *min,
+                max_ms: *max,
+                sector: *dir,
+            };
+            if let Err(e) = show_wind_window(
+                &query,
+                &require_api_key(&options.config),
+                &criteria,
+                *length,
+                *hours,
+                &options,
+            )
+            .await
+            {
+                errors::exit(e);
+            }
+            return Ok(());
+        }
+        Some(Command::WillItRain {
+            location,
+            after,
+            lasting,
+            threshold,
+        }) => {
+            let query = command_query(location, &opt, "will-it-rain");
+            let (after, lasting) = (after.as_secs() as i64, lasting.as_secs().max(60) as i64);
+            let api_key = require_api_key(&options.config);
+            match will_it_rain(&query, &api_key, after, lasting, *threshold, &options).await {
+                Ok(true) => process::exit(0),
+                Ok(false) => process::exit(1),
+                Err(e) => errors::exit(e),
+            }
+        }
+        Some(Command::Notify {
+            location,
+            city,
+            condition,
+        }) => {
+            let query = command_query(&location.clone().or(city.clone()), &opt, "notify");
+            let api_key = options
+                .api_key()
+                .unwrap_or_else(|| require_api_key(&options.config));
+            match notify_if(&query, &api_key, condition, &options).await {
+                Ok(true) => process::exit(0),
+                Ok(false) => process::exit(1),
+                Err(e) => errors::exit(e),
+            }
+        }
+        Some(Command::Check {
+            location,
+            warn,
+            crit,
+        }) => {
+            let query = location
+                .as_deref()
+                .map(|location| city_query(location, &opt))
+                .or_else(|| flag_query(&opt));
+            let status = run_check(query, warn, crit, &options).await;
+            process::exit(status as i32);
+        }
+        Some(Command::History {
+            location,
+            city,
+            since,
+        }) => {
+            let filter = storage::Filter {
+                location: location
+                    .clone()
+                    .or(city.clone())
+                    .or_else(|| opt.city.first().cloned()),
+                since: since_time(*since, &options),
+            };
+            if let Err(e) = show_history(&filter, &options).await {
+                errors::exit(e as errors::Error);
+            }
+            return Ok(());
+        }
+        Some(Command::Db(DbCommand::Export { location, since })) => {
+            let filter = storage::Filter {
+                location: location.clone(),
+                since: since_time(*since, &options),
+            };
+            if let Err(e) = export_observations(&filter, &options).await {
+                errors::exit(e as errors::Error);
+            }
+            return Ok(());
+        }
+        Some(Command::Db(DbCommand::Calibrate { location, since })) => {
+            let filter = storage::Filter {
+                location: Some(location.clone()),
+                since: since_time(*since, &options),
+            };
+            if let Err(e) = run_calibrate(location, &filter, &options).await {
+                errors::exit(e as errors::Error);
+            }
+            return Ok(());
+        }
+        Some(Command::Db(DbCommand::Backfill {
+            city,
+            from,
+            to,
+            restart,
+        })) => {
+            let newest = options.clock.now().date_naive()
+                - chrono::Duration::days(backfill::ARCHIVE_DELAY_DAYS);
+            if let Err(e) =
+                run_backfill(city, *from, to.unwrap_or(newest), *restart, &options).await
+            {
+                errors::exit(e);
+            }
+            return Ok(());
+        }
+        Some(Command::Save { alias, location }) => {
+            let query = command_query(location, &opt, "save <alias>");
+            // Only OpenWeatherMap's place search needs a key.
+            let api_key = options
+                .api_key()
+                .unwrap_or_else(|| require_api_key(&options.config));
+            if let Err(e) = save_location(alias, &query, &api_key, &options).await {
+                errors::exit(e);
+            }
+            return Ok(());
+        }
+        Some(Command::Saved(SavedCommand::List)) => {
+            match saved::load() {
+                Ok(locations) => list_saved(&locations, &options),
+                Err(e) => errors::exit(e),
+            }
+            return Ok(());
+        }
+        Some(Command::Saved(SavedCommand::Remove { alias })) => {
+            match saved::remove(alias) {
+                Ok(removed) if removed.is_empty() => {
+                    eprintln!(
+                        "{} No saved location is called '{}'; see `weather saved list`",
+                        "Error:".bright_red(),
+                        alias
+                    );
+                    process::exit(errors::OTHER);
+                }
+                Ok(removed) => {
+                    for location in removed {
+                        match &location.alias {
+                            Some(alias) => println!("Removed {} ({})", alias, location.name),
+                            None => println!("Removed {}", location.name),
+                        }
+                    }
+                }
+                Err(e) => errors::exit(e),
+            }
+            return Ok(());
+        }
+        Some(Command::Auth(AuthCommand::Netatmo)) => {
+            match tokens::read_refresh_token().and_then(|token| tokens::store_netatmo(&token)) {
+                Ok(path) => println!("Netatmo token saved to {}", path.display()),
+                Err(e) => {
+                    errors::exit(e);
+                }
+            }
+            return Ok(());
+        }
+        Some(Command::Healthcheck { url }) => {
+            if let Err(e) = server::healthcheck(url).await {
+                eprintln!("{} {}", "Unhealthy:".bright_red(), e);
+                process::exit(1);
+            }
+            return Ok(());
+        }
+        // Handled as the alias was parsed.
+        Some(Command::Other(_)) => unreachable!(),
+        None => {}
+    }
+
+    let api_key = options
+        .api_key()
+        .unwrap_or_else(|| require_api_key(&options.config));
+
+    if let Some(watch) = &options.watch {
+        if opt.city.len() > 1 {
+            eprintln!("{} --watch takes one location", "Error:".bright_red());
+            process::exit(errors::OTHER);
+        }
+        let query = query.unwrap_or(Query::Here(location::Source::Auto));
+        run_watch(&query, &api_key, watch.interval, &options).await;
+    } else if opt.tui {
+        if opt.city.len() > 1 {
+            eprintln!("{} --tui takes one location", "Error:".bright_red());
+            process::exit(errors::OTHER);
+        }
+        let query = query.unwrap_or(Query::Here(location::Source::Auto));
+        if let Err(e) = show_tui(&query, &api_key, opt.screenshot.as_deref(), &options).await {
+            errors::exit(e);
+        }
+    } else if opt.city.len() > 1 || opt.favorites {
+        let queries: Vec<Query> = if opt.favorites {
+            favorite_queries()
+        } else {
+            opt.city.iter().map(|city| city_query(city, &opt)).collect()
+        };
+        let severity = show_cities(&queries, &api_key, &options).await;
+        if opt.exit_status {
+            process::exit(severity.exit_code());
+        }
+    } else if let Some(query) = query {
+        match get_and_display_weather(&query, &api_key, &options).await {
+            Ok(severity) if opt.exit_status => process::exit(severity.exit_code()),
+            Ok(_) => {}
+            Err(e) => errors::exit(e),
+        }
+    } else if !std::io::stdin().is_terminal() {
+        // Nobody to ask for a city, e.g. under cron or in a script.
+        let query = Query::Here(location::Source::Ip);
+        if let Err(e) = get_and_display_weather(&query, &api_key, &options).await {
+            errors::exit(e);
+        }
+    } else {
+        // Interactive mode
+        println!("{}", "🌤️  Weather CLI v1.0".bold());
+        println!("{}", "Enter 'q' or 'exit' to quit".italic());
+
+        loop {
+            let city = get_input("Enter city name:").await;
+
+            if city.to_lowercase() == "q" || city.to_lowercase() == "exit" {
+                println!("👋 Goodbye!");
+                break;
+            }
+
+            let query = match choose_place(&with_country(&city, &opt), &api_key, &options).await {
+                Ok(query) => query,
+                Err(e) => {
+                    errors::print(e);
+                    println!();
+                    continue;
+                }
+            };
+            match get_and_display_weather(&query, &api_key, &options).await {
+                Ok(_) => {}
+                Err(e) => errors::print(e),
+            }
+
+            println!(); // Add a newline for better readability
+        }
+    }
+
+    Ok(())
+}
+
+/// `--watch`: shows the weather for `query` as each new observation
+/// arrives, checking every `interval` until interrupted. A failed refresh
+/// is reported and retried at the next one.
+async fn run_watch(
+    query: &Query,
+    api_key: &str,
+    interval: std::time::Duration,
+    options: &DisplayOptions,
+) {
+    let client = options
+        .weather_client(api_key)
+        .unwrap_or_else(|e| errors::exit(e));
+    let place = report::resolve_place(&client, query)
+        .await
+        .with_context(|| format!("Failed to look up {}", query))
+        .unwrap_or_else(|e| errors::exit(e));
+    let updates = client.watch_with(
+        place.clone(),
+        interval,
+        ChangeFilter::NewObservation,
+        Cancel::default(),
+    );
+    let mut updates = std::pin::pin!(updates);
+    while let Some(update) = updates.next().await {
+        let fetched = match update {
+            WeatherUpdate::Report { report, .. } => Ok((place.clone(), *report)),
+            WeatherUpdate::Error(e) => Err(e).with_context(|| {
+                format!("Failed to fetch the weather for {}", place.english_name())
+            }),
+        };
+        if std::io::stdout().is_terminal() {
+            // Clear the screen and move the cursor home.
+            print!("\x1B[2J\x1B[H");
+        }
+        if let Err(e) = display_fetched(query, fetched, &client, api_key, options).await {
+            errors::print(e);
+        }
+        let now = options.clock.now().with_timezone(&Local).fixed_offset();
+        println!();
+        println!(
+            "{}",
+            format!(
+                "Updated {}, checking every {} for a new observation; changes since the last one are highlighted. Ctrl-C to stop.",
+                options.locale.time(&now),
+                clock::span(interval.as_secs() as i64)
+            )
+            .dimmed()
+        );
+    }
+}
+
+/// The location selected by `--city` (the first, if repeated),
+/// `--lat`/`--lon`, `--location`, `--here` or `--auto`.
+fn flag_query(opt: &Opt) -> Option<Query> {
+    if let (Some(lat), Some(lon)) = (opt.lat, opt.lon) {
+        return Some(Query::Coordinates { lat, lon });
+    }
+    match opt.city.first() {
+        Some(city) => Some(city_query(city, opt)),
+        None => match &opt.location {
+            Some(source) => Some(Query::Here(source.clone())),
+            None if opt.here => Some(Query::Here(location::Source::Auto)),
+            None if opt.auto => Some(Query::Here(location::Source::Ip)),
+            None => None,
+        },
+    }
+}
+
+/// A location name as given, narrowed down by `--country` and `--select`,
+/// unless it is the alias of a saved location.
+fn city_query(name: &str, opt: &Opt) -> Query {
+    match saved::find(name) {
+        Ok(Some(location)) => return Query::Saved(location),
+        Ok(None) => {}
+        Err(e) => eprintln!("{} {}", "Saved locations unavailable:".yellow(), e),
+    }
+    let name = with_country(name, opt);
+    match opt.select {
+        Some(index) if index > 1 => Query::Choice { name, index },
+        _ => Query::City(name),
+    }
+}
+
+/// `name` with the `--country` code appended, unless it names a country
+/// (or state) of its own.
+fn with_country(name: &str, opt: &Opt) -> String {
+    match &opt.country {
+        Some(country) if !name.contains(',') => format!("{},{}", name.trim(), country),
+        _ => name.to_string(),
+    }
+}
+
+fn parse_select(text: &str) -> Result<usize, String> {
+    match text.parse::<usize>() {
+        Ok(index) if index >= 1 => Ok(index),
+        _ => Err(format!("Expected a match number from 1, not '{}'", text)),
+    }
+}
+
+fn parse_heading(text: &str) -> Result<f64, String> {
+    match text.parse::<f64>() {
+        Ok(degrees) if (0.0..=360.0).contains(&degrees) => Ok(degrees),
+        _ => Err(format!(
+            "Expected a heading in degrees from 0 to 360, not '{}'",
+            text
+        )),
+    }
+}
+
+/// An OpenWeatherMap language code such as `de` or `zh_cn`; `zh-CN` is
+/// taken as `zh_cn`.
+fn parse_language(text: &str) -> Result<String, String> {
+    let code = text.replace('-', "_").to_lowercase();
+    let mut parts = code.split('_');
+    let valid = parts.next().is_some_and(|language| {
+        language.len() == 2 && language.chars().all(|c| c.is_ascii_lowercase())
+    }) && parts
+        .all(|region| region.len() == 2 && region.chars().all(|c| c.is_ascii_lowercase()));
+    if valid {
+        Ok(code)
+    } else {
+        Err(format!(
+            "Expected a language code such as de or zh_cn, not '{}'",
+            text
+        ))
+    }
+}
+
+/// A wind speed in knots, as metres per second.
+fn parse_knots(text: &str) -> Result<f64, String> {
+    match text.parse::<f64>() {
+        Ok(knots) if knots >= 0.0 => Ok(knots * wind_window::KNOT_MS),
+        _ => Err(format!("Expected a wind speed in knots, not '{}'", text)),
+    }
+}
+
+fn parse_country(text: &str) -> Result<String, String> {
+    if text.len() == 2 && text.chars().all(|c| c.is_ascii_alphabetic()) {
+        Ok(text.to_ascii_uppercase())
+    } else {
+        Err(format!(
+            "Expected a two-letter country code such as US or GB, not '{}'",
+            text
+        ))
+    }
+}
+
+/// For interactive mode: when several places match `name`, lists them and
+/// asks which one is meant.
+async fn choose_place(
+    name: &str,
+    api_key: &str,
+    options: &DisplayOptions,
+) -> Result<Query, Box<dyn std::error::Error>> {
+    let places = options
+        .weather_client(api_key)?
+        .search(name, geocode::MAX_MATCHES, &report::cancel())
+        .await
+        .with_context(|| format!("Failed to look up '{}'", name))?;
+    if places.len() < 2 {
+        return Ok(Query::City(name.to_string()));
+    }
+    println!("Several places match '{}':", name);
+    for line in numbered_places(&places) {
+        println!("  {}", line);
+    }
+    loop {
+        let answer = get_input(&format!(
+            "Which one? (1-{}, Enter for the first)",
+            places.len()
+        ))
+        .await;
+        match answer.parse::<usize>() {
+            _ if answer.is_empty() => return Ok(Query::City(name.to_string())),
+            Ok(1) => return Ok(Query::City(name.to_string())),
+            Ok(index) if (2..=places.len()).contains(&index) => {
+                return Ok(Query::Choice {
+                    name: name.to_string(),
+                    index,
+                })
+            }
+            _ => println!("{}", "Enter one of the numbers listed".yellow()),
+        }
+    }
+}
+
+/// `places` as a numbered list: name, state, country and coordinates.
+fn numbered_places(places: &[Place]) -> Vec<String> {
+    places
+        .iter()
+        .enumerate()
+        .map(|(i, place)| {
+            let region = place.region();
+            format!(
+                "{}. {}{} ({:.4}, {:.4})",
+                i + 1,
+                place.english_name().bold(),
+                if region.is_empty() {
+                    String::new()
+                } else {
+                    format!(", {}", region)
+                },
+                place.lat,
+                place.lon
+            )
+        })
+        .collect()
+}
+
+/// The location for a subcommand: its positional argument, else the flags.
+/// Exits with a usage hint when neither is given.
+fn command_query(location: &Option<String>, opt: &Opt, command: &str) -> Query {
+    let query = match location {
+        Some(location) => Some(city_query(location, opt)),
+        None => flag_query(opt),
+    };
+    query.unwrap_or_else(|| {
+        eprintln!(
+            "{} Give a location: `weather {} <location>`, --city, --lat/--lon or --here",
+            "Error:".bright_red(),
+            command
+        );
+        process::exit(errors::OTHER);
+    })
+}
+
+/// The saved location `weather <alias>` names. Exits when none has that
+/// alias, or when a subcommand or another location is given with it.
+fn alias_query(alias: &str, opt: &Opt, located: bool) -> Query {
+    if opt.cmd.is_some() {
+        eprintln!(
+            "{} Give a saved location after the subcommand, e.g. `weather forecast {}`",
+            "Error:".bright_red(),
+            alias
+        );
+        process::exit(errors::OTHER);
+    }
+    if located || opt.favorites || !opt.city.is_empty() {
+        eprintln!(
+            "{} `weather {}` takes no other location",
+            "Error:".bright_red(),
+            alias
+        );
+        process::exit(errors::OTHER);
+    }
+    match saved::find(alias) {
+        Ok(Some(location)) => Query::Saved(location),
+        Ok(None) => {
+            eprintln!(
+                "{} '{}' is neither a subcommand nor a saved location; see `weather --help` and `weather saved list`",
+                "Error:".bright_red(),
+                alias
+            );
+            process::exit(errors::OTHER);
+        }
+        Err(e) => errors::exit(e),
+    }
+}
+
+/// `--favorites`: every saved location, in the order saved. Exits when
+/// there are none.
+fn favorite_queries() -> Vec<Query> {
+    let locations = saved::load().unwrap_or_else(|e| errors::exit(e));
+    if locations.is_empty() {
+        eprintln!(
+            "{} No saved locations; save one with `weather save <alias> <place>`",
+            "Error:".bright_red()
+        );
+        process::exit(errors::OTHER);
+    }
+    locations.into_iter().map(Query::Saved).collect()
+}
+
+/// `weather save`: looks `query` up and saves the place under `alias`.
+async fn save_location(
+    alias: &str,
+    query: &Query,
+    api_key: &str,
+    options: &DisplayOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let place = report::resolve_place(&options.weather_client(api_key)?, query)
+        .await
+        .with_context(|| format!("Failed to look up {}", query))?;
+    let mut location = saved::Location::new(place);
+    location.alias = Some(alias.to_string());
+    let name = location.name.clone();
+    saved::add(location).context("Cannot save the location")?;
+    println!(
+        "Saved {} as {}; show it with `weather {}`",
+        name,
+        alias.bold(),
+        alias
+    );
+    Ok(())
+}
+
+/// `weather saved list`: each location's alias, name and coordinates.
+fn list_saved(locations: &[saved::Location], options: &DisplayOptions) {
+    if options.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(locations).unwrap_or_default()
+        );
+        return;
+    }
+    if locations.is_empty() {
+        println!("No saved locations; save one with `weather save <alias> <place>`");
+        return;
+    }
+    let rows: Vec<Vec<String>> = locations
+        .iter()
+        .map(|location| {
+            vec![
+                location.alias.as_deref().unwrap_or("-").bold().to_string(),
+                location.name.clone(),
+                format!("{:.4}, {:.4}", location.place.lat, location.place.lon)
+                    .dimmed()
+                    .to_string(),
+            ]
+        })
+        .collect();
+    for line in render::columns(&rows) {
+        println!("{}", line);
+    }
+}
+
+/// An alias for `weather save`: one word that `weather <alias>` cannot
+/// take for a subcommand.
+fn parse_alias(alias: &str) -> Result<String, String> {
+    if alias.is_empty() || alias.starts_with('-') || alias.contains(char::is_whitespace) {
+        return Err(format!("Expected one word such as home, not '{}'", alias));
+    }
+    match Opt::from_iter_safe(["weather", alias]) {
+        Ok(Opt {
+            cmd: Some(Command::Other(_)),
+            ..
+        }) => Ok(alias.to_string()),
+        _ => Err(format!(
+            "'{}' is a subcommand of weather; choose another alias",
+            alias
+        )),
+    }
+}
+
+fn daemon_options(
+    opt: &Opt,
+    refresh: &RefreshArgs,
+    hook: Option<PathBuf>,
+    config: &config::Config,
+) -> Result<daemon::DaemonOptions, Box<dyn std::error::Error>> {
+    Ok(daemon::DaemonOptions {
+        source: opt.location.clone().unwrap_or(location::Source::Auto),
+        interval: std::time::Duration::from_secs(refresh.interval.max(1)),
+        geofence_m: refresh.geofence,
+        storage: match &config.storage {
+            settings if settings.record => Some(storage::open(settings)?),
+            _ => None,
+        },
+        hook,
+    })
+}
+
+fn require_api_key(config: &config::Config) -> String {
+    match config.api_key() {
+        Some(key) => key,
+        None => {
+            eprintln!("{}",
+                "Error: OPEN_WEATHER_MAP_API environment variable not set. Please add it to your .env file, or set api_key in config.toml."
+                .bright_red()
+            );
+            process::exit(errors::OTHER);
+        }
+    }
+}
+
+async fn get_input(input_msg: &str) -> String {
+    let mut input = String::new();
+    println!("{} ", input_msg.bright_cyan());
+    std::io::stdin().read_line(&mut input).unwrap_or_default();
+    // trim() also drops the '\r' of CRLF line endings; a BOM can appear when
+    // input is piped from PowerShell.
+    input.trim().trim_start_matches('\u{feff}').to_string()
+}
+
+/// Shows the weather for `query` and returns the worst configured threshold
+/// severity it reaches.
+async fn get_and_display_weather(
+    query: &Query,
+    api_key: &str,
+    options: &DisplayOptions,
+) -> Result<thresholds::Severity, Box<dyn std::error::Error>> {
+    let client = options.weather_client(api_key)?;
+    let fetched = report::get_cached_weather(query, &client, options).await;
+    display_fetched(query, fetched, &client, api_key, options).await
+}
+
+/// Several `--city` locations: their conditions are fetched concurrently,
+/// then each is shown in the order given, as for one. A location that fails
+/// is reported without hiding the others. Returns the worst threshold
+/// severity among them.
+async fn show_cities(
+    queries: &[Query],
+    api_key: &str,
+    options: &DisplayOptions,
+) -> thresholds::Severity {
+    let client = options
+        .weather_client(api_key)
+        .unwrap_or_else(|e| errors::exit(e));
+    let fetches = join_all(
+        queries
+            .iter()
+            .map(|query| report::get_cached_weather(query, &client, options)),
+    )
+    .await;
+    let mut worst = thresholds::Severity::Ok;
+    for (query, fetched) in queries.iter().zip(fetches) {
+        match display_fetched(query, fetched, &client, api_key, options).await {
+            Ok(severity) => worst = worst.max(severity),
+            Err(e) => errors::print(e),
+        }
+    }
+    worst
+}
+
+/// Shows the conditions [`report::get_cached_weather`] fetched for `query`,
+/// with everything the options add to them.
+async fn display_fetched(
+    query: &Query,
+    fetched: Result<(Place, model::Report), Box<dyn std::error::Error>>,
+    client: &WeatherClient,
+    api_key: &str,
+    options: &DisplayOptions,
+) -> Result<thresholds::Severity, Box<dyn std::error::Error>> {
+    match fetched {
+        Ok((place, report)) => {
+            let age = options.clock.now().timestamp() - report.observed_at.timestamp();
+            if let Some(max_age) = options.max_age {
+                if age > max_age.as_secs() as i64 {
+                    return Err(format!(
+                        "The observation for {} is {} old, older than --max-age {}",
+                        query,
+                        clock::span(age),
+                        clock::span(max_age.as_secs() as i64)
+                    )
+                    .into());
+                }
+            }
+            // The card marks it in its header; other outputs get a warning.
+            let card = !(options.json
+                || options.influx
+                || options.compact
+                || options.summary
+                || options.simple);
+            if options.is_stale(age) && !card {
+                eprintln!(
+                    "{} observed {} ago",
+                    "Stale data:".yellow(),
+                    clock::span(age)
+                );
+            }
+            let forecast = if options.needs_forecast() {
+                // Everything using it falls back to current conditions if
+                // this fails.
+                forecast::get_forecast(&http_client()?, place.lat, place.lon, api_key)
+                    .await
+                    .ok()
+            } else {
+                None
+            };
+
+            let (report, alerts_elsewhere) =
+                report::complete(query, &place, report, client, api_key, options).await?;
+
+            // Only what is displayed is calibrated; see [`calibration`].
+            let calibration = report::calibration_for(query, options);
+            let mut shown = report.clone();
+            if let Some(calibration) = calibration {
+                calibration.apply(&mut shown.current);
+            }
+
+            if options.alerts_only {
+                if options.json {
+                    println!("{}", serde_json::to_string_pretty(&report.alerts)?);
+                } else if let Some(elsewhere) = alerts_elsewhere {
+                    display::display_alerts(&report.alerts, elsewhere, &shown, options);
+                }
+            } else if options.json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else if options.influx {
+                let line = influx::line(&report);
+                match &options.influx_endpoint {
+                    Some(endpoint) => influx::write(&http_client()?, endpoint, &line).await?,
+                    None => println!("{}", line),
+                }
+            } else if options.compact {
+                println!("{}", display::compact_line(&shown, &place, options));
+            } else if options.summary {
+                println!("{}", summary::summarize(&shown, forecast.as_ref(), options));
+            } else if options.simple {
+                display::display_simple(&shown, options);
+            } else {
+                display::display_weather(&shown, &place, calibration, options);
+                if options.source_info {
+                    display::display_source(&shown, &place, options);
+                }
+                if let Some(hyperlocal) = &report.hyperlocal {
+                    display::display_hyperlocal(hyperlocal, &report.current, options);
+                }
+                if let Some(local) = &report.local {
+                    display::display_station(local, &report.current, options);
+                }
+                if options.today {
+                    if let Err(e) = display_timeline(&place, api_key, options).await {
+                        eprintln!("{} {}", "Timeline unavailable:".yellow(), e);
+                    }
+                }
+                if let Some(station) = &options.river {
+                    match river::report(&http_client()?, station).await {
+                        Ok(report) => display::display_river(&report, options),
+                        Err(e) => eprintln!("{} {}", "River gauge unavailable:".yellow(), e),
+                    }
+                }
+                if let Some(elsewhere) = alerts_elsewhere {
+                    display::display_alerts(&report.alerts, elsewhere, &shown, options);
+                }
+                if options.roads {
+                    display::display_roads(
+                        &roads::assess(&shown, forecast.as_ref()),
+                        &shown,
+                        options,
+                    );
+                }
+            }
+            read_aloud(&shown, forecast.as_ref(), options)?;
+            if options.notify_ice {
+                notify_ice(&shown, forecast.as_ref(), options);
+            }
+
+            let breaches = thresholds::evaluate(&options.config.thresholds, &shown.current);
+            if options.notify && !breaches.is_empty() {
+                notify_breaches(&breaches, &shown);
+            }
+            if options.notify && !report.alerts.is_empty() {
+                notify_alerts(&report.alerts, &shown);
+            }
+            if options.bell || options.sound.is_some() {
+                let events = breaches
+                    .iter()
+                    .map(|b| format!("{} {:?}", b.field, b.severity))
+                    .chain(report.alerts.iter().map(|alert| {
+                        format!(
+                            "{}: {}",
+                            alert.event,
+                            alert.headline.as_deref().unwrap_or("")
+                        )
+                    }))
+                    .collect();
+                if options.any_new(events) {
+                    sound_alarm(options);
+                }
+            }
+            if let Some(watch) = &options.watch {
+                *watch.previous.lock().unwrap_or_else(|e| e.into_inner()) = Some(shown.current);
+            }
+            if options.alerts_only {
+                return Ok(match report.alerts.first().map(|alert| alert.severity) {
+                    None => thresholds::Severity::Ok,
+                    Some(model::Severity::Extreme | model::Severity::Severe) => {
+                        thresholds::Severity::Critical
+                    }
+                    Some(_) => thresholds::Severity::Warn,
+                });
+            }
+            Ok(breaches
+                .first()
+                .map_or(thresholds::Severity::Ok, |b| b.severity))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Prints the `--output` status segment for `query` from the cache, see
+/// [`widget`] for the contract. Never fails: errors leave the line empty.
+async fn print_widget(
+    format: widget::Format,
+    query: &Query,
+    refresh: bool,
+    options: &DisplayOptions,
+) {
+    // Reading the cache needs no key.
+    let api_key = options.api_key();
+    let client = match options.weather_client(api_key.as_deref().unwrap_or_default()) {
+        Ok(client) => client,
+        Err(e) => return errors::print(e),
+    };
+    let response_path = report::response_cache_path(query, &client, options);
+    if refresh {
+        match &api_key {
+            Some(_) => match report::get_weather(query, &client).await {
+                Ok((place, report)) => {
+                    let written = match (query, &response_path) {
+                        (Query::Here(_), _) => daemon::state_file()
+                            .ok_or_else(|| "Cannot determine the cache directory".into())
+                            .and_then(|path| {
+                                cache::write_report(&path, &report::build_report(report, options))
+                            }),
+                        (_, Some(path)) => {
+                            cache::write_response(path, &cache::Response { place, report })
+                        }
+                        (_, None) => Ok(()),
+                    };
+                    if let Err(e) = written {
+                        errors::print(e);
+                    }
+                }
+                Err(e) => errors::print(e),
+            },
+            None => eprintln!(
+                "{} OPEN_WEATHER_MAP_API is not set; showing the cached report",
+                "Error:".bright_red()
+            ),
+        }
+    }
+    let report = match query {
+        Query::Here(_) => daemon::state_file().and_then(|path| cache::read_report(&path)),
+        _ => response_path
+            .and_then(|path| cache::read_response(&path, std::time::Duration::MAX))
+            .map(|(response, _)| report::build_report(response.report, options)),
+    };
+    if let Some(report) = report {
+        println!(
+            "{}",
+            widget::render(
+                format,
+                &report,
+                options.clock.now(),
+                options.units,
+                options.ascii
+            )
+        );
+    }
+}
+
+/// Prints the plugin line for `weather check`. Without `--warn` or `--crit`
+/// the thresholds from the config apply.
+async fn run_check(
+    query: Option<Query>,
+    warn: &[String],
+    crit: &[String],
+    options: &DisplayOptions,
+) -> nagios::Status {
+    let result = async {
+        let query = query.ok_or("Give a location: `weather check <location>`, --city or --here")?;
+        let thresholds = if warn.is_empty() && crit.is_empty() {
+            options.config.thresholds.clone()
+        } else {
+            thresholds::from_expressions(warn, crit)?
+        };
+        let api_key = options.api_key().ok_or("OPEN_WEATHER_MAP_API is not set")?;
+        let (place, report) =
+            report::get_weather(&query, &options.weather_client(&api_key)?).await?;
+        Ok::<_, Box<dyn std::error::Error>>(nagios::check(
+            place.english_name(),
+            &report.current,
+            &thresholds,
+        ))
+    };
+    match result.await {
+        Ok((status, line)) => {
+            println!("{}", line);
+            status
+        }
+        Err(e) => {
+            println!("WEATHER UNKNOWN - {}", e);
+            nagios::Status::Unknown
+        }
+    }
+}
+
+/// `weather notify`: sends a desktop notification, and prints what it
+/// says, when the conditions at `query` match. Returns whether they did.
+async fn notify_if(
+    query: &Query,
+    api_key: &str,
+    condition: &condition::Condition,
+    options: &DisplayOptions,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let client = options.weather_client(api_key)?;
+    let (place, report) = report::get_cached_weather(query, &client, options).await?;
+    let Some(matched) = condition.matches(&report.current) else {
+        return Ok(false);
+    };
+    let title = format!("Weather in {}", place.english_name());
+    let body = matched.join(", ");
+    println!("{}: {}", title, body);
+    notify::send(&title, &body).context("Notification failed")?;
+    Ok(true)
+}
+
+/// Appends `report` to the observation log.
+async fn record(
+    report: &model::Report,
+    settings: &storage::Settings,
+) -> Result<(), storage::Error> {
+    storage::open(settings)?
+        .append(std::slice::from_ref(report))
+        .await
+}
+
+async fn export_observations(
+    filter: &storage::Filter,
+    options: &DisplayOptions,
+) -> Result<(), storage::Error> {
+    let storage = storage::open(&options.config.storage)?;
+    for report in storage.observations(filter).await? {
+        println!("{}", serde_json::to_string(&report)?);
+    }
+    Ok(())
+}
+
+async fn show_history(
+    filter: &storage::Filter,
+    options: &DisplayOptions,
+) -> Result<(), storage::Error> {
+    let storage = storage::open(&options.config.storage)?;
+    let reports = storage.observations(filter).await?;
+    let Some(summary) = history::summarize(&reports) else {
+        let place = filter.location.as_deref().unwrap_or("any location");
+        if options.config.storage.record {
+            println!(
+                "No readings recorded for {} in {}",
+                place,
+                storage.describe()
+            );
+        } else {
+            println!(
+                "No readings recorded for {}. Record each lookup with [storage] record = true \
+                 in the config.",
+                place
+            );
+        }
+        return Ok(());
+    };
+
+    if options.json {
+        let history = serde_json::json!({ "summary": summary, "readings": reports });
+        println!("{}", serde_json::to_string_pretty(&history)?);
+        return Ok(());
+    }
+
+    let temperature = |celsius| options.quantity(options.units.temperature(celsius));
+    let local = |report: &model::Report| {
+        local_datetime(
+            report.observed_at.timestamp(),
+            report.location.utc_offset_seconds,
+        )
+    };
+    let mut card = Card::new();
+    card.line(format!(
+        "{}{} {}",
+        options.icon("📓"),
+        "History for".bright_green(),
+        filter.location.as_deref().unwrap_or("all locations").bold()
+    ));
+    card.line(format!(
+        "{} reading{}, {} to {}",
+        summary.readings,
+        if summary.readings == 1 { "" } else { "s" },
+        summary.first.format("%Y-%m-%d %H:%M"),
+        summary.last.format("%Y-%m-%d %H:%M UTC")
+    ));
+    card.line(format!(
+        "Temperature: min {}, max {}, mean {}",
+        temperature(summary.min_temperature_c).bright_blue(),
+        temperature(summary.max_temperature_c).bright_red(),
+        temperature(summary.mean_temperature_c)
+    ));
+    let rows: Vec<Vec<String>> = reports
+        .iter()
+        .map(|report| {
+            let mut row = vec![local(report).format("%Y-%m-%d %H:%M").to_string()];
+            if filter.location.is_none() {
+                row.push(report.location.name.clone());
+            }
+            row.extend([
+                temperature(report.current.temperature_c),
+                report.current.condition.description.clone(),
+                options.quantity(options.units.speed(report.current.wind_speed_ms)),
+                report.source.provider.dimmed().to_string(),
+            ]);
+            row
+        })
+        .collect();
+    for line in render::columns(&rows) {
+        card.line(format!("  {}", line));
+    }
+    println!();
+    card.print();
+    Ok(())
+}
+
+/// The start of a `--since` window ending now.
+fn since_time(
+    since: Option<std::time::Duration>,
+    options: &DisplayOptions,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    // Durations reaching before the epoch mean everything.
+    since.and_then(|since| {
+        options
+            .clock
+            .now()
+            .checked_sub_signed(chrono::Duration::from_std(since).ok()?)
+    })
+}
+
+async fn run_calibrate(
+    location: &str,
+    filter: &storage::Filter,
+    options: &DisplayOptions,
+) -> Result<(), storage::Error> {
+    let storage = storage::open(&options.config.storage)?;
+    let reports = storage.observations(filter).await?;
+    let learned = calibration::learn(&reports);
+    if learned.is_empty() {
+        println!(
+            "Fewer than {} station readings recorded for {}. Record reports with a \
+             station attached ([storage] record = true and --pws or [stations]) first.",
+            calibration::MIN_SAMPLES,
+            location
+        );
+        return Ok(());
+    }
+
+    let locale = &options.locale;
+    let mut card = Card::new();
+    card.line(format!("Calibration for {}", location.bold()));
+    for field in &learned {
+        let unit = match field.key {
+            "temp_offset" => "°C",
+            "humidity_offset" => "%",
+            _ => "hPa",
+        };
+        card.line(format!(
+            "{}: {} {}",
+            field.label,
+            locale.quantity(field.offset, 1, unit),
+            format!(
+                "(± {}, {} readings)",
+                locale.quantity(field.spread, 1, unit),
+                field.samples
+            )
+            .dimmed()
+        ));
+    }
+    println!();
+    card.print();
+
+    println!();
+    println!("Add to config.toml:");
+    println!();
+    println!("[calibration.{:?}]", location);
+    for field in &learned {
+        println!("{} = {:.1}", field.key, field.offset);
+    }
+    Ok(())
+}
+
+async fn run_backfill(
+    city: &str,
+    from: chrono::NaiveDate,
+    to: chrono::NaiveDate,
+    restart: bool,
+    options: &DisplayOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if from > to {
+        return Err(format!("--from {} is after --to {}", from, to).into());
+    }
+    let api_key = options
+        .config
+        .api_key()
+        .ok_or("OPEN_WEATHER_MAP_API is not set")?;
+    let storage = storage::open(&options.config.storage)?;
+    // The archive is large; allow for slow pages.
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(60))
+        .build()?;
+    let place = WeatherClient::with_http(client.clone(), api_key.as_str())
+        .resolve_city(city, &report::cancel())
+        .await?;
+    let job = backfill::Backfill {
+        city,
+        place: &place,
+        from,
+        to,
+        restart,
+    };
+    backfill::run(&client, &job, storage.as_ref()).await
+}
+
+fn http_client() -> reqwest::Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()
+}
+
+/// Wet/dry slots for the hours ahead, their UTC offset, and whether they come
+/// from the coarse 3-hourly forecast.
+async fn precipitation_slots(
+    client: &reqwest::Client,
+    place: &Place,
+    api_key: &str,
+) -> Result<(Vec<safe_window::Slot>, i32, bool), Box<dyn std::error::Error>> {
+    // The minutely nowcast needs One Call 3.0; the free 3-hourly forecast
+    // still gives a coarse answer.
+    match onecall::get_onecall(client, place.lat, place.lon, api_key).await {
+        Ok(onecall) => Ok((
+            safe_window::from_onecall(&onecall),
+            onecall.timezone_offset,
+            false,
+        )),
+        Err(_) => {
+            let forecast = forecast::get_forecast(client, place.lat, place.lon, api_key).await?;
+            Ok((
+                safe_window::from_forecast(&forecast),
+                forecast.city.timezone,
+                true,
+            ))
+        }
+    }
+}
+
+/// The hourly forecast, its UTC offset, and whether it is interpolated from
+/// the 3-hourly forecast because One Call 3.0 is unavailable.
+async fn hourly_forecast(
+    client: &reqwest::Client,
+    place: &Place,
+    api_key: &str,
+) -> Result<(Vec<hourly::Hour>, i32, bool), Box<dyn std::error::Error>> {
+    match onecall::get_onecall(client, place.lat, place.lon, api_key).await {
+        Ok(onecall) => Ok((
+            hourly::from_onecall(&onecall),
+            onecall.timezone_offset,
+            false,
+        )),
+        Err(_) => {
+            let forecast = forecast::get_forecast(client, place.lat, place.lon, api_key).await?;
+            Ok((
+                hourly::from_forecast(&forecast),
+                forecast.city.timezone,
+                true,
+            ))
+        }
+    }
+}
+
+/// Prints the chance of precipitation in the window starting `after` seconds
+/// from now and `lasting` seconds long, and returns whether it reaches
+/// `threshold` percent.
+async fn will_it_rain(
+    query: &Query,
+    api_key: &str,
+    after: i64,
+    lasting: i64,
+    threshold: u8,
+    options: &DisplayOptions,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let client = http_client()?;
+    let place = report::resolve_place(&options.weather_client(api_key)?, query)
+        .await
+        .with_context(|| format!("Failed to look up {}", query))?;
+    let (slots, timezone, coarse) = precipitation_slots(&client, &place, api_key).await?;
+
+    let from = options.clock.now().timestamp() + after;
+    let until = from + lasting;
+    let chance = safe_window::chance_of_precipitation(&slots, from, until)
+        .ok_or("The forecast doesn't reach that far ahead")?;
+    let rain = chance * 100.0 >= threshold as f64;
+
+    if options.json {
+        let answer = serde_json::json!({
+            "rain": rain,
+            "probability": (chance * 100.0).round() / 100.0,
+            "from": from,
+            "until": until,
+            "coarse": coarse,
+        });
+        println!("{}", serde_json::to_string_pretty(&answer)?);
+        return Ok(rain);
+    }
+
+    let time = |t: i64| options.locale.time(&local_datetime(t, timezone));
+    let answer = if rain {
+        "Yes".bright_blue()
+    } else {
+        "No".green()
+    };
+    println!(
+        "{} — {} chance of rain between {} and {}{}",
+        answer.bold(),
+        options.locale.quantity((chance * 100.0).round(), 0, "%"),
+        time(from),
+        time(until),
+        if coarse {
+            " (3-hourly forecast)".dimmed().to_string()
+        } else {
+            String::new()
+        }
+    );
+    Ok(rain)
+}
+
+/// `weather compare`: one card, with a row of current conditions per
+/// place, the warmest and coldest marked, then a column per place for the
+/// forecast days. Each row is the same local day at every place; places
+/// that cannot be found get a row with the error.
+async fn show_compare(
+    queries: &[Query],
+    api_key: &str,
+    count: usize,
+    options: &DisplayOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if count > 5 {
+        return Err(format!("--days must be between 0 and 5, not {}", count).into());
+    }
+    let weather = options.weather_client(api_key)?;
+    let client = http_client()?;
+    let fetched = join_all(queries.iter().map(|query| async {
+        let (place, report) = report::get_cached_weather(query, &weather, options).await?;
+        let forecast = if count > 0 {
+            // A row without the forecast still compares current conditions.
+            forecast::get_forecast(&client, place.lat, place.lon, api_key)
+                .await
+                .ok()
+        } else {
+            None
+        };
+        Ok::<_, Box<dyn std::error::Error>>((place, report, forecast))
+    }))
+    .await;
+
+    // One place that cannot be found leaves a gap; only when none can is
+    // there nothing to compare.
+    let mut cities = Vec::new();
+    let mut failures = Vec::new();
+    for (query, result) in queries.iter().zip(fetched) {
+        match result {
+            Ok(city) => cities.push(city),
+            Err(e) => failures.push((query, e)),
+        }
+    }
+    if cities.is_empty() {
+        if let Some((_, e)) = failures.into_iter().next() {
+            return Err(e);
+        }
+        return Ok(());
+    }
+
+    let temperatures: Vec<f64> = cities
+        .iter()
+        .map(|(_, report, _)| report.current.temperature_c)
+        .collect();
+    let feels_like: Vec<f64> = cities
+        .iter()
+        .map(|(_, report, _)| report.current.feels_like_c)
+        .collect();
+    let mut rows = vec![["", "Now", "Temp", "Feels like", "Humidity", "Wind"]
+        .iter()
+        .map(|heading| heading.bold().to_string())
+        .collect::<Vec<_>>()];
+    for (place, report, _) in &cities {
+        rows.push(vec![
+            format!(
+                "{}, {}",
+                place.english_name().bold(),
+                report.location.country
+            ),
+            format!(
+                "{} {}",
+                display::condition_icon(report, options),
+                display::condition_name(&report.current.condition.kind)
+            ),
+            extreme(
+                format_temperature(report.current.temperature_c, options),
+                report.current.temperature_c,
+                &temperatures,
+            ),
+            extreme(
+                format_temperature(report.current.feels_like_c, options),
+                report.current.feels_like_c,
+                &feels_like,
+            ),
+            options
+                .locale
+                .quantity(report.current.humidity_percent as f64, 0, "%"),
+            options.quantity(options.units.speed(report.current.wind_speed_ms)),
+        ]);
+    }
+    for (query, e) in &failures {
+        rows.push(vec![
+            query.to_string().bold().to_string(),
+            e.to_string().bright_red().to_string(),
+        ]);
+    }
+
+    let mut card = Card::new();
+    card.line(format!(
+        "{}{}",
+        options.icon("🌍"),
+        "Now, warmest in red, coldest in blue".bright_green()
+    ));
+    for line in render::columns(&rows) {
+        card.line(line);
+    }
+
+    if count > 0 {
+        card.rule();
+        let now = options.clock.now().timestamp();
+        let mut rows = vec![std::iter::once(String::new())
+            .chain(
+                cities
+                    .iter()
+                    .map(|(place, _, _)| place.english_name().bold().to_string()),
+            )
+            .collect::<Vec<_>>()];
+        let today = |timezone: i32| local_datetime(now, timezone).date_naive();
+        let days: Vec<Vec<outlook::Day>> = cities
+            .iter()
+            .map(|(_, _, forecast)| forecast.as_ref().map(outlook::days).unwrap_or_default())
+            .collect();
+        for offset in 0..count as i64 {
+            let first =
+                today(cities[0].1.location.utc_offset_seconds) + chrono::Duration::days(offset);
+            let name = match offset {
+                0 => "Today".to_string(),
+                1 => "Tomorrow".to_string(),
+                _ => first.format("%A").to_string(),
+            };
+            let mut row = vec![name.bold().to_string()];
+            for ((_, report, _), days) in cities.iter().zip(&days) {
+                let date =
+                    today(report.location.utc_offset_seconds) + chrono::Duration::days(offset);
+                row.push(match days.iter().find(|day| day.date == date) {
+                    Some(day) => format!(
+                        "{} / {} {:>3}% {}",
+                        format_temperature(day.min_c, options),
+                        format_temperature(day.max_c, options),
+                        day.pop_percent,
+                        if options.ascii {
+                            get_weather_ascii(&day.condition, false)
+                        } else {
+                            get_weather_emoji(&day.condition, false)
+                        }
+                    ),
+                    None => "–".dimmed().to_string(),
+                });
+            }
+            rows.push(row);
+        }
+        for line in render::columns(&rows) {
+            card.line(line);
+        }
+    }
+    println!();
+    card.print();
+    Ok(())
+}
+
+/// `text` in red if `value` is the highest of `values`, in blue if the
+/// lowest; as is when all are equal.
+fn extreme(text: String, value: f64, values: &[f64]) -> String {
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    if max <= min {
+        text
+    } else if value >= max {
+        text.bright_red().bold().to_string()
+    } else if value <= min {
+        text.bright_blue().bold().to_string()
+    } else {
+        text
+    }
+}
+
+async fn show_hourly(
+    query: &Query,
+    api_key: &str,
+    count: usize,
+    options: &DisplayOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !(1..=hourly::MAX_HOURS).contains(&count) {
+        return Err(format!(
+            "--hours must be between 1 and {}, not {}",
+            hourly::MAX_HOURS,
+            count
+        )
+        .into());
+    }
+    let client = http_client()?;
+    let place = report::resolve_place(&options.weather_client(api_key)?, query)
+        .await
+        .with_context(|| format!("Failed to look up {}", query))?;
+    let (hours, timezone, coarse) = hourly_forecast(&client, &place, api_key).await?;
+    let mut hours = hourly::upcoming(&hours, options.clock.now().timestamp(), count);
+    if let Some(heading) = options.heading {
+        hourly::set_heading(&mut hours, heading);
+    }
+
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&hours)?);
+        return Ok(());
+    }
+
+    let mut card = Card::new();
+    card.line(format!(
+        "{}{} {}",
+        options.icon("🕒"),
+        format!("Next {} hours in", hours.len()).bright_green(),
+        place.english_name().bold()
+    ));
+    let hour_label = |t: i64| local_datetime(t, timezone).format("%H").to_string();
+    for line in hourly::chart(
+        &hours,
+        options.units,
+        hour_label,
+        options.heading,
+        options.ascii,
+    ) {
+        card.line(line);
+    }
+    if coarse {
+        card.line(
+            "Based on the 3-hourly forecast (One Call 3.0 unavailable)"
+                .dimmed()
+                .to_string(),
+        );
+    }
+    card.print();
+    Ok(())
+}
+
+async fn show_solar(
+    query: &Query,
+    api_key: &str,
+    count: u32,
+    sunshine_hours: bool,
+    options: &DisplayOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !(1..=solar::MAX_DAYS).contains(&count) {
+        return Err(format!(
+            "--days must be between 1 and {}, not {}",
+            solar::MAX_DAYS,
+            count
+        )
+        .into());
+    }
+    let client = http_client()?;
+    let place = report::resolve_place(&options.weather_client(api_key)?, query)
+        .await
+        .with_context(|| format!("Failed to look up {}", query))?;
+    let forecast = openmeteo::solar(&client, place.lat, place.lon, count)
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to fetch the solar forecast for {}",
+                place.english_name()
+            )
+        })?;
+    let days = solar::days(&forecast);
+
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&days)?);
+        return Ok(());
+    }
+
+    let timezone = forecast.utc_offset_seconds;
+    let today = local_datetime(options.clock.now().timestamp(), timezone).date_naive();
+    let mut card = Card::new();
+    card.line(format!(
+        "{}{} {}",
+        options.icon("🌞"),
+        "Sunshine in".bright_green(),
+        place.english_name().bold()
+    ));
+    for day in &days {
+        let date = local_datetime(day.start, timezone).date_naive();
+        let name = match (date - today).num_days() {
+            0 => "Today".to_string(),
+            1 => "Tomorrow".to_string(),
+            _ => date.format("%A").to_string(),
+        };
+        card.line(format!("{}: {}", name.bold(), solar::summary(day)));
+        if sunshine_hours {
+            continue;
+        }
+        for hour in &day.hours {
+            let label = local_datetime(hour.start, timezone)
+                .format("%H:%M")
+                .to_string();
+            card.line(solar::hour_line(hour, &label, options.ascii));
+        }
+    }
+    card.print();
+    Ok(())
+}
+
+async fn show_gpx(
+    file: &Path,
+    start: Option<gpx::Start>,
+    every: std::time::Duration,
+    options: &DisplayOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let xml = std::fs::read_to_string(file)
+        .with_context(|| format!("Failed to read {}", file.display()))?;
+    let points = gpx::parse(&xml).with_context(|| format!("Failed to read {}", file.display()))?;
+    let legs = gpx::legs(&points, every.as_secs().max(60) as i64);
+    if legs.len() > route::MAX_POINTS {
+        return Err(format!(
+            "The track makes {} legs, more than {}; give a longer --every",
+            legs.len(),
+            route::MAX_POINTS
+        )
+        .into());
+    }
+
+    // Where the start is local time, the offset is not known until the
+    // forecast arrives; a day either way covers any offset.
+    let now = options.clock.now();
+    let duration = points[points.len() - 1].time - points[0].time;
+    let end = match start {
+        Some(start) => start.resolve(now, 0) + duration + chrono::Duration::days(1),
+        None => points[points.len() - 1].time,
+    };
+    if start.is_none() && end < now {
+        return Err(format!(
+            "The track ended on {}; give --start to ride it again",
+            end.format("%Y-%m-%d")
+        )
+        .into());
+    }
+    let days = (end - now).num_days() + 2;
+    if days > route::MAX_DAYS as i64 {
+        return Err("The track ends beyond the 16-day forecast".into());
+    }
+    let points_ahead: Vec<route::Point> = legs.iter().map(gpx::Leg::point).collect();
+    let forecasts = route::forecast(&http_client()?, &points_ahead, days as u32)
+        .await
+        .context("Failed to fetch the forecast along the track")?;
+    let timezone = forecasts
+        .first()
+        .map_or(0, |forecast| forecast.utc_offset_seconds);
+    let started = match start {
+        Some(start) => start.resolve(now, timezone),
+        None => points[0].time,
+    }
+    .timestamp();
+    let rows = legs
+        .iter()
+        .zip(&forecasts)
+        .map(|(leg, forecast)| {
+            gpx::weather(leg, started, forecast).ok_or_else(|| {
+                format!(
+                    "The forecast doesn't reach {}",
+                    local_datetime(started + leg.from_s, timezone).format("%a %H:%M")
+                )
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+        return Ok(());
+    }
+
+    let time = |t: i64| local_datetime(t, timezone).format("%H:%M").to_string();
+    let distance = |m| options.quantity(options.units.distance(m));
+    let speed = |ms| options.quantity(options.units.speed(ms));
+    let mut card = Card::new();
+    let name = file.file_stem().map_or_else(
+        || file.display().to_string(),
+        |stem| stem.to_string_lossy().into_owned(),
+    );
+    card.line(format!(
+        "{}{} {}",
+        options.icon("🧭"),
+        "Weather along".bright_green(),
+        name.bold()
+    ));
+    card.line(
+        format!(
+            "{} in {}, setting off {}",
+            distance(rows.last().map_or(0.0, |row| row.leg.to_m)),
+            clock::span(duration.num_seconds()),
+            local_datetime(started, timezone).format("%a %-d %b %H:%M")
+        )
+        .dimmed()
+        .to_string(),
+    );
+    let table: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            let wind = format!(
+                "{} {}",
+                speed(row.wind_speed_ms),
+                options.catalog.compass(row.wind_from_deg)
+            );
+            let relative = match row.headwind_ms {
+                Some(head) if head.abs() >= 0.5 => {
+                    let text = format!(
+                        "{} {}",
+                        speed(head.abs()),
+                        if head > 0.0 { "headwind" } else { "tailwind" }
+                    );
+                    if head > 0.0 {
+                        text.yellow().to_string()
+                    } else {
+                        text.green().to_string()
+                    }
+                }
+                Some(_) => "crosswind".to_string(),
+                None => "-".to_string(),
+            };
+            vec![
+                format!("{}–{}", time(row.start), time(row.end)),
+                format!("to {}", distance(row.leg.to_m)),
+                row.leg.elevation_m.map_or("-".to_string(), |m| {
+                    options.quantity(options.units.elevation(m))
+                }),
+                options.quantity(options.units.temperature(row.temperature_c)),
+                row.condition.to_string(),
+                row.precipitation_probability.map_or("-".to_string(), |p| {
+                    let text = format!(
+                        "{} rain",
+                        options.locale.quantity((p * 100.0).round(), 0, "%")
+                    );
+                    if p >= 0.5 {
+                        text.bright_blue().to_string()
+                    } else {
+                        text
+                    }
+                }),
+                wind,
+                relative,
+            ]
+        })
+        .collect();
+    for line in render::columns(&table) {
+        card.line(format!("  {}", line));
+    }
+    println!();
+    card.print();
+    Ok(())
+}
+
+async fn show_events(
+    file: &Path,
+    api_key: &str,
+    options: &DisplayOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let text = std::fs::read_to_string(file)
+        .with_context(|| format!("Failed to read {}", file.display()))?;
+    let events = ics::parse(&text).with_context(|| format!("Failed to read {}", file.display()))?;
+
+    // Until the place is known so is not its offset from UTC; half a day
+    // either way covers any.
+    let now = options.clock.now().timestamp();
+    let horizon = now + route::MAX_DAYS as i64 * 86_400;
+    let slack = 14 * 3600;
+    let (located, unlocated): (Vec<ics::Event>, Vec<ics::Event>) = events
+        .into_iter()
+        .filter(|event| {
+            event.end_timestamp(0) + slack > now && event.start.timestamp(0) - slack < horizon
+        })
+        .partition(|event| event.location.is_some() || event.geo.is_some());
+
+    // Each location is looked up once, by its whole text and then by less
+    // of it; a location found nowhere leaves its events without weather.
+    let client = http_client()?;
+    let weather = options.weather_client(api_key)?;
+    let mut points: Vec<route::Point> = Vec::new();
+    let mut found: Vec<(String, Option<(String, usize)>)> = Vec::new();
+    let mut places = Vec::new();
+    for event in &located {
+        let location = event.location.clone().unwrap_or_default();
+        if let Some((lat, lon)) = event.geo {
+            let name = if location.is_empty() {
+                format!("{:.4}, {:.4}", lat, lon)
+            } else {
+                location.clone()
+            };
+            points.push(route::Point {
+                lat,
+                lon,
+                elevation: None,
+            });
+            places.push(Some((name, points.len() - 1)));
+            continue;
+        }
+        if let Some((_, place)) = found.iter().find(|(known, _)| *known == location) {
+            places.push(place.clone());
+            continue;
+        }
+        let mut place = None;
+        for name in events::place_names(&location) {
+            match weather.resolve_city(&name, &report::cancel()).await {
+                Ok(found) => {
+                    points.push(route::Point {
+                        lat: found.lat,
+                        lon: found.lon,
+                        elevation: None,
+                    });
+                    place = Some((found.english_name().to_string(), points.len() - 1));
+                    break;
+                }
+                Err(WeatherError::CityNotFound(_)) => {}
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to look up '{}'", location))
+                }
+            }
+        }
+        found.push((location, place.clone()));
+        places.push(place);
+    }
+    if points.len() > route::MAX_POINTS {
+        return Err(format!(
+            "The events are at {} places, more than {}",
+            points.len(),
+            route::MAX_POINTS
+        )
+        .into());
+    }
+    let forecasts = if points.is_empty() {
+        Vec::new()
+    } else {
+        route::forecast(&client, &points, route::MAX_DAYS)
+            .await
+            .context("Failed to fetch the forecast for the events")?
+    };
+
+    let mut rows: Vec<(events::Annotated, i32)> = located
+        .iter()
+        .zip(&places)
+        .map(|(event, place)| {
+            let location = event.location.clone().unwrap_or_default();
+            let forecast = place
+                .as_ref()
+                .and_then(|(name, i)| Some((name, points[*i], forecasts.get(*i)?)));
+            let (start, end, weather, offset, place) = match forecast {
+                Some((name, point, forecast)) => {
+                    let (start, end, weather) = events::weather(event, point, forecast);
+                    (
+                        start,
+                        end,
+                        weather,
+                        forecast.utc_offset_seconds,
+                        Some(name.clone()),
+                    )
+                }
+                None => (
+                    event.start.timestamp(0),
+                    event.end_timestamp(0),
+                    None,
+                    0,
+                    None,
+                ),
+            };
+            let annotated = events::Annotated {
+                summary: event.summary.clone(),
+                location,
+                all_day: event.all_day(),
+                start,
+                end,
+                place,
+                weather,
+            };
+            (annotated, offset)
+        })
+        .filter(|(event, _)| event.end > now && event.start < horizon)
+        .collect();
+    rows.sort_by_key(|(event, _)| event.start);
+
+    if options.json {
+        let rows: Vec<&events::Annotated> = rows.iter().map(|(event, _)| event).collect();
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+        return Ok(());
+    }
+
+    let mut card = Card::new();
+    let name = file.file_stem().map_or_else(
+        || file.display().to_string(),
+        |stem| stem.to_string_lossy().into_owned(),
+    );
+    card.line(format!(
+        "{}{} {}",
+        options.icon("📅"),
+        "Weather for".bright_green(),
+        name.bold()
+    ));
+    if rows.is_empty() {
+        card.line(
+            "No upcoming events with a location in the next 16 days"
+                .dimmed()
+                .to_string(),
+        );
+    }
+    for (event, offset) in &rows {
+        let when = local_datetime(event.start, *offset);
+        let when = if event.all_day {
+            format!("{} all day", when.format("%a %-d %b"))
+        } else {
+            when.format("%a %-d %b %H:%M").to_string()
+        };
+        let at = event.location.split(',').next().unwrap_or_default().trim();
+        let title = if at.is_empty() {
+            event.summary.clone()
+        } else {
+            format!("{}, {}", event.summary, at)
+        };
+        let forecast = match (&event.place, &event.weather) {
+            (Some(_), Some(weather)) => {
+                let mut text = format!(
+                    "{}, {}",
+                    options.quantity(options.units.temperature(weather.temperature_c)),
+                    weather.condition
+                );
+                if let Some(p) = weather.precipitation_probability {
+                    let rain = format!(
+                        "{} rain",
+                        options.locale.quantity((p * 100.0).round(), 0, "%")
+                    );
+                    let rain = if p >= 0.5 {
+                        rain.bright_blue().to_string()
+                    } else {
+                        rain
+                    };
+                    text = format!("{}, {}", text, rain);
+                }
+                text
+            }
+            (Some(_), None) => "beyond the forecast".dimmed().to_string(),
+            (None, _) => "place not found".dimmed().to_string(),
+        };
+        card.line(format!("  {} – {}: {}", when.bold(), title, forecast));
+    }
+    if !unlocated.is_empty() {
+        card.line(
+            format!(
+                "{} upcoming event{} without a location left out",
+                unlocated.len(),
+                if unlocated.len() == 1 { "" } else { "s" }
+            )
+            .dimmed()
+            .to_string(),
+        );
+    }
+    println!();
+    card.print();
+    Ok(())
+}
+
+async fn show_wake(
+    query: &Query,
+    api_key: &str,
+    offset: i64,
+    days: u32,
+    cron: bool,
+    command: &str,
+    options: &DisplayOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !(1..=wake::MAX_DAYS).contains(&days) {
+        return Err(format!(
+            "--days must be between 1 and {}, not {}",
+            wake::MAX_DAYS,
+            days
+        )
+        .into());
+    }
+    if offset.abs() >= 12 * 3600 {
+        return Err("--offset must be less than 12 hours from sunrise".into());
+    }
+    let place = report::resolve_place(&options.weather_client(api_key)?, query)
+        .await
+        .with_context(|| format!("Failed to look up {}", query))?;
+
+    // Today's alarm is left out once it has gone off.
+    let now = options.clock.now();
+    let today = local_datetime(now.timestamp(), wake::solar_offset(place.lon)).date_naive();
+    let (alarms, sunless) = wake::alarms(place.lat, place.lon, today, days, offset);
+    let alarms: Vec<wake::Alarm> = alarms
+        .into_iter()
+        .filter(|alarm| alarm.at > now.timestamp())
+        .collect();
+    if !sunless.is_empty() {
+        eprintln!(
+            "{} No sunrise in {} on {} of the days (polar night or midnight sun), so no alarm then",
+            "Note:".yellow(),
+            place.english_name(),
+            sunless.len()
+        );
+    }
+
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&alarms)?);
+    } else if cron {
+        println!(
+            "# Wake {} in {}",
+            wake::relation(offset),
+            place.english_name()
+        );
+        println!("{}", wake::CRON_NOTE);
+        for alarm in &alarms {
+            println!("{}", wake::cron_line(alarm, command));
+        }
+    } else {
+        print!(
+            "{}",
+            wake::calendar(
+                &alarms,
+                place.english_name(),
+                place.lat,
+                place.lon,
+                offset,
+                now
+            )
+        );
+    }
+    Ok(())
+}
+
+async fn show_mountain(
+    query: &Query,
+    api_key: &str,
+    summit: Option<f64>,
+    base: Option<f64>,
+    days: u32,
+    options: &DisplayOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !(1..=mountain::MAX_DAYS).contains(&days) {
+        return Err(format!(
+            "--days must be between 1 and {}, not {}",
+            mountain::MAX_DAYS,
+            days
+        )
+        .into());
+    }
+    let client = http_client()?;
+    let place = report::resolve_place(&options.weather_client(api_key)?, query)
+        .await
+        .with_context(|| format!("Failed to look up {}", query))?;
+    let terrain = match (summit, base) {
+        (Some(_), Some(_)) => Vec::new(),
+        _ => weather_providers::mountain::elevations(
+            &client,
+            &mountain::terrain_points(place.lat, place.lon),
+        )
+        .await
+        .context("Failed to fetch the terrain elevation")?,
+    };
+    let levels = mountain::levels(&terrain, summit, base)?;
+    let bands = weather_providers::mountain::forecast(&client, place.lat, place.lon, &levels, days)
+        .await
+        .with_context(|| format!("Failed to fetch the mountain forecast for {}", query))?;
+    let forecast = mountain::days(&bands);
+
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&forecast)?);
+        return Ok(());
+    }
+
+    let timezone = bands.first().map_or(0, |band| band.utc_offset_seconds);
+    let today = local_datetime(options.clock.now().timestamp(), timezone).date_naive();
+    let elevation = |metres| options.quantity(options.units.elevation(metres));
+    let temperature = |celsius| options.quantity(options.units.temperature(celsius));
+    let mut card = Card::new();
+    card.line(format!(
+        "{}{} {}",
+        options.icon("⛰️"),
+        "Mountain forecast for".bright_green(),
+        place.english_name().bold()
+    ));
+    for day in &forecast {
+        card.line(
+            match (day.date - today).num_days() {
+                0 => "Today".to_string(),
+                1 => "Tomorrow".to_string(),
+                _ => day.date.format("%A %-d %B").to_string(),
+            }
+            .bold()
+            .to_string(),
+        );
+        let rows: Vec<Vec<String>> = day
+            .levels
+            .iter()
+            .map(|level| {
+                let wind = match (level.wind_speed_max_ms, level.wind_gusts_max_ms) {
+                    (Some(speed), gusts) => {
+                        let text = format!(
+                            "{}{} {}{}",
+                            options.icon("💨"),
+                            level
+                                .wind_from_deg
+                                .map_or("", |from| options.catalog.compass(from)),
+                            options.quantity(options.units.speed(speed)),
+                            gusts.map_or(String::new(), |gusts| format!(
+                                ", gusts {}",
+                                options.quantity(options.units.speed(gusts))
+                            ))
+                        );
+                        if speed >= mountain::STRONG_WIND_MS {
+                            text.bright_red().to_string()
+                        } else {
+                            text
+                        }
+                    }
+                    (None, _) => "-".to_string(),
+                };
+                let precipitation = match level.precipitation_mm {
+                    Some(mm) if mm > 0.0 => format!(
+                        "{} {}",
+                        options.quantity(options.units.precipitation(mm)),
+                        if level.mostly_snow() { "snow" } else { "rain" }
+                    ),
+                    Some(_) => "dry".to_string(),
+                    None => "-".to_string(),
+                };
+                vec![
+                    level.level.label().bold().to_string(),
+                    elevation(level.elevation_m),
+                    match (level.temperature_min_c, level.temperature_max_c) {
+                        (Some(min), Some(max)) => {
+                            format!("{} to {}", temperature(min), temperature(max))
+                        }
+                        _ => "-".to_string(),
+                    },
+                    level.condition.unwrap_or("-").to_string(),
+                    wind,
+                    precipitation,
+                ]
+            })
+            .collect();
+        for line in render::columns(&rows) {
+            card.line(format!("  {}", line));
+        }
+        if let (Some(min), Some(max)) = (day.freezing_level_min_m, day.freezing_level_max_m) {
+            card.line(format!(
+                "  {} {}",
+                "Freezing level".dimmed(),
+                if (max - min).abs() < 50.0 {
+                    elevation(min)
+                } else {
+                    format!("{}–{}", elevation(min), elevation(max))
+                }
+            ));
+        }
+    }
+    println!();
+    card.print();
+    Ok(())
+}
+
+/// `weather surf` at a place that is not a configured spot, which needs
+/// the way its beach faces.
+async fn surf_at(
+    query: &Query,
+    facing: Option<f64>,
+    api_key: &str,
+    days: u32,
+    options: &DisplayOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(facing) = facing else {
+        let spots = &options.config.surf.spots;
+        let known = if spots.is_empty() {
+            "none are configured under [surf.spots]".to_string()
+        } else {
+            format!(
+                "configured spots: {}",
+                spots.keys().cloned().collect::<Vec<_>>().join(", ")
+            )
+        };
+        return Err(format!(
+            "{} is not a surf spot; give --facing with the way its beach faces ({})",
+            query, known
+        )
+        .into());
+    };
+    let place = report::resolve_place(&options.weather_client(api_key)?, query)
+        .await
+        .with_context(|| format!("Failed to look up {}", query))?;
+    let spot = surf::Spot {
+        lat: place.lat,
+        lon: place.lon,
+        facing,
+    };
+    show_surf(place.english_name(), &spot, days, options).await
+}
+
+async fn show_surf(
+    name: &str,
+    spot: &surf::Spot,
+    days: u32,
+    options: &DisplayOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !(1..=surf::MAX_DAYS).contains(&days) {
+        return Err(format!(
+            "--days must be between 1 and {}, not {}",
+            surf::MAX_DAYS,
+            days
+        )
+        .into());
+    }
+    let (marine, wind) = marine::forecast(&http_client()?, spot.lat, spot.lon, days)
+        .await
+        .with_context(|| format!("Failed to fetch the marine forecast for {}", name))?;
+    let now = options.clock.now().timestamp();
+    let sessions = surf::sessions(&marine, &wind, spot, now);
+    if sessions.is_empty() {
+        return Err(format!("No wave forecast for {}; is it on an open coast?", name).into());
+    }
+
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&sessions)?);
+        return Ok(());
+    }
+
+    let timezone = marine.utc_offset_seconds;
+    let today = local_datetime(now, timezone).date_naive();
+    let time = |t: i64| local_datetime(t, timezone).format("%H:%M").to_string();
+    let mut card = Card::new();
+    card.line(format!(
+        "{}{} {} {}",
+        options.icon("🏄"),
+        "Surf at".bright_green(),
+        name.bold(),
+        format!(
+            "(beach faces {}, {:.0}°)",
+            options.catalog.compass(spot.facing),
+            spot.facing
+        )
+        .dimmed()
+    ));
+    let (full, empty) = if options.ascii {
+        ("*", "-")
+    } else {
+        ("★", "☆")
+    };
+    let mut day = None;
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let flush = |card: &mut Card, rows: &mut Vec<Vec<String>>| {
+        for line in render::columns(rows) {
+            card.line(format!("  {}", line));
+        }
+        rows.clear();
+    };
+    for session in &sessions {
+        let date = local_datetime(session.start, timezone).date_naive();
+        if day != Some(date) {
+            flush(&mut card, &mut rows);
+            day = Some(date);
+            card.line(
+                match (date - today).num_days() {
+                    0 => "Today".to_string(),
+                    1 => "Tomorrow".to_string(),
+                    _ => date.format("%A").to_string(),
+                }
+                .bold()
+                .to_string(),
+            );
+        }
+        let label = session.rating.label();
+        let rating = match session.rating {
+            surf::Rating::Epic => label.bright_magenta().bold(),
+            surf::Rating::Good => label.bright_green(),
+            surf::Rating::Fair => label.yellow(),
+            surf::Rating::Poor => label.bright_red(),
+            surf::Rating::Flat => label.dimmed(),
+        };
+        let stars = format!(
+            "{}{}",
+            full.repeat(session.rating.stars()),
+            empty.repeat(4 - session.rating.stars())
+        );
+        rows.push(vec![
+            session.period.label().to_string(),
+            format!("{}–{}", time(session.start), time(session.end)),
+            rating.to_string(),
+            stars,
+            format!(
+                "{} at {:.0} s from {}",
+                options.quantity(options.units.height(session.swell_height_m)),
+                session.swell_period_s,
+                options.catalog.compass(session.swell_from_deg)
+            ),
+            format!(
+                "{} {}",
+                options.quantity(options.units.speed(session.wind_speed_ms)),
+                session.wind.label()
+            ),
+        ]);
+    }
+    flush(&mut card, &mut rows);
+    card.print();
+    Ok(())
+}
+
+async fn show_air(
+    query: &Query,
+    api_key: &str,
+    options: &DisplayOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let client = http_client()?;
+    let place = report::resolve_place(&options.weather_client(api_key)?, query)
+        .await
+        .with_context(|| format!("Failed to look up {}", query))?;
+    let sample = air::current(&client, place.lat, place.lon, api_key)
+        .await
+        .with_context(|| format!("Failed to fetch air quality for {}", place.english_name()))?;
+
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&sample)?);
+        return Ok(());
+    }
+
+    let locale = &options.locale;
+    let mut card = Card::new();
+    card.line(format!(
+        "{}{} {}",
+        options.icon("🏭"),
+        "Air quality in".bright_green(),
+        place.english_name().bold()
+    ));
+    card.line(format!(
+        "Index: {} ({})",
+        air_level(
+            sample.main.aqi,
+            &format!("{} {}", sample.main.aqi, air_level_name(sample.main.aqi))
+        ),
+        clock::relative(options.clock.now().timestamp(), sample.dt)
+    ));
+    for (pollutant, concentration) in sample.components.pollutants() {
+        let level = pollutant.level(concentration);
+        card.line(format!(
+            "{:<6} {:>12}  {}",
+            pollutant.label,
+            locale.quantity(concentration, 1, "µg/m³"),
+            air_level(level, air_level_name(level))
+        ));
+    }
+    println!();
+    card.print();
+    Ok(())
+}
+
+fn air_level_name(level: u8) -> &'static str {
+    air::LEVELS[usize::from(level.clamp(1, 5)) - 1]
+}
+
+/// `text` coloured for air quality index `level`, 1 (good) to 5.
+fn air_level(level: u8, text: &str) -> String {
+    match level {
+        1 => text.green(),
+        2 => text.bright_green(),
+        3 => text.yellow(),
+        4 => text.bright_red(),
+        _ => text.red().bold(),
+    }
+    .to_string()
+}
+
+async fn show_dry_windows(
+    query: &Query,
+    api_key: &str,
+    min_minutes: i64,
+    hours: i64,
+    options: &DisplayOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let client = http_client()?;
+    let place = report::resolve_place(&options.weather_client(api_key)?, query)
+        .await
+        .with_context(|| format!("Failed to look up {}", query))?;
+
+    let (slots, timezone, coarse) = precipitation_slots(&client, &place, api_key).await?;
+
+    let now = options.clock.now().timestamp();
+    let until = now + hours.max(1) * 3600;
+    let windows = safe_window::dry_windows(&slots, now, until, min_minutes.max(1));
+    let (wet, storm) = safe_window::outlook(&slots, now, until);
+    let time = |t: i64| options.locale.time(&local_datetime(t, timezone));
+
+    let mut card = Card::new();
+    let heading = if storm {
+        format!("{}Thunderstorms expected", options.icon("⛈️"))
+    } else if wet {
+        format!("{}Rain expected", options.icon("🌧️"))
+    } else {
+        format!("{}No rain expected", options.icon("🌤️"))
+    };
+    card.line(format!(
+        "{} near {} in the next {}h",
+        heading.bright_green(),
+        place.english_name().bold(),
+        hours
+    ));
+
+    if wet {
+        if windows.is_empty() {
+            card.line(format!("No dry window of {}+ minutes", min_minutes.max(1)));
+        }
+        for window in &windows {
+            let start = if window.start <= now {
+                "now".to_string()
+            } else {
+                time(window.start)
+            };
+            let minutes = window.minutes();
+            card.line(format!(
+                "{}Dry window {}–{} ({}h {:02}m)",
+                options.icon("🚶"),
+                start.bold(),
+                time(window.end).bold(),
+                minutes / 60,
+                minutes % 60
+            ));
+        }
+    }
+    if coarse {
+        card.line(
+            "Based on the 3-hourly forecast (One Call 3.0 unavailable)"
+                .dimmed()
+                .to_string(),
+        );
+    }
+
+    println!();
+    card.print();
+    Ok(())
+}
+
+async fn show_wind_window(
+    query: &Query,
+    api_key: &str,
+    criteria: &wind_window::Criteria,
+    min_hours: i64,
+    hours: i64,
+    options: &DisplayOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !(1..=120).contains(&hours) {
+        return Err(format!("--hours must be between 1 and 120, not {}", hours).into());
+    }
+    if criteria.min_ms > criteria.max_ms {
+        return Err("--min must not be above --max".into());
+    }
+    let client = http_client()?;
+    let place = report::resolve_place(&options.weather_client(api_key)?, query)
+        .await
+        .with_context(|| format!("Failed to look up {}", query))?;
+    let (forecast, timezone, mut coarse) = hourly_forecast(&client, &place, api_key).await?;
+    let now = options.clock.now().timestamp();
+    let mut forecast = hourly::upcoming(&forecast, now, hours as usize);
+    if forecast.len() < hours as usize && !coarse {
+        // One Call ends after 48 hours; the 3-hourly forecast goes on.
+        let longer = forecast::get_forecast(&client, place.lat, place.lon, api_key).await?;
+        forecast = hourly::upcoming(&hourly::from_forecast(&longer), now, hours as usize);
+        coarse = true;
+    }
+    let windows = wind_window::windows(&forecast, criteria, min_hours.max(1));
+
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&windows)?);
+        return Ok(());
+    }
+
+    let knots = |ms: f64| format!("{:.0}", ms / wind_window::KNOT_MS);
+    let today = local_datetime(now, timezone).date_naive();
+    let when = |t: i64| {
+        let time = local_datetime(t, timezone);
+        let day = match (time.date_naive() - today).num_days() {
+            0 => "Today".to_string(),
+            1 => "Tomorrow".to_string(),
+            _ => time.format("%A").to_string(),
+        };
+        (day, time.format("%H:%M").to_string())
+    };
+
+    let mut card = Card::new();
+    card.line(format!(
+        "{}{} near {} in the next {}h",
+        options.icon("🪁"),
+        "Wind windows".bright_green(),
+        place.english_name().bold(),
+        hours
+    ));
+    let sector = criteria.sector.map_or_else(String::new, |sector| {
+        format!(" from {:.0}° to {:.0}°", sector.from, sector.to)
+    });
+    card.line(
+        format!(
+            "{}–{} kn{}",
+            knots(criteria.min_ms),
+            knots(criteria.max_ms),
+            sector
+        )
+        .dimmed()
+        .to_string(),
+    );
+    if windows.is_empty() {
+        card.line(format!(
+            "No window of {}h or more; the wind won't suit",
+            min_hours.max(1)
+        ));
+    }
+    for window in &windows {
+        let ((start_day, start), (end_day, end)) = (when(window.start), when(window.end));
+        let end = if end_day == start_day {
+            end
+        } else {
+            format!("{} {}", end_day, end)
+        };
+        let gusts = window
+            .max_gust_ms
+            .map_or_else(String::new, |gust| format!(", gusts {} kn", knots(gust)));
+        let (lightest, strongest) = (knots(window.min_speed_ms), knots(window.max_speed_ms));
+        let speed = if lightest == strongest {
+            lightest
+        } else {
+            format!("{}–{}", lightest, strongest)
+        };
+        card.line(format!(
+            "{} {}–{} ({}h): {} kn from {}{}",
+            start_day,
+            start.bold(),
+            end.bold(),
+            window.hours(),
+            speed,
+            options.catalog.compass(window.from_deg),
+            gusts
+        ));
+    }
+    if coarse {
+        card.line("Based on the 3-hourly forecast".dimmed().to_string());
+    }
+
+    println!();
+    card.print();
+    Ok(())
+}
+
+/// Prints the daily forecast for up to `count` days (with `weekend`, only
+/// the weekend days among them), or with `diff` its changes since the last
+/// run, and keeps this run's rollup for the next one.
+async fn show_forecast(
+    query: &Query,
+    api_key: &str,
+    count: usize,
+    diff: bool,
+    weekend: bool,
+    options: &DisplayOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // The free forecast endpoint covers five days in 3-hour steps.
+    if !(1..=5).contains(&count) {
+        return Err(format!("--days must be between 1 and 5, not {}", count).into());
+    }
+    let client = http_client()?;
+    let place = report::resolve_place(&options.weather_client(api_key)?, query)
+        .await
+        .with_context(|| format!("Failed to look up {}", query))?;
+    let forecast = forecast::get_forecast(&client, place.lat, place.lon, api_key).await?;
+    let days = outlook::days(&forecast);
+
+    let now = options.clock.now();
+    let today = local_datetime(now.timestamp(), forecast.city.timezone).date_naive();
+    let snapshot_path = outlook::snapshot_path(place.english_name());
+    let previous = snapshot_path.as_deref().and_then(outlook::read_snapshot);
+    if let Some(path) = &snapshot_path {
+        let snapshot = outlook::Snapshot {
+            fetched_at: now,
+            days: days.clone(),
+        };
+        if let Err(e) = outlook::write_snapshot(path, &snapshot) {
+            eprintln!("{} {}", "Cannot keep the forecast for --diff:".yellow(), e);
+        }
+    }
+    let day_name = |date: chrono::NaiveDate| match (date - today).num_days() {
+        0 => "Today".to_string(),
+        1 => "Tomorrow".to_string(),
+        _ => date.format("%A").to_string(),
+    };
+
+    let week = options.locale.week();
+    let shown: Vec<&outlook::Day> = days
+        .iter()
+        .filter(|day| day.date >= today)
+        .take(count)
+        .filter(|day| !weekend || week.is_weekend(day.date))
+        .collect();
+
+    let mut card = Card::new();
+    if !diff {
+        if options.json {
+            println!("{}", serde_json::to_string_pretty(&shown)?);
+            return Ok(());
+        }
+        card.line(format!(
+            "{}{} {}",
+            options.icon("📅"),
+            "Forecast for".bright_green(),
+            place.english_name().bold()
+        ));
+        if shown.is_empty() {
+            card.line("No weekend days in the forecast range".to_string());
+        }
+        for (i, day) in shown.iter().enumerate() {
+            // A rule where a new week begins, unless it begins the list.
+            if i > 0 && week.starts_week(day.date) {
+                card.line(if options.ascii { "--" } else { "──" }.dimmed().to_string());
+            }
+            let name = format!("{:<9}", day_name(day.date));
+            let name = if week.is_weekend(day.date) {
+                name.bold().cyan()
+            } else {
+                name.bold()
+            };
+            let icon = if options.ascii {
+                get_weather_ascii(&day.condition, false)
+            } else {
+                get_weather_emoji(&day.condition, false)
+            };
+            let precipitation = if day.precipitation_mm > 0.0 {
+                options.quantity(options.units.precipitation(day.precipitation_mm))
+            } else {
+                String::new()
+            };
+            let other_date = match options.config.calendar.format(day.date) {
+                Some(date) => format!("  {}", date.dimmed()),
+                None => String::new(),
+            };
+            card.line(format!(
+                "{} {} / {}  {:>3}% rain {:>7}  {} {}{}",
+                name,
+                format_temperature(day.min_c, options),
+                format_temperature(day.max_c, options),
+                day.pop_percent,
+                precipitation,
+                icon,
+                day.condition,
+                other_date
+            ));
+        }
+        println!();
+        card.print();
+        return Ok(());
+    }
+
+    let Some(previous) = previous else {
+        if options.json {
+            println!("[]");
+        } else {
+            println!(
+                "No earlier forecast for {} yet; run again later to see what changed",
+                place.english_name()
+            );
+        }
+        return Ok(());
+    };
+    let mut changes = outlook::diff(&previous.days, &days, today);
+    changes.retain(|change| shown.iter().any(|day| day.date == change.date));
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&changes)?);
+        return Ok(());
+    }
+    let fetched = local_datetime(previous.fetched_at.timestamp(), forecast.city.timezone);
+    card.line(format!(
+        "{}{} {} since {} {}",
+        options.icon("🔀"),
+        "Forecast changes for".bright_green(),
+        place.english_name().bold(),
+        options.locale.date(&fetched),
+        options.locale.time(&fetched)
+    ));
+    if changes.is_empty() {
+        card.line("No notable changes".to_string());
+    }
+    for outlook::DayChange { date, change } in &changes {
+        let day = day_name(*date);
+        card.line(match change {
+            outlook::Change::Rain { now, was } => {
+                format!("{} now {}% rain, was {}%", day, now.to_string().bold(), was)
+            }
+            outlook::Change::High { now, was } => format!(
+                "{} high now {}, was {}",
+                day,
+                format_temperature(*now, options).bold(),
+                format_temperature(*was, options)
+            ),
+            outlook::Change::Low { now, was } => format!(
+                "{} low now {}, was {}",
+                day,
+                format_temperature(*now, options).bold(),
+                format_temperature(*was, options)
+            ),
+            outlook::Change::Condition { now, was } => {
+                format!("{} now {}, was {}", day, now.bold(), was)
+            }
+        });
+    }
+    println!();
+    card.print();
+    Ok(())
+}
+
+/// `--tui`: fetches the conditions and forecast for `query`, then hands the
+/// terminal over to [`tui::run`], or with `--screenshot` writes the
+/// dashboard to a file.
+async fn show_tui(
+    query: &Query,
+    api_key: &str,
+    screenshot: Option<&Path>,
+    options: &DisplayOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let client = options.weather_client(api_key)?;
+    let (place, report) = report::get_cached_weather(query, &client, options)
+        .await
+        .with_context(|| format!("Failed to look up {}", query))?;
+    let forecast = forecast::get_forecast(&http_client()?, place.lat, place.lon, api_key).await?;
+    match screenshot {
+        Some(path) => tui::screenshot(place, report, forecast, options, path),
+        None => tui::run(place, report, forecast, api_key, options).await,
+    }
+}
+
+async fn show_tides(
+    query: &Query,
+    api_key: &str,
+    options: &DisplayOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // The current report supplies the location's UTC offset.
+    let (place, report) = report::get_weather(query, &options.weather_client(api_key)?)
+        .await
+        .with_context(|| format!("Failed to look up {}", query))?;
+    let now = options.clock.now();
+    let prediction = tides::predict(&http_client()?, &place, now).await?;
+
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&prediction)?);
+        return Ok(());
+    }
+
+    let locale = &options.locale;
+    let mut card = Card::new();
+    card.line(format!(
+        "{}{} {} ({})",
+        options.icon("🌊"),
+        "Tides near".bright_green(),
+        place.english_name().bold(),
+        options.date(&local_datetime(
+            now.timestamp(),
+            report.location.utc_offset_seconds
+        ))
+    ));
+    card.line(format!(
+        "Station: {} ({}{})",
+        prediction.station,
+        prediction.provider,
+        prediction.distance_m.map_or_else(String::new, |d| format!(
+            ", {} away",
+            options.quantity(options.units.distance(d))
+        ))
+    ));
+
+    let upcoming: Vec<_> = prediction
+        .tides
+        .iter()
+        .filter(|t| t.time >= now.timestamp())
+        .take(6)
+        .collect();
+    if upcoming.is_empty() {
+        card.line("No tide predictions for the next two days".to_string());
+    }
+    for tide in upcoming {
+        let (icon, label) = if tide.high {
+            (options.icon("🔼"), "High".bright_cyan())
+        } else {
+            (options.icon("🔽"), "Low ".cyan())
+        };
+        card.line(format!(
+            "{}{} {} ({})  {}",
+            icon,
+            label,
+            locale.time(&local_datetime(
+                tide.time,
+                report.location.utc_offset_seconds
+            )),
+            clock::relative(now.timestamp(), tide.time),
+            options.quantity(options.units.height(tide.height))
+        ));
+    }
+    card.line(
+        format!("Heights above {}", prediction.datum)
+            .dimmed()
+            .to_string(),
+    );
+
+    println!();
+    card.print();
+    Ok(())
+}
+
+/// Prints the `--today` strip, from One Call hourly data or else the
+/// 3-hourly forecast.
+async fn display_timeline(
+    place: &Place,
+    api_key: &str,
+    options: &DisplayOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let client = http_client()?;
+    let (hours, timezone, coarse) =
+        match onecall::get_onecall(&client, place.lat, place.lon, api_key).await {
+            Ok(onecall) => (
+                timeline::from_onecall(&onecall),
+                onecall.timezone_offset,
+                false,
+            ),
+            Err(_) => {
+                let forecast =
+                    forecast::get_forecast(&client, place.lat, place.lon, api_key).await?;
+                (
+                    timeline::from_forecast(&forecast),
+                    forecast.city.timezone,
+                    true,
+                )
+            }
+        };
+
+    let mut card = Card::new();
+    card.line(format!(
+        "{}{}",
+        options.icon("🕒"),
+        "Next 24 hours".bright_green()
+    ));
+    let hour_label = |t: i64| local_datetime(t, timezone).format("%H").to_string();
+    for line in timeline::strip(
+        &hours,
+        options.clock.now().timestamp(),
+        hour_label,
+        options.ascii,
+    ) {
+        card.line(line);
+    }
+    card.line(timeline::legend(options.ascii));
+    if coarse {
+        card.line(
+            "Based on the 3-hourly forecast (One Call 3.0 unavailable)"
+                .dimmed()
+                .to_string(),
+        );
+    }
+    card.print();
+    Ok(())
+}
+
+fn notify_breaches(breaches: &[thresholds::Breach], report: &model::Report) {
+    let worst = if breaches[0].severity == thresholds::Severity::Critical {
+        "Critical"
+    } else {
+        "Warning"
+    };
+    let body = breaches
+        .iter()
+        .map(|b| {
+            let level = match b.severity {
+                thresholds::Severity::Critical => "critical",
+                _ => "warn",
+            };
+            format!("{} {:.1} ({} at {})", b.field, b.value, level, b.limit)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let title = format!("{}: weather in {}", worst, report.location.name);
+    if let Err(e) = notify::send(&title, &body) {
+        eprintln!("{} {}", "Notification failed:".yellow(), e);
+    }
+}
+
+/// `--bell` and `--sound`.
+fn sound_alarm(options: &DisplayOptions) {
+    if options.bell {
+        bell::ring();
+    }
+    if let Some(path) = &options.sound {
+        if let Err(e) = bell::play(path) {
+            eprintln!("{} {}", "Sound failed:".yellow(), e);
+        }
+    }
+}
+
+/// Notifies of the alerts in force, the worst in the title.
+fn notify_alerts(alerts: &[model::Alert], report: &model::Report) {
+    let title = format!("{}: {}", alerts[0].event, report.location.name);
+    let body = alerts
+        .iter()
+        .map(|alert| {
+            alert
+                .headline
+                .clone()
+                .unwrap_or_else(|| alert.event.clone())
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if let Err(e) = notify::send(&title, &body) {
+        eprintln!("{} {}", "Notification failed:".yellow(), e);
+    }
+}
+
+/// Notifies when the coming night looks icy, for running from cron.
+fn notify_ice(
+    report: &model::Report,
+    forecast: Option<&forecast::Forecast>,
+    options: &DisplayOptions,
+) {
+    let advisory = roads::assess(report, forecast);
+    let Some(overnight) = advisory
+        .overnight
+        .filter(|a| a.risk >= roads::IceRisk::Moderate)
+    else {
+        return;
+    };
+    let body = format!(
+        "{}: {} from {}",
+        report.location.name,
+        overnight.reason,
+        options.locale.time(&local_datetime(
+            overnight.at,
+            report.location.utc_offset_seconds
+        ))
+    );
+    let title = format!("{} ice risk on roads tonight", overnight.risk.label());
+    if let Err(e) = notify::send(&title, &body) {
+        eprintln!("{} {}", "Notification failed:".yellow(), e);
+    }
+}
+
+fn read_aloud(
+    report: &model::Report,
+    forecast: Option<&forecast::Forecast>,
+    options: &DisplayOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !options.speak && options.speak_file.is_none() {
+        return Ok(());
+    }
+
+    let text = summary::spoken_summary(report, forecast, options);
+    if let Some(path) = &options.speak_file {
+        speak::write_text(&text, path)?;
+    }
+    if options.speak {
+        speak::speak(&text)?;
+    }
+    Ok(())
+}
+
+async fn explain(
+    field: Option<&str>,
+    city: Option<&str>,
+    options: &DisplayOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(field) = field else {
+        println!("{}", "Available fields:".bold());
+        for entry in glossary::ENTRIES {
+            println!("  {:<18} {}", entry.key.bright_cyan(), entry.title);
+        }
+        return Ok(());
+    };
+
+    let entry = glossary::lookup(field).ok_or_else(|| {
+        format!(
+            "Unknown field '{}'. Run `report explain` to list available fields.",
+            field
+        )
+    })?;
+
+    println!("{}", entry.title.bold());
+    println!("{}", entry.description);
+    println!("{} {}", "Learn more:".dimmed(), entry.link.underline());
+
+    if let Some(city) = city {
+        let api_key = options
+            .api_key()
+            .unwrap_or_else(|| require_api_key(&options.config));
+        let (_, report) =
+            report::get_city_weather(city, &options.weather_client(&api_key)?).await?;
+        if let Some(context) = glossary::context(entry, &report.current, options) {
+            println!(
+                "\n{} {}",
+                format!("In {}:", report.location.name).bright_green(),
+                context
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn format_temperature(celsius: f64, options: &DisplayOptions) -> String {
+    options.quantity(options.units.temperature(celsius))
+}
+
+impl DisplayOptions {
+    /// Formats a converted value in the locale's style, e.g.
+    /// `options.quantity(options.units.speed(ms))`.
+    fn quantity(&self, (value, decimals, unit): units::Quantity) -> String {
+        self.locale.quantity(value, decimals, unit)
+    }
+
+    /// Colours `text` by the severity of `field`'s configured threshold, or
+    /// with `normal` when no threshold is reached. Under `--watch`, values
+    /// that changed since the previous refresh are also shown reversed.
+    fn highlight(
+        &self,
+        current: &model::Conditions,
+        field: &str,
+        text: &str,
+        normal: fn(&str) -> ColoredString,
+    ) -> String {
+        let severity = self.config.thresholds.get(field).and_then(|threshold| {
+            Some(threshold.severity(thresholds::field_value(current, field)?))
+        });
+        let text = match severity {
+            Some(thresholds::Severity::Critical) => text.bright_red().bold(),
+            Some(thresholds::Severity::Warn) => text.yellow().bold(),
+            _ => normal(text),
+        };
+        if self.changed(current, field) {
+            text.reversed().to_string()
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Whether any of `events` is new: under `--watch`, missing from the
+    /// previous refresh, which they replace; otherwise whether there are any.
+    fn any_new(&self, events: Vec<String>) -> bool {
+        let Some(watch) = &self.watch else {
+            return !events.is_empty();
+        };
+        let mut previous = watch.events.lock().unwrap_or_else(|e| e.into_inner());
+        let new = events.iter().any(|event| !previous.contains(event));
+        *previous = events;
+        new
+    }
+
+    /// Whether `field` differs from the previous `--watch` refresh.
+    fn changed(&self, current: &model::Conditions, field: &str) -> bool {
+        let Some(watch) = &self.watch else {
+            return false;
+        };
+        let previous = watch.previous.lock().unwrap_or_else(|e| e.into_inner());
+        previous.as_ref().is_some_and(|previous| {
+            thresholds::field_value(previous, field) != thresholds::field_value(current, field)
+        })
+    }
+
+    /// Whether an observation `age` seconds old is past the configured
+    /// staleness threshold.
+    fn is_stale(&self, age: i64) -> bool {
+        age > self.config.freshness.stale_after.as_secs() as i64
+    }
+
+    fn needs_forecast(&self) -> bool {
+        self.summary || self.speak || self.speak_file.is_some() || self.roads || self.notify_ice
+    }
+
+    /// The date of `datetime` in the locale's format, followed by the same
+    /// day in the configured calendar, if any.
+    fn date(&self, datetime: &DateTime<FixedOffset>) -> String {
+        let date = self.locale.date(datetime);
+        match self.config.calendar.format(datetime.date_naive()) {
+            Some(other) => format!("{} · {}", date, other),
+            None => date,
+        }
+    }
+
+    /// The `--provider` backend, using `api_key` if it needs one.
+    fn weather_provider(&self, api_key: &str) -> Arc<dyn WeatherProvider> {
+        match self.provider {
+            Provider::OpenWeatherMap => Arc::new(provider::OpenWeatherMap::new(api_key)),
+            Provider::OpenMeteo => Arc::new(provider::OpenMeteo),
+        }
+    }
+
+    /// A client of the `--provider` backend, for looking places and
+    /// current conditions up.
+    fn weather_client(&self, api_key: &str) -> reqwest::Result<WeatherClient> {
+        Ok(WeatherClient::with_http(http_client()?, api_key)
+            .with_provider(self.weather_provider(api_key)))
+    }
+
+    /// The OpenWeatherMap API key, or an empty one when the provider
+    /// needs none. Forecast features fail without a real key.
+    fn api_key(&self) -> Option<String> {
+        self.config
+            .api_key()
+            .or_else(|| (self.provider == Provider::OpenMeteo).then(String::new))
+    }
+
+    /// A label icon followed by a space, or nothing in ASCII mode.
+    fn icon(&self, emoji: &'static str) -> String {
+        if self.ascii {
+            String::new()
+        } else {
+            format!("{} ", emoji)
+        }
+    }
+}
+
+fn get_weather_emoji(condition: &str, night: bool) -> &'static str {
+    match condition.to_lowercase().as_str() {
+        "clear" if night => "🌙",
+        "clear" => "☀️",
+        "thunderstorm" => "⛈️",
+        "drizzle" => "🌦️",
+        "rain" => "🌧️",
+        "snow" => "❄️",
+        "mist" | "smoke" | "haze" | "dust" | "fog" | "sand" | "ash" | "squall" => "🌫️",
+        "clouds" => "☁️",
+        "tornado" => "🌪️",
+        _ => "🌤️",
+    }
+}
+
+fn get_weather_ascii(condition: &str, night: bool) -> &'static str {
+    match condition.to_lowercase().as_str() {
+        "clear" if night => "(C)",
+        "clear" => "(*)",
+        "thunderstorm" => "/!\\",
+        "drizzle" => "(',')",
+        "rain" => "(///)",
+        "snow" => "(***)",
+        "mist" | "smoke" | "haze" | "dust" | "fog" | "sand" | "ash" | "squall" => "(===)",
+        "clouds" => "(~~)",
+        "tornado" => "(@)",
+        _ => "(~)",
+    }
+}
+
+fn local_datetime(timestamp: i64, timezone_offset: i32) -> DateTime<FixedOffset> {
+    let datetime = Utc.timestamp_opt(timestamp, 0).unwrap();
+    datetime.with_timezone(&FixedOffset::east_opt(timezone_offset).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clear_nights_show_the_moon() {
+        assert_eq!(get_weather_emoji("Clear", false), "☀️");
+        assert_eq!(get_weather_emoji("Clear", true), "🌙");
+        assert_eq!(get_weather_emoji("Rain", true), "🌧️");
+    }
+}
@@ -0,0 +1,282 @@
+//! Versions of the files the CLI keeps on disk, and the steps that bring
+//! files written by earlier releases up to date.
+//!
+//! - The config file carries a top-level `version`; a file without one is
+//!   version 1. Migrating rewrites it, so comments are lost; the original is
+//!   kept next to it as `config.toml.v<N>.bak`.
+//! - The SQLite observation log records its version in `PRAGMA
+//!   user_version` (0 for databases created before versioning, the same
+//!   layout as version 1). It is copied to `<file>.v<N>.bak` before
+//!   migrating.
+//! - The cache directory has a `version` file. The cache is disposable, so
+//!   a cache no step leads from, including one from a newer release, is
+//!   emptied instead of refused.
+//!
+//! A config or database from a newer release is refused rather than
+//! misread. To change a format, bump its version here and add a step to it.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const CONFIG_VERSION: u32 = 1;
+pub const SQLITE_VERSION: u32 = 1;
+pub const CACHE_VERSION: u32 = 2;
+
+/// Brings data of version `to - 1` to version `to`.
+pub struct Step<T> {
+    pub to: u32,
+    pub apply: fn(&mut T) -> Result<(), String>,
+}
+
+/// Edits of the config table.
+pub const CONFIG_STEPS: &[Step<toml::Table>] = &[];
+/// SQL statements appended to the migration transaction.
+pub const SQLITE_STEPS: &[Step<String>] = &[];
+/// Changes inside the cache directory.
+pub const CACHE_STEPS: &[Step<PathBuf>] = &[Step {
+    to: 2,
+    apply: |dir| drop_responses(dir),
+}];
+
+/// Cached responses were OpenWeatherMap's current weather before version 2,
+/// and are reports since; the old ones are dropped to be fetched again.
+fn drop_responses(dir: &Path) -> Result<(), String> {
+    let responses = dir.join("responses");
+    match fs::remove_dir_all(&responses) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(format!("Cannot remove {}: {}", responses.display(), e))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The steps from version `found` to `current`, in order. Fails for data
+/// newer than `current` or a version no step leads from.
+pub fn pending<'a, T>(
+    what: &str,
+    found: u32,
+    current: u32,
+    steps: &'a [Step<T>],
+) -> Result<Vec<&'a Step<T>>, String> {
+    if found > current {
+        return Err(format!(
+            "{} is format version {}, from a newer release; this one reads up to version {}",
+            what, found, current
+        ));
+    }
+    let pending: Vec<_> = steps.iter().filter(|step| step.to > found).collect();
+    let reachable = pending
+        .iter()
+        .enumerate()
+        .all(|(i, step)| step.to == found + 1 + i as u32);
+    if !reachable || found + pending.len() as u32 != current {
+        return Err(format!(
+            "{} is format version {}, which this release cannot migrate to version {}",
+            what, found, current
+        ));
+    }
+    Ok(pending)
+}
+
+/// Applies `steps` to `data` in order.
+pub fn apply<T>(data: &mut T, steps: &[&Step<T>]) -> Result<(), String> {
+    for step in steps {
+        (step.apply)(data).map_err(|e| format!("Migrating to version {}: {}", step.to, e))?;
+    }
+    Ok(())
+}
+
+/// Copies `path` to `<path>.v<version>.bak`, keeping an existing backup of
+/// that version: it is the one taken before the first attempt.
+pub fn backup(path: &Path, version: u32) -> Result<PathBuf, String> {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".v{}.bak", version));
+    let backup = PathBuf::from(name);
+    if !backup.exists() {
+        fs::copy(path, &backup).map_err(|e| {
+            format!(
+                "Cannot back up {} before migrating it: {}",
+                path.display(),
+                e
+            )
+        })?;
+    }
+    Ok(backup)
+}
+
+/// Migrates the parsed config file at `path` in place, writing the result
+/// back after backing up the original.
+pub fn config(path: &Path, table: &mut toml::Table) -> Result<(), String> {
+    let found = match table.remove("version") {
+        None => 1,
+        Some(toml::Value::Integer(version)) if version >= 1 => version as u32,
+        Some(other) => return Err(format!("Invalid config version {}", other)),
+    };
+    let what = format!("Config {}", path.display());
+    let steps = pending(&what, found, CONFIG_VERSION, CONFIG_STEPS)?;
+    if steps.is_empty() {
+        return Ok(());
+    }
+    let backup = backup(path, found)?;
+    apply(table, &steps)?;
+
+    let mut written = toml::Table::new();
+    written.insert("version".to_string(), (CONFIG_VERSION as i64).into());
+    written.extend(table.clone());
+    let text = toml::to_string_pretty(&written).map_err(|e| e.to_string())?;
+    fs::write(path, text).map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+    eprintln!(
+        "Migrated {} to version {} (original kept as {})",
+        path.display(),
+        CONFIG_VERSION,
+        backup.display()
+    );
+    Ok(())
+}
+
+/// Brings the cache directory `dir` to [`CACHE_VERSION`], emptying it
+/// when there is no way there. Crash reports are kept.
+pub fn cache(dir: &Path) -> Result<(), String> {
+    if !dir.exists() {
+        return Ok(());
+    }
+    let stamp = dir.join("version");
+    let found = match fs::read_to_string(&stamp) {
+        Ok(text) => text.trim().parse().unwrap_or(0),
+        // A cache from before versioning, or none yet: both format 1.
+        Err(_) => 1,
+    };
+    if found == CACHE_VERSION && stamp.exists() {
+        return Ok(());
+    }
+    let mut path = dir.to_path_buf();
+    let migrated = pending("The cache", found, CACHE_VERSION, CACHE_STEPS)
+        .and_then(|steps| apply(&mut path, &steps));
+    if migrated.is_err() {
+        for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+            if entry.file_name() == "crashes" {
+                continue;
+            }
+            let path = entry.path();
+            if path.is_dir() {
+                fs::remove_dir_all(&path).ok();
+            } else {
+                fs::remove_file(&path).ok();
+            }
+        }
+    }
+    fs::write(&stamp, format!("{}\n", CACHE_VERSION))
+        .map_err(|e| format!("Cannot write {}: {}", stamp.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEPS: &[Step<Vec<u32>>] = &[
+        Step {
+            to: 2,
+            apply: |data| {
+                data.push(2);
+                Ok(())
+            },
+        },
+        Step {
+            to: 3,
+            apply: |data| {
+                data.push(3);
+                Ok(())
+            },
+        },
+    ];
+
+    fn versions(steps: &[&Step<Vec<u32>>]) -> Vec<u32> {
+        steps.iter().map(|step| step.to).collect()
+    }
+
+    /// An empty directory of its own for a test.
+    fn scratch(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("wather-migrate-{}-{}", name, std::process::id()));
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn nothing_pending_at_the_current_version() {
+        assert!(pending("Data", 3, 3, STEPS).unwrap().is_empty());
+        assert!(pending("Data", 1, 1, &[] as &[Step<Vec<u32>>])
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn chains_the_steps_from_the_version_found() {
+        let steps = pending("Data", 1, 3, STEPS).unwrap();
+        assert_eq!(versions(&steps), [2, 3]);
+        let mut data = Vec::new();
+        apply(&mut data, &steps).unwrap();
+        assert_eq!(data, [2, 3]);
+        assert_eq!(versions(&pending("Data", 2, 3, STEPS).unwrap()), [3]);
+    }
+
+    #[test]
+    fn refuses_a_gap_in_the_chain() {
+        let gapped = STEPS.iter().map(|step| Step {
+            to: step.to * 2,
+            apply: step.apply,
+        });
+        let gapped: Vec<_> = gapped.collect();
+        // Steps to 4 and 6: nothing leads from 1 to 2, or 4 to 5.
+        assert!(pending("Data", 1, 6, &gapped).is_err());
+        assert!(pending("Data", 3, 6, &gapped).is_err());
+        // Nor is there a step to the current version.
+        assert!(pending("Data", 1, 4, STEPS).is_err());
+    }
+
+    #[test]
+    fn refuses_a_newer_version() {
+        let error = pending("Config", 4, 3, STEPS).err().unwrap();
+        assert!(error.contains("newer release"), "{}", error);
+    }
+
+    #[test]
+    fn empties_an_unreachable_cache_but_keeps_crashes() {
+        let dir = scratch("unreachable");
+        fs::create_dir_all(dir.join("responses/open-meteo")).unwrap();
+        fs::write(dir.join("responses/open-meteo/berlin.json"), "{}").unwrap();
+        fs::write(dir.join("geocode.json"), "{}").unwrap();
+        fs::create_dir_all(dir.join("crashes")).unwrap();
+        fs::write(dir.join("crashes/crash.toml"), "").unwrap();
+        fs::write(dir.join("version"), "9\n").unwrap();
+
+        cache(&dir).unwrap();
+        assert!(!dir.join("responses").exists());
+        assert!(!dir.join("geocode.json").exists());
+        assert!(dir.join("crashes/crash.toml").exists());
+        assert_eq!(
+            fs::read_to_string(dir.join("version")).unwrap(),
+            format!("{}\n", CACHE_VERSION)
+        );
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn migrates_a_cache_from_before_versioning() {
+        let dir = scratch("unversioned");
+        fs::create_dir_all(dir.join("responses")).unwrap();
+        fs::write(dir.join("responses/berlin.json"), "{}").unwrap();
+        fs::write(dir.join("geocode.json"), "{}").unwrap();
+
+        cache(&dir).unwrap();
+        // Version 2 drops the responses only.
+        assert!(!dir.join("responses").exists());
+        assert!(dir.join("geocode.json").exists());
+        assert_eq!(
+            fs::read_to_string(dir.join("version")).unwrap(),
+            format!("{}\n", CACHE_VERSION)
+        );
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,169 @@
+//! `weather mountain`: the forecast at the summit, halfway up and at the
+//! base of a peak, with the freezing level. Unless given, the summit is
+//! the terrain elevation at the peak's coordinates and the base the lowest
+//! terrain within a few kilometres of it.
+
+use chrono::NaiveDate;
+use serde::Serialize;
+use weather_providers::mountain::Band;
+use weather_providers::openmeteo;
+
+/// The most days the forecast covers.
+pub const MAX_DAYS: u32 = 16;
+
+/// Distances from the peak, in metres, at which the base is looked for,
+/// each in the eight principal directions.
+const BASE_RADII_M: [f64; 3] = [2500.0, 5000.0, 7500.0];
+
+/// Metres in a degree of latitude.
+const DEGREE_M: f64 = 111_320.0;
+
+/// Wind, in m/s, from which walking on an exposed ridge gets hard.
+pub const STRONG_WIND_MS: f64 = 15.0;
+
+/// The peak followed by the points around it the base is taken from, as
+/// (lat, lon).
+pub fn terrain_points(lat: f64, lon: f64) -> Vec<(f64, f64)> {
+    let mut points = vec![(lat, lon)];
+    for radius in BASE_RADII_M {
+        for step in 0..8 {
+            let bearing = (step as f64 * 45.0).to_radians();
+            points.push((
+                lat + radius * bearing.cos() / DEGREE_M,
+                lon + radius * bearing.sin() / (DEGREE_M * lat.to_radians().cos()),
+            ));
+        }
+    }
+    points
+}
+
+/// The elevations of the summit, the middle and the base, in metres, from
+/// the terrain at [`terrain_points`] where `summit` or `base` is not given.
+pub fn levels(terrain: &[f64], summit: Option<f64>, base: Option<f64>) -> Result<[f64; 3], String> {
+    let summit = summit
+        .or_else(|| terrain.first().copied())
+        .ok_or("No terrain elevation for the summit; give --summit")?;
+    let base = base
+        .or_else(|| terrain.iter().skip(1).copied().reduce(f64::min))
+        .ok_or("No terrain elevation around the summit; give --base")?;
+    if base >= summit {
+        return Err(format!(
+            "The base, at {:.0} m, is not below the summit, at {:.0} m; give --summit and --base",
+            base, summit
+        ));
+    }
+    Ok([summit, (summit + base) / 2.0, base])
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Summit,
+    Mid,
+    Base,
+}
+
+impl Level {
+    /// The levels in the order of [`levels`].
+    pub const ALL: [Level; 3] = [Level::Summit, Level::Mid, Level::Base];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Level::Summit => "Summit",
+            Level::Mid => "Mid",
+            Level::Base => "Base",
+        }
+    }
+}
+
+/// One local day of the forecast, top to bottom.
+#[derive(Debug, Clone, Serialize)]
+pub struct Day {
+    pub date: NaiveDate,
+    /// The range of the freezing level over the day, in metres.
+    pub freezing_level_min_m: Option<f64>,
+    pub freezing_level_max_m: Option<f64>,
+    pub levels: Vec<LevelDay>,
+}
+
+/// The day at one level. Values are missing where the models have none.
+#[derive(Debug, Clone, Serialize)]
+pub struct LevelDay {
+    pub level: Level,
+    pub elevation_m: f64,
+    pub temperature_min_c: Option<f64>,
+    pub temperature_max_c: Option<f64>,
+    /// Rain and snow together, as water.
+    pub precipitation_mm: Option<f64>,
+    pub snowfall_cm: Option<f64>,
+    pub wind_speed_max_ms: Option<f64>,
+    pub wind_gusts_max_ms: Option<f64>,
+    /// Where the wind mostly blows from, in degrees.
+    pub wind_from_deg: Option<f64>,
+    pub condition: Option<&'static str>,
+}
+
+impl LevelDay {
+    /// Whether most of the precipitation falls as snow: 7 cm of snow is
+    /// about 10 mm of water.
+    pub fn mostly_snow(&self) -> bool {
+        match (self.snowfall_cm, self.precipitation_mm) {
+            (Some(snow), Some(total)) if total > 0.0 => snow * 10.0 / 7.0 >= total / 2.0,
+            _ => false,
+        }
+    }
+}
+
+/// The forecast days of `bands`, one band per entry of [`Level::ALL`].
+pub fn days(bands: &[Band]) -> Vec<Day> {
+    let Some(top) = bands.first() else {
+        return Vec::new();
+    };
+    let offset = top.utc_offset_seconds as i64;
+    top.daily
+        .time
+        .iter()
+        .enumerate()
+        .filter_map(|(i, &start)| {
+            let date = chrono::DateTime::from_timestamp(start + offset, 0)?.date_naive();
+            let freezing = top
+                .hourly
+                .time
+                .iter()
+                .zip(&top.hourly.freezing_level_height)
+                .filter(|(time, _)| (start..start + 86_400).contains(*time))
+                .filter_map(|(_, height)| *height);
+            let levels = Level::ALL
+                .iter()
+                .zip(bands)
+                .map(|(&level, band)| {
+                    let daily = &band.daily;
+                    let at = |series: &Vec<Option<f64>>| series.get(i).copied().flatten();
+                    LevelDay {
+                        level,
+                        elevation_m: band.elevation,
+                        temperature_min_c: at(&daily.temperature_2m_min),
+                        temperature_max_c: at(&daily.temperature_2m_max),
+                        precipitation_mm: at(&daily.precipitation_sum),
+                        snowfall_cm: at(&daily.snowfall_sum),
+                        wind_speed_max_ms: at(&daily.wind_speed_10m_max),
+                        wind_gusts_max_ms: at(&daily.wind_gusts_10m_max),
+                        wind_from_deg: at(&daily.wind_direction_10m_dominant),
+                        condition: daily
+                            .weather_code
+                            .get(i)
+                            .copied()
+                            .flatten()
+                            .map(openmeteo::describe),
+                    }
+                })
+                .collect();
+            Some(Day {
+                date,
+                freezing_level_min_m: freezing.clone().reduce(f64::min),
+                freezing_level_max_m: freezing.reduce(f64::max),
+                levels,
+            })
+        })
+        .collect()
+}
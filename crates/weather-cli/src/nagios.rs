@@ -0,0 +1,126 @@
+//! `weather check`: a Nagios/Icinga plugin reporting threshold breaches with
+//! the standard exit codes and performance data.
+
+use crate::thresholds::{self, Direction, Severity, Threshold, Thresholds};
+use weather_core::model::Conditions;
+
+/// Plugin states, whose discriminants are the exit codes monitoring systems
+/// expect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok = 0,
+    Warning = 1,
+    Critical = 2,
+    Unknown = 3,
+}
+
+impl Status {
+    pub fn label(self) -> &'static str {
+        match self {
+            Status::Ok => "OK",
+            Status::Warning => "WARNING",
+            Status::Critical => "CRITICAL",
+            Status::Unknown => "UNKNOWN",
+        }
+    }
+}
+
+/// The plugin output line for `current`, and its status.
+pub fn check(place: &str, current: &Conditions, thresholds: &Thresholds) -> (Status, String) {
+    let breaches = thresholds::evaluate(thresholds, current);
+    let status = match breaches.first().map(|b| b.severity) {
+        Some(Severity::Critical) => Status::Critical,
+        Some(Severity::Warn) => Status::Warning,
+        _ => Status::Ok,
+    };
+    let summary = if breaches.is_empty() {
+        format!(
+            "{:.1} °C, {}",
+            current.temperature_c, current.condition.description
+        )
+    } else {
+        breaches
+            .iter()
+            .map(|b| {
+                let level = match b.severity {
+                    Severity::Critical => "critical",
+                    _ => "warn",
+                };
+                format!("{} {:.1} ({} at {})", b.field, b.value, level, b.limit)
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let line = format!(
+        "WEATHER {} - {}: {} | {}",
+        status.label(),
+        place,
+        summary,
+        perfdata(current, thresholds)
+    );
+    (status, line)
+}
+
+/// Performance data for every core field that has a value, plus any checked
+/// metric, with the checked limits as Nagios ranges.
+fn perfdata(current: &Conditions, thresholds: &Thresholds) -> String {
+    let mut fields: Vec<&str> = thresholds::FIELDS.to_vec();
+    fields.extend(
+        thresholds
+            .keys()
+            .map(String::as_str)
+            .filter(|field| !thresholds::FIELDS.contains(field)),
+    );
+    fields
+        .into_iter()
+        .filter_map(|field| {
+            let value = thresholds::field_value(current, field)?;
+            let threshold = thresholds.get(field);
+            let range = |limit: fn(&Threshold) -> Option<f64>| {
+                threshold.and_then(|t| Some(nagios_range(t.direction, limit(t)?)))
+            };
+            let unit = match field {
+                "humidity" | "cloudiness" => "%",
+                _ => "",
+            };
+            Some(format!(
+                "{}={}{};{};{};;",
+                field,
+                (value * 100.0).round() / 100.0,
+                unit,
+                range(|t| t.warn).unwrap_or_default(),
+                range(|t| t.critical).unwrap_or_default()
+            ))
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The range of values that alerts, inclusive like the thresholds
+/// themselves: `@35:` alerts at 35 and above, `@~:5` at 5 and below.
+/// Without the `@`, a range is the values that do *not* alert, and the
+/// limit itself would pass.
+fn nagios_range(direction: Direction, limit: f64) -> String {
+    match direction {
+        Direction::Above => format!("@{}:", limit),
+        Direction::Below => format!("@~:{}", limit),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges_alert_at_the_limit_like_the_thresholds() {
+        assert_eq!(nagios_range(Direction::Above, 35.0), "@35:");
+        assert_eq!(nagios_range(Direction::Below, 5.0), "@~:5");
+        let above = Threshold {
+            direction: Direction::Above,
+            warn: Some(35.0),
+            critical: None,
+        };
+        assert_eq!(above.severity(35.0), Severity::Warn);
+        assert_eq!(above.severity(34.9), Severity::Ok);
+    }
+}
@@ -0,0 +1,64 @@
+//! Desktop notifications through the platform's notification facilities,
+//! driven by their command-line tools so that Termux is covered too.
+
+use crate::location;
+use crate::speak::find_executable;
+use std::process::Command;
+
+/// Shows a toast through the WinRT API. Title and body arrive in environment
+/// variables so they never need quoting inside the script.
+const WINDOWS_TOAST: &str = "\
+[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] | Out-Null
+$template = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02)
+$text = $template.GetElementsByTagName('text')
+$text.Item(0).AppendChild($template.CreateTextNode($env:WEATHER_NOTIFY_TITLE)) | Out-Null
+$text.Item(1).AppendChild($template.CreateTextNode($env:WEATHER_NOTIFY_BODY)) | Out-Null
+$toast = [Windows.UI.Notifications.ToastNotification]::new($template)
+[Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\\WindowsPowerShell\\v1.0\\powershell.exe').Show($toast)
+";
+
+/// Shows a desktop notification: a toast on Windows, Notification Center on
+/// macOS, termux-notification on Android and `notify-send` (libnotify)
+/// elsewhere.
+pub fn send(title: &str, body: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("powershell");
+        command
+            .args(["-NoProfile", "-NonInteractive", "-Command", WINDOWS_TOAST])
+            .env("WEATHER_NOTIFY_TITLE", title)
+            .env("WEATHER_NOTIFY_BODY", body);
+        command
+    } else if cfg!(target_os = "macos") {
+        // Passed as script arguments rather than spliced into AppleScript.
+        let mut command = Command::new("osascript");
+        command.args([
+            "-e",
+            "on run argv",
+            "-e",
+            "display notification (item 2 of argv) with title (item 1 of argv)",
+            "-e",
+            "end run",
+            title,
+            body,
+        ]);
+        command
+    } else if location::is_termux() {
+        let mut command = Command::new("termux-notification");
+        command.args(["--title", title, "--content", body]);
+        command
+    } else {
+        let mut command = Command::new("notify-send");
+        command.args(["--app-name=weather", title, body]);
+        command
+    };
+
+    let program = command.get_program().to_string_lossy().into_owned();
+    if find_executable(&program).is_none() {
+        return Err(format!("Cannot send notifications: {} not found", program).into());
+    }
+    let status = command.status()?;
+    if !status.success() {
+        return Err(format!("{} exited with {}", program, status).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,179 @@
+//! `weather forecast`: the 3-hourly forecast rolled up into days, and how
+//! those days changed since the previous run.
+//!
+//! Each run keeps its rollup in the cache directory, so `--diff` compares
+//! against whatever was fetched last for the place. How much a forecast
+//! moves between runs is itself worth knowing.
+
+use crate::{cache, platform};
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use weather_providers::forecast::Forecast;
+use weather_providers::openweathermap;
+
+/// Changes smaller than these are noise, not news.
+const POP_CHANGE_PERCENT: i32 = 20;
+const TEMPERATURE_CHANGE_C: f64 = 2.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Day {
+    /// Local date at the place.
+    pub date: NaiveDate,
+    pub min_c: f64,
+    pub max_c: f64,
+    /// Highest probability of precipitation of the day's slots.
+    pub pop_percent: i32,
+    /// Rain and snow over the day's slots in mm (absent from rollups kept
+    /// before it was recorded).
+    #[serde(default)]
+    pub precipitation_mm: f64,
+    /// The condition kind of most of the day's slots.
+    pub condition: String,
+}
+
+/// A rollup as persisted between runs.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub fetched_at: DateTime<Utc>,
+    pub days: Vec<Day>,
+}
+
+/// The forecast grouped into local days, earliest first.
+pub fn days(forecast: &Forecast) -> Vec<Day> {
+    let offset = FixedOffset::east_opt(forecast.city.timezone)
+        .unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
+    let mut days: Vec<(Day, Vec<&'static str>)> = Vec::new();
+    for entry in &forecast.list {
+        let Some(time) = offset.timestamp_opt(entry.dt, 0).single() else {
+            continue;
+        };
+        let kind =
+            openweathermap::condition_kind(entry.weather.first().map_or("", |w| w.main.as_str()));
+        let pop = (entry.pop * 100.0).round() as i32;
+        let precipitation = entry.rain.as_ref().map_or(0.0, |r| r.three_hours)
+            + entry.snow.as_ref().map_or(0.0, |s| s.three_hours);
+        match days.last_mut() {
+            Some((day, kinds)) if day.date == time.date_naive() => {
+                day.min_c = day.min_c.min(entry.main.temp_min);
+                day.max_c = day.max_c.max(entry.main.temp_max);
+                day.pop_percent = day.pop_percent.max(pop);
+                day.precipitation_mm += precipitation;
+                kinds.push(kind);
+            }
+            _ => days.push((
+                Day {
+                    date: time.date_naive(),
+                    min_c: entry.main.temp_min,
+                    max_c: entry.main.temp_max,
+                    pop_percent: pop,
+                    precipitation_mm: precipitation,
+                    condition: String::new(),
+                },
+                vec![kind],
+            )),
+        }
+    }
+    days.into_iter()
+        .map(|(mut day, kinds)| {
+            day.condition = most_common(&kinds).to_string();
+            day
+        })
+        .collect()
+}
+
+/// The most frequent of `kinds`; ties go to the earliest.
+fn most_common<'a>(kinds: &[&'a str]) -> &'a str {
+    let count = |kind: &str| kinds.iter().filter(|k| **k == kind).count();
+    let mut best = kinds.first().copied().unwrap_or("other");
+    for kind in kinds {
+        if count(kind) > count(best) {
+            best = kind;
+        }
+    }
+    best
+}
+
+#[derive(Debug, Serialize)]
+pub struct DayChange {
+    pub date: NaiveDate,
+    #[serde(flatten)]
+    pub change: Change,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "field", rename_all = "snake_case")]
+pub enum Change {
+    Rain { now: i32, was: i32 },
+    High { now: f64, was: f64 },
+    Low { now: f64, was: f64 },
+    Condition { now: String, was: String },
+}
+
+/// Notable changes for each day both rollups cover, from `today` on.
+pub fn diff(previous: &[Day], current: &[Day], today: NaiveDate) -> Vec<DayChange> {
+    let mut changes = Vec::new();
+    for day in current.iter().filter(|day| day.date >= today) {
+        let Some(before) = previous.iter().find(|d| d.date == day.date) else {
+            continue;
+        };
+        if day.condition != before.condition {
+            changes.push(DayChange {
+                date: day.date,
+                change: Change::Condition {
+                    now: day.condition.clone(),
+                    was: before.condition.clone(),
+                },
+            });
+        }
+        if (day.pop_percent - before.pop_percent).abs() >= POP_CHANGE_PERCENT {
+            changes.push(DayChange {
+                date: day.date,
+                change: Change::Rain {
+                    now: day.pop_percent,
+                    was: before.pop_percent,
+                },
+            });
+        }
+        if (day.max_c - before.max_c).abs() >= TEMPERATURE_CHANGE_C {
+            changes.push(DayChange {
+                date: day.date,
+                change: Change::High {
+                    now: day.max_c,
+                    was: before.max_c,
+                },
+            });
+        }
+        if (day.min_c - before.min_c).abs() >= TEMPERATURE_CHANGE_C {
+            changes.push(DayChange {
+                date: day.date,
+                change: Change::Low {
+                    now: day.min_c,
+                    was: before.min_c,
+                },
+            });
+        }
+    }
+    changes
+}
+
+/// Where the last rollup for `place` is kept.
+pub fn snapshot_path(place: &str) -> Option<PathBuf> {
+    platform::dir(platform::Dir::Cache).map(|dir| {
+        dir.join("forecasts")
+            .join(format!("{}.json", cache::slug(place)))
+    })
+}
+
+pub fn read_snapshot(path: &Path) -> Option<Snapshot> {
+    let bytes = std::fs::read(path).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+pub fn write_snapshot(path: &Path, snapshot: &Snapshot) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, serde_json::to_vec(snapshot)?)?;
+    Ok(())
+}
@@ -0,0 +1,99 @@
+//! Platform-specific locations and terminal capabilities.
+
+use std::env;
+use std::path::PathBuf;
+
+const APP_DIR: &str = "weather-cli";
+
+/// Kinds of per-user directories the application stores files in.
+#[derive(Debug, Clone, Copy)]
+pub enum Dir {
+    /// Settings the user edits: `%APPDATA%`, `~/Library/Application Support`
+    /// or `$XDG_CONFIG_HOME`.
+    Config,
+    /// Disposable data: `%LOCALAPPDATA%`, `~/Library/Caches` or
+    /// `$XDG_CACHE_HOME`.
+    Cache,
+    /// Data worth keeping, such as history: `%APPDATA%`,
+    /// `~/Library/Application Support` or `$XDG_DATA_HOME`.
+    Data,
+}
+
+/// The application's directory of the given kind, or `None` when the home
+/// directory can't be determined.
+pub fn dir(kind: Dir) -> Option<PathBuf> {
+    base_dir(kind).map(|base| base.join(APP_DIR))
+}
+
+#[cfg(windows)]
+fn base_dir(kind: Dir) -> Option<PathBuf> {
+    let var = match kind {
+        Dir::Config | Dir::Data => "APPDATA",
+        Dir::Cache => "LOCALAPPDATA",
+    };
+    env::var_os(var).map(PathBuf::from)
+}
+
+#[cfg(target_os = "macos")]
+fn base_dir(kind: Dir) -> Option<PathBuf> {
+    let home = PathBuf::from(env::var_os("HOME")?);
+    Some(match kind {
+        Dir::Config | Dir::Data => home.join("Library/Application Support"),
+        Dir::Cache => home.join("Library/Caches"),
+    })
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn base_dir(kind: Dir) -> Option<PathBuf> {
+    // Termux exposes the app's own cache directory, which Android may clear
+    // when storage runs low: exactly the semantics a cache wants.
+    if let (Dir::Cache, true) = (kind, crate::location::is_termux()) {
+        if let Some(cache) = env::var_os("PREFIX")
+            .map(PathBuf::from)
+            .and_then(|prefix| Some(prefix.parent()?.parent()?.join("cache")))
+        {
+            return Some(cache);
+        }
+    }
+
+    let (var, fallback) = match kind {
+        Dir::Config => ("XDG_CONFIG_HOME", ".config"),
+        Dir::Cache => ("XDG_CACHE_HOME", ".cache"),
+        Dir::Data => ("XDG_DATA_HOME", ".local/share"),
+    };
+    // Plain Android shells (e.g. `adb shell`) have a read-only `/` as HOME.
+    let home = env::var_os("HOME")
+        .filter(|home| !(cfg!(target_os = "android") && home == "/"))
+        .map(PathBuf::from)
+        .or_else(|| cfg!(target_os = "android").then(|| PathBuf::from("/data/local/tmp")));
+    env::var_os(var)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home.map(|home| home.join(fallback)))
+}
+
+/// Turns on ANSI escape processing in legacy Windows consoles (conhost);
+/// a no-op elsewhere.
+pub fn enable_ansi() {
+    #[cfg(windows)]
+    {
+        if colored::control::set_virtual_terminal(true).is_err() {
+            colored::control::set_override(false);
+        }
+    }
+}
+
+/// Best guess whether the terminal can draw emoji. Legacy Windows consoles and
+/// the Linux virtual console can't; Windows Terminal, VS Code and most other
+/// emulators can. `WEATHER_ASCII=1` forces the ASCII fallback.
+pub fn supports_emoji() -> bool {
+    if env::var_os("WEATHER_ASCII").is_some_and(|v| v != "0") {
+        return false;
+    }
+    if cfg!(windows) {
+        return env::var_os("WT_SESSION").is_some()
+            || env::var("TERM_PROGRAM").is_ok_and(|p| p == "vscode")
+            || env::var_os("ConEmuANSI").is_some();
+    }
+    !env::var("TERM").is_ok_and(|term| term == "linux" || term == "dumb")
+}
@@ -0,0 +1,65 @@
+use weather_core::model::Conditions;
+
+/// Turns the current conditions into everyday advice.
+pub struct Recommendation {
+    /// How it feels outside, e.g. "Cold and rainy".
+    pub feel: String,
+    /// What to do about it, e.g. "wear a raincoat!".
+    pub advice: &'static str,
+}
+
+impl Recommendation {
+    /// One friendly sentence, e.g. "Cold and rainy — wear a raincoat!".
+    pub fn sentence(&self) -> String {
+        format!("{} — {}", self.feel, self.advice)
+    }
+}
+
+pub fn recommend(current: &Conditions) -> Recommendation {
+    let condition = current.condition.kind.as_str();
+    let feels_like = current.feels_like_c;
+    let windy = current.wind_gust_ms.unwrap_or(current.wind_speed_ms) >= 12.0;
+
+    let temperature_word = match feels_like {
+        t if t < 0.0 => "Freezing",
+        t if t < 10.0 => "Cold",
+        t if t < 18.0 => "Cool",
+        t if t < 25.0 => "Mild",
+        t if t < 30.0 => "Warm",
+        _ => "Hot",
+    };
+
+    let condition_word = match condition {
+        "thunderstorm" => Some("stormy"),
+        "drizzle" => Some("drizzly"),
+        "rain" => Some("rainy"),
+        "snow" => Some("snowy"),
+        "clear" => Some("sunny"),
+        "clouds" => Some("cloudy"),
+        "fog" => Some("foggy"),
+        _ => None,
+    }
+    .or(if windy { Some("windy") } else { None });
+
+    let feel = match condition_word {
+        Some(word) => format!("{} and {}", temperature_word, word),
+        None => temperature_word.to_string(),
+    };
+
+    let advice = match condition {
+        "thunderstorm" => "stay indoors if you can!",
+        "rain" => "wear a raincoat!",
+        "drizzle" => "take an umbrella!",
+        "snow" => "wear boots and mittens!",
+        "fog" => "take care crossing the road!",
+        _ if feels_like < 0.0 => "bundle up in a warm coat, hat and gloves!",
+        _ if feels_like < 10.0 => "wear a warm coat!",
+        _ if feels_like >= 30.0 => "drink lots of water and wear a hat!",
+        _ if windy => "hold on to your hat!",
+        _ if feels_like < 18.0 => "bring a jacket!",
+        "clear" if feels_like >= 25.0 => "don't forget sunscreen!",
+        _ => "enjoy your day!",
+    };
+
+    Recommendation { feel, advice }
+}
@@ -0,0 +1,298 @@
+use colored::Colorize;
+use unicode_width::UnicodeWidthStr;
+
+const MIN_CARD_WIDTH: usize = 39;
+
+/// A bordered block of lines whose right edge stays aligned regardless of
+/// double-width CJK characters, emoji or right-to-left text in the content.
+pub struct Card {
+    /// `None` for a rule across the card.
+    lines: Vec<Option<String>>,
+}
+
+impl Card {
+    pub fn new() -> Self {
+        Card { lines: Vec::new() }
+    }
+
+    pub fn line(&mut self, text: impl Into<String>) {
+        self.lines.push(Some(text.into()));
+    }
+
+    /// A horizontal rule between the lines before and after.
+    pub fn rule(&mut self) {
+        self.lines.push(None);
+    }
+
+    pub fn print(&self) {
+        for row in self.rows() {
+            println!("{}", row);
+        }
+    }
+
+    /// The card's rows, borders included, all of the same width.
+    pub fn rows(&self) -> Vec<String> {
+        let width = self
+            .lines
+            .iter()
+            .flatten()
+            .map(|line| display_width(line))
+            .max()
+            .unwrap_or(0)
+            .max(MIN_CARD_WIDTH);
+        let border = "═".repeat(width + 2);
+
+        let mut rows = vec![format!("╔{}╗", border).bright_yellow().to_string()];
+        for line in &self.lines {
+            let Some(line) = line else {
+                rows.push(
+                    format!("╟{}╢", "─".repeat(width + 2))
+                        .bright_yellow()
+                        .to_string(),
+                );
+                continue;
+            };
+            rows.push(format!(
+                "{} {}{} {}",
+                "║".bright_yellow(),
+                line,
+                " ".repeat(width - display_width(line)),
+                "║".bright_yellow()
+            ));
+        }
+        rows.push(format!("╚{}╝", border).bright_yellow().to_string());
+        rows
+    }
+}
+
+/// Lays `rows` out in left-aligned columns two spaces apart, whatever the
+/// colours and character widths of the cells.
+pub fn columns(rows: &[Vec<String>]) -> Vec<String> {
+    let mut widths: Vec<usize> = Vec::new();
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            let width = display_width(cell);
+            match widths.get_mut(i) {
+                Some(max) => *max = (*max).max(width),
+                None => widths.push(width),
+            }
+        }
+    }
+    rows.iter()
+        .map(|row| {
+            let mut line = String::new();
+            for (i, cell) in row.iter().enumerate() {
+                if i > 0 {
+                    line.push_str("  ");
+                }
+                line.push_str(cell);
+                if i + 1 < row.len() {
+                    line.push_str(&" ".repeat(widths[i] - display_width(cell)));
+                }
+            }
+            line
+        })
+        .collect()
+}
+
+/// Breaks `text` into lines of at most `width` columns at spaces, keeping
+/// its paragraphs apart with an empty line. A word wider than `width` gets
+/// a line of its own.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.split("\n\n") {
+        let words: Vec<&str> = paragraph.split_whitespace().collect();
+        if words.is_empty() {
+            continue;
+        }
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        let mut line = String::new();
+        for word in words {
+            if !line.is_empty() && display_width(&line) + 1 + display_width(word) > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Number of terminal columns `text` occupies, ignoring ANSI color escapes.
+pub fn display_width(text: &str) -> usize {
+    strip_ansi(text).width()
+}
+
+/// Wraps text containing right-to-left script in Unicode directional isolates
+/// so that bidi-aware terminals don't reorder the surrounding label or border.
+pub fn isolate(text: &str) -> String {
+    if text.chars().any(is_rtl) {
+        format!("\u{2068}{}\u{2069}", text)
+    } else {
+        text.to_string()
+    }
+}
+
+fn is_rtl(c: char) -> bool {
+    matches!(c as u32,
+        0x0590..=0x08FF | 0xFB1D..=0xFDFF | 0xFE70..=0xFEFF | 0x10800..=0x10FFF | 0x1E800..=0x1EFFF)
+}
+
+fn strip_ansi(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            // Skip a CSI sequence: ESC '[' parameters final-byte
+            if chars.next() == Some('[') {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Renders digits, sign and degree symbol in a five-line block font, e.g. for
+/// a large temperature readout. Unsupported characters are skipped.
+pub fn big_text(text: &str) -> Vec<String> {
+    let mut rows = vec![String::new(); 5];
+    for c in text.chars() {
+        let Some(glyph) = big_glyph(c) else {
+            continue;
+        };
+        for (row, line) in rows.iter_mut().zip(glyph.iter()) {
+            row.push_str(line);
+            row.push(' ');
+        }
+    }
+    rows
+}
+
+fn big_glyph(c: char) -> Option<[&'static str; 5]> {
+    let glyph = match c {
+        '0' => ["███", "█ █", "█ █", "█ █", "███"],
+        '1' => [" █ ", "██ ", " █ ", " █ ", "███"],
+        '2' => ["███", "  █", "███", "█  ", "███"],
+        '3' => ["███", "  █", "███", "  █", "███"],
+        '4' => ["█ █", "█ █", "███", "  █", "  █"],
+        '5' => ["███", "█  ", "███", "  █", "███"],
+        '6' => ["███", "█  ", "███", "█ █", "███"],
+        '7' => ["███", "  █", "  █", "  █", "  █"],
+        '8' => ["███", "█ █", "███", "█ █", "███"],
+        '9' => ["███", "█ █", "███", "  █", "███"],
+        '-' => ["   ", "   ", "███", "   ", "   "],
+        '°' => ["██", "██", "  ", "  ", "  "],
+        'C' => ["███", "█  ", "█  ", "█  ", "███"],
+        'F' => ["███", "█  ", "██ ", "█  ", "█  "],
+        'K' => ["█ █", "██ ", "█  ", "██ ", "█ █"],
+        _ => return None,
+    };
+    Some(glyph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_width_counts_double_width_characters() {
+        assert_eq!(display_width("Berlin"), 6);
+        assert_eq!(display_width("東京"), 4);
+        assert_eq!(display_width("서울"), 4);
+        assert_eq!(display_width("☀️"), 2);
+        assert_eq!(display_width("🌧️"), 2);
+        assert_eq!(display_width("🌡"), 1);
+    }
+
+    #[test]
+    fn display_width_ignores_colour_escapes() {
+        assert_eq!(display_width("\u{1b}[1;33m東京\u{1b}[0m"), 4);
+        assert_eq!(display_width("\u{1b}[38;5;208m21°C\u{1b}[0m"), 4);
+    }
+
+    #[test]
+    fn isolate_wraps_right_to_left_text_only() {
+        assert_eq!(isolate("القاهرة"), "\u{2068}القاهرة\u{2069}");
+        assert_eq!(isolate("ירושלים"), "\u{2068}ירושלים\u{2069}");
+        assert_eq!(isolate("Berlin"), "Berlin");
+        assert_eq!(isolate("東京"), "東京");
+    }
+
+    #[test]
+    fn isolates_take_no_columns() {
+        assert_eq!(display_width(&isolate("תל אביב")), display_width("תל אביב"));
+    }
+
+    #[test]
+    fn card_rows_align_with_wide_and_right_to_left_names() {
+        let mut card = Card::new();
+        card.line("Weather in 東京都");
+        card.line(format!("Weather in {}", isolate("القاهرة")));
+        card.rule();
+        card.line("🌧️ Rain, 12°C");
+        card.line("x".repeat(60));
+        let rows = card.rows();
+        let widths: Vec<usize> = rows.iter().map(|row| display_width(row)).collect();
+        assert_eq!(rows.len(), 7);
+        assert!(widths.iter().all(|&width| width == 64), "{:?}", widths);
+    }
+
+    #[test]
+    fn card_is_at_least_the_minimum_width() {
+        let mut card = Card::new();
+        card.line("東京");
+        assert!(card
+            .rows()
+            .iter()
+            .all(|row| display_width(row) == MIN_CARD_WIDTH + 4));
+    }
+
+    #[test]
+    fn columns_align_across_scripts() {
+        let rows = vec![
+            vec!["東京".to_string(), "21°C".to_string(), "clear".to_string()],
+            vec!["Berlin".to_string(), "9°C".to_string(), "rain".to_string()],
+            vec![isolate("القاهرة"), "30°C".to_string(), "sunny".to_string()],
+        ];
+        let lines = columns(&rows);
+        let starts: Vec<usize> = lines
+            .iter()
+            .zip(&rows)
+            .map(|(line, row)| {
+                let prefix = line.rfind(row[2].as_str()).unwrap();
+                display_width(&line[..prefix])
+            })
+            .collect();
+        assert!(
+            starts.iter().all(|&start| start == starts[0]),
+            "{:?}",
+            starts
+        );
+    }
+
+    #[test]
+    fn wrap_keeps_lines_within_width() {
+        let lines = wrap(
+            "東京 大阪 名古屋 札幌 福岡 神戸 京都\n\nsecond paragraph",
+            10,
+        );
+        assert!(
+            lines.iter().all(|line| display_width(line) <= 10),
+            "{:?}",
+            lines
+        );
+        assert!(lines.contains(&String::new()));
+        assert_eq!(lines.last().map(String::as_str), Some("paragraph"));
+    }
+}
@@ -0,0 +1,315 @@
+//! The report for a query: looked up through a [`WeatherClient`], so that
+//! its middleware, cancellation and retries apply, reused from the cache
+//! while fresh, then completed with what the options add to it.
+
+use crate::errors::ResultExt;
+use crate::{cache, calibration, clock, hyperlocal, location, station, tokens};
+use crate::{http_client, numbered_places, record, DisplayOptions, Provider, Query};
+use colored::Colorize;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::Duration;
+use weather_core::{atmosphere, metrics, model};
+use weather_providers::client::{Cancel, WeatherClient};
+use weather_providers::geocode::{self, Place};
+use weather_providers::{alerts, pws, WeatherError};
+
+/// Longest a lookup may take, retries included, before it fails.
+const LOOKUP_DEADLINE: Duration = Duration::from_secs(30);
+
+/// The derived metrics `--performance` and `--ballistics` show, added to
+/// the report's when either is given.
+const AIR_METRICS: [&str; 3] = ["air_density", "pressure_altitude", "density_altitude"];
+
+/// When a lookup starting now gives up.
+pub fn cancel() -> Cancel {
+    Cancel::default().with_timeout(LOOKUP_DEADLINE)
+}
+
+pub async fn get_weather(
+    query: &Query,
+    client: &WeatherClient,
+) -> Result<(Place, model::Report), Box<dyn std::error::Error>> {
+    let place = resolve_place(client, query)
+        .await
+        .with_context(|| format!("Failed to look up {}", query))?;
+    let report = client
+        .current(&place, &cancel())
+        .await
+        .with_context(|| format!("Failed to fetch the weather for {}", place.english_name()))?;
+    Ok((place, report))
+}
+
+/// [`get_weather`], reusing the response to the same lookup within the
+/// weather TTL unless `--no-cache` is given.
+pub async fn get_cached_weather(
+    query: &Query,
+    client: &WeatherClient,
+    options: &DisplayOptions,
+) -> Result<(Place, model::Report), Box<dyn std::error::Error>> {
+    let path = response_cache_path(query, client, options);
+    if let Some(path) = path.as_ref().filter(|_| !options.no_cache) {
+        if let Some((response, age)) = cache::read_response(path, options.config.cache.weather_ttl)
+        {
+            eprintln!(
+                "{} fetched {} ago; --no-cache to refresh",
+                "Cached:".dimmed(),
+                clock::span(age.as_secs() as i64)
+            );
+            return Ok((response.place, response.report));
+        }
+    }
+    let (place, report) = get_weather(query, client).await?;
+    let response = cache::Response { place, report };
+    if let Some(path) = &path {
+        // The cache never fails a lookup.
+        cache::write_response(path, &response).ok();
+    }
+    Ok((response.place, response.report))
+}
+
+/// Where the response to `query` from `client`'s provider is cached.
+pub fn response_cache_path(
+    query: &Query,
+    client: &WeatherClient,
+    options: &DisplayOptions,
+) -> Option<PathBuf> {
+    let provider = client.provider().name();
+    // OpenWeatherMap's descriptions are in the language asked for.
+    let cache_name = match &options.lang {
+        Some(lang) if options.provider == Provider::OpenWeatherMap => {
+            format!("{}-{}", provider, lang)
+        }
+        _ => provider.to_string(),
+    };
+    cache::response_path(&cache_name, query)
+}
+
+pub async fn resolve_place(
+    client: &WeatherClient,
+    query: &Query,
+) -> Result<Place, Box<dyn std::error::Error>> {
+    let source = match query {
+        Query::City(city) => return Ok(client.resolve_city(city, &cancel()).await?),
+        Query::Choice { name, index } => {
+            let places = client.search(name, geocode::MAX_MATCHES, &cancel()).await?;
+            if places.is_empty() {
+                return Err(WeatherError::CityNotFound(name.to_string()).into());
+            }
+            let count = places.len();
+            let list = numbered_places(&places).join("\n  ");
+            return places.into_iter().nth(index - 1).ok_or_else(|| {
+                format!(
+                    "'{}' matches {} places, not {}:\n  {}",
+                    name, count, index, list
+                )
+                .into()
+            });
+        }
+        Query::Here(source) => source,
+        Query::Saved(location) => return Ok(location.place.clone()),
+        // Trailheads and campsites have no name to geocode: the place is
+        // named after its coordinates.
+        &Query::Coordinates { lat, lon } => {
+            return Ok(Place {
+                name: query.to_string(),
+                local_names: Default::default(),
+                lat,
+                lon,
+                country: String::new(),
+                state: None,
+            })
+        }
+    };
+    let position = location::current_position(source).await?;
+    match &position.place {
+        Some(place) => eprintln!(
+            "{} {} ({:.4}, {:.4}) via {}; if that is wrong, give --city or --lat and --lon",
+            "Location:".dimmed(),
+            place.bold(),
+            position.lat,
+            position.lon,
+            position.source
+        ),
+        None => eprintln!(
+            "{} {:.4}, {:.4} via {}{}",
+            "Location:".dimmed(),
+            position.lat,
+            position.lon,
+            position.source,
+            position
+                .accuracy
+                .map_or_else(String::new, |a| format!(" (±{:.0} m)", a))
+        ),
+    }
+    Ok(client
+        .reverse(position.lat, position.lon, &cancel())
+        .await?)
+}
+
+pub async fn get_city_weather(
+    city: &str,
+    client: &WeatherClient,
+) -> Result<(Place, model::Report), Box<dyn std::error::Error>> {
+    let place = client.resolve_city(city, &cancel()).await?;
+    let report = client.current(&place, &cancel()).await?;
+    Ok((place, report))
+}
+
+/// `report` for `query` completed with what the options ask for: the
+/// derived metrics, the personal weather station, the hyperlocal
+/// estimate and the alerts in force, then recorded if the config says so.
+/// Also returns how many alerts for the surrounding area do not cover the
+/// place, once they have been fetched.
+pub async fn complete(
+    query: &Query,
+    place: &Place,
+    report: model::Report,
+    client: &WeatherClient,
+    api_key: &str,
+    options: &DisplayOptions,
+) -> Result<(model::Report, Option<usize>), Box<dyn std::error::Error>> {
+    let mut report = build_report(report, options);
+    if let Some(station) = station_for(query, options) {
+        match observe_station(station).await {
+            Ok(mut local) => {
+                local.discrepancies = station::compare(&local, &report.current)
+                    .into_iter()
+                    .filter(|c| c.discrepant)
+                    .map(|c| c.field.to_string())
+                    .collect();
+                report.local = Some(local);
+            }
+            Err(e) => eprintln!("{} {}", "Weather station unavailable:".yellow(), e),
+        }
+    }
+    if options.hyperlocal {
+        match hyperlocal::interpolate(client, place).await {
+            Ok(hyperlocal) => report.hyperlocal = Some(hyperlocal),
+            Err(e) => eprintln!("{} {}", "Hyperlocal unavailable:".yellow(), e),
+        }
+    }
+    let mut alerts_elsewhere = None;
+    if options.alerts {
+        let client = http_client()?;
+        let mut all = match alerts::for_place(&client, place, api_key).await {
+            Ok(all) => Some(all),
+            // No alerts must not read as none in force.
+            Err(e) if options.alerts_only => return Err(e).context("Alerts unavailable"),
+            Err(e) => {
+                eprintln!("{} {}", "Alerts unavailable:".yellow(), e);
+                None
+            }
+        };
+        for url in &options.config.alerts.cap_feeds {
+            match alerts::cap_feed(&client, url, place, options.clock.now()).await {
+                Ok(feed) => all.get_or_insert_with(Vec::new).extend(feed),
+                Err(e) => {
+                    eprintln!("{} {}: {}", "Alert feed unavailable:".yellow(), url, e)
+                }
+            }
+        }
+        if let Some(mut all) = all {
+            // A feed may carry the same alert as the built-in source.
+            let mut seen = HashSet::new();
+            all.retain(|alert| seen.insert((alert.event.clone(), alert.headline.clone())));
+            let (mut here, elsewhere): (Vec<_>, Vec<_>) = all
+                .into_iter()
+                .partition(|alert| alert.covers(place.lat, place.lon));
+            here.sort_by_key(|alert| alert.severity);
+            alerts_elsewhere = Some(elsewhere.len());
+            report.alerts = here;
+        }
+    }
+    if options.config.storage.record {
+        if let Err(e) = record(&report, &options.config.storage).await {
+            eprintln!("{} {}", "Recording failed:".yellow(), e);
+        }
+    }
+    Ok((report, alerts_elsewhere))
+}
+
+/// How config tables keyed by location (`[stations]`, `[calibration]`)
+/// name `query`: the slug of the place given, or `here`. Bare coordinates
+/// have no name.
+fn config_key(query: &Query) -> Option<String> {
+    match query {
+        Query::City(name) | Query::Choice { name, .. } => Some(cache::slug(name)),
+        Query::Here(_) => Some("here".to_string()),
+        Query::Coordinates { .. } => None,
+        Query::Saved(location) => Some(cache::slug(location.label())),
+    }
+}
+
+/// The personal weather station for `query`: `--pws`, else the one the
+/// config's `[stations]` table gives for the location.
+pub fn station_for<'a>(query: &Query, options: &'a DisplayOptions) -> Option<&'a pws::Station> {
+    options.pws.as_ref().or_else(|| {
+        let key = config_key(query)?;
+        options
+            .config
+            .stations
+            .iter()
+            .find(|(name, _)| cache::slug(name) == key)
+            .map(|(_, station)| station)
+    })
+}
+
+/// The config's `[calibration]` offsets for `query`, unless all zero.
+pub fn calibration_for<'a>(
+    query: &Query,
+    options: &'a DisplayOptions,
+) -> Option<&'a calibration::Calibration> {
+    let key = config_key(query)?;
+    options
+        .config
+        .calibration
+        .iter()
+        .find(|(name, _)| cache::slug(name) == key)
+        .map(|(_, calibration)| calibration)
+        .filter(|calibration| !calibration.is_zero())
+}
+
+pub async fn observe_station(
+    station: &pws::Station,
+) -> Result<model::LocalObservation, Box<dyn std::error::Error>> {
+    let client = http_client()?;
+    let token = if station.needs_netatmo_token() {
+        Some(
+            tokens::netatmo_access_token(&client)
+                .await
+                .context("Cannot sign in to Netatmo")?,
+        )
+    } else {
+        None
+    };
+    pws::observe(&client, station, token.as_deref()).await
+}
+
+/// `report` with the elevation and derived metrics the options ask for.
+pub fn build_report(mut report: model::Report, options: &DisplayOptions) -> model::Report {
+    // A given elevation overrides the provider's ground-level pressure.
+    if let Some(elevation) = options.elevation {
+        let current = &mut report.current;
+        current.station_pressure_hpa = Some(atmosphere::station_pressure(
+            f64::from(current.pressure_hpa),
+            elevation,
+            current.temperature_c,
+        ));
+    }
+    let mut keys = options.config.metrics.json.clone();
+    if options.performance || options.ballistics {
+        keys.extend(
+            AIR_METRICS
+                .iter()
+                .filter(|key| !keys.iter().any(|k| k == *key))
+                .map(|key| key.to_string())
+                .collect::<Vec<_>>(),
+        );
+    }
+    report.derived = metrics::compute(&report.current, &keys)
+        .into_iter()
+        .map(|(calculator, value)| (calculator.key.to_string(), value))
+        .collect();
+    report
+}
@@ -0,0 +1,168 @@
+//! Rule-based road ice risk from current conditions and the forecast.
+
+use crate::local_datetime;
+use chrono::Timelike;
+use weather_core::metrics::dew_point;
+use weather_core::model::Report;
+use weather_providers::forecast::Forecast;
+
+/// How far back wet roads count as "recently wet" when temperatures drop.
+const WET_MEMORY_SECONDS: i64 = 6 * 3600;
+/// Forecast hours searched for the coming night.
+const OVERNIGHT_HORIZON_SECONDS: i64 = 24 * 3600;
+/// OpenWeatherMap's condition code for freezing rain in the forecast;
+/// descriptions follow `--lang`, so they cannot be matched. Current
+/// conditions carry no code: rain falling now near freezing is rated the
+/// same either way.
+const FREEZING_RAIN: i32 = 511;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IceRisk {
+    Low,
+    Moderate,
+    High,
+}
+
+impl IceRisk {
+    pub fn label(self) -> &'static str {
+        match self {
+            IceRisk::Low => "Low",
+            IceRisk::Moderate => "Moderate",
+            IceRisk::High => "High",
+        }
+    }
+}
+
+/// Risk at one moment and the rule that produced it.
+#[derive(Debug, Clone)]
+pub struct Assessment {
+    pub risk: IceRisk,
+    pub reason: &'static str,
+    /// Unix timestamp the assessment applies to.
+    pub at: i64,
+}
+
+#[derive(Debug)]
+pub struct Advisory {
+    pub now: Assessment,
+    /// The worst point of the coming night, when a forecast is available.
+    pub overnight: Option<Assessment>,
+}
+
+/// One point in time the rules are applied to.
+struct Sample<'a> {
+    dt: i64,
+    temp: f64,
+    humidity: f64,
+    condition: &'a str,
+    freezing: bool,
+    /// Liquid precipitation falling or recorded.
+    wet: bool,
+    snow: bool,
+}
+
+pub fn assess(report: &Report, forecast: Option<&Forecast>) -> Advisory {
+    let now = report.observed_at.timestamp();
+    let kind = report.current.condition.kind.as_str();
+    let current = Sample {
+        dt: now,
+        temp: report.current.temperature_c,
+        humidity: report.current.humidity_percent as f64,
+        condition: kind,
+        freezing: false,
+        wet: fell(report.current.rain_mm) || is_liquid(kind),
+        snow: fell(report.current.snow_mm) || kind == "snow",
+    };
+
+    let mut samples = vec![current];
+    if let Some(forecast) = forecast {
+        for entry in forecast
+            .list
+            .iter()
+            .filter(|e| e.dt > now && e.dt <= now + OVERNIGHT_HORIZON_SECONDS)
+        {
+            let condition = entry.weather.first();
+            let main = condition.map_or("", |c| c.main.as_str());
+            samples.push(Sample {
+                dt: entry.dt,
+                temp: entry.main.temp,
+                humidity: entry.main.humidity as f64,
+                condition: main,
+                freezing: condition.is_some_and(|c| c.id == FREEZING_RAIN),
+                wet: entry.rain.as_ref().is_some_and(|r| r.three_hours > 0.0)
+                    || (is_liquid(main) && entry.pop >= 0.3),
+                snow: entry.snow.as_ref().is_some_and(|s| s.three_hours > 0.0),
+            });
+        }
+    }
+
+    let assessments: Vec<Assessment> = samples
+        .iter()
+        .map(|sample| {
+            let wet_recently = samples
+                .iter()
+                .any(|s| s.wet && s.dt <= sample.dt && sample.dt - s.dt <= WET_MEMORY_SECONDS);
+            let (risk, reason) = rate(sample, wet_recently);
+            Assessment {
+                risk,
+                reason,
+                at: sample.dt,
+            }
+        })
+        .collect();
+
+    let overnight = assessments
+        .iter()
+        .skip(1)
+        .filter(|a| is_overnight(local_datetime(a.at, report.location.utc_offset_seconds).hour()))
+        // The earliest of the worst, so the time says when to worry.
+        .fold(None::<&Assessment>, |worst, a| match worst {
+            Some(w) if w.risk >= a.risk => Some(w),
+            _ => Some(a),
+        })
+        .cloned();
+
+    Advisory {
+        now: assessments[0].clone(),
+        overnight,
+    }
+}
+
+fn rate(sample: &Sample, wet_recently: bool) -> (IceRisk, &'static str) {
+    let dew_point = dew_point(sample.temp, sample.humidity);
+    let clear = sample.condition.eq_ignore_ascii_case("clear");
+
+    if sample.freezing {
+        (IceRisk::High, "freezing rain")
+    } else if sample.wet && sample.temp <= 1.0 {
+        (IceRisk::High, "rain falling near freezing")
+    } else if wet_recently && sample.temp <= 0.0 {
+        (IceRisk::High, "wet roads refreezing")
+    } else if sample.snow && sample.temp <= 1.0 {
+        (IceRisk::Moderate, "snow settling on roads")
+    } else if sample.temp <= 0.0 && sample.temp - dew_point <= 1.0 {
+        (IceRisk::Moderate, "frost and black ice likely")
+    } else if wet_recently && sample.temp <= 2.0 {
+        (IceRisk::Moderate, "wet roads close to freezing")
+    } else if clear && sample.temp <= 3.0 {
+        // Road surfaces under clear skies cool below the air temperature.
+        (IceRisk::Low, "ground frost possible on untreated roads")
+    } else {
+        (IceRisk::Low, "no ice expected")
+    }
+}
+
+fn fell(precipitation: Option<f64>) -> bool {
+    precipitation.is_some_and(|mm| mm > 0.0)
+}
+
+fn is_liquid(condition: &str) -> bool {
+    matches!(
+        condition.to_lowercase().as_str(),
+        "rain" | "drizzle" | "thunderstorm"
+    )
+}
+
+fn is_overnight(hour: u32) -> bool {
+    !(9..21).contains(&hour)
+}
@@ -0,0 +1,157 @@
+//! Finds dry gaps between showers and storms, e.g. "dry window 14:30–16:00",
+//! over the minutely nowcast and hourly forecast.
+
+use weather_providers::forecast::Forecast;
+use weather_providers::onecall::OneCall;
+
+/// Rates below this (mm/h) are treated as dry; radar noise and a few drops
+/// shouldn't split a window.
+const WET_RATE_MM_H: f64 = 0.1;
+/// Chance of precipitation from which an hour is treated as wet.
+const WET_POP: f64 = 0.3;
+/// Confidence in a wet minute of the radar nowcast.
+const NOWCAST_CONFIDENCE: f64 = 0.9;
+
+/// A stretch of time that is either wet or dry.
+#[derive(Debug, Clone, Copy)]
+pub struct Slot {
+    pub start: i64,
+    pub end: i64,
+    pub wet: bool,
+    /// Thunderstorms in the slot.
+    pub storm: bool,
+    /// Precipitation hazard per second: the chance of staying dry for `t`
+    /// seconds of the slot is `exp(-hazard * t)`. Lets a probability given
+    /// for a whole hour be applied to part of it.
+    pub hazard: f64,
+}
+
+/// Hazard rate for a `chance` of precipitation over `seconds`.
+fn hazard(chance: f64, seconds: i64) -> f64 {
+    -(1.0 - chance.clamp(0.0, 0.999)).ln() / seconds as f64
+}
+
+/// A dry interval, as Unix timestamps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Window {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl Window {
+    pub fn minutes(&self) -> i64 {
+        (self.end - self.start) / 60
+    }
+}
+
+/// Slots from One Call data: the minutely nowcast first, then hourly entries
+/// for the time it doesn't cover.
+pub fn from_onecall(onecall: &OneCall) -> Vec<Slot> {
+    let mut slots: Vec<Slot> = onecall
+        .minutely
+        .iter()
+        .map(|m| Slot {
+            start: m.dt,
+            end: m.dt + 60,
+            wet: m.precipitation >= WET_RATE_MM_H,
+            storm: false,
+            hazard: if m.precipitation >= WET_RATE_MM_H {
+                hazard(NOWCAST_CONFIDENCE, 60)
+            } else {
+                0.0
+            },
+        })
+        .collect();
+    let nowcast_end = slots.last().map_or(i64::MIN, |s| s.end);
+
+    for hour in &onecall.hourly {
+        let (start, end) = (hour.dt.max(nowcast_end), hour.dt + 3600);
+        if start >= end {
+            continue;
+        }
+        let storm = hour.weather.iter().any(|w| is_storm(w.id));
+        let rain = hour.rain.as_ref().and_then(|r| r.one_hour).unwrap_or(0.0);
+        slots.push(Slot {
+            start,
+            end,
+            wet: storm || hour.pop >= WET_POP || rain >= WET_RATE_MM_H,
+            storm,
+            hazard: hazard(hour.pop, 3600),
+        });
+    }
+    slots
+}
+
+/// Slots from the 3-hourly forecast, used when One Call isn't available.
+pub fn from_forecast(forecast: &Forecast) -> Vec<Slot> {
+    forecast
+        .list
+        .iter()
+        .map(|entry| {
+            let storm = entry.weather.iter().any(|w| is_storm(w.id));
+            let rain = entry.rain.as_ref().map_or(0.0, |r| r.three_hours);
+            Slot {
+                start: entry.dt,
+                end: entry.dt + 3 * 3600,
+                wet: storm || entry.pop >= WET_POP || rain >= WET_RATE_MM_H * 3.0,
+                storm,
+                hazard: hazard(entry.pop, 3 * 3600),
+            }
+        })
+        .collect()
+}
+
+/// Maximal dry stretches between `from` and `until` lasting at least
+/// `min_minutes`. Time not covered by any slot is unknown and ends a window.
+pub fn dry_windows(slots: &[Slot], from: i64, until: i64, min_minutes: i64) -> Vec<Window> {
+    let mut windows = Vec::new();
+    let mut current: Option<Window> = None;
+
+    for slot in slots {
+        let (start, end) = (slot.start.max(from), slot.end.min(until));
+        if start >= end {
+            continue;
+        }
+        match current.as_mut() {
+            Some(window) if !slot.wet && window.end == start => window.end = end,
+            _ => {
+                windows.extend(current.take());
+                if !slot.wet {
+                    current = Some(Window { start, end });
+                }
+            }
+        }
+    }
+    windows.extend(current);
+
+    windows.retain(|w| w.minutes() >= min_minutes);
+    windows
+}
+
+/// Whether any slot in the range is wet, and whether any is stormy.
+pub fn outlook(slots: &[Slot], from: i64, until: i64) -> (bool, bool) {
+    let relevant = slots.iter().filter(|s| s.end > from && s.start < until);
+    relevant.fold((false, false), |(wet, storm), s| {
+        (wet || s.wet, storm || s.storm)
+    })
+}
+
+/// Chance of any precipitation between `from` and `until`, combining the
+/// slots covering the range. `None` if part of the range isn't covered.
+pub fn chance_of_precipitation(slots: &[Slot], from: i64, until: i64) -> Option<f64> {
+    let mut covered = 0;
+    let mut exposure = 0.0;
+    for slot in slots {
+        let overlap = slot.end.min(until) - slot.start.max(from);
+        if overlap > 0 {
+            covered += overlap;
+            exposure += slot.hazard * overlap as f64;
+        }
+    }
+    (covered >= until - from).then(|| 1.0 - (-exposure).exp())
+}
+
+/// OpenWeatherMap condition codes 2xx are thunderstorms.
+fn is_storm(condition_id: i32) -> bool {
+    (200..300).contains(&condition_id)
+}
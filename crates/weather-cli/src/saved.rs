@@ -0,0 +1,137 @@
+//! Saved locations, kept in `locations.json` in the data directory. Each
+//! keeps the place it was geocoded to, so showing it needs no lookup by
+//! name and cannot land on another place of the same name.
+//!
+//! A location saved with `weather save <alias> <place>` is shown with
+//! `weather <alias>`, or wherever a place name is taken.
+
+use crate::platform;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use weather_providers::geocode::Place;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Location {
+    /// How the location is listed, e.g. "Pune, Maharashtra, IN".
+    pub name: String,
+    /// The short name given with `weather save`, e.g. "home".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    pub place: Place,
+}
+
+impl Location {
+    pub fn new(place: Place) -> Self {
+        let name = match place.region() {
+            region if region.is_empty() => place.english_name().to_string(),
+            region => format!("{}, {}", place.english_name(), region),
+        };
+        Location {
+            name,
+            alias: None,
+            place,
+        }
+    }
+
+    /// Whether `alias` names this location, whatever its case.
+    pub fn is_called(&self, alias: &str) -> bool {
+        self.alias
+            .as_deref()
+            .is_some_and(|own| own.eq_ignore_ascii_case(alias))
+    }
+
+    /// The alias, else the name.
+    pub fn label(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
+}
+
+/// Location of the saved locations file.
+pub fn path() -> Option<PathBuf> {
+    platform::dir(platform::Dir::Data).map(|dir| dir.join("locations.json"))
+}
+
+/// The saved locations, in the order they were added.
+pub fn load() -> Result<Vec<Location>, Box<dyn std::error::Error>> {
+    match path() {
+        Some(path) => read(&path),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn read(path: &Path) -> Result<Vec<Location>, Box<dyn std::error::Error>> {
+    match std::fs::read(path) {
+        Ok(bytes) => Ok(serde_json::from_slice(&bytes)
+            .map_err(|e| format!("Invalid saved locations {}: {}", path.display(), e))?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(format!("Cannot read {}: {}", path.display(), e).into()),
+    }
+}
+
+/// The saved location called `alias`.
+pub fn find(alias: &str) -> Result<Option<Location>, Box<dyn std::error::Error>> {
+    Ok(load()?.into_iter().find(|saved| saved.is_called(alias)))
+}
+
+/// Saves `location` and returns the updated list. With an alias, it
+/// replaces the location of that alias and an unaliased one of the same
+/// name; without, it replaces the place of those of the same name, which
+/// keep their aliases.
+pub fn add(location: Location) -> Result<Vec<Location>, Box<dyn std::error::Error>> {
+    let path = path().ok_or("Cannot determine the data directory")?;
+    let mut locations = read(&path)?;
+    match &location.alias {
+        Some(alias) => {
+            locations.retain(|saved| {
+                !saved.is_called(alias) && (saved.alias.is_some() || saved.name != location.name)
+            });
+            locations.push(location);
+        }
+        None => {
+            let mut found = false;
+            for saved in locations
+                .iter_mut()
+                .filter(|saved| saved.name == location.name)
+            {
+                saved.place = location.place.clone();
+                found = true;
+            }
+            if !found {
+                locations.push(location);
+            }
+        }
+    }
+    write(&path, &locations)?;
+    Ok(locations)
+}
+
+/// Forgets the location called `alias`, else those named `alias`, and
+/// returns what was removed.
+pub fn remove(alias: &str) -> Result<Vec<Location>, Box<dyn std::error::Error>> {
+    let path = path().ok_or("Cannot determine the data directory")?;
+    let locations = read(&path)?;
+    let by_alias = locations.iter().any(|saved| saved.is_called(alias));
+    let (removed, kept): (Vec<Location>, Vec<Location>) =
+        locations.into_iter().partition(|saved| {
+            if by_alias {
+                saved.is_called(alias)
+            } else {
+                saved.name.eq_ignore_ascii_case(alias)
+            }
+        });
+    if !removed.is_empty() {
+        write(&path, &kept)?;
+    }
+    Ok(removed)
+}
+
+/// Writes the list via a rename, so readers never see half of it.
+fn write(path: &Path, locations: &[Location]) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let staged = path.with_extension("json.tmp");
+    std::fs::write(&staged, serde_json::to_vec_pretty(locations)?)?;
+    std::fs::rename(&staged, path)?;
+    Ok(())
+}
@@ -0,0 +1,233 @@
+//! Screenshots of the `--tui` dashboard: a rendered ratatui buffer written
+//! as an SVG image (`.svg`), plain text (`.txt`) or text with ANSI colours
+//! (any other name, e.g. `.ans`, shown as it was by `cat` or `less -R`).
+
+use ratatui::buffer::{Buffer, Cell};
+use ratatui::style::{Color, Modifier};
+use ratatui::text::Span;
+use std::fmt::Write;
+use std::path::Path;
+
+/// Width and height of a cell in the SVG, for a 14px monospace font.
+const CELL_WIDTH: f64 = 8.4;
+const CELL_HEIGHT: f64 = 18.0;
+const FONT_SIZE: u32 = 14;
+/// The terminal's own colours, where the dashboard leaves them unset.
+const FOREGROUND: &str = "#d4d4d4";
+const BACKGROUND: &str = "#1e1e1e";
+
+/// Writes `buffer` to `path` in the format its extension names.
+pub fn write(buffer: &Buffer, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase);
+    let content = match extension.as_deref() {
+        Some("svg") => svg(buffer),
+        Some("txt") => text(buffer),
+        Some("png") | Some("jpg") | Some("jpeg") => {
+            return Err("Screenshots can be .svg images, .txt or ANSI text, not bitmaps".into())
+        }
+        _ => ansi(buffer),
+    };
+    std::fs::write(path, content)
+        .map_err(|e| format!("Cannot write {}: {}", path.display(), e).into())
+}
+
+/// The cells of each row, leaving out those hidden by a wide character
+/// before them, with the column each starts at.
+fn rows(buffer: &Buffer) -> impl Iterator<Item = Vec<(u16, &Cell)>> {
+    let width = buffer.area.width as usize;
+    buffer.content.chunks(width.max(1)).map(|row| {
+        let mut cells = Vec::new();
+        let mut hidden = 0;
+        for (x, cell) in row.iter().enumerate() {
+            if hidden > 0 {
+                hidden -= 1;
+                continue;
+            }
+            hidden = Span::raw(cell.symbol()).width().saturating_sub(1);
+            cells.push((x as u16, cell));
+        }
+        cells
+    })
+}
+
+fn text(buffer: &Buffer) -> String {
+    let mut out = String::new();
+    for row in rows(buffer) {
+        let line: String = row.iter().map(|(_, cell)| cell.symbol()).collect();
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+fn ansi(buffer: &Buffer) -> String {
+    let mut out = String::new();
+    for row in rows(buffer) {
+        let mut last = None;
+        for (_, cell) in row {
+            let style = (cell.fg, cell.bg, cell.modifier);
+            if last != Some(style) {
+                out.push_str(&sgr(cell));
+                last = Some(style);
+            }
+            out.push_str(cell.symbol());
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    out
+}
+
+/// The escape sequence that sets the style of `cell` from scratch.
+fn sgr(cell: &Cell) -> String {
+    let mut codes = vec!["0".to_string()];
+    let modifiers = [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::SLOW_BLINK, "5"),
+        (Modifier::RAPID_BLINK, "6"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::HIDDEN, "8"),
+        (Modifier::CROSSED_OUT, "9"),
+    ];
+    for (modifier, code) in modifiers {
+        if cell.modifier.contains(modifier) {
+            codes.push(code.to_string());
+        }
+    }
+    codes.extend(ansi_color(cell.fg, 30));
+    codes.extend(ansi_color(cell.bg, 40));
+    format!("\x1b[{}m", codes.join(";"))
+}
+
+/// The SGR parameters of `color`; `base` is 30 for the foreground and 40
+/// for the background.
+fn ansi_color(color: Color, base: u8) -> Option<String> {
+    let code = match color {
+        Color::Reset => return None,
+        Color::Black => base,
+        Color::Red => base + 1,
+        Color::Green => base + 2,
+        Color::Yellow => base + 3,
+        Color::Blue => base + 4,
+        Color::Magenta => base + 5,
+        Color::Cyan => base + 6,
+        Color::Gray => base + 7,
+        Color::DarkGray => base + 60,
+        Color::LightRed => base + 61,
+        Color::LightGreen => base + 62,
+        Color::LightYellow => base + 63,
+        Color::LightBlue => base + 64,
+        Color::LightMagenta => base + 65,
+        Color::LightCyan => base + 66,
+        Color::White => base + 67,
+        Color::Indexed(i) => return Some(format!("{};5;{}", base + 8, i)),
+        Color::Rgb(r, g, b) => return Some(format!("{};2;{};{};{}", base + 8, r, g, b)),
+    };
+    Some(code.to_string())
+}
+
+fn svg(buffer: &Buffer) -> String {
+    let (width, height) = (
+        f64::from(buffer.area.width) * CELL_WIDTH,
+        f64::from(buffer.area.height) * CELL_HEIGHT,
+    );
+    let mut out = String::new();
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" font-family="monospace" font-size="{FONT_SIZE}">"#
+    )
+    .ok();
+    writeln!(
+        out,
+        r#"<rect width="100%" height="100%" fill="{BACKGROUND}"/>"#
+    )
+    .ok();
+    for (y, row) in rows(buffer).enumerate() {
+        let top = y as f64 * CELL_HEIGHT;
+        // Each cell is placed on its own so that the font's widths, of
+        // emoji in particular, cannot shift the rest of the row.
+        for (x, cell) in row {
+            let left = f64::from(x) * CELL_WIDTH;
+            let (mut fg, mut bg) = (
+                svg_color(cell.fg).unwrap_or_else(|| FOREGROUND.to_string()),
+                svg_color(cell.bg),
+            );
+            if cell.modifier.contains(Modifier::REVERSED) {
+                (fg, bg) = (bg.unwrap_or_else(|| BACKGROUND.to_string()), Some(fg));
+            }
+            if let Some(bg) = bg {
+                let cells = Span::raw(cell.symbol()).width().max(1) as f64;
+                writeln!(
+                    out,
+                    r#"<rect x="{left:.1}" y="{top}" width="{:.1}" height="{CELL_HEIGHT}" fill="{bg}"/>"#,
+                    cells * CELL_WIDTH
+                )
+                .ok();
+            }
+            let symbol = cell.symbol();
+            if symbol.trim().is_empty() || cell.modifier.contains(Modifier::HIDDEN) {
+                continue;
+            }
+            let mut attributes = format!(r#"fill="{fg}""#);
+            if cell.modifier.contains(Modifier::BOLD) {
+                attributes.push_str(r#" font-weight="bold""#);
+            }
+            if cell.modifier.contains(Modifier::ITALIC) {
+                attributes.push_str(r#" font-style="italic""#);
+            }
+            if cell.modifier.contains(Modifier::DIM) {
+                attributes.push_str(r#" opacity="0.6""#);
+            }
+            if cell.modifier.contains(Modifier::UNDERLINED) {
+                attributes.push_str(r#" text-decoration="underline""#);
+            }
+            writeln!(
+                out,
+                r#"<text x="{left:.1}" y="{:.1}" {attributes}>{}</text>"#,
+                top + CELL_HEIGHT * 0.75,
+                escape(symbol)
+            )
+            .ok();
+        }
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+/// The colours of a common dark terminal theme.
+fn svg_color(color: Color) -> Option<String> {
+    let hex = match color {
+        Color::Reset => return None,
+        Color::Black => "#000000",
+        Color::Red => "#cd3131",
+        Color::Green => "#0dbc79",
+        Color::Yellow => "#e5e510",
+        Color::Blue => "#2472c8",
+        Color::Magenta => "#bc3fbc",
+        Color::Cyan => "#11a8cd",
+        Color::Gray => "#e5e5e5",
+        Color::DarkGray => "#666666",
+        Color::LightRed => "#f14c4c",
+        Color::LightGreen => "#23d18b",
+        Color::LightYellow => "#f5f543",
+        Color::LightBlue => "#3b8eea",
+        Color::LightMagenta => "#d670d6",
+        Color::LightCyan => "#29b8db",
+        Color::White => "#ffffff",
+        Color::Rgb(r, g, b) => return Some(format!("#{:02x}{:02x}{:02x}", r, g, b)),
+        // Not used by the dashboard; the foreground stands in.
+        Color::Indexed(_) => FOREGROUND,
+    };
+    Some(hex.to_string())
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
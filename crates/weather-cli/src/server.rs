@@ -0,0 +1,543 @@
+//! `weather serve`: a small HTTP API over the same lookups as the CLI.
+
+use crate::cache;
+use axum::extract::{ConnectInfo, Query, Request, State};
+use axum::http::{header, HeaderValue, Method, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{IntoParams, Modify, OpenApi, ToSchema};
+use weather_core::model::{
+    Alert, Certainty, Condition, Conditions, GridPoint, Hyperlocal, LocalObservation, Location,
+    Report, Severity, Source, Urgency,
+};
+use weather_providers::client::{Cancel, CancellationToken, Cancelled, WeatherClient};
+use weather_providers::WeatherError;
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Weather CLI server",
+        description = "Current weather lookups served by `weather serve`."
+    ),
+    paths(current_weather, healthz, readyz),
+    components(schemas(Report, Source, Location, Conditions, Condition, LocalObservation, Hyperlocal, GridPoint, Alert, Severity, Urgency, Certainty, ErrorBody, Health, cache::Stats)),
+    modifiers(&BearerAuth)
+)]
+struct ApiDoc;
+
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "bearer_auth",
+                SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+            );
+        }
+    }
+}
+
+/// Settings for `weather serve`.
+pub struct ServeOptions {
+    pub bind: SocketAddr,
+    /// Require `Authorization: Bearer <token>` on API requests.
+    pub token: Option<String>,
+    /// Requests allowed per client IP per minute; 0 disables the limit.
+    pub rate_limit: u32,
+    /// Origins allowed to call the API from a browser; `*` allows any.
+    pub cors_origins: Vec<String>,
+    pub cache: cache::Settings,
+}
+
+struct AppState {
+    api_key: String,
+    client: WeatherClient,
+    cache: cache::TwoTier,
+    /// Cancelled on Ctrl-C; aborts lookups still in flight.
+    shutdown: CancellationToken,
+    token: Option<String>,
+    limiter: RateLimiter,
+    /// When the provider last answered successfully.
+    last_success: Mutex<Option<Instant>>,
+}
+
+/// Longest a lookup may take, retries included, before the request fails.
+const REQUEST_DEADLINE: Duration = Duration::from_secs(20);
+
+/// How long a successful provider response counts as proof of readiness.
+const READY_FRESHNESS: Duration = Duration::from_secs(600);
+
+/// Fixed-window request counter per client address.
+struct RateLimiter {
+    limit: u32,
+    window: Duration,
+    clients: Mutex<HashMap<IpAddr, (Instant, u32)>>,
+}
+
+impl RateLimiter {
+    fn new(limit: u32) -> Self {
+        RateLimiter {
+            limit,
+            window: Duration::from_secs(60),
+            clients: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Counts a request from `client`; on refusal returns the seconds until
+    /// the client's window resets.
+    fn check(&self, client: IpAddr) -> Result<(), u64> {
+        self.check_at(client, Instant::now())
+    }
+
+    fn check_at(&self, client: IpAddr, now: Instant) -> Result<(), u64> {
+        if self.limit == 0 {
+            return Ok(());
+        }
+
+        let mut clients = self.clients.lock().unwrap_or_else(|e| e.into_inner());
+        if clients.len() > 10_000 {
+            clients.retain(|_, (start, _)| now.duration_since(*start) < self.window);
+        }
+
+        let (start, count) = clients.entry(client).or_insert((now, 0));
+        if now.duration_since(*start) >= self.window {
+            *start = now;
+            *count = 0;
+        }
+        if *count >= self.limit {
+            let reset = self.window.saturating_sub(now.duration_since(*start));
+            return Err(reset.as_secs().max(1));
+        }
+        *count += 1;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+struct WeatherQuery {
+    /// City name, optionally with a country code, e.g. `London,GB`.
+    city: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Health {
+    /// `ok`, `ready` or `unavailable`.
+    pub status: String,
+    /// Seconds since the provider last answered successfully, if ever.
+    pub last_success_seconds_ago: Option<u64>,
+    pub error: Option<String>,
+    /// Cache hit and miss counts by endpoint (`weather`, `geocode`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache: Option<HashMap<String, cache::Stats>>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorBody {
+    pub error: String,
+}
+
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(ErrorBody { error: self.1 })).into_response()
+    }
+}
+
+pub fn router(
+    api_key: String,
+    options: &ServeOptions,
+    shutdown: CancellationToken,
+) -> Result<Router, reqwest::Error> {
+    let state = Arc::new(AppState {
+        client: WeatherClient::with_http(crate::http_client()?, api_key.clone()),
+        cache: cache::TwoTier::new(&options.cache),
+        shutdown,
+        api_key,
+        token: options.token.clone(),
+        limiter: RateLimiter::new(options.rate_limit),
+        last_success: Mutex::new(None),
+    });
+
+    let api = Router::new()
+        .route("/weather", get(current_weather))
+        .route_layer(middleware::from_fn_with_state(state.clone(), authorize))
+        .route_layer(middleware::from_fn_with_state(state.clone(), rate_limit));
+
+    let mut app = Router::new()
+        .merge(api)
+        .route("/openapi.json", get(openapi))
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .with_state(state);
+
+    if !options.cors_origins.is_empty() {
+        app = app.layer(cors_layer(&options.cors_origins));
+    }
+    Ok(app.layer(middleware::from_fn(log_request)))
+}
+
+pub async fn serve(
+    options: ServeOptions,
+    api_key: String,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let listener = tokio::net::TcpListener::bind(options.bind).await?;
+    if options.token.is_none() && !options.bind.ip().is_loopback() {
+        eprintln!("Warning: serving on a non-loopback address without --token");
+    }
+    println!("Serving weather API on http://{}", listener.local_addr()?);
+
+    let shutdown = CancellationToken::new();
+    let app = router(api_key, &options, shutdown.clone())?;
+    tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                println!("Stopping");
+                shutdown.cancel();
+            }
+        }
+    });
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown.cancelled_owned())
+    .await?;
+    Ok(())
+}
+
+fn cors_layer(origins: &[String]) -> CorsLayer {
+    let allow_origin = if origins.iter().any(|o| o == "*") {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(
+            origins
+                .iter()
+                .filter_map(|origin| HeaderValue::from_str(origin).ok()),
+        )
+    };
+    CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods([Method::GET])
+        .allow_headers([header::AUTHORIZATION])
+}
+
+async fn log_request(
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let started = Instant::now();
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let response = next.run(request).await;
+    eprintln!(
+        "{} {} {} {} {}ms",
+        client.ip(),
+        method,
+        path,
+        response.status().as_u16(),
+        started.elapsed().as_millis()
+    );
+    response
+}
+
+async fn rate_limit(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match state.limiter.check(client.ip()) {
+        Ok(()) => next.run(request).await,
+        Err(retry_after) => {
+            let mut response = ApiError(
+                StatusCode::TOO_MANY_REQUESTS,
+                "Rate limit exceeded".to_string(),
+            )
+            .into_response();
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+            response
+        }
+    }
+}
+
+async fn authorize(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let Some(expected) = &state.token else {
+        return next.run(request).await;
+    };
+
+    let supplied = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match supplied {
+        Some(token) if constant_time_eq(token.as_bytes(), expected.as_bytes()) => {
+            next.run(request).await
+        }
+        _ => {
+            let mut response = ApiError(
+                StatusCode::UNAUTHORIZED,
+                "Missing or invalid bearer token".to_string(),
+            )
+            .into_response();
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+            response
+        }
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Current weather for a city.
+#[utoipa::path(
+    get,
+    path = "/weather",
+    params(WeatherQuery),
+    responses(
+        (status = 200, description = "Current conditions", body = Report),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorBody),
+        (status = 404, description = "City not found", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded", body = ErrorBody),
+        (status = 502, description = "Upstream provider error", body = ErrorBody),
+        (status = 503, description = "Server shutting down", body = ErrorBody),
+        (status = 504, description = "Provider too slow to answer", body = ErrorBody)
+    ),
+    security((), ("bearer_auth" = []))
+)]
+async fn current_weather(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<WeatherQuery>,
+) -> Result<Json<Report>, ApiError> {
+    if let Some(report) = state.cache.report(&query.city) {
+        return Ok(Json(report));
+    }
+    let cancel = Cancel::token(state.shutdown.child_token()).with_timeout(REQUEST_DEADLINE);
+    let lookup = async {
+        let place = match state.cache.place(&query.city) {
+            Some(place) => place,
+            None => {
+                let place = state.client.resolve_city(&query.city, &cancel).await?;
+                state.cache.store_place(&query.city, &place);
+                place
+            }
+        };
+        let report = state.client.current(&place, &cancel).await?;
+        state.cache.store_report(&query.city, &report);
+        Ok::<_, WeatherError>(report)
+    };
+    match lookup.await {
+        Ok(report) => {
+            state.mark_success();
+            Ok(Json(report))
+        }
+        Err(e) => {
+            let message = redact_api_key(&e.to_string(), &state.api_key);
+            let status = match e {
+                WeatherError::Cancelled(Cancelled::Cancelled) => StatusCode::SERVICE_UNAVAILABLE,
+                WeatherError::Cancelled(Cancelled::DeadlineExceeded) => StatusCode::GATEWAY_TIMEOUT,
+                WeatherError::CityNotFound(_) | WeatherError::NoData(_) => StatusCode::NOT_FOUND,
+                WeatherError::RateLimited { .. } => StatusCode::SERVICE_UNAVAILABLE,
+                _ => StatusCode::BAD_GATEWAY,
+            };
+            Err(ApiError(status, message))
+        }
+    }
+}
+
+/// Liveness: the process is up and serving requests.
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    responses((status = 200, description = "Server is alive", body = Health))
+)]
+async fn healthz(State(state): State<Arc<AppState>>) -> Json<Health> {
+    Json(Health {
+        status: "ok".to_string(),
+        last_success_seconds_ago: state.last_success_age().map(|age| age.as_secs()),
+        error: None,
+        cache: Some(state.cache.stats()),
+    })
+}
+
+/// Readiness: the weather provider is reachable with the configured key,
+/// judged from a recent successful lookup or, failing that, a live probe.
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    responses(
+        (status = 200, description = "Provider reachable", body = Health),
+        (status = 503, description = "Provider unreachable", body = Health)
+    )
+)]
+async fn readyz(State(state): State<Arc<AppState>>) -> (StatusCode, Json<Health>) {
+    let fresh = state
+        .last_success_age()
+        .is_some_and(|age| age < READY_FRESHNESS);
+
+    let error = if fresh {
+        None
+    } else {
+        match probe_provider(&state.api_key).await {
+            Ok(()) => {
+                state.mark_success();
+                None
+            }
+            Err(e) => Some(redact_api_key(&e.to_string(), &state.api_key)),
+        }
+    };
+
+    let (status, label) = match error {
+        None => (StatusCode::OK, "ready"),
+        Some(_) => (StatusCode::SERVICE_UNAVAILABLE, "unavailable"),
+    };
+    let health = Health {
+        status: label.to_string(),
+        last_success_seconds_ago: state.last_success_age().map(|age| age.as_secs()),
+        error,
+        cache: None,
+    };
+    (status, Json(health))
+}
+
+impl AppState {
+    fn mark_success(&self) {
+        *self.last_success.lock().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
+    }
+
+    fn last_success_age(&self) -> Option<Duration> {
+        self.last_success
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .map(|at| at.elapsed())
+    }
+}
+
+/// Makes the cheapest authenticated provider request to check reachability.
+async fn probe_provider(api_key: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let res = crate::http_client()?
+        .get("https://api.openweathermap.org/geo/1.0/direct")
+        .query(&[("q", "London"), ("limit", "1"), ("appid", api_key)])
+        .send()
+        .await?;
+    if !res.status().is_success() {
+        return Err(format!("Provider answered HTTP {}", res.status()).into());
+    }
+    Ok(())
+}
+
+/// `weather healthcheck`: asks a running server whether it is ready and
+/// returns an error (non-zero exit) otherwise, for container HEALTHCHECKs.
+pub async fn healthcheck(url: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let res = reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()?
+        .get(url)
+        .send()
+        .await?;
+    let status = res.status();
+    let health = res.json::<Health>().await?;
+
+    if status.is_success() {
+        println!("{}", health.status);
+        Ok(())
+    } else {
+        Err(format!(
+            "{} ({})",
+            health.status,
+            health.error.unwrap_or_else(|| format!("HTTP {}", status))
+        )
+        .into())
+    }
+}
+
+/// Upstream errors can include the request URL, which carries the API key.
+fn redact_api_key(message: &str, api_key: &str) -> String {
+    if api_key.is_empty() {
+        message.to_string()
+    } else {
+        message.replace(api_key, "[redacted]")
+    }
+}
+
+async fn openapi() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    const CLIENT: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+    #[test]
+    fn limits_requests_per_window() {
+        let limiter = RateLimiter::new(3);
+        let start = Instant::now();
+        for _ in 0..3 {
+            assert_eq!(limiter.check_at(CLIENT, start), Ok(()));
+        }
+        let later = start + Duration::from_secs(15);
+        assert_eq!(limiter.check_at(CLIENT, later), Err(45));
+        // Other clients have their own count.
+        let other = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+        assert_eq!(limiter.check_at(other, later), Ok(()));
+    }
+
+    #[test]
+    fn refusal_waits_at_least_a_second() {
+        let limiter = RateLimiter::new(1);
+        let start = Instant::now();
+        assert_eq!(limiter.check_at(CLIENT, start), Ok(()));
+        let almost = start + limiter.window - Duration::from_millis(1);
+        assert_eq!(limiter.check_at(CLIENT, almost), Err(1));
+    }
+
+    #[test]
+    fn window_resets() {
+        let limiter = RateLimiter::new(1);
+        let start = Instant::now();
+        assert_eq!(limiter.check_at(CLIENT, start), Ok(()));
+        assert!(limiter.check_at(CLIENT, start).is_err());
+        let next = start + limiter.window;
+        assert_eq!(limiter.check_at(CLIENT, next), Ok(()));
+        assert!(limiter.check_at(CLIENT, next).is_err());
+    }
+
+    #[test]
+    fn zero_limit_allows_everything() {
+        let limiter = RateLimiter::new(0);
+        let start = Instant::now();
+        for _ in 0..1000 {
+            assert_eq!(limiter.check_at(CLIENT, start), Ok(()));
+        }
+    }
+
+    #[test]
+    fn compares_tokens() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(constant_time_eq(b"", b""));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+        assert!(!constant_time_eq(b"secret", b""));
+    }
+}
@@ -0,0 +1,123 @@
+//! `weather solar`: how sunny the coming days really are, from Open-Meteo's
+//! radiation forecast, which accounts for cloud cover. Each daylight hour
+//! gets its minutes of direct sunshine and its irradiance: global
+//! horizontal (GHI, what a flat panel or a garden bed receives) and direct
+//! normal (DNI, what a panel tracking the sun receives).
+
+use colored::Colorize;
+use serde::Serialize;
+use weather_providers::openmeteo::Solar;
+
+/// The most days Open-Meteo forecasts.
+pub const MAX_DAYS: u32 = 16;
+
+/// Width of the sunshine bar of an hour, in cells.
+const BAR_WIDTH: usize = 12;
+
+#[derive(Debug, Serialize)]
+pub struct Day {
+    /// Local midnight starting the day.
+    pub start: i64,
+    pub sunshine_hours: Option<f64>,
+    pub daylight_hours: Option<f64>,
+    /// Global radiation over the day, in kWh/m².
+    pub radiation_kwh_m2: Option<f64>,
+    /// The hours the sun is up.
+    pub hours: Vec<Hour>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Hour {
+    pub start: i64,
+    pub sunshine_minutes: f64,
+    /// Mean irradiances over the hour, in W/m².
+    pub ghi: f64,
+    pub dni: f64,
+    pub diffuse: f64,
+}
+
+/// The forecast by day, each with its daylight hours.
+pub fn days(solar: &Solar) -> Vec<Day> {
+    let hourly = &solar.hourly;
+    let daily = &solar.daily;
+    daily
+        .time
+        .iter()
+        .enumerate()
+        .map(|(d, &start)| {
+            let end = start + 86_400;
+            let hours = hourly
+                .time
+                .iter()
+                .enumerate()
+                .filter_map(|(i, &time)| {
+                    // Values are for the hour before `time`; the hour ends
+                    // inside the day.
+                    let hour_start = time - 3600;
+                    let daytime = hourly.is_day.get(i).copied().flatten() == Some(1)
+                        || hourly.shortwave_radiation.get(i).copied().flatten() > Some(0.0);
+                    if hour_start < start || hour_start >= end || !daytime {
+                        return None;
+                    }
+                    let value = |values: &[Option<f64>]| values.get(i).copied().flatten();
+                    Some(Hour {
+                        start: hour_start,
+                        sunshine_minutes: value(&hourly.sunshine_duration).unwrap_or(0.0) / 60.0,
+                        ghi: value(&hourly.shortwave_radiation).unwrap_or(0.0),
+                        dni: value(&hourly.direct_normal_irradiance).unwrap_or(0.0),
+                        diffuse: value(&hourly.diffuse_radiation).unwrap_or(0.0),
+                    })
+                })
+                .collect();
+            let value = |values: &[Option<f64>]| values.get(d).copied().flatten();
+            Day {
+                start,
+                sunshine_hours: value(&daily.sunshine_duration).map(|s| s / 3600.0),
+                daylight_hours: value(&daily.daylight_duration).map(|s| s / 3600.0),
+                // 1 kWh is 3.6 MJ.
+                radiation_kwh_m2: value(&daily.shortwave_radiation_sum).map(|mj| mj / 3.6),
+                hours,
+            }
+        })
+        .collect()
+}
+
+/// The day's summary, e.g. "6.2 h of sunshine in 11.0 h of daylight (56%),
+/// 2.8 kWh/m²".
+pub fn summary(day: &Day) -> String {
+    let mut parts = Vec::new();
+    match (day.sunshine_hours, day.daylight_hours) {
+        (Some(sunshine), Some(daylight)) if daylight > 0.0 => parts.push(format!(
+            "{:.1} h of sunshine in {:.1} h of daylight ({:.0}%)",
+            sunshine,
+            daylight,
+            sunshine / daylight * 100.0
+        )),
+        (Some(sunshine), _) => parts.push(format!("{:.1} h of sunshine", sunshine)),
+        _ => parts.push("Sunshine unknown".to_string()),
+    }
+    if let Some(radiation) = day.radiation_kwh_m2 {
+        parts.push(format!("{:.1} kWh/m²", radiation));
+    }
+    parts.join(", ")
+}
+
+/// An hour's line: the time, a bar of its sunshine, and its irradiance.
+pub fn hour_line(hour: &Hour, label: &str, ascii: bool) -> String {
+    let filled =
+        ((hour.sunshine_minutes / 60.0 * BAR_WIDTH as f64).round() as usize).min(BAR_WIDTH);
+    let (full, empty) = if ascii { ("#", ".") } else { ("█", "░") };
+    let bar = format!(
+        "{}{}",
+        full.repeat(filled).bright_yellow(),
+        empty.repeat(BAR_WIDTH - filled).dimmed()
+    );
+    format!(
+        "{}  {} {:>2} min  GHI {:>4.0} W/m²  DNI {:>4.0} W/m²",
+        label,
+        bar,
+        hour.sunshine_minutes.round() as i64,
+        hour.ghi,
+        hour.dni
+    )
+}
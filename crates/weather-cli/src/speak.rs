@@ -0,0 +1,70 @@
+use std::env;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Speech engines tried in order, with the arguments that make them read the
+/// text from standard input.
+const ENGINES: &[(&str, &[&str])] = &[
+    ("say", &[]),
+    ("espeak-ng", &["--stdin"]),
+    ("espeak", &["--stdin"]),
+    ("spd-say", &["-w", "-e"]),
+    (
+        "powershell",
+        &[
+            "-NoProfile",
+            "-Command",
+            "Add-Type -AssemblyName System.Speech; \
+             (New-Object System.Speech.Synthesis.SpeechSynthesizer).Speak([Console]::In.ReadToEnd())",
+        ],
+    ),
+];
+
+/// Reads `text` aloud with the first speech engine found on `PATH`.
+pub fn speak(text: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let (program, args) = ENGINES
+        .iter()
+        .find_map(|(name, args)| find_executable(name).map(|path| (path, *args)))
+        .ok_or(
+            "No text-to-speech engine found (tried say, espeak-ng, espeak, spd-say). \
+                Use --speak-file to write the text for another TTS tool.",
+        )?;
+
+    let mut child = Command::new(&program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()?;
+    child
+        .stdin
+        .take()
+        .ok_or("Failed to open stdin of the speech engine")?
+        .write_all(text.as_bytes())?;
+
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(format!("{} exited with {}", program.display(), status).into());
+    }
+    Ok(())
+}
+
+/// Writes `text` for an external TTS tool; `-` writes to standard output.
+pub fn write_text(text: &str, path: &Path) -> std::io::Result<()> {
+    if path == Path::new("-") {
+        println!("{}", text);
+        Ok(())
+    } else {
+        std::fs::write(path, format!("{}\n", text))
+    }
+}
+
+pub fn find_executable(name: &str) -> Option<PathBuf> {
+    let path = env::var_os("PATH")?;
+    env::split_paths(&path).find_map(|dir| {
+        [name.to_string(), format!("{}.exe", name)]
+            .iter()
+            .map(|file| dir.join(file))
+            .find(|candidate| candidate.is_file())
+    })
+}
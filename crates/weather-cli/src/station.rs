@@ -0,0 +1,56 @@
+//! Comparison of a personal weather station (`--pws`) with the provider's
+//! current conditions.
+
+use weather_core::model::{Conditions, LocalObservation};
+
+/// Differences beyond which a station and the provider disagree. A backyard
+/// sensor and a model grid point or airport station routinely differ by a
+/// little; these catch a misplaced sensor or a provider far off the mark.
+const LIMITS: [(&str, &str, f64); 4] = [
+    ("temperature_c", "Temperature", 3.0),
+    ("humidity_percent", "Humidity", 15.0),
+    ("pressure_hpa", "Pressure", 4.0),
+    ("wind_speed_ms", "Wind", 4.0),
+];
+
+/// One value reported by both the station and the provider.
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    /// Key of the field in the JSON model, e.g. `temperature_c`.
+    pub field: &'static str,
+    pub label: &'static str,
+    pub station: f64,
+    pub provider: f64,
+    /// Whether the difference exceeds the field's limit.
+    pub discrepant: bool,
+}
+
+impl Comparison {
+    /// Station minus provider.
+    pub fn difference(&self) -> f64 {
+        self.station - self.provider
+    }
+}
+
+/// Compares every value the station reports with the provider's.
+pub fn compare(local: &LocalObservation, current: &Conditions) -> Vec<Comparison> {
+    LIMITS
+        .iter()
+        .filter_map(|&(field, label, limit)| {
+            let (station, provider) = match field {
+                "temperature_c" => (local.temperature_c?, current.temperature_c),
+                "humidity_percent" => (local.humidity_percent?, current.humidity_percent as f64),
+                "pressure_hpa" => (local.pressure_hpa?, current.pressure_hpa as f64),
+                "wind_speed_ms" => (local.wind_speed_ms?, current.wind_speed_ms),
+                _ => return None,
+            };
+            Some(Comparison {
+                field,
+                label,
+                station,
+                provider,
+                discrepant: (station - provider).abs() > limit,
+            })
+        })
+        .collect()
+}
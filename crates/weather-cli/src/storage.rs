@@ -0,0 +1,407 @@
+//! The observation log: every recorded report, in a pluggable backend chosen
+//! in the config file.
+//!
+//! ```toml
+//! [storage]
+//! backend = "sqlite"   # jsonl (default), sqlite or influx
+//! record = true        # append each lookup and daemon refresh
+//!
+//! [storage.influx]
+//! url = "http://nas.local:8086"
+//! bucket = "weather"
+//! org = "home"
+//! token = "..."
+//! ```
+//!
+//! Appending is idempotent: a report whose location, observation time and
+//! provider are already stored is skipped, so overlapping cron runs and
+//! daemon refreshes of an unchanged observation add nothing. (Influx does
+//! the same by itself: a point with the same series and timestamp replaces
+//! the earlier one.)
+//!
+//! Local backends live in the data directory unless `path` is given. The
+//! SQLite backend drives the `sqlite3` command-line shell, so no database
+//! library is linked in. Influx is write-only: read it back with the tools
+//! of the stack it feeds.
+
+use crate::speak::find_executable;
+use crate::{http_client, influx, migrate, platform};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::future::Future;
+use std::io::Write;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::sync::OnceCell;
+use weather_core::model::Report;
+
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, Error>> + Send + 'a>>;
+
+/// A place observations are appended to and, for local backends, read from.
+pub trait Storage: Send + Sync {
+    /// Human-readable description, e.g. the file path.
+    fn describe(&self) -> String;
+
+    /// Appends `reports` as one batch, skipping observations already stored.
+    fn append<'a>(&'a self, reports: &'a [Report]) -> BoxFuture<'a, ()>;
+
+    /// Stored reports matching `filter`, oldest first.
+    fn observations<'a>(&'a self, filter: &'a Filter) -> BoxFuture<'a, Vec<Report>>;
+}
+
+#[derive(Debug, Default)]
+pub struct Filter {
+    /// Location name, compared case-insensitively.
+    pub location: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl Filter {
+    fn matches(&self, report: &Report) -> bool {
+        self.location
+            .as_ref()
+            .is_none_or(|name| report.location.name.eq_ignore_ascii_case(name))
+            && self.since.is_none_or(|since| report.observed_at >= since)
+    }
+}
+
+/// What makes two reports the same observation.
+fn key(report: &Report) -> (String, i64, String) {
+    (
+        report.location.name.clone(),
+        report.observed_at.timestamp(),
+        report.source.provider.clone(),
+    )
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    #[default]
+    Jsonl,
+    Sqlite,
+    Influx,
+}
+
+/// The `[storage]` section of the config.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    pub backend: Backend,
+    /// File of a local backend, instead of the data directory.
+    pub path: Option<PathBuf>,
+    /// Whether lookups are appended to the log.
+    pub record: bool,
+    pub influx: Option<influx::Target>,
+}
+
+pub fn validate(settings: &Settings) -> Result<(), String> {
+    if settings.backend == Backend::Influx && settings.influx.is_none() {
+        return Err("storage backend 'influx' needs a [storage.influx] section".to_string());
+    }
+    Ok(())
+}
+
+/// Opens the configured backend.
+pub fn open(settings: &Settings) -> Result<Box<dyn Storage>, String> {
+    let path = |file: &str| {
+        settings
+            .path
+            .clone()
+            .or_else(|| platform::dir(platform::Dir::Data).map(|dir| dir.join(file)))
+            .ok_or_else(|| "Cannot determine the data directory".to_string())
+    };
+    Ok(match settings.backend {
+        Backend::Jsonl => Box::new(JsonlStorage {
+            path: path("observations.jsonl")?,
+        }),
+        Backend::Sqlite => Box::new(SqliteStorage {
+            path: path("observations.sqlite3")?,
+            prepared: OnceCell::new(),
+        }),
+        Backend::Influx => Box::new(InfluxStorage {
+            target: settings
+                .influx
+                .clone()
+                .ok_or("storage backend 'influx' needs a [storage.influx] section")?,
+        }),
+    })
+}
+
+/// One JSON report per line, appended to a file.
+struct JsonlStorage {
+    path: PathBuf,
+}
+
+impl Storage for JsonlStorage {
+    fn describe(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn append<'a>(&'a self, reports: &'a [Report]) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            if let Some(dir) = self.path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            let mut seen: HashSet<_> = self.read()?.iter().map(key).collect();
+            let mut lines = String::new();
+            for report in reports {
+                if seen.insert(key(report)) {
+                    lines.push_str(&serde_json::to_string(report)?);
+                    lines.push('\n');
+                }
+            }
+            if lines.is_empty() {
+                return Ok(());
+            }
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+            // A single write, so concurrent appends don't interleave.
+            file.write_all(lines.as_bytes())?;
+            Ok(())
+        })
+    }
+
+    fn observations<'a>(&'a self, filter: &'a Filter) -> BoxFuture<'a, Vec<Report>> {
+        Box::pin(async move {
+            let mut reports: Vec<Report> = self
+                .read()?
+                .into_iter()
+                .filter(|report| filter.matches(report))
+                .collect();
+            reports.sort_by_key(|r| r.observed_at);
+            Ok(reports)
+        })
+    }
+}
+
+impl JsonlStorage {
+    /// Every stored report, in file order. Duplicates written before
+    /// appending became idempotent are dropped.
+    fn read(&self) -> Result<Vec<Report>, Error> {
+        let text = match std::fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut seen = HashSet::new();
+        let mut reports = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let report: Report = serde_json::from_str(line)
+                .map_err(|e| format!("{} line {}: {}", self.path.display(), number + 1, e))?;
+            if seen.insert(key(&report)) {
+                reports.push(report);
+            }
+        }
+        Ok(reports)
+    }
+}
+
+/// A SQLite database, driven through the `sqlite3` shell.
+struct SqliteStorage {
+    path: PathBuf,
+    /// Set once the schema exists and is at [`migrate::SQLITE_VERSION`].
+    /// That is done on first use rather than when opening, so that opening
+    /// the log only to describe it does not create the database.
+    prepared: OnceCell<()>,
+}
+
+const SQLITE_SCHEMA: &str = "\
+CREATE TABLE IF NOT EXISTS observations (
+    id INTEGER PRIMARY KEY,
+    observed_at INTEGER NOT NULL,
+    location TEXT NOT NULL,
+    provider TEXT NOT NULL,
+    report TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS observations_location_time
+    ON observations (location, observed_at);
+";
+
+impl SqliteStorage {
+    /// Runs `sql` once the schema is in place, returning the output with
+    /// one row per line.
+    async fn execute(&self, sql: &str) -> Result<String, Error> {
+        self.prepared
+            .get_or_try_init(|| async {
+                self.migrate().await?;
+                self.run(&format!(
+                    "{}PRAGMA user_version = {};\n",
+                    SQLITE_SCHEMA,
+                    migrate::SQLITE_VERSION
+                ))
+                .await
+                .map(drop)
+            })
+            .await?;
+        self.run(sql).await
+    }
+
+    /// Brings an existing database to the current version, backing it up
+    /// first when there is anything to do.
+    async fn migrate(&self) -> Result<(), Error> {
+        if !self.path.exists() {
+            return Ok(());
+        }
+        // 0 is a database from before versioning, laid out as version 1.
+        let found = self
+            .run("PRAGMA user_version;\n")
+            .await?
+            .trim()
+            .parse::<u32>()?
+            .max(1);
+        let what = format!("The observation database {}", self.path.display());
+        let steps = migrate::pending(&what, found, migrate::SQLITE_VERSION, migrate::SQLITE_STEPS)?;
+        if steps.is_empty() {
+            return Ok(());
+        }
+        migrate::backup(&self.path, found)?;
+        let mut sql = String::from("BEGIN;\n");
+        migrate::apply(&mut sql, &steps)?;
+        sql.push_str(&format!(
+            "PRAGMA user_version = {};\nCOMMIT;\n",
+            migrate::SQLITE_VERSION
+        ));
+        self.run(&sql).await?;
+        Ok(())
+    }
+
+    /// Runs `sql` as is.
+    async fn run(&self, sql: &str) -> Result<String, Error> {
+        let program = find_executable("sqlite3")
+            .ok_or("The sqlite storage backend needs the sqlite3 command-line shell")?;
+        if let Some(dir) = self.path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        let mut child = Command::new(program)
+            .args(["-batch", "-bail", "-list", "-noheader"])
+            .arg(&self.path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+        let mut stdin = child
+            .stdin
+            .take()
+            .ok_or("Failed to open stdin of sqlite3")?;
+        // Written while the output is read, so a large result cannot fill
+        // the pipe and stall both sides.
+        let write = async move {
+            stdin.write_all(sql.as_bytes()).await?;
+            stdin.shutdown().await
+        };
+        let (written, output) = tokio::join!(write, child.wait_with_output());
+        let output = output?;
+        if !output.status.success() {
+            return Err(format!(
+                "sqlite3: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into());
+        }
+        written?;
+        Ok(String::from_utf8(output.stdout)?)
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn describe(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn append<'a>(&'a self, reports: &'a [Report]) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            let mut sql = String::from("BEGIN;\n");
+            for report in reports {
+                let (location, observed_at, provider) = key(report);
+                let (location, provider) = (sql_string(&location), sql_string(&provider));
+                sql.push_str(&format!(
+                    "INSERT INTO observations (observed_at, location, provider, report) \
+                     SELECT {observed_at}, {location}, {provider}, {} \
+                     WHERE NOT EXISTS (SELECT 1 FROM observations WHERE location = {location} \
+                     AND observed_at = {observed_at} AND provider = {provider});\n",
+                    sql_string(&serde_json::to_string(report)?),
+                ));
+            }
+            sql.push_str("COMMIT;\n");
+            self.execute(&sql).await?;
+            Ok(())
+        })
+    }
+
+    fn observations<'a>(&'a self, filter: &'a Filter) -> BoxFuture<'a, Vec<Report>> {
+        Box::pin(async move {
+            // Rows duplicated before appending became idempotent count once.
+            let mut conditions = vec!["id IN (SELECT MIN(id) FROM observations \
+                 GROUP BY location, observed_at, provider)"
+                .to_string()];
+            if let Some(location) = &filter.location {
+                conditions.push(format!(
+                    "location = {} COLLATE NOCASE",
+                    sql_string(location)
+                ));
+            }
+            if let Some(since) = filter.since {
+                conditions.push(format!("observed_at >= {}", since.timestamp()));
+            }
+            let output = self
+                .execute(&format!(
+                    "SELECT report FROM observations WHERE {} ORDER BY observed_at;",
+                    conditions.join(" AND ")
+                ))
+                .await?;
+            // Reports are stored as compact JSON, so each row is one line.
+            output
+                .lines()
+                .map(|line| serde_json::from_str(line).map_err(Error::from))
+                .collect()
+        })
+    }
+}
+
+/// A single-quoted SQL string literal. The shell reads SQL as text and
+/// cannot bind parameters, so values are quoted instead.
+fn sql_string(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// An InfluxDB or VictoriaMetrics server, written with line protocol.
+struct InfluxStorage {
+    target: influx::Target,
+}
+
+impl Storage for InfluxStorage {
+    fn describe(&self) -> String {
+        self.target.url.clone()
+    }
+
+    fn append<'a>(&'a self, reports: &'a [Report]) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            let lines: Vec<String> = reports.iter().map(influx::line).collect();
+            influx::write(&http_client()?, &self.target.endpoint(), &lines.join("\n"))
+                .await
+                .map_err(|e| e.to_string().into())
+        })
+    }
+
+    fn observations<'a>(&'a self, _filter: &'a Filter) -> BoxFuture<'a, Vec<Report>> {
+        Box::pin(async move {
+            Err(format!(
+                "The influx backend is write-only; query {} directly",
+                self.target.url
+            )
+            .into())
+        })
+    }
+}
@@ -0,0 +1,379 @@
+//! Template-based natural-language summaries of current and forecast weather,
+//! e.g. "Cloudy this morning, clearing by noon; high of 21."
+
+use crate::i18n::Catalog;
+use crate::{local_datetime, recommend, DisplayOptions};
+use chrono::Timelike;
+use weather_core::model::Report;
+use weather_providers::forecast::{Forecast, ForecastEntry};
+
+/// Broad sky condition used for text planning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Sky {
+    Clear,
+    Cloudy,
+    Fog,
+    Drizzle,
+    Rain,
+    Snow,
+    Storm,
+}
+
+impl Sky {
+    fn from_condition(main: &str) -> Sky {
+        match main.to_lowercase().as_str() {
+            "clear" => Sky::Clear,
+            "thunderstorm" | "squall" | "tornado" => Sky::Storm,
+            "drizzle" => Sky::Drizzle,
+            "rain" => Sky::Rain,
+            "snow" => Sky::Snow,
+            "mist" | "fog" | "haze" | "smoke" | "dust" | "sand" | "ash" => Sky::Fog,
+            _ => Sky::Cloudy,
+        }
+    }
+
+    fn is_wet(self) -> bool {
+        matches!(self, Sky::Drizzle | Sky::Rain | Sky::Snow | Sky::Storm)
+    }
+
+    /// Message key of the adjective, e.g. "Sunny".
+    fn adjective(self, night: bool) -> &'static str {
+        match self {
+            Sky::Clear if night => "sky.clear_night",
+            Sky::Clear => "sky.clear",
+            Sky::Cloudy => "sky.cloudy",
+            Sky::Fog => "sky.fog",
+            Sky::Drizzle => "sky.drizzle",
+            Sky::Rain => "sky.rain",
+            Sky::Snow => "sky.snow",
+            Sky::Storm => "sky.storm",
+        }
+    }
+
+    /// Message key of the phrase describing a change *to* this condition.
+    fn transition(self) -> &'static str {
+        match self {
+            Sky::Clear => "transition.clear",
+            Sky::Cloudy => "transition.cloudy",
+            Sky::Fog => "transition.fog",
+            Sky::Drizzle => "transition.drizzle",
+            Sky::Rain => "transition.rain",
+            Sky::Snow => "transition.snow",
+            Sky::Storm => "transition.storm",
+        }
+    }
+
+    fn precipitation_noun(self) -> &'static str {
+        match self {
+            Sky::Snow => "precipitation.snow",
+            Sky::Storm => "precipitation.storm",
+            _ => "precipitation.rain",
+        }
+    }
+}
+
+/// Part of the day a forecast entry falls into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Period {
+    Overnight,
+    Morning,
+    Afternoon,
+    Evening,
+}
+
+impl Period {
+    fn from_hour(hour: u32) -> Period {
+        match hour {
+            0..=5 => Period::Overnight,
+            6..=11 => Period::Morning,
+            12..=17 => Period::Afternoon,
+            _ => Period::Evening,
+        }
+    }
+
+    fn is_night(self) -> bool {
+        matches!(self, Period::Overnight | Period::Evening)
+    }
+
+    /// Message key of "this morning", used for the period a segment starts
+    /// in.
+    fn during(self, tomorrow: bool) -> &'static str {
+        match (self, tomorrow) {
+            (Period::Overnight, _) => "during.overnight",
+            (Period::Morning, false) => "during.morning",
+            (Period::Morning, true) => "during.tomorrow_morning",
+            (Period::Afternoon, false) => "during.afternoon",
+            (Period::Afternoon, true) => "during.tomorrow_afternoon",
+            (Period::Evening, false) => "during.evening",
+            (Period::Evening, true) => "during.tomorrow_evening",
+        }
+    }
+
+    /// Message key of "by noon", used for the period a change happens in.
+    fn by(self) -> &'static str {
+        match self {
+            Period::Overnight => "by.overnight",
+            Period::Morning => "by.morning",
+            Period::Afternoon => "by.afternoon",
+            Period::Evening => "by.evening",
+        }
+    }
+}
+
+/// A run of consecutive hours with the same sky condition.
+struct Segment {
+    sky: Sky,
+    period: Period,
+    tomorrow: bool,
+}
+
+/// Forecast hours covered by the summary.
+const HORIZON_HOURS: i64 = 15;
+
+/// Summarizes the current conditions and, when available, the forecast for
+/// the rest of the day in one or two sentences.
+pub fn summarize(report: &Report, forecast: Option<&Forecast>, options: &DisplayOptions) -> String {
+    let catalog = &options.catalog;
+    let observed = report.observed_at.timestamp();
+    let offset = report.location.utc_offset_seconds;
+    let now = local_datetime(observed, offset);
+    let current_sky = Sky::from_condition(&report.current.condition.kind);
+
+    let upcoming: Vec<&ForecastEntry> = forecast
+        .map(|f| {
+            f.list
+                .iter()
+                .filter(|e| e.dt > observed && e.dt <= observed + HORIZON_HOURS * 3600)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut sentences = Vec::new();
+
+    // Sentence 1: how the sky evolves, plus the high (or low, late in the day).
+    let segments = plan_segments(report, current_sky, &upcoming);
+    let mut first = describe_segments(&segments, catalog);
+    let today_temps = upcoming
+        .iter()
+        .filter(|e| local_datetime(e.dt, offset).date_naive() == now.date_naive())
+        .map(|e| e.main.temp_max)
+        .chain(std::iter::once(report.current.temperature_c));
+    if now.hour() < 17 {
+        let high = today_temps.fold(f64::MIN, f64::max);
+        first = catalog.format(
+            "summary.high",
+            &[
+                ("text", &first),
+                ("temp", &whole_degrees(high, options).to_string()),
+            ],
+        );
+    } else {
+        let low = upcoming
+            .iter()
+            .map(|e| e.main.temp_min)
+            .chain(std::iter::once(report.current.temperature_c))
+            .fold(f64::MAX, f64::min);
+        first = catalog.format(
+            "summary.low",
+            &[
+                ("text", &first),
+                ("temp", &whole_degrees(low, options).to_string()),
+            ],
+        );
+    }
+    sentences.push(first);
+
+    // Sentence 2: the most noteworthy extra detail, if any.
+    if let Some(chance) = precipitation_chance(report, &segments, &upcoming, catalog) {
+        sentences.push(chance);
+    } else if let Some(gusts) = strongest_gust(report, &upcoming).filter(|g| *g >= 12.0) {
+        sentences.push(catalog.format("summary.windy", &[("speed", &whole_speed(gusts, options))]));
+    } else if upcoming.is_empty() {
+        sentences.push(catalog.format(
+            "summary.feels_like",
+            &[(
+                "temp",
+                &whole_degrees(report.current.feels_like_c, options).to_string(),
+            )],
+        ));
+    }
+
+    sentences
+        .into_iter()
+        .map(|s| format!("{}.", s))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The summary followed by everyday advice, worded to be read aloud.
+pub fn spoken_summary(
+    report: &Report,
+    forecast: Option<&Forecast>,
+    options: &DisplayOptions,
+) -> String {
+    options.catalog.format(
+        "summary.spoken",
+        &[
+            ("place", &report.location.name),
+            ("summary", &summarize(report, forecast, options)),
+            (
+                "advice",
+                &capitalize(recommend::recommend(&report.current).advice),
+            ),
+        ],
+    )
+}
+
+fn plan_segments(report: &Report, current: Sky, upcoming: &[&ForecastEntry]) -> Vec<Segment> {
+    let offset = report.location.utc_offset_seconds;
+    let now = local_datetime(report.observed_at.timestamp(), offset);
+    let mut segments = vec![Segment {
+        sky: current,
+        period: Period::from_hour(now.hour()),
+        tomorrow: false,
+    }];
+
+    for entry in upcoming {
+        let time = local_datetime(entry.dt, offset);
+        let mut sky = entry
+            .weather
+            .first()
+            .map_or(Sky::Cloudy, |w| Sky::from_condition(&w.main));
+        // A rain icon with a low chance of rain is not worth a transition.
+        if sky.is_wet() && entry.pop < 0.3 {
+            sky = Sky::Cloudy;
+        }
+        if segments.last().map(|s| s.sky) != Some(sky) {
+            segments.push(Segment {
+                sky,
+                period: Period::from_hour(time.hour()),
+                tomorrow: time.date_naive() > now.date_naive() && time.hour() >= 6,
+            });
+        }
+    }
+
+    // Merge changes within the same part of the day, keeping any wet
+    // condition, so the sentence mentions each period at most once.
+    let mut merged: Vec<Segment> = Vec::new();
+    for segment in segments {
+        match merged.last_mut() {
+            Some(last) if last.period == segment.period && last.tomorrow == segment.tomorrow => {
+                if segment.sky.is_wet() {
+                    last.sky = segment.sky;
+                }
+            }
+            _ => merged.push(segment),
+        }
+    }
+    merged.dedup_by(|next, previous| next.sky == previous.sky);
+
+    merged.truncate(3);
+    merged
+}
+
+fn describe_segments(segments: &[Segment], catalog: &Catalog) -> String {
+    let first = &segments[0];
+    let sky = catalog.text(first.sky.adjective(first.period.is_night()));
+
+    if segments.len() == 1 {
+        let key = match first.period {
+            Period::Overnight | Period::Evening => "summary.only_tonight",
+            _ => "summary.only_today",
+        };
+        return catalog.format(key, &[("sky", sky)]);
+    }
+
+    let mut text = catalog.format(
+        "summary.start",
+        &[
+            ("sky", sky),
+            ("when", catalog.text(first.period.during(first.tomorrow))),
+        ],
+    );
+    for segment in &segments[1..] {
+        let when = if segment.tomorrow {
+            segment.period.during(true)
+        } else {
+            segment.period.by()
+        };
+        text = catalog.format(
+            "summary.change",
+            &[
+                ("start", &text),
+                ("change", catalog.text(segment.sky.transition())),
+                ("when", catalog.text(when)),
+            ],
+        );
+    }
+    text
+}
+
+fn precipitation_chance(
+    report: &Report,
+    segments: &[Segment],
+    upcoming: &[&ForecastEntry],
+    catalog: &Catalog,
+) -> Option<String> {
+    let offset = report.location.utc_offset_seconds;
+    let now = local_datetime(report.observed_at.timestamp(), offset);
+    let wettest = upcoming.iter().max_by(|a, b| {
+        a.pop
+            .partial_cmp(&b.pop)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    })?;
+    if wettest.pop < 0.3 {
+        return None;
+    }
+
+    let sky = wettest
+        .weather
+        .first()
+        .map_or(Sky::Rain, |w| Sky::from_condition(&w.main));
+    if segments.iter().any(|s| s.sky == sky) && wettest.pop >= 0.7 {
+        // Already announced as a transition with near certainty.
+        return None;
+    }
+
+    let time = local_datetime(wettest.dt, offset);
+    let tomorrow = time.date_naive() > now.date_naive() && time.hour() >= 6;
+    Some(catalog.format(
+        "summary.chance",
+        &[
+            (
+                "percent",
+                &((wettest.pop * 100.0).round() as i64).to_string(),
+            ),
+            ("precipitation", catalog.text(sky.precipitation_noun())),
+            (
+                "when",
+                catalog.text(Period::from_hour(time.hour()).during(tomorrow)),
+            ),
+        ],
+    ))
+}
+
+fn strongest_gust(report: &Report, upcoming: &[&ForecastEntry]) -> Option<f64> {
+    upcoming
+        .iter()
+        .map(|e| e.wind.gust.unwrap_or(e.wind.speed))
+        .chain(report.current.wind_gust_ms)
+        .reduce(f64::max)
+}
+
+/// A wind speed rounded to whole units, with its unit, e.g. `14 m/s`.
+fn whole_speed(metres_per_second: f64, options: &DisplayOptions) -> String {
+    let (speed, _, unit) = options.units.speed(metres_per_second);
+    options.locale.quantity(speed, 0, unit)
+}
+
+fn whole_degrees(celsius: f64, options: &DisplayOptions) -> i64 {
+    options.units.temperature(celsius).0.round() as i64
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
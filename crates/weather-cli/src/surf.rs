@@ -0,0 +1,360 @@
+//! `weather surf`: a rating of each session, dawn, midday and dusk, at a
+//! surf spot, from Open-Meteo's swell forecast and the wind relative to the
+//! way the beach faces. Spots are set up in the config:
+//!
+//! ```toml
+//! [surf.spots."Fistral"]
+//! lat = 50.4167
+//! lon = -5.1
+//! facing = 300  # towards the sea, in degrees from north
+//! ```
+//!
+//! Swell reaches a beach smaller the further it arrives from straight in,
+//! and longer periods carry more power. Wind blowing off the land holds the
+//! faces up; wind off the sea, and to a lesser degree across the beach,
+//! chops them up.
+
+use crate::wind_window;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use weather_core::{astro, wind};
+use weather_providers::marine::{Marine, MarineWind};
+
+/// The most days the marine forecast covers.
+pub const MAX_DAYS: u32 = 7;
+
+/// Swell smaller than this where it meets the beach, in metres, makes no
+/// waves worth paddling out for.
+const FLAT_M: f64 = 0.3;
+/// Swell from this much above [`FLAT_M`] is as big as the rating counts.
+const FULL_SIZE_M: f64 = 1.2;
+/// Periods, in seconds, from short wind swell to long groundswell.
+const SHORT_PERIOD_S: f64 = 5.0;
+const LONG_PERIOD_S: f64 = 12.0;
+/// Wind below this, in m/s, leaves the surface glassy.
+const CALM_MS: f64 = 2.0;
+
+/// `[surf]` in the config.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    /// Surf spots by the name given to `weather surf`.
+    pub spots: BTreeMap<String, Spot>,
+}
+
+impl Settings {
+    /// The spot called `name`, whatever its case, and its configured name.
+    pub fn find(&self, name: &str) -> Option<(&str, &Spot)> {
+        self.spots
+            .iter()
+            .find(|(spot, _)| spot.eq_ignore_ascii_case(name))
+            .map(|(spot, settings)| (spot.as_str(), settings))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Spot {
+    pub lat: f64,
+    pub lon: f64,
+    /// The direction the beach faces, out to sea, in degrees from north.
+    pub facing: f64,
+}
+
+/// Checks the position and orientation of each spot.
+pub fn validate(settings: &Settings) -> Result<(), String> {
+    for (name, spot) in &settings.spots {
+        if !(-90.0..=90.0).contains(&spot.lat) || !(-180.0..=180.0).contains(&spot.lon) {
+            return Err(format!(
+                "Surf spot '{}': lat must be within ±90 and lon within ±180",
+                name
+            ));
+        }
+        if !(0.0..=360.0).contains(&spot.facing) {
+            return Err(format!(
+                "Surf spot '{}': facing must be in degrees from 0 to 360, not {}",
+                name, spot.facing
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// The way a beach faces, for `--facing`: degrees or a compass point.
+pub fn parse_facing(text: &str) -> Result<f64, String> {
+    wind_window::bearing(text).ok_or_else(|| {
+        format!(
+            "Expected the direction the beach faces in degrees from 0 to 360 or a compass point such as WSW, not '{}'",
+            text
+        )
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Period {
+    Dawn,
+    Midday,
+    Dusk,
+}
+
+impl Period {
+    pub fn label(self) -> &'static str {
+        match self {
+            Period::Dawn => "Dawn",
+            Period::Midday => "Midday",
+            Period::Dusk => "Dusk",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rating {
+    Flat,
+    Poor,
+    Fair,
+    Good,
+    Epic,
+}
+
+impl Rating {
+    pub fn label(self) -> &'static str {
+        match self {
+            Rating::Flat => "Flat",
+            Rating::Poor => "Poor",
+            Rating::Fair => "Fair",
+            Rating::Good => "Good",
+            Rating::Epic => "Epic",
+        }
+    }
+
+    /// Stars out of four.
+    pub fn stars(self) -> usize {
+        self as usize
+    }
+}
+
+/// The wind relative to the beach.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Wind {
+    Calm,
+    /// From the land: the best.
+    Offshore,
+    CrossShore,
+    /// From the sea.
+    Onshore,
+}
+
+impl Wind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Wind::Calm => "glassy",
+            Wind::Offshore => "offshore",
+            Wind::CrossShore => "cross-shore",
+            Wind::Onshore => "onshore",
+        }
+    }
+}
+
+/// The conditions over a session, averaged over its hours.
+#[derive(Debug, Clone, Serialize)]
+pub struct Session {
+    pub period: Period,
+    /// Unix timestamps.
+    pub start: i64,
+    pub end: i64,
+    pub swell_height_m: f64,
+    pub swell_period_s: f64,
+    /// Where the swell comes from, in degrees.
+    pub swell_from_deg: f64,
+    pub wind_speed_ms: f64,
+    pub wind_from_deg: f64,
+    pub wind: Wind,
+    pub rating: Rating,
+}
+
+/// The sessions of each forecast day at `spot` that have not ended by
+/// `now`, in order. Sessions without a wave forecast are left out; at the
+/// poles, so are dawn and dusk when the sun does not rise or set.
+pub fn sessions(marine: &Marine, wind: &MarineWind, spot: &Spot, now: i64) -> Vec<Session> {
+    let offset = marine.utc_offset_seconds;
+    let local_date = |time: i64| {
+        chrono::DateTime::from_timestamp(time + offset as i64, 0).map(|time| time.date_naive())
+    };
+    let (Some(first), Some(last)) = (
+        marine.hourly.time.first().and_then(|&t| local_date(t)),
+        marine.hourly.time.last().and_then(|&t| local_date(t)),
+    ) else {
+        return Vec::new();
+    };
+
+    let mut sessions = Vec::new();
+    for date in first.iter_days().take_while(|date| *date <= last) {
+        let sun = astro::sun_times(spot.lat, spot.lon, date, offset);
+        let windows = [
+            (
+                Period::Dawn,
+                sun.sunrise.map(|rise| (rise - 1800, rise + 9000)),
+            ),
+            (
+                Period::Midday,
+                Some((sun.solar_noon - 5400, sun.solar_noon + 5400)),
+            ),
+            (Period::Dusk, sun.sunset.map(|set| (set - 9000, set + 1800))),
+        ];
+        for (period, window) in windows {
+            let Some((start, end)) = window.filter(|(_, end)| *end > now) else {
+                continue;
+            };
+            sessions.extend(session(marine, wind, spot, period, start, end));
+        }
+    }
+    sessions
+}
+
+fn session(
+    marine: &Marine,
+    wind: &MarineWind,
+    spot: &Spot,
+    period: Period,
+    start: i64,
+    end: i64,
+) -> Option<Session> {
+    let hourly = &marine.hourly;
+    let during = |times: &[i64], series| samples(times, series, start, end);
+    // The swell, else the waves when the models give no swell apart.
+    let mut swell = during(
+        &hourly.time,
+        [
+            &hourly.swell_wave_height,
+            &hourly.swell_wave_period,
+            &hourly.swell_wave_direction,
+        ],
+    );
+    if swell.is_empty() {
+        swell = during(
+            &hourly.time,
+            [
+                &hourly.wave_height,
+                &hourly.wave_period,
+                &hourly.wave_direction,
+            ],
+        );
+    }
+    let winds = samples(
+        &wind.hourly.time,
+        [&wind.hourly.wind_speed_10m, &wind.hourly.wind_direction_10m],
+        start,
+        end,
+    );
+    if swell.is_empty() || winds.is_empty() {
+        return None;
+    }
+
+    let mean = |values: &[[f64; 3]], k: usize| {
+        values.iter().map(|v| v[k]).sum::<f64>() / values.len() as f64
+    };
+    let swell_height_m = mean(&swell, 0);
+    let swell_period_s = mean(&swell, 1);
+    let swell_from_deg = mean_direction(swell.iter().map(|s| s[2]));
+    let wind_speed_ms = winds.iter().map(|w| w[0]).sum::<f64>() / winds.len() as f64;
+    let wind_from_deg = mean_direction(winds.iter().map(|w| w[1]));
+    let (wind, rating) = rate(
+        spot,
+        swell_height_m,
+        swell_period_s,
+        swell_from_deg,
+        wind_speed_ms,
+        wind_from_deg,
+    );
+    Some(Session {
+        period,
+        start,
+        end,
+        swell_height_m,
+        swell_period_s,
+        swell_from_deg,
+        wind_speed_ms,
+        wind_from_deg,
+        wind,
+        rating,
+    })
+}
+
+/// The values of `series` at each of `times` within `start..end` where
+/// none is missing.
+fn samples<const N: usize>(
+    times: &[i64],
+    series: [&Vec<Option<f64>>; N],
+    start: i64,
+    end: i64,
+) -> Vec<[f64; N]> {
+    times
+        .iter()
+        .enumerate()
+        .filter(|(_, time)| (start..end).contains(*time))
+        .filter_map(|(i, _)| {
+            let mut values = [0.0; N];
+            for (value, series) in values.iter_mut().zip(series) {
+                *value = series.get(i).copied().flatten()?;
+            }
+            Some(values)
+        })
+        .collect()
+}
+
+/// Directions averaged as vectors, so 350° and 10° make north.
+fn mean_direction(degrees: impl Iterator<Item = f64>) -> f64 {
+    let (x, y) = degrees.fold((0.0, 0.0), |(x, y), degrees: f64| {
+        let angle = degrees.to_radians();
+        (x + angle.sin(), y + angle.cos())
+    });
+    f64::atan2(x, y).to_degrees().rem_euclid(360.0)
+}
+
+fn rate(
+    spot: &Spot,
+    swell_height_m: f64,
+    swell_period_s: f64,
+    swell_from_deg: f64,
+    wind_speed_ms: f64,
+    wind_from_deg: f64,
+) -> (Wind, Rating) {
+    // Relative to the beach, a wind "heading" out to sea blows onshore.
+    let components = wind::components(wind_speed_ms, wind_from_deg, spot.facing);
+    let off_axis = (wind_from_deg - spot.facing + 180.0).rem_euclid(360.0) - 180.0;
+    let kind = match off_axis.abs() {
+        _ if wind_speed_ms < CALM_MS => Wind::Calm,
+        angle if angle <= 45.0 => Wind::Onshore,
+        angle if angle >= 135.0 => Wind::Offshore,
+        _ => Wind::CrossShore,
+    };
+
+    let exposure = (swell_from_deg - spot.facing).to_radians().cos().max(0.0);
+    let height = swell_height_m * exposure;
+    if height < FLAT_M {
+        return (kind, Rating::Flat);
+    }
+    let size = ((height - FLAT_M) / FULL_SIZE_M).min(1.0);
+    let power =
+        ((swell_period_s - SHORT_PERIOD_S) / (LONG_PERIOD_S - SHORT_PERIOD_S)).clamp(0.0, 1.0);
+    let swell = size * (0.4 + 0.6 * power);
+    let wind = match kind {
+        Wind::Calm => 1.0,
+        // A strong offshore wind makes waves hard to catch.
+        _ => (1.0
+            - components.head.max(0.0) / 8.0
+            - components.cross.abs() / 16.0
+            - (-components.head - 10.0).max(0.0) / 10.0)
+            .clamp(0.1, 1.0),
+    };
+    let rating = match swell * wind {
+        score if score < 0.2 => Rating::Poor,
+        score if score < 0.45 => Rating::Fair,
+        score if score < 0.7 => Rating::Good,
+        _ => Rating::Epic,
+    };
+    (kind, rating)
+}
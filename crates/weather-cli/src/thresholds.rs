@@ -0,0 +1,250 @@
+//! User-defined warn/critical thresholds per field, from the config:
+//!
+//! ```toml
+//! [thresholds.wind_gust]
+//! warn = 15
+//! critical = 25
+//!
+//! [thresholds.temperature]
+//! direction = "below"
+//! warn = 0
+//! critical = -10
+//! ```
+//!
+//! Values are in metric units (°C, m/s, %, hPa, metres of visibility, mm of
+//! rain). Fields are the core values below or any derived metric key.
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use weather_core::metrics;
+use weather_core::model::Conditions;
+
+/// Core fields that thresholds can be set on, besides derived metrics.
+pub const FIELDS: &[&str] = &[
+    "temperature",
+    "feels_like",
+    "humidity",
+    "pressure",
+    "wind_speed",
+    "wind_gust",
+    "visibility",
+    "cloudiness",
+    "rain_1h",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Ok,
+    Warn,
+    Critical,
+}
+
+impl Severity {
+    /// The exit status for `--exit-status`: 0, 1 for a warning, 2 when
+    /// critical.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Severity::Ok => 0,
+            Severity::Warn => 1,
+            Severity::Critical => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    /// Values at or above the limits breach them.
+    #[default]
+    Above,
+    /// Values at or below the limits breach them.
+    Below,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Threshold {
+    pub direction: Direction,
+    pub warn: Option<f64>,
+    pub critical: Option<f64>,
+}
+
+impl Threshold {
+    pub fn severity(&self, value: f64) -> Severity {
+        let breached = |limit: Option<f64>| {
+            limit.is_some_and(|limit| match self.direction {
+                Direction::Above => value >= limit,
+                Direction::Below => value <= limit,
+            })
+        };
+        if breached(self.critical) {
+            Severity::Critical
+        } else if breached(self.warn) {
+            Severity::Warn
+        } else {
+            Severity::Ok
+        }
+    }
+}
+
+pub type Thresholds = BTreeMap<String, Threshold>;
+
+/// A field whose current value is at or beyond one of its limits.
+#[derive(Debug)]
+pub struct Breach {
+    pub field: String,
+    pub value: f64,
+    pub severity: Severity,
+    pub limit: f64,
+}
+
+/// Checks field names and that the critical limit lies beyond the warn one.
+pub fn validate(thresholds: &Thresholds) -> Result<(), String> {
+    for (field, threshold) in thresholds {
+        check_field(field)?;
+        if let (Some(warn), Some(critical)) = (threshold.warn, threshold.critical) {
+            let ordered = match threshold.direction {
+                Direction::Above => critical >= warn,
+                Direction::Below => critical <= warn,
+            };
+            if !ordered {
+                return Err(format!(
+                    "Threshold '{}': critical ({}) must be beyond warn ({})",
+                    field, critical, warn
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Fails unless `field` is a core field or a derived metric.
+pub fn check_field(field: &str) -> Result<(), String> {
+    if FIELDS.contains(&field) || metrics::lookup(field).is_some() {
+        return Ok(());
+    }
+    Err(format!(
+        "Unknown threshold field '{}'. Available: {}, or a metric ({})",
+        field,
+        FIELDS.join(", "),
+        metrics::CALCULATORS
+            .iter()
+            .map(|c| c.key)
+            .collect::<Vec<_>>()
+            .join(", ")
+    ))
+}
+
+/// Short names accepted in threshold expressions.
+const ALIASES: &[(&str, &str)] = &[
+    ("temp", "temperature"),
+    ("wind", "wind_speed"),
+    ("gust", "wind_gust"),
+    ("rain", "rain_1h"),
+    ("clouds", "cloudiness"),
+];
+
+/// Builds thresholds from expressions such as `temp>35` or
+/// `visibility<1000`, as given to `weather check`. `>` and `>=` breach at
+/// or above the limit and `<` and `<=` at or below it, as in the config.
+pub fn from_expressions(warn: &[String], critical: &[String]) -> Result<Thresholds, String> {
+    let mut thresholds = Thresholds::new();
+    for (expression, is_critical) in warn
+        .iter()
+        .map(|e| (e, false))
+        .chain(critical.iter().map(|e| (e, true)))
+    {
+        let (field, direction, limit) = parse_expression(expression)?;
+        let threshold = thresholds.entry(field.clone()).or_insert(Threshold {
+            direction,
+            ..Threshold::default()
+        });
+        if threshold.direction != direction {
+            return Err(format!(
+                "Threshold '{}': warn and critical must use the same direction",
+                field
+            ));
+        }
+        if is_critical {
+            threshold.critical = Some(limit);
+        } else {
+            threshold.warn = Some(limit);
+        }
+    }
+    validate(&thresholds)?;
+    Ok(thresholds)
+}
+
+fn parse_expression(expression: &str) -> Result<(String, Direction, f64), String> {
+    let invalid = || {
+        format!(
+            "Invalid threshold '{}': expected e.g. 'temp>35' or 'visibility<1000'",
+            expression
+        )
+    };
+    let split = expression.find(['<', '>']).ok_or_else(invalid)?;
+    let (field, rest) = expression.split_at(split);
+    let (direction, limit) = match rest.split_at(1) {
+        (">", limit) => (Direction::Above, limit),
+        (_, limit) => (Direction::Below, limit),
+    };
+    let limit = limit.strip_prefix('=').unwrap_or(limit);
+
+    let limit = limit.trim().parse().map_err(|_| invalid())?;
+    Ok((field_name(field), direction, limit))
+}
+
+/// The field a name in an expression stands for, e.g. `temperature` for
+/// `temp`.
+pub fn field_name(name: &str) -> String {
+    let name = name.trim().to_lowercase();
+    ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map_or(name.clone(), |(_, field)| field.to_string())
+}
+
+/// Current value of a core field or derived metric, if known.
+pub fn field_value(current: &Conditions, field: &str) -> Option<f64> {
+    let value = match field {
+        "temperature" => current.temperature_c,
+        "feels_like" => current.feels_like_c,
+        "humidity" => current.humidity_percent as f64,
+        "pressure" => current.pressure_hpa as f64,
+        "wind_speed" => current.wind_speed_ms,
+        "wind_gust" => current.wind_gust_ms?,
+        "visibility" => current.visibility_m as f64,
+        "cloudiness" => current.cloud_cover_percent as f64,
+        "rain_1h" => current.rain_mm?,
+        _ => {
+            return metrics::compute(current, &[field.to_string()])
+                .first()
+                .map(|(_, value)| *value)
+        }
+    };
+    Some(value)
+}
+
+/// Every configured threshold the current conditions breach, worst first.
+pub fn evaluate(thresholds: &Thresholds, current: &Conditions) -> Vec<Breach> {
+    let mut breaches: Vec<Breach> = thresholds
+        .iter()
+        .filter_map(|(field, threshold)| {
+            let value = field_value(current, field)?;
+            let severity = threshold.severity(value);
+            let limit = match severity {
+                Severity::Ok => return None,
+                Severity::Warn => threshold.warn?,
+                Severity::Critical => threshold.critical?,
+            };
+            Some(Breach {
+                field: field.clone(),
+                value,
+                severity,
+                limit,
+            })
+        })
+        .collect();
+    breaches.sort_by_key(|b| std::cmp::Reverse(b.severity));
+    breaches
+}
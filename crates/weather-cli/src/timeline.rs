@@ -0,0 +1,150 @@
+//! The 24-hour strip under the card with `--today`: one column per hour, a
+//! coloured block for the condition above a bar for precipitation
+//! intensity.
+
+use colored::Colorize;
+use weather_providers::forecast::Forecast;
+use weather_providers::onecall::OneCall;
+use weather_providers::openweathermap;
+
+const HOURS: usize = 24;
+
+/// An hour label every this many columns.
+const LABEL_EVERY: usize = 6;
+
+/// Intensity bars by upper bound in mm/h; heavier rates get a full block.
+const INTENSITY: [(f64, &str, &str); 4] = [
+    (0.1, " ", " "),
+    (0.5, "▁", "."),
+    (2.0, "▃", ":"),
+    (5.0, "▅", "|"),
+];
+
+#[derive(Debug, Clone, Copy)]
+pub struct Hour {
+    pub start: i64,
+    pub kind: &'static str,
+    /// Precipitation rate in mm/h.
+    pub precipitation: f64,
+}
+
+pub fn from_onecall(onecall: &OneCall) -> Vec<Hour> {
+    onecall
+        .hourly
+        .iter()
+        .map(|hour| Hour {
+            start: hour.dt,
+            kind: openweathermap::condition_kind(
+                hour.weather.first().map_or("", |w| w.main.as_str()),
+            ),
+            precipitation: hour.rain.as_ref().and_then(|r| r.one_hour).unwrap_or(0.0),
+        })
+        .collect()
+}
+
+/// Hours from the 3-hourly forecast: each entry fills three columns at its
+/// average rate.
+pub fn from_forecast(forecast: &Forecast) -> Vec<Hour> {
+    forecast
+        .list
+        .iter()
+        .flat_map(|entry| {
+            let kind = openweathermap::condition_kind(
+                entry.weather.first().map_or("", |w| w.main.as_str()),
+            );
+            let volume = entry.rain.as_ref().map_or(0.0, |r| r.three_hours)
+                + entry.snow.as_ref().map_or(0.0, |s| s.three_hours);
+            (0..3).map(move |i| Hour {
+                start: entry.dt + i * 3600,
+                kind,
+                precipitation: volume / 3.0,
+            })
+        })
+        .collect()
+}
+
+/// The strip's lines for the `HOURS` hours from `now`: hour labels,
+/// conditions and intensity. `hour_label` formats a column's start time.
+pub fn strip(
+    hours: &[Hour],
+    now: i64,
+    hour_label: impl Fn(i64) -> String,
+    ascii: bool,
+) -> Vec<String> {
+    let first_hour = now - now.rem_euclid(3600);
+    let columns: Vec<(i64, Option<&Hour>)> = (0..HOURS as i64)
+        .map(|i| {
+            let start = first_hour + i * 3600;
+            let hour = hours
+                .iter()
+                .find(|h| h.start <= start && start < h.start + 3600);
+            (start, hour)
+        })
+        .collect();
+
+    let mut labels = String::new();
+    for (i, (start, _)) in columns.iter().enumerate().step_by(LABEL_EVERY) {
+        let label = hour_label(*start);
+        labels.push_str(&" ".repeat(i.saturating_sub(labels.chars().count())));
+        labels.push_str(&label);
+    }
+
+    let conditions: String = columns
+        .iter()
+        .map(|(_, hour)| match hour {
+            Some(hour) => condition_cell(hour.kind, ascii),
+            None => " ".to_string(),
+        })
+        .collect();
+    let intensity: String = columns
+        .iter()
+        .map(|(_, hour)| {
+            let rate = hour.map_or(0.0, |h| h.precipitation);
+            let (bar, plain) = INTENSITY
+                .iter()
+                .find(|(limit, _, _)| rate < *limit)
+                .map_or(("█", "#"), |(_, bar, plain)| (*bar, *plain));
+            if ascii {
+                plain.to_string()
+            } else {
+                bar.bright_blue().to_string()
+            }
+        })
+        .collect();
+    vec![labels, conditions, intensity]
+}
+
+/// The legend line for the condition colours, or letters in ASCII mode.
+pub fn legend(ascii: bool) -> String {
+    ["clear", "clouds", "fog", "rain", "snow", "thunderstorm"]
+        .iter()
+        .map(|kind| format!("{} {}", condition_cell(kind, ascii), kind))
+        .collect::<Vec<_>>()
+        .join("  ")
+}
+
+fn condition_cell(kind: &str, ascii: bool) -> String {
+    if ascii {
+        return match kind {
+            "clear" => "o",
+            "clouds" => "c",
+            "fog" => "=",
+            "drizzle" | "rain" => "r",
+            "snow" => "*",
+            "thunderstorm" => "T",
+            _ => "?",
+        }
+        .to_string();
+    }
+    let block = "█";
+    match kind {
+        "clear" => block.bright_yellow(),
+        "clouds" => block.white(),
+        "fog" => block.bright_black(),
+        "drizzle" | "rain" => block.blue(),
+        "snow" => block.bright_white(),
+        "thunderstorm" => block.magenta(),
+        _ => block.normal(),
+    }
+    .to_string()
+}
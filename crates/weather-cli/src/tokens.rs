@@ -0,0 +1,144 @@
+//! OAuth tokens for cloud weather station accounts, kept in `tokens.json`
+//! in the data directory and refreshed when they expire.
+//!
+//! Netatmo needs the client id and secret of an app registered at
+//! dev.netatmo.com in `NETATMO_CLIENT_ID` and `NETATMO_CLIENT_SECRET`; the
+//! first refresh token comes from that app's token generator and is stored
+//! with `weather auth netatmo`, which reads it from standard input.
+
+use crate::platform;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use weather_providers::netatmo;
+
+/// Refresh this long before the access token expires, so it does not run
+/// out mid-request.
+const EXPIRY_MARGIN_SECONDS: i64 = 60;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TokenFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    netatmo: Option<StoredToken>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredToken {
+    access_token: Option<String>,
+    refresh_token: String,
+    /// Unix timestamp after which the access token is no longer valid.
+    expires_at: i64,
+}
+
+/// Location of the token file.
+pub fn path() -> Option<PathBuf> {
+    platform::dir(platform::Dir::Data).map(|dir| dir.join("tokens.json"))
+}
+
+fn load(path: &Path) -> Result<TokenFile, Box<dyn std::error::Error>> {
+    match std::fs::read(path) {
+        Ok(bytes) => Ok(serde_json::from_slice(&bytes)
+            .map_err(|e| format!("Invalid token file {}: {}", path.display(), e))?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(TokenFile::default()),
+        Err(e) => Err(format!("Cannot read {}: {}", path.display(), e).into()),
+    }
+}
+
+/// Writes the tokens via a rename. The staged file is created readable
+/// only by the user, so the tokens are never visible to others, even
+/// briefly.
+fn save(path: &Path, tokens: &TokenFile) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let staged = path.with_extension("json.tmp");
+    // A file left over from an interrupted save keeps its permissions, so
+    // it is replaced rather than reused.
+    match std::fs::remove_file(&staged) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+        _ => {}
+    }
+    let mut file = create_private(&staged)?;
+    file.write_all(&serde_json::to_vec_pretty(tokens)?)?;
+    file.sync_all()?;
+    std::fs::rename(&staged, path)?;
+    Ok(())
+}
+
+fn create_private(path: &Path) -> std::io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)
+}
+
+/// Reads a refresh token from standard input, prompting for it on a
+/// terminal. Tokens are not taken as arguments, which other users can see
+/// in the process list and which end up in shell history.
+pub fn read_refresh_token() -> Result<String, Box<dyn std::error::Error>> {
+    let stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        eprint!("Refresh token: ");
+        std::io::stderr().flush()?;
+    }
+    let mut line = String::new();
+    stdin.lock().read_line(&mut line)?;
+    let token = line.trim();
+    if token.is_empty() {
+        return Err("No refresh token given on standard input".into());
+    }
+    Ok(token.to_string())
+}
+
+/// Stores a Netatmo refresh token, replacing any earlier authorisation.
+pub fn store_netatmo(refresh_token: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let path = path().ok_or("Cannot determine the data directory")?;
+    let mut tokens = load(&path)?;
+    tokens.netatmo = Some(StoredToken {
+        access_token: None,
+        refresh_token: refresh_token.to_string(),
+        expires_at: 0,
+    });
+    save(&path, &tokens)?;
+    Ok(path)
+}
+
+/// A valid Netatmo access token, refreshed and stored again when the
+/// current one has expired. Uses the real time, not `--as-of`.
+pub async fn netatmo_access_token(
+    client: &reqwest::Client,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let now = chrono::Utc::now().timestamp();
+    let path = path().ok_or("Cannot determine the data directory")?;
+    let mut tokens = load(&path)?;
+    let stored = tokens
+        .netatmo
+        .as_ref()
+        .ok_or("Not signed in to Netatmo; run `weather auth netatmo`")?;
+    if let Some(access_token) = &stored.access_token {
+        if stored.expires_at - EXPIRY_MARGIN_SECONDS > now {
+            return Ok(access_token.clone());
+        }
+    }
+
+    let (Ok(client_id), Ok(client_secret)) = (
+        env::var("NETATMO_CLIENT_ID"),
+        env::var("NETATMO_CLIENT_SECRET"),
+    ) else {
+        return Err("NETATMO_CLIENT_ID and NETATMO_CLIENT_SECRET must be set".into());
+    };
+    let token = netatmo::refresh(client, &client_id, &client_secret, &stored.refresh_token).await?;
+    tokens.netatmo = Some(StoredToken {
+        access_token: Some(token.access_token.clone()),
+        refresh_token: token.refresh_token,
+        expires_at: now + token.expires_in,
+    });
+    save(&path, &tokens)?;
+    Ok(token.access_token)
+}
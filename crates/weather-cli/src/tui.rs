@@ -0,0 +1,1061 @@
+//! `--tui`: a full-screen dashboard. Panels show the current conditions,
+//! the days ahead and a strip of the next hours above a scrollable list of
+//! the forecast in 3-hour steps. The list scrolls with the keys in
+//! [`crate::keymap`] and the mouse wheel; the day tabs above it follow the
+//! scroll position and jump to their day when clicked.
+//!
+//! The location given and the saved locations are tabs at the top, switched
+//! with keys or the mouse; each is fetched when first shown, then checked
+//! for a new observation every `[cache] weather_ttl` while shown. The
+//! search key (`/`) opens a search for places that geocodes as you type;
+//! choosing a match adds it to the saved locations and shows it.
+//!
+//! The split key (`v`) shows the next location beside the one shown, both
+//! lists scrolling together; the swap key (`x`) exchanges the two sides.
+//!
+//! The screenshot key (`s`) writes the dashboard as shown to a file in the
+//! current directory; `--screenshot` writes it without opening it.
+
+use crate::display::{condition_icon, condition_name};
+use crate::keymap::{Action, Keymap};
+use crate::units::{Quantity, Units};
+use crate::DisplayOptions;
+use crate::{clock, get_weather_ascii, get_weather_emoji, local_datetime, outlook, saved};
+use futures_util::future::{join_all, LocalBoxFuture};
+use futures_util::stream::LocalBoxStream;
+use futures_util::StreamExt;
+use ratatui::backend::TestBackend;
+use ratatui::buffer::Buffer;
+use ratatui::crossterm::event::{
+    DisableMouseCapture, EnableMouseCapture, Event, EventStream, KeyCode, KeyEvent, KeyEventKind,
+    KeyModifiers, MouseButton, MouseEventKind,
+};
+use ratatui::crossterm::execute;
+use ratatui::layout::{Constraint, Flex, Layout, Margin, Position, Rect, Size};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{
+    Block, Clear, List, ListItem, ListState, Paragraph, Scrollbar, ScrollbarOrientation,
+    ScrollbarState, Wrap,
+};
+use ratatui::{DefaultTerminal, Frame, Terminal};
+use serde::Deserialize;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::time::Instant;
+use weather_core::model::Report;
+use weather_providers::client::{Cancel, ChangeFilter, WeatherUpdate};
+use weather_providers::forecast::{self, Forecast, ForecastEntry};
+use weather_providers::geocode::{self, Place};
+use weather_providers::WeatherClient;
+
+/// Rows moved by one step of the mouse wheel.
+const WHEEL_STEP: usize = 3;
+/// Typing pause after which the search is sent, so that a lookup is not
+/// spent on every keystroke.
+const SEARCH_DELAY: Duration = Duration::from_millis(300);
+/// Shorter searches match too much to be useful.
+const MIN_SEARCH_CHARS: usize = 2;
+/// Columns of each step in the strip of the next hours.
+const STRIP_STEP_WIDTH: u16 = 10;
+/// Size of `--screenshot` when not run in a terminal.
+const SCREENSHOT_SIZE: Size = Size::new(100, 30);
+
+/// `[tui]` in the config.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    pub keys: Keymap,
+}
+
+/// What is shown for a location.
+struct Loaded {
+    report: Report,
+    forecast: Forecast,
+}
+
+/// A location tab, fetched when first shown.
+struct Tab {
+    location: saved::Location,
+    data: Option<Loaded>,
+    /// Why the last fetch failed; switching to the tab again retries.
+    error: Option<String>,
+}
+
+type Lookup<'a> = LocalBoxFuture<'a, (String, Result<Vec<Place>, String>)>;
+/// The index of each tab fetched for, and what was fetched.
+type Fetch<'a> = LocalBoxFuture<'a, Vec<(usize, Result<Loaded, String>)>>;
+/// Refreshes of the locations shown, by the index of their tab.
+type Updates<'a> = LocalBoxStream<'a, (usize, WeatherUpdate)>;
+
+/// Shows the dashboard, starting with `place`, until a quit key is pressed.
+pub async fn run(
+    place: Place,
+    report: Report,
+    forecast: Forecast,
+    api_key: &str,
+    options: &DisplayOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !std::io::stdout().is_terminal() {
+        return Err("--tui needs a terminal".into());
+    }
+    let client = crate::http_client()?;
+    let weather = options.weather_client(api_key)?;
+    let mut app = App::new(tabs(place, report, forecast)?, options);
+
+    let mut terminal = ratatui::try_init()?;
+    let result = match execute!(std::io::stdout(), EnableMouseCapture) {
+        Ok(()) => app.run(&mut terminal, &client, &weather, api_key).await,
+        Err(e) => Err(e),
+    };
+    // Restore the terminal whatever happened, so that errors are readable.
+    execute!(std::io::stdout(), DisableMouseCapture).ok();
+    ratatui::restore();
+    Ok(result?)
+}
+
+/// `--screenshot`: writes the dashboard `run` would open to `path`, at the
+/// size of the terminal if there is one.
+pub fn screenshot(
+    place: Place,
+    report: Report,
+    forecast: Forecast,
+    options: &DisplayOptions,
+    path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let size = match ratatui::crossterm::terminal::size() {
+        Ok((width, height)) if std::io::stdout().is_terminal() => Size::new(width, height),
+        _ => SCREENSHOT_SIZE,
+    };
+    let mut app = App::new(tabs(place, report, forecast)?, options);
+    crate::screenshot::write(&app.capture(size)?, path)
+}
+
+/// The tab of `place`, then those of the other saved locations.
+fn tabs(
+    place: Place,
+    report: Report,
+    forecast: Forecast,
+) -> Result<Vec<Tab>, Box<dyn std::error::Error>> {
+    let first = saved::Location::new(place);
+    let mut tabs = vec![Tab {
+        location: first.clone(),
+        data: Some(Loaded { report, forecast }),
+        error: None,
+    }];
+    let others = saved::load()?.into_iter().filter(|l| l.name != first.name);
+    tabs.extend(others.map(|location| Tab {
+        location,
+        data: None,
+        error: None,
+    }));
+    Ok(tabs)
+}
+
+/// One line of the forecast list: a day's heading or a forecast step.
+struct Row {
+    day: usize,
+    line: Line<'static>,
+}
+
+/// The forecast list of a location.
+#[derive(Default)]
+struct Steps {
+    /// Tab label of each day in the forecast.
+    days: Vec<String>,
+    /// Index in `rows` of each day's heading.
+    starts: Vec<usize>,
+    rows: Vec<Row>,
+}
+
+/// The search opened with the search key.
+#[derive(Default)]
+struct Search {
+    query: String,
+    results: Vec<Place>,
+    selected: usize,
+    /// What the results show: searching, nothing found or an error.
+    note: Option<String>,
+}
+
+/// What a key asks of the event loop, beyond changing the view.
+enum Effect {
+    Quit,
+    /// The search text changed.
+    Lookup,
+    /// A search result was chosen.
+    Open(Place),
+    /// The locations shown changed.
+    Switch,
+    Screenshot,
+}
+
+struct App<'a> {
+    options: &'a DisplayOptions,
+    keys: &'a Keymap,
+    units: Units,
+    tabs: Vec<Tab>,
+    /// The location shown, on the left in the split view.
+    tab: usize,
+    steps: Steps,
+    /// The location on the right in the split view, and its list.
+    split: Option<(usize, Steps)>,
+    /// First row shown, in both lists of the split view.
+    offset: usize,
+    /// The day whose tab is highlighted.
+    day: usize,
+    /// Rows that fit in the list at the last draw.
+    page: usize,
+    /// Where the location and day tabs were drawn, for mouse clicks.
+    location_tabs: Vec<Rect>,
+    day_tabs: Vec<Rect>,
+    search: Option<Search>,
+    /// Shown in place of the key help until the next key.
+    status: Option<String>,
+}
+
+impl<'a> App<'a> {
+    fn new(tabs: Vec<Tab>, options: &'a DisplayOptions) -> Self {
+        let mut app = App {
+            options,
+            keys: &options.config.tui.keys,
+            units: options.units,
+            tabs,
+            tab: 0,
+            steps: Steps::default(),
+            split: None,
+            offset: 0,
+            day: 0,
+            page: 0,
+            location_tabs: Vec::new(),
+            day_tabs: Vec::new(),
+            search: None,
+            status: None,
+        };
+        app.rebuild();
+        app
+    }
+
+    fn quantity(&self, (value, decimals, unit): Quantity) -> String {
+        self.options.locale.quantity(value, decimals, unit)
+    }
+
+    fn temperature(&self, celsius: f64) -> String {
+        self.quantity(self.units.temperature(celsius))
+    }
+
+    fn icon(&self, condition: &str) -> &'static str {
+        if self.options.ascii {
+            get_weather_ascii(condition, false)
+        } else {
+            get_weather_emoji(condition, false)
+        }
+    }
+
+    /// Rebuilds the forecast lists of the locations shown, e.g. after one
+    /// was fetched or the units changed, keeping the scroll position.
+    fn rebuild(&mut self) {
+        self.steps = self.steps(self.tab);
+        if let Some((tab, _)) = self.split {
+            self.split = Some((tab, self.steps(tab)));
+        }
+        self.scroll_to(self.offset);
+    }
+
+    /// The forecast list of the location in tab `tab`, empty until fetched.
+    fn steps(&self, tab: usize) -> Steps {
+        let Some(data) = &self.tabs[tab].data else {
+            return Steps::default();
+        };
+        let options = self.options;
+        let timezone = data.forecast.city.timezone;
+        let today = local_datetime(options.clock.now().timestamp(), timezone).date_naive();
+        let mut steps = Steps::default();
+        let mut last = None;
+        for entry in &data.forecast.list {
+            let local = local_datetime(entry.dt, timezone);
+            let date = local.date_naive();
+            if last != Some(date) {
+                last = Some(date);
+                let (name, tab) = match (date - today).num_days() {
+                    0 => ("Today".to_string(), "Today".to_string()),
+                    1 => ("Tomorrow".to_string(), "Tomorrow".to_string()),
+                    _ => (
+                        date.format("%A").to_string(),
+                        date.format("%a %-d").to_string(),
+                    ),
+                };
+                steps.days.push(tab);
+                steps.starts.push(steps.rows.len());
+                steps.rows.push(Row {
+                    day: steps.days.len() - 1,
+                    line: Line::from(format!("{} · {}", name, options.date(&local)))
+                        .bold()
+                        .cyan(),
+                });
+            }
+            steps.rows.push(Row {
+                day: steps.days.len() - 1,
+                line: self.step(entry, &options.locale.short_time(&local)),
+            });
+        }
+        steps
+    }
+
+    async fn run(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        client: &'a reqwest::Client,
+        weather: &'a WeatherClient,
+        api_key: &'a str,
+    ) -> std::io::Result<()> {
+        let mut events = EventStream::new();
+        // When to send the search typed so far, and the lookups in flight.
+        // A newer search drops the lookup of an older one, and showing
+        // other locations drops the fetches and refreshes of those before.
+        let mut due: Option<Instant> = None;
+        let mut lookup: Option<Lookup> = None;
+        let mut fetch: Option<Fetch> = None;
+        let mut updates: Option<Updates> = self.watch(weather);
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            tokio::select! {
+                event = events.next() => {
+                    let effect = match event {
+                        Some(event) => self.handle(event?),
+                        None => Some(Effect::Quit),
+                    };
+                    match effect {
+                        Some(Effect::Quit) => return Ok(()),
+                        Some(Effect::Lookup) => {
+                            lookup = None;
+                            due = self
+                                .search
+                                .as_ref()
+                                .filter(|search| search.query.chars().count() >= MIN_SEARCH_CHARS)
+                                .map(|_| Instant::now() + SEARCH_DELAY);
+                        }
+                        Some(Effect::Open(place)) => {
+                            self.search = None;
+                            let location = saved::Location::new(place);
+                            self.status = Some(match saved::add(location.clone()) {
+                                Ok(_) => format!("Saved {}", location.name),
+                                Err(e) => format!("Cannot save {}: {}", location.name, e),
+                            });
+                            let tab = match self.tabs.iter().position(|tab| tab.location.name == location.name) {
+                                Some(tab) => tab,
+                                None => {
+                                    self.tabs.push(Tab {
+                                        location,
+                                        data: None,
+                                        error: None,
+                                    });
+                                    self.tabs.len() - 1
+                                }
+                            };
+                            self.switch(tab);
+                            fetch = self.fetch(client, weather, api_key);
+                            updates = self.watch(weather);
+                        }
+                        Some(Effect::Switch) => {
+                            fetch = self.fetch(client, weather, api_key);
+                            updates = self.watch(weather);
+                        }
+                        Some(Effect::Screenshot) => {
+                            let path = PathBuf::from(format!(
+                                "weather-{}.ans",
+                                chrono::Utc::now().format("%Y%m%dT%H%M%SZ")
+                            ));
+                            let written = self
+                                .capture(terminal.size()?)
+                                .map_err(Box::from)
+                                .and_then(|buffer| crate::screenshot::write(&buffer, &path));
+                            self.status = Some(match written {
+                                Ok(()) => format!("Saved screenshot to {}", path.display()),
+                                Err(e) => e.to_string(),
+                            });
+                        }
+                        None => {}
+                    }
+                }
+                _ = tokio::time::sleep_until(due.unwrap_or_else(Instant::now)), if due.is_some() => {
+                    due = None;
+                    if let Some(search) = &mut self.search {
+                        search.note = Some("Searching…".to_string());
+                        let query = search.query.clone();
+                        lookup = Some(Box::pin(async move {
+                            let places = weather
+                                .search(&query, geocode::MAX_MATCHES, &crate::report::cancel())
+                                .await
+                                .map_err(|e| e.to_string());
+                            (query, places)
+                        }));
+                    }
+                }
+                (query, places) = async { lookup.as_mut().expect("guarded").await }, if lookup.is_some() => {
+                    lookup = None;
+                    if let Some(search) = self.search.as_mut().filter(|search| search.query == query) {
+                        search.selected = 0;
+                        (search.results, search.note) = match places {
+                            Ok(places) if places.is_empty() => {
+                                (places, Some(format!("No places match '{}'", query)))
+                            }
+                            Ok(places) => (places, None),
+                            Err(e) => (Vec::new(), Some(e)),
+                        };
+                    }
+                }
+                fetched = async { fetch.as_mut().expect("guarded").await }, if fetch.is_some() => {
+                    fetch = None;
+                    for (tab, loaded) in fetched {
+                        match loaded {
+                            Ok(loaded) => self.tabs[tab].data = Some(loaded),
+                            Err(e) => self.tabs[tab].error = Some(e),
+                        }
+                    }
+                    self.rebuild();
+                    updates = self.watch(weather);
+                }
+                update = async { updates.as_mut().expect("guarded").next().await }, if updates.is_some() => {
+                    match update {
+                        Some((tab, WeatherUpdate::Report { report, .. })) => {
+                            if let Some(data) = &mut self.tabs[tab].data {
+                                data.report = *report;
+                                self.rebuild();
+                            }
+                        }
+                        Some((_, WeatherUpdate::Error(e))) => {
+                            self.status = Some(format!("Refresh failed: {}", e));
+                        }
+                        None => updates = None,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Draws the dashboard off screen at `size`, as it would be shown.
+    fn capture(&mut self, size: Size) -> std::io::Result<Buffer> {
+        let mut terminal = Terminal::new(TestBackend::new(size.width, size.height))?;
+        terminal.draw(|frame| self.draw(frame))?;
+        Ok(terminal.backend().buffer().clone())
+    }
+
+    /// The tabs of the locations shown: the one on the left of the split
+    /// view first.
+    fn shown(&self) -> Vec<usize> {
+        let mut shown = vec![self.tab];
+        shown.extend(self.split.as_ref().map(|(tab, _)| *tab));
+        shown
+    }
+
+    /// Checks the locations shown for new observations, once fetched.
+    fn watch(&self, weather: &'a WeatherClient) -> Option<Updates<'a>> {
+        let watches: Vec<_> = self
+            .shown()
+            .into_iter()
+            .filter_map(|tab| {
+                let data = self.tabs[tab].data.as_ref()?;
+                let updates = weather.watch_from(
+                    self.tabs[tab].location.place.clone(),
+                    data.report.clone(),
+                    self.options.config.cache.weather_ttl,
+                    ChangeFilter::NewObservation,
+                    Cancel::default(),
+                );
+                Some(updates.map(move |update| (tab, update)).boxed_local())
+            })
+            .collect();
+        if watches.is_empty() {
+            return None;
+        }
+        Some(Box::pin(futures_util::stream::select_all(watches)))
+    }
+
+    /// Fetches the locations shown that were not yet.
+    fn fetch(
+        &self,
+        client: &'a reqwest::Client,
+        weather: &'a WeatherClient,
+        api_key: &'a str,
+    ) -> Option<Fetch<'a>> {
+        let places: Vec<(usize, Place)> = self
+            .shown()
+            .into_iter()
+            .filter(|&tab| self.tabs[tab].data.is_none())
+            .map(|tab| (tab, self.tabs[tab].location.place.clone()))
+            .collect();
+        if places.is_empty() {
+            return None;
+        }
+        Some(Box::pin(join_all(places.into_iter().map(
+            |(tab, place)| async move {
+                let (report, forecast) = futures_util::future::join(
+                    weather.current(&place, &crate::report::cancel()),
+                    forecast::get_forecast(client, place.lat, place.lon, api_key),
+                )
+                .await;
+                let loaded = match (report, forecast) {
+                    (Ok(report), Ok(forecast)) => Ok(Loaded { report, forecast }),
+                    (Err(e), _) | (_, Err(e)) => Err(e.to_string()),
+                };
+                (tab, loaded)
+            },
+        ))))
+    }
+
+    fn handle(&mut self, event: Event) -> Option<Effect> {
+        match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                self.status = None;
+                if self.search.is_some() {
+                    return self.type_search(key);
+                }
+                match self.keys.action(&key)? {
+                    Action::Quit => return Some(Effect::Quit),
+                    Action::Search => self.search = Some(Search::default()),
+                    Action::Screenshot => return Some(Effect::Screenshot),
+                    Action::NextLocation => {
+                        return self.switch((self.tab + 1) % self.tabs.len());
+                    }
+                    Action::PreviousLocation => {
+                        return self.switch(self.tab.checked_sub(1).unwrap_or(self.tabs.len() - 1));
+                    }
+                    Action::Split => return self.toggle_split(),
+                    Action::Swap => self.swap(),
+                    Action::ToggleUnits => {
+                        self.units = match self.units {
+                            Units::Metric => Units::Imperial,
+                            Units::Imperial => Units::Standard,
+                            Units::Standard => Units::Metric,
+                        };
+                        self.status = Some(format!("Units: {:?}", self.units).to_lowercase());
+                        self.rebuild();
+                    }
+                    action => self.apply(action),
+                }
+            }
+            Event::Mouse(mouse) if self.search.is_none() => {
+                let clicked = Position::new(mouse.column, mouse.row);
+                match mouse.kind {
+                    MouseEventKind::ScrollDown => self.scroll_to(self.offset + WHEEL_STEP),
+                    MouseEventKind::ScrollUp => {
+                        self.scroll_to(self.offset.saturating_sub(WHEEL_STEP))
+                    }
+                    MouseEventKind::Down(MouseButton::Left) => {
+                        if let Some(day) = self.day_tabs.iter().position(|r| r.contains(clicked)) {
+                            self.select_day(day);
+                        }
+                        if let Some(tab) =
+                            self.location_tabs.iter().position(|r| r.contains(clicked))
+                        {
+                            return self.switch(tab);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            // Anything else, e.g. a resize, only needs a redraw.
+            _ => {}
+        }
+        None
+    }
+
+    /// Shows the location in tab `tab` from the top. In the split view it
+    /// replaces the left side, or swaps sides if already on the right.
+    fn switch(&mut self, tab: usize) -> Option<Effect> {
+        if tab == self.tab && self.tabs[tab].data.is_some() {
+            return None;
+        }
+        if let Some((right, _)) = &mut self.split {
+            if *right == tab {
+                *right = self.tab;
+            }
+        }
+        self.tab = tab;
+        self.tabs[tab].error = None;
+        self.offset = 0;
+        self.rebuild();
+        Some(Effect::Switch)
+    }
+
+    /// Shows the next location beside the one shown, or closes the split.
+    fn toggle_split(&mut self) -> Option<Effect> {
+        if self.split.take().is_some() {
+            self.rebuild();
+            return Some(Effect::Switch);
+        }
+        if self.tabs.len() < 2 {
+            self.status =
+                Some("The split view needs a second location; search for one".to_string());
+            return None;
+        }
+        let right = (self.tab + 1) % self.tabs.len();
+        self.tabs[right].error = None;
+        self.split = Some((right, Steps::default()));
+        self.rebuild();
+        Some(Effect::Switch)
+    }
+
+    /// Exchanges the sides of the split view.
+    fn swap(&mut self) {
+        let Some((right, _)) = &mut self.split else {
+            self.status = Some("Nothing to swap outside the split view".to_string());
+            return;
+        };
+        std::mem::swap(right, &mut self.tab);
+        self.rebuild();
+    }
+
+    /// Keys in the search: text is typed rather than bound to actions.
+    fn type_search(&mut self, key: KeyEvent) -> Option<Effect> {
+        let search = self.search.as_mut()?;
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('c') if ctrl => return Some(Effect::Quit),
+            KeyCode::Esc => self.search = None,
+            KeyCode::Enter => {
+                let place = search.results.get(search.selected)?.clone();
+                return Some(Effect::Open(place));
+            }
+            KeyCode::Down => {
+                search.selected = (search.selected + 1).min(search.results.len().saturating_sub(1))
+            }
+            KeyCode::Up => search.selected = search.selected.saturating_sub(1),
+            KeyCode::Backspace => {
+                search.query.pop();
+                return Some(Effect::Lookup);
+            }
+            KeyCode::Char(c) if !ctrl => {
+                search.query.push(c);
+                return Some(Effect::Lookup);
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn apply(&mut self, action: Action) {
+        // Paging keeps one row of the previous page in view.
+        let page = self.page.saturating_sub(1).max(1);
+        match action {
+            Action::ScrollDown => self.scroll_to(self.offset + 1),
+            Action::ScrollUp => self.scroll_to(self.offset.saturating_sub(1)),
+            Action::PageDown => self.scroll_to(self.offset + page),
+            Action::PageUp => self.scroll_to(self.offset.saturating_sub(page)),
+            Action::Top => self.scroll_to(0),
+            Action::Bottom => self.scroll_to(usize::MAX),
+            Action::NextDay => self.select_day(self.day + 1),
+            Action::PreviousDay => self.select_day(self.day.saturating_sub(1)),
+            // Handled with the event, as they need more than the view.
+            Action::Quit
+            | Action::Search
+            | Action::NextLocation
+            | Action::PreviousLocation
+            | Action::ToggleUnits
+            | Action::Screenshot
+            | Action::Split
+            | Action::Swap => {}
+        }
+    }
+
+    /// The last offset that fills the page, in the longer list of the
+    /// split view.
+    fn max_offset(&self) -> usize {
+        let rows = self.split.as_ref().map_or(0, |(_, steps)| steps.rows.len());
+        rows.max(self.steps.rows.len()).saturating_sub(self.page)
+    }
+
+    fn scroll_to(&mut self, offset: usize) {
+        self.offset = offset.min(self.max_offset());
+        self.day = day_at(&self.steps, self.offset);
+    }
+
+    /// Scrolls to the heading of `day`. The last days may not reach the
+    /// top of the list; their tab is highlighted all the same.
+    fn select_day(&mut self, day: usize) {
+        if let Some(&start) = self.steps.starts.get(day) {
+            self.offset = start.min(self.max_offset());
+            self.day = day;
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [locations, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let names: Vec<&str> = self
+            .tabs
+            .iter()
+            .map(|tab| tab.location.name.as_str())
+            .collect();
+        self.location_tabs = draw_tabs(frame, locations, &names, &self.shown());
+
+        let [left, right] = match self.split {
+            Some(_) => Layout::horizontal([Constraint::Fill(1); 2]).areas(body),
+            None => [body, Rect::default()],
+        };
+        let [.., list] = pane_areas(left);
+        self.page = Block::bordered().inner(list).height as usize;
+        self.offset = self.offset.min(self.max_offset());
+        self.day_tabs = self.draw_pane(frame, left, self.tab, &self.steps);
+        if let Some((tab, steps)) = &self.split {
+            self.draw_pane(frame, right, *tab, steps);
+        }
+
+        let footer_line = match (&self.status, &self.search) {
+            (Some(status), _) => Line::from(status.clone()).yellow(),
+            (None, Some(_)) => Line::from("Enter save and show  ↑/↓ choose  Esc cancel").dim(),
+            (None, None) => self.help().dim(),
+        };
+        frame.render_widget(footer_line, footer);
+
+        if let Some(search) = &self.search {
+            // Over the forecast lists, whether one or two.
+            let [.., lists] = pane_areas(body);
+            draw_search(frame, lists, search);
+        }
+    }
+
+    /// Draws the panels and forecast list of the location in tab `tab`,
+    /// and returns where its day tabs went.
+    fn draw_pane(&self, frame: &mut Frame, area: Rect, tab: usize, steps: &Steps) -> Vec<Rect> {
+        let [panels, strip, days, list] = pane_areas(area);
+        let [now, ahead] =
+            Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)])
+                .areas(panels);
+        // Side by side, each says which location it is.
+        let now_block = match self.split {
+            Some(_) => Block::bordered().title(format!(" {} ", self.tabs[tab].location.name)),
+            None => Block::bordered().title(" Now "),
+        };
+        let ahead_block = Block::bordered().title(" Next days ");
+        let strip_block = Block::bordered().title(" Next hours ");
+        let (now_inner, ahead_inner, strip_inner) = (
+            now_block.inner(now),
+            ahead_block.inner(ahead),
+            strip_block.inner(strip),
+        );
+        frame.render_widget(now_block, now);
+        frame.render_widget(ahead_block, ahead);
+        frame.render_widget(strip_block, strip);
+        match &self.tabs[tab].data {
+            Some(data) => {
+                frame.render_widget(Paragraph::new(self.now(&data.report)), now_inner);
+                frame.render_widget(Paragraph::new(self.ahead(&data.forecast)), ahead_inner);
+                frame.render_widget(
+                    Paragraph::new(self.strip(&data.forecast, strip_inner.width)),
+                    strip_inner,
+                );
+            }
+            None => match &self.tabs[tab].error {
+                Some(e) => frame.render_widget(
+                    Paragraph::new(format!("No weather: {}", e))
+                        .red()
+                        .wrap(Wrap { trim: true }),
+                    now_inner,
+                ),
+                None => frame.render_widget(Line::from("Loading…").dim(), now_inner),
+            },
+        }
+
+        // The shorter list of the split view stops at its end.
+        let max_offset = steps.rows.len().saturating_sub(self.page);
+        let offset = self.offset.min(max_offset);
+        let day_names: Vec<&str> = steps.days.iter().map(String::as_str).collect();
+        let day_tabs = draw_tabs(frame, days, &day_names, &[day_at(steps, offset)]);
+
+        let block = Block::bordered().title(" Forecast in 3-hour steps ");
+        let inner = block.inner(list);
+        frame.render_widget(block, list);
+        let lines: Vec<Line> = steps.rows[offset..]
+            .iter()
+            .take(self.page)
+            .map(|row| row.line.clone())
+            .collect();
+        frame.render_widget(Paragraph::new(lines), inner);
+        let mut scrollbar = ScrollbarState::new(max_offset).position(offset);
+        frame.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight),
+            list.inner(Margin::new(0, 1)),
+            &mut scrollbar,
+        );
+        day_tabs
+    }
+
+    /// The current conditions panel.
+    fn now(&self, report: &Report) -> Vec<Line<'static>> {
+        let options = self.options;
+        let current = &report.current;
+        let condition = &current.condition;
+        let now = options.clock.now().timestamp();
+        let local = local_datetime(now, report.location.utc_offset_seconds);
+        let mut wind = format!(
+            "Wind {} {}",
+            self.quantity(self.units.speed(current.wind_speed_ms)),
+            options.catalog.compass(current.wind_direction_deg as f64)
+        );
+        if let Some(gust) = current.wind_gust_ms {
+            wind = format!("{}, gusts {}", wind, self.quantity(self.units.speed(gust)));
+        }
+        vec![
+            Line::from(format!(
+                "{} {} ({})",
+                condition_icon(report, options),
+                condition_name(&condition.kind),
+                condition.description
+            ))
+            .bold(),
+            Line::from(vec![
+                Span::from(self.temperature(current.temperature_c))
+                    .bold()
+                    .yellow(),
+                Span::from(format!(
+                    "  feels like {}",
+                    self.temperature(current.feels_like_c)
+                )),
+            ]),
+            Line::from(format!(
+                "Min/Max {}/{}",
+                self.temperature(current.temperature_min_c),
+                self.temperature(current.temperature_max_c)
+            )),
+            Line::from(format!(
+                "Humidity {}%  Pressure {}",
+                current.humidity_percent,
+                self.quantity(self.units.pressure(current.pressure_hpa as f64))
+            )),
+            Line::from(wind),
+            Line::from(format!(
+                "Visibility {}  Clouds {}%",
+                self.quantity(self.units.distance(current.visibility_m as f64)),
+                current.cloud_cover_percent
+            )),
+            Line::from(format!(
+                "{} local, observed {} ago",
+                options.locale.short_time(&local),
+                clock::span((now - report.observed_at.timestamp()).max(0))
+            ))
+            .dim(),
+        ]
+    }
+
+    /// The days ahead panel: one line per day from today.
+    fn ahead(&self, forecast: &Forecast) -> Vec<Line<'static>> {
+        let now = self.options.clock.now().timestamp();
+        let today = local_datetime(now, forecast.city.timezone).date_naive();
+        outlook::days(forecast)
+            .iter()
+            .filter(|day| day.date >= today)
+            .map(|day| {
+                let name = match (day.date - today).num_days() {
+                    0 => "Today".to_string(),
+                    1 => "Tomorrow".to_string(),
+                    _ => day.date.format("%A").to_string(),
+                };
+                Line::from(vec![
+                    Span::from(format!("{:<9} ", name)).bold(),
+                    Span::from(format!(
+                        "{} / {}",
+                        self.temperature(day.min_c),
+                        self.temperature(day.max_c)
+                    )),
+                    Span::from(format!(" {:>3}% ", day.pop_percent)).blue(),
+                    Span::from(format!("{} {}", self.icon(&day.condition), day.condition)),
+                ])
+            })
+            .collect()
+    }
+
+    /// The strip of the next hours: a column per step, as many as fit.
+    fn strip(&self, forecast: &Forecast, width: u16) -> Vec<Line<'static>> {
+        let now = self.options.clock.now().timestamp();
+        let steps: Vec<&ForecastEntry> = forecast
+            .list
+            .iter()
+            // Keep the step under way.
+            .filter(|entry| entry.dt + 3 * 3600 > now)
+            .take((width / STRIP_STEP_WIDTH) as usize)
+            .collect();
+        let width = STRIP_STEP_WIDTH as usize;
+        let column = |text: String| {
+            let pad = width.saturating_sub(Span::from(text.as_str()).width());
+            format!("{}{}", text, " ".repeat(pad))
+        };
+        let row = |cell: &dyn Fn(&ForecastEntry) -> String| {
+            steps
+                .iter()
+                .map(|entry| column(cell(entry)))
+                .collect::<String>()
+        };
+        let timezone = forecast.city.timezone;
+        vec![
+            Line::from(row(&|entry| {
+                self.options
+                    .locale
+                    .short_time(&local_datetime(entry.dt, timezone))
+            }))
+            .bold(),
+            Line::from(row(&|entry| {
+                self.icon(entry.weather.first().map_or("", |w| w.main.as_str()))
+                    .to_string()
+            })),
+            Line::from(row(&|entry| self.temperature(entry.main.temp))).yellow(),
+            Line::from(row(&|entry| format!("{}%", (entry.pop * 100.0).round()))).blue(),
+        ]
+    }
+
+    /// A forecast step: time, condition, temperatures, rain and wind.
+    fn step(&self, entry: &ForecastEntry, time: &str) -> Line<'static> {
+        let condition = entry.weather.first().map_or("", |w| w.main.as_str());
+        let precipitation: f64 = entry
+            .rain
+            .iter()
+            .chain(&entry.snow)
+            .map(|p| p.three_hours)
+            .sum();
+        let precipitation = if precipitation > 0.0 {
+            self.quantity(self.units.precipitation(precipitation))
+        } else {
+            String::new()
+        };
+        Line::from(vec![
+            Span::from(format!("  {:>8}  ", time)).bold(),
+            Span::from(format!("{} {:<13}", self.icon(condition), condition)),
+            Span::from(format!("{:>9}", self.temperature(entry.main.temp))).yellow(),
+            Span::from(format!(
+                "  feels {:>9}",
+                self.temperature(entry.main.feels_like)
+            ))
+            .dim(),
+            Span::from(format!("  {:>3}% ", (entry.pop * 100.0).round())).blue(),
+            Span::from(format!("{:>8}", precipitation)).blue(),
+            Span::from(format!(
+                "  wind {}",
+                self.quantity(self.units.speed(entry.wind.speed))
+            )),
+        ])
+    }
+
+    /// The first key of each action, e.g. "q quit  j/k scroll".
+    fn help(&self) -> Line<'static> {
+        let pairs = [
+            (Action::Quit, None),
+            (Action::ScrollDown, Some(Action::ScrollUp)),
+            (Action::PageDown, Some(Action::PageUp)),
+            (Action::NextDay, Some(Action::PreviousDay)),
+            (Action::Top, Some(Action::Bottom)),
+            (Action::NextLocation, Some(Action::PreviousLocation)),
+            (Action::ToggleUnits, None),
+            (Action::Search, None),
+            (Action::Screenshot, None),
+            (Action::Split, None),
+            (Action::Swap, None),
+        ];
+        let mut parts: Vec<String> = pairs
+            .iter()
+            .filter_map(|&(action, other)| {
+                let mut keys = self.keys.first(action)?.to_string();
+                if let Some(key) = other.and_then(|other| self.keys.first(other)) {
+                    keys = format!("{}/{}", keys, key);
+                }
+                Some(format!("{} {}", keys, action.label()))
+            })
+            .collect();
+        parts.push("wheel scroll".to_string());
+        parts.push("click a tab".to_string());
+        Line::from(parts.join("  "))
+    }
+}
+
+/// Where a location's panels, strip of the next hours, day tabs and
+/// forecast list go in `area`.
+fn pane_areas(area: Rect) -> [Rect; 4] {
+    Layout::vertical([
+        Constraint::Length(9),
+        Constraint::Length(6),
+        Constraint::Length(1),
+        Constraint::Min(3),
+    ])
+    .areas(area)
+}
+
+/// The day of the row at `offset`.
+fn day_at(steps: &Steps, offset: usize) -> usize {
+    steps.rows.get(offset).map_or(0, |row| row.day)
+}
+
+/// Draws `labels` as a row of tabs with those in `selected` highlighted,
+/// and returns where each went.
+fn draw_tabs(frame: &mut Frame, area: Rect, labels: &[&str], selected: &[usize]) -> Vec<Rect> {
+    let mut rects = Vec::new();
+    let mut spans = Vec::new();
+    let mut x = area.x;
+    for (i, label) in labels.iter().enumerate() {
+        let tab = Span::from(format!(" {} ", label));
+        let tab = if selected.contains(&i) {
+            tab.style(Style::new().bold().reversed())
+        } else {
+            tab
+        };
+        let width = tab.width() as u16;
+        rects.push(Rect::new(x, area.y, width, 1).intersection(area));
+        x = x.saturating_add(width + 1);
+        spans.push(tab);
+        spans.push(Span::from(" "));
+    }
+    frame.render_widget(Line::from(spans), area);
+    rects
+}
+
+/// The search box over the forecast list: the text typed, then the
+/// matches or a note.
+fn draw_search(frame: &mut Frame, area: Rect, search: &Search) {
+    let [area] = Layout::horizontal([Constraint::Max(60)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::vertical([Constraint::Length(geocode::MAX_MATCHES as u16 + 4)])
+        .flex(Flex::Center)
+        .areas(area);
+    let block = Block::bordered().title(" Search places ");
+    let inner = block.inner(area);
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+
+    let [input, _, results] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Min(0),
+    ])
+    .areas(inner);
+    frame.render_widget(Line::from(format!("/ {}", search.query)), input);
+    frame.set_cursor_position(Position::new(
+        input.x + 2 + Span::from(search.query.as_str()).width() as u16,
+        input.y,
+    ));
+    if let Some(note) = &search.note {
+        frame.render_widget(
+            Paragraph::new(note.as_str())
+                .wrap(Wrap { trim: true })
+                .dim(),
+            results,
+        );
+        return;
+    }
+    let items: Vec<ListItem> = search
+        .results
+        .iter()
+        .map(|place| ListItem::new(saved::Location::new(place.clone()).name))
+        .collect();
+    let mut state = ListState::default().with_selected(Some(search.selected));
+    frame.render_stateful_widget(
+        List::new(items).highlight_style(Style::new().reversed()),
+        results,
+        &mut state,
+    );
+}
@@ -0,0 +1,110 @@
+//! Unit systems for displayed values, chosen with `--units` or `units` in
+//! the config. Providers, the report model, JSON output and thresholds are
+//! metric throughout; values are converted only when they are rendered.
+
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// A value in a display unit, as taken by [`crate::locale::Locale::quantity`]:
+/// the value, its decimals and the unit symbol.
+pub type Quantity = (f64, usize, &'static str);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Units {
+    /// °C, m/s, hPa, km and mm.
+    #[default]
+    Metric,
+    /// °F, mph, inHg, miles and inches.
+    Imperial,
+    /// As metric, with temperatures in kelvin, like OpenWeatherMap's
+    /// standard units.
+    Standard,
+}
+
+impl FromStr for Units {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "metric" => Ok(Units::Metric),
+            "imperial" => Ok(Units::Imperial),
+            "standard" => Ok(Units::Standard),
+            other => Err(format!(
+                "Unknown units '{}', expected metric, imperial or standard",
+                other
+            )),
+        }
+    }
+}
+
+impl Units {
+    pub fn temperature(self, celsius: f64) -> Quantity {
+        match self {
+            Units::Metric => (celsius, 1, "°C"),
+            Units::Imperial => (celsius_to_fahrenheit(celsius), 1, "°F"),
+            Units::Standard => (celsius + 273.15, 1, "K"),
+        }
+    }
+
+    /// A difference between two temperatures, e.g. a calibration offset.
+    pub fn temperature_difference(self, celsius: f64) -> Quantity {
+        match self {
+            Units::Metric => (celsius, 1, "°C"),
+            Units::Imperial => (celsius * 9.0 / 5.0, 1, "°F"),
+            Units::Standard => (celsius, 1, "K"),
+        }
+    }
+
+    pub fn speed(self, metres_per_second: f64) -> Quantity {
+        match self {
+            Units::Imperial => (metres_per_second * 2.236_936, 1, "mph"),
+            Units::Metric | Units::Standard => (metres_per_second, 1, "m/s"),
+        }
+    }
+
+    pub fn pressure(self, hpa: f64) -> Quantity {
+        match self {
+            Units::Imperial => (hpa * 0.029_53, 2, "inHg"),
+            Units::Metric | Units::Standard => (hpa, 0, "hPa"),
+        }
+    }
+
+    /// A distance over the ground, e.g. visibility: metres under a
+    /// kilometre, else kilometres, or feet under a tenth of a mile.
+    pub fn distance(self, metres: f64) -> Quantity {
+        match self {
+            Units::Imperial if metres < 160.934 => (metres * 3.280_84, 0, "ft"),
+            Units::Imperial => (metres / 1609.344, 1, "mi"),
+            _ if metres < 1000.0 => (metres, 0, "m"),
+            Units::Metric | Units::Standard => (metres / 1000.0, 1, "km"),
+        }
+    }
+
+    /// A height, e.g. of the tide or the cloud base.
+    pub fn height(self, metres: f64) -> Quantity {
+        match self {
+            Units::Imperial => (metres * 3.280_84, 1, "ft"),
+            Units::Metric | Units::Standard => (metres, 2, "m"),
+        }
+    }
+
+    /// An altitude, e.g. of a summit or the freezing level.
+    pub fn elevation(self, metres: f64) -> Quantity {
+        match self {
+            Units::Imperial => (metres * 3.280_84, 0, "ft"),
+            Units::Metric | Units::Standard => (metres, 0, "m"),
+        }
+    }
+
+    pub fn precipitation(self, mm: f64) -> Quantity {
+        match self {
+            Units::Imperial => (mm / 25.4, 2, "in"),
+            Units::Metric | Units::Standard => (mm, 1, "mm"),
+        }
+    }
+}
+
+pub fn celsius_to_fahrenheit(celsius: f64) -> f64 {
+    (celsius * 9.0 / 5.0) + 32.0
+}
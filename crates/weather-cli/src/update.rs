@@ -0,0 +1,158 @@
+//! `weather self-update`: replaces the running binary with the latest GitHub
+//! release.
+//!
+//! Releases are expected to carry one raw binary per target, named
+//! `weather-<target triple>` (plus `.exe` on Windows), and a `SHA256SUMS` file
+//! in `sha256sum` format covering them.
+
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::time::Duration;
+use weather_providers::retry;
+
+const RELEASES_URL: &str =
+    "https://api.github.com/repos/kushwahramkumar2003/rust_wather_cli/releases/latest";
+const CHECKSUMS_ASSET: &str = "SHA256SUMS";
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    assets: Vec<Asset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+pub async fn self_update(check_only: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let client = reqwest::Client::builder()
+        .user_agent(concat!("weather-cli/", env!("CARGO_PKG_VERSION")))
+        .timeout(Duration::from_secs(60))
+        .build()?;
+
+    let release = retry::send(client.get(RELEASES_URL))
+        .await?
+        .error_for_status()?
+        .json::<Release>()
+        .await?;
+
+    let current = env!("CARGO_PKG_VERSION");
+    let latest = release.tag_name.trim_start_matches('v');
+    if !is_newer(latest, current) {
+        println!("weather {} is up to date", current);
+        return Ok(());
+    }
+
+    println!("Update available: {} → {}", current, latest);
+    if check_only {
+        return Ok(());
+    }
+
+    let asset_name = asset_name();
+    let binary_asset = find_asset(&release, &asset_name)?;
+    let checksums_asset = find_asset(&release, CHECKSUMS_ASSET)?;
+
+    let checksums = retry::send(client.get(&checksums_asset.browser_download_url))
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    let expected = expected_checksum(&checksums, &asset_name)
+        .ok_or_else(|| format!("{} has no entry for {}", CHECKSUMS_ASSET, asset_name))?;
+
+    println!("Downloading {}...", asset_name);
+    let binary = retry::send(client.get(&binary_asset.browser_download_url))
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+
+    let actual = hex(&Sha256::digest(&binary));
+    if !actual.eq_ignore_ascii_case(&expected) {
+        return Err(format!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            asset_name, expected, actual
+        )
+        .into());
+    }
+
+    replace_current_exe(&binary)?;
+    println!("Updated weather to {}", latest);
+    Ok(())
+}
+
+fn asset_name() -> String {
+    let target = env!("TARGET");
+    if cfg!(windows) {
+        format!("weather-{}.exe", target)
+    } else {
+        format!("weather-{}", target)
+    }
+}
+
+fn find_asset<'a>(release: &'a Release, name: &str) -> Result<&'a Asset, String> {
+    release
+        .assets
+        .iter()
+        .find(|asset| asset.name == name)
+        .ok_or_else(|| format!("Release {} has no asset named {}", release.tag_name, name))
+}
+
+/// Finds the hash for `file` in `sha256sum` output ("<hex>  <name>" lines,
+/// with an optional `*` marking binary mode).
+fn expected_checksum(checksums: &str, file: &str) -> Option<String> {
+    checksums.lines().find_map(|line| {
+        let (hash, name) = line.split_once(char::is_whitespace)?;
+        (name.trim().trim_start_matches('*') == file).then(|| hash.to_string())
+    })
+}
+
+/// Compares dotted numeric versions, ignoring any pre-release suffix.
+fn is_newer(candidate: &str, current: &str) -> bool {
+    fn parse(version: &str) -> Vec<u64> {
+        version
+            .split(['-', '+'])
+            .next()
+            .unwrap_or("")
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    }
+    parse(candidate) > parse(current)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Writes the new binary next to the running one and swaps it into place.
+/// Windows can't overwrite a running executable, so the old one is moved
+/// aside first and left for the next run to clean up.
+fn replace_current_exe(binary: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    let current = std::env::current_exe()?;
+    let staged = current.with_extension("new");
+    std::fs::write(&staged, binary)?;
+    make_executable(&staged)?;
+
+    if cfg!(windows) {
+        let old = current.with_extension("old");
+        let _ = std::fs::remove_file(&old);
+        std::fs::rename(&current, &old)?;
+    }
+    std::fs::rename(&staged, &current)?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
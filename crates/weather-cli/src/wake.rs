@@ -0,0 +1,153 @@
+//! `weather wake`: alarms a fixed time before or after each sunrise, for
+//! people who get up with the sun, as an iCalendar alarm series or as
+//! crontab lines. Only the ephemeris is needed, so no forecast is fetched.
+
+use crate::clock;
+use crate::ics;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Timelike, Utc};
+use serde::Serialize;
+use weather_core::astro;
+
+/// The most days of alarms.
+pub const MAX_DAYS: u32 = 366;
+
+/// `--offset`: a duration such as `30min`, before sunrise when preceded
+/// by `-`; in seconds.
+pub fn parse_offset(value: &str) -> Result<i64, String> {
+    let (sign, duration) = match value.trim().strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, value.trim().trim_start_matches('+')),
+    };
+    Ok(sign * clock::parse_duration(duration)?.as_secs() as i64)
+}
+
+/// One day's alarm. Times are Unix timestamps.
+#[derive(Debug, Clone, Serialize)]
+pub struct Alarm {
+    pub date: NaiveDate,
+    pub sunrise: i64,
+    pub at: i64,
+}
+
+/// The offset from UTC of solar time at `lon`, to the hour, in seconds.
+pub fn solar_offset(lon: f64) -> i32 {
+    (lon / 15.0).round() as i32 * 3600
+}
+
+/// The alarms `offset` seconds from sunrise at `lat`, `lon` on `days`
+/// days from `from`, and the days without a sunrise. Days run from
+/// the place's solar midnight, which is close enough to its clocks' for
+/// telling one morning from the next. Under the polar night or the
+/// midnight sun there is no sunrise.
+pub fn alarms(
+    lat: f64,
+    lon: f64,
+    from: NaiveDate,
+    days: u32,
+    offset: i64,
+) -> (Vec<Alarm>, Vec<NaiveDate>) {
+    let solar_offset = solar_offset(lon);
+    let mut alarms = Vec::new();
+    let mut sunless = Vec::new();
+    for date in from.iter_days().take(days as usize) {
+        match astro::sun_times(lat, lon, date, solar_offset).sunrise {
+            Some(sunrise) => alarms.push(Alarm {
+                date,
+                sunrise,
+                // Alarm clocks keep whole minutes.
+                at: (sunrise + offset + 30).div_euclid(60) * 60,
+            }),
+            None => sunless.push(date),
+        }
+    }
+    (alarms, sunless)
+}
+
+/// How far the alarm is from sunrise, in words: `30 min before sunrise`.
+pub fn relation(offset: i64) -> String {
+    match offset {
+        0 => "at sunrise".to_string(),
+        _ => format!(
+            "{} {} sunrise",
+            clock::span(offset.abs()),
+            if offset < 0 { "before" } else { "after" }
+        ),
+    }
+}
+
+/// The alarms as an iCalendar series for `place`: one short event per
+/// morning with an alarm when it starts. UIDs stay the same for a place
+/// and day, so importing again updates the series rather than doubling it.
+pub fn calendar(
+    alarms: &[Alarm],
+    place: &str,
+    lat: f64,
+    lon: f64,
+    offset: i64,
+    now: DateTime<Utc>,
+) -> String {
+    let events: Vec<ics::NewEvent> = alarms
+        .iter()
+        .map(|alarm| ics::NewEvent {
+            uid: format!(
+                "wake-{}-{:.3}-{:.3}@wather-app",
+                alarm.date.format("%Y%m%d"),
+                lat,
+                lon
+            ),
+            summary: "Wake up".to_string(),
+            description: format!("{} in {}", relation(offset), place),
+            start: DateTime::from_timestamp(alarm.at, 0).unwrap_or(now),
+            duration: Duration::minutes(15),
+            alarm: true,
+        })
+        .collect();
+    ics::write(&events, now)
+}
+
+/// A crontab line running `command` at `alarm` in the machine's time
+/// zone, which is the one cron uses. Cron has no year, so the line names
+/// the day and month and fires again on that date a year later; see
+/// [`CRON_NOTE`]. `%` in the command is escaped, as cron would otherwise
+/// end the command there.
+pub fn cron_line(alarm: &Alarm, command: &str) -> String {
+    let at = DateTime::from_timestamp(alarm.at, 0)
+        .unwrap_or_default()
+        .with_timezone(&Local);
+    format!(
+        "{} {} {} {} * {}",
+        at.minute(),
+        at.hour(),
+        at.day(),
+        at.month(),
+        command.replace('%', "\\%")
+    )
+}
+
+/// Printed above the crontab lines.
+pub const CRON_NOTE: &str =
+    "# Each line fires on its date every year: generate new lines before they come round again.";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cron_lines_escape_percent_signs() {
+        let alarm = Alarm {
+            date: NaiveDate::from_ymd_opt(2024, 6, 21).unwrap(),
+            sunrise: 1_718_935_200,
+            at: 1_718_935_200,
+        };
+        let line = cron_line(&alarm, "date +%H:%M >> woke");
+        assert!(line.ends_with(" * date +\\%H:\\%M >> woke"), "{}", line);
+        assert_eq!(line.split(' ').count(), 9);
+    }
+
+    #[test]
+    fn offsets_read_as_words() {
+        assert_eq!(parse_offset("-30min"), Ok(-1800));
+        assert_eq!(relation(0), "at sunrise");
+        assert_eq!(relation(-1800), "30m before sunrise");
+    }
+}
@@ -0,0 +1,82 @@
+//! One-line formats for editor and shell status lines (`--output`).
+//!
+//! These run on every redraw, so they follow a fast-path contract:
+//!
+//! - Only the cache is read: the last response fetched for the location by
+//!   any `weather` run, or for the current location the report `weather
+//!   daemon` keeps fresh. No API key, network or location lookup is needed.
+//! - Output is a single line without ANSI colours, finishing well under
+//!   50 ms.
+//! - Without a cached response nothing is printed and the exit status is 0, so
+//!   the segment simply stays empty.
+//! - A report older than [`STALE_AFTER`] is prefixed with `~`.
+//! - `--refresh` fetches first and updates the cache. Only this touches the
+//!   network; if it fails the cached report is still shown.
+
+use crate::units::Units;
+use crate::{clock, get_weather_ascii, get_weather_emoji};
+use chrono::{DateTime, Utc};
+use std::str::FromStr;
+use weather_core::model::Report;
+
+/// Age from which a cached report is marked as stale.
+pub const STALE_AFTER: chrono::Duration = chrono::Duration::hours(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// For a lualine component in Neovim; `%` is escaped as statusline
+    /// syntax requires.
+    Lualine,
+    /// For a starship `[custom]` module, which adds its own styling.
+    StarshipModule,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lualine" => Ok(Format::Lualine),
+            "starship-module" => Ok(Format::StarshipModule),
+            _ => Err(format!(
+                "Unknown output format '{}'. Use lualine or starship-module",
+                s
+            )),
+        }
+    }
+}
+
+/// Renders `report` as a status line segment, e.g. "☀️ 21°C".
+pub fn render(
+    format: Format,
+    report: &Report,
+    now: DateTime<Utc>,
+    units: Units,
+    ascii: bool,
+) -> String {
+    let current = &report.current;
+    let night = match (current.sunrise, current.sunset) {
+        (Some(sunrise), Some(sunset)) => {
+            !clock::is_daytime(now.timestamp(), sunrise.timestamp(), sunset.timestamp())
+        }
+        _ => false,
+    };
+    let icon = if ascii {
+        get_weather_ascii(&current.condition.kind, night)
+    } else {
+        get_weather_emoji(&current.condition.kind, night)
+    };
+    let (temperature, _, unit) = units.temperature(current.temperature_c);
+    let temperature = format!("{:.0}{}", temperature, unit);
+    let stale = if now - report.observed_at > STALE_AFTER {
+        "~"
+    } else {
+        ""
+    };
+
+    let line = format!("{}{} {}", stale, icon, temperature);
+    match format {
+        Format::Lualine => line.replace('%', "%%"),
+        Format::StarshipModule => line,
+    }
+}
@@ -0,0 +1,145 @@
+//! `weather wind-window`: the hours ahead when the wind suits a kite, a
+//! paraglider or a sail, within a range of speeds and, optionally, from a
+//! sector of directions. Consecutive suitable hours make one window.
+//!
+//! Speeds are in knots, as wind sports reckon them.
+
+use crate::hourly::Hour;
+use serde::Serialize;
+use std::str::FromStr;
+
+/// Metres per second in a knot.
+pub const KNOT_MS: f64 = 1852.0 / 3600.0;
+
+/// The 16 compass points, clockwise from north in 22.5° steps.
+const POINTS: [&str; 16] = [
+    "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW", "NW",
+    "NNW",
+];
+
+/// The directions from `from` clockwise to `to`, in degrees; `300-30`
+/// wraps through north.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Sector {
+    pub from: f64,
+    pub to: f64,
+}
+
+impl Sector {
+    pub fn contains(&self, degrees: f64) -> bool {
+        let width = (self.to - self.from).rem_euclid(360.0);
+        (degrees - self.from).rem_euclid(360.0) <= width
+    }
+}
+
+impl FromStr for Sector {
+    type Err = String;
+
+    /// `180-240`, or compass points such as `S-WSW`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split_once('-')
+            .and_then(|(from, to)| Some((bearing(from)?, bearing(to)?)))
+            .map(|(from, to)| Sector { from, to })
+            .ok_or_else(|| {
+                format!(
+                    "Expected directions such as 180-240 or S-WSW, clockwise, not '{}'",
+                    s
+                )
+            })
+    }
+}
+
+/// Degrees from north given as a number from 0 to 360 or a compass point
+/// such as `WSW`.
+pub fn bearing(text: &str) -> Option<f64> {
+    let text = text.trim();
+    match text.parse::<f64>() {
+        Ok(degrees) if (0.0..=360.0).contains(&degrees) => Some(degrees),
+        Ok(_) => None,
+        Err(_) => POINTS
+            .iter()
+            .position(|point| point.eq_ignore_ascii_case(text))
+            .map(|i| i as f64 * 22.5),
+    }
+}
+
+/// What the wind must do for an hour to count.
+#[derive(Debug, Clone, Copy)]
+pub struct Criteria {
+    pub min_ms: f64,
+    pub max_ms: f64,
+    /// Where it must blow from; any direction when `None`.
+    pub sector: Option<Sector>,
+}
+
+impl Criteria {
+    pub fn suits(&self, hour: &Hour) -> bool {
+        (self.min_ms..=self.max_ms).contains(&hour.wind_speed_ms)
+            && self
+                .sector
+                .is_none_or(|sector| sector.contains(hour.wind_from_deg))
+    }
+}
+
+/// Consecutive hours that suit, as Unix timestamps, and the wind over them.
+#[derive(Debug, Clone, Serialize)]
+pub struct Window {
+    pub start: i64,
+    pub end: i64,
+    pub min_speed_ms: f64,
+    pub max_speed_ms: f64,
+    /// The strongest gust, when the forecast gives gusts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_gust_ms: Option<f64>,
+    /// The mean direction the wind blows from, in degrees.
+    pub from_deg: f64,
+}
+
+impl Window {
+    pub fn hours(&self) -> i64 {
+        (self.end - self.start) / 3600
+    }
+}
+
+/// The windows among `hours`, in order, lasting at least `min_hours`.
+pub fn windows(hours: &[Hour], criteria: &Criteria, min_hours: i64) -> Vec<Window> {
+    let mut windows = Vec::new();
+    let mut run: Vec<&Hour> = Vec::new();
+    for hour in hours {
+        // A gap in the forecast ends a window like an unsuitable hour.
+        let follows = run
+            .last()
+            .is_some_and(|last| last.start + 3600 == hour.start);
+        if !follows || !criteria.suits(hour) {
+            windows.extend(window(&run));
+            run.clear();
+        }
+        if criteria.suits(hour) {
+            run.push(hour);
+        }
+    }
+    windows.extend(window(&run));
+    windows.retain(|window| window.hours() >= min_hours);
+    windows
+}
+
+fn window(run: &[&Hour]) -> Option<Window> {
+    let (first, last) = (run.first()?, run.last()?);
+    let speeds = run.iter().map(|hour| hour.wind_speed_ms);
+    // Directions are averaged as vectors, so 350° and 10° make north.
+    let (x, y) = run.iter().fold((0.0, 0.0), |(x, y), hour| {
+        let angle = hour.wind_from_deg.to_radians();
+        (x + angle.sin(), y + angle.cos())
+    });
+    Some(Window {
+        start: first.start,
+        end: last.start + 3600,
+        min_speed_ms: speeds.clone().fold(f64::INFINITY, f64::min),
+        max_speed_ms: speeds.fold(0.0, f64::max),
+        max_gust_ms: run
+            .iter()
+            .filter_map(|hour| hour.wind_gust_ms)
+            .reduce(f64::max),
+        from_deg: f64::atan2(x, y).to_degrees().rem_euclid(360.0),
+    })
+}
@@ -0,0 +1,373 @@
+//! Offline sun and moon ephemeris.
+//!
+//! The sun follows the NOAA solar calculator equations (accurate to about a
+//! minute for sunrise and sunset away from the poles); the moon uses Paul
+//! Schlyter's low-precision orbital elements with the main perturbation
+//! terms, good to a few minutes for rise and set times.
+
+use chrono::{NaiveDate, NaiveTime};
+use std::f64::consts::PI;
+
+/// Altitude of the sun's centre at sunrise/sunset, allowing for refraction
+/// and the solar radius.
+pub const SUNRISE_ALTITUDE: f64 = -0.833;
+pub const CIVIL_ALTITUDE: f64 = -6.0;
+pub const NAUTICAL_ALTITUDE: f64 = -12.0;
+pub const ASTRONOMICAL_ALTITUDE: f64 = -18.0;
+
+const SYNODIC_MONTH_DAYS: f64 = 29.530_588_853;
+/// A reference new moon: 2000-01-06 18:14 UTC.
+const REFERENCE_NEW_MOON_JD: f64 = 2_451_550.26;
+
+/// Start of morning twilight (dawn) and end of evening twilight (dusk), as
+/// Unix timestamps. `None` when the sun doesn't cross the phase's altitude
+/// that day, e.g. astronomical twilight lasting all night in a northern June.
+#[derive(Debug, Clone, Copy)]
+pub struct Twilight {
+    pub dawn: Option<i64>,
+    pub dusk: Option<i64>,
+}
+
+/// Solar events for one local calendar day.
+#[derive(Debug, Clone, Copy)]
+pub struct SunTimes {
+    pub sunrise: Option<i64>,
+    pub sunset: Option<i64>,
+    pub solar_noon: i64,
+    pub civil: Twilight,
+    pub nautical: Twilight,
+    pub astronomical: Twilight,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MoonPhase {
+    /// Position in the lunation, 0.0 (new) through 0.5 (full) to 1.0.
+    pub age: f64,
+    /// Illuminated fraction of the disc, 0.0 to 1.0.
+    pub illumination: f64,
+    pub name: &'static str,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MoonTimes {
+    pub moonrise: Option<i64>,
+    pub moonset: Option<i64>,
+}
+
+fn julian_day(timestamp: i64) -> f64 {
+    timestamp as f64 / 86_400.0 + 2_440_587.5
+}
+
+/// Unix timestamp of local midnight at the start of `date`.
+fn local_midnight(date: NaiveDate, utc_offset: i32) -> i64 {
+    date.and_time(NaiveTime::MIN).and_utc().timestamp() - utc_offset as i64
+}
+
+/// Solar declination (degrees) and equation of time (minutes) at `jd`.
+fn solar_coordinates(jd: f64) -> (f64, f64) {
+    let t = (jd - 2_451_545.0) / 36_525.0;
+    let l0 = (280.466_46 + t * (36_000.769_83 + t * 0.000_303_2)).rem_euclid(360.0);
+    let m = 357.529_11 + t * (35_999.050_29 - 0.000_153_7 * t);
+    let e = 0.016_708_634 - t * (0.000_042_037 + 0.000_000_126_7 * t);
+    let m_rad = m.to_radians();
+    let center = m_rad.sin() * (1.914_602 - t * (0.004_817 + 0.000_014 * t))
+        + (2.0 * m_rad).sin() * (0.019_993 - 0.000_101 * t)
+        + (3.0 * m_rad).sin() * 0.000_289;
+    let true_longitude = l0 + center;
+    let omega = (125.04 - 1_934.136 * t).to_radians();
+    let apparent_longitude = true_longitude - 0.005_69 - 0.004_78 * omega.sin();
+    let mean_obliquity =
+        23.0 + (26.0 + (21.448 - t * (46.815 + t * (0.000_59 - t * 0.001_813))) / 60.0) / 60.0;
+    let obliquity = (mean_obliquity + 0.002_56 * omega.cos()).to_radians();
+
+    let declination = (obliquity.sin() * apparent_longitude.to_radians().sin()).asin();
+
+    let y = (obliquity / 2.0).tan().powi(2);
+    let l0_rad = l0.to_radians();
+    let equation_of_time = 4.0
+        * (y * (2.0 * l0_rad).sin() - 2.0 * e * m_rad.sin()
+            + 4.0 * e * y * m_rad.sin() * (2.0 * l0_rad).cos()
+            - 0.5 * y * y * (4.0 * l0_rad).sin()
+            - 1.25 * e * e * (2.0 * m_rad).sin())
+        .to_degrees();
+
+    (declination.to_degrees(), equation_of_time)
+}
+
+/// Elevation of the sun's centre above the horizon in degrees, without
+/// refraction.
+pub fn solar_elevation(lat: f64, lon: f64, at: i64) -> f64 {
+    let (declination, equation_of_time) = solar_coordinates(julian_day(at));
+    let utc_minutes = at.rem_euclid(86_400) as f64 / 60.0;
+    let true_solar_time = (utc_minutes + equation_of_time + 4.0 * lon).rem_euclid(1_440.0);
+    let hour_angle = (true_solar_time / 4.0 - 180.0).to_radians();
+    let (lat, declination) = (lat.to_radians(), declination.to_radians());
+    let cos_zenith =
+        lat.sin() * declination.sin() + lat.cos() * declination.cos() * hour_angle.cos();
+    90.0 - cos_zenith.clamp(-1.0, 1.0).acos().to_degrees()
+}
+
+/// Morning and evening times at which the sun's centre is at `altitude`,
+/// measured from `solar_noon` and refined once with the declination at the
+/// estimated event time.
+fn solar_event(lat: f64, solar_noon: i64, altitude: f64) -> (Option<i64>, Option<i64>) {
+    let hour_angle_at = |at: i64| -> Option<f64> {
+        let (declination, _) = solar_coordinates(julian_day(at));
+        let (lat, declination) = (lat.to_radians(), declination.to_radians());
+        let cos_hour_angle = (altitude.to_radians().sin() - lat.sin() * declination.sin())
+            / (lat.cos() * declination.cos());
+        (-1.0..=1.0)
+            .contains(&cos_hour_angle)
+            .then(|| cos_hour_angle.acos().to_degrees())
+    };
+    // One degree of hour angle is four minutes of time.
+    let event = |sign: f64| {
+        let first = solar_noon + (sign * hour_angle_at(solar_noon)? * 240.0) as i64;
+        let refined =
+            hour_angle_at(first).map_or(first, |h| solar_noon + (sign * h * 240.0) as i64);
+        Some(refined)
+    };
+    (event(-1.0), event(1.0))
+}
+
+/// Sunrise, sunset, solar noon and the three twilight phases for a local
+/// date.
+pub fn sun_times(lat: f64, lon: f64, date: NaiveDate, utc_offset: i32) -> SunTimes {
+    let midnight = local_midnight(date, utc_offset);
+    let (_, equation_of_time) = solar_coordinates(julian_day(midnight + 43_200));
+    let mut solar_noon = midnight - midnight.rem_euclid(86_400)
+        + ((720.0 - 4.0 * lon - equation_of_time) * 60.0) as i64;
+    while solar_noon < midnight {
+        solar_noon += 86_400;
+    }
+    while solar_noon >= midnight + 86_400 {
+        solar_noon -= 86_400;
+    }
+
+    let twilight = |altitude: f64| {
+        let (dawn, dusk) = solar_event(lat, solar_noon, altitude);
+        Twilight { dawn, dusk }
+    };
+    let (sunrise, sunset) = solar_event(lat, solar_noon, SUNRISE_ALTITUDE);
+
+    SunTimes {
+        sunrise,
+        sunset,
+        solar_noon,
+        civil: twilight(CIVIL_ALTITUDE),
+        nautical: twilight(NAUTICAL_ALTITUDE),
+        astronomical: twilight(ASTRONOMICAL_ALTITUDE),
+    }
+}
+
+pub fn moon_phase(at: i64) -> MoonPhase {
+    let age = ((julian_day(at) - REFERENCE_NEW_MOON_JD) / SYNODIC_MONTH_DAYS).rem_euclid(1.0);
+    let illumination = (1.0 - (2.0 * PI * age).cos()) / 2.0;
+    let name = match age {
+        a if !(0.0339..0.9661).contains(&a) => "New Moon",
+        a if a < 0.2161 => "Waxing Crescent",
+        a if a < 0.2839 => "First Quarter",
+        a if a < 0.4661 => "Waxing Gibbous",
+        a if a < 0.5339 => "Full Moon",
+        a if a < 0.7161 => "Waning Gibbous",
+        a if a < 0.7839 => "Last Quarter",
+        _ => "Waning Crescent",
+    };
+    MoonPhase {
+        age,
+        illumination,
+        name,
+    }
+}
+
+/// Geocentric right ascension and declination of the moon (degrees) and its
+/// distance in Earth radii.
+fn moon_coordinates(at: i64) -> (f64, f64, f64) {
+    let d = julian_day(at) - 2_451_543.5;
+    let deg = |x: f64| x.rem_euclid(360.0);
+
+    let n = deg(125.1228 - 0.052_953_808_3 * d).to_radians();
+    let i = 5.1454_f64.to_radians();
+    let w = deg(318.0634 + 0.164_357_322_3 * d);
+    let a = 60.2666;
+    let e = 0.054_900;
+    let m = deg(115.3654 + 13.064_992_950_9 * d);
+
+    // Eccentric anomaly by Newton iteration.
+    let m_rad = m.to_radians();
+    let mut ecc = m_rad + e * m_rad.sin() * (1.0 + e * m_rad.cos());
+    for _ in 0..5 {
+        ecc -= (ecc - e * ecc.sin() - m_rad) / (1.0 - e * ecc.cos());
+    }
+    let xv = a * (ecc.cos() - e);
+    let yv = a * ((1.0 - e * e).sqrt() * ecc.sin());
+    let v = yv.atan2(xv);
+    let r = (xv * xv + yv * yv).sqrt();
+
+    let vw = v + w.to_radians();
+    let xh = r * (n.cos() * vw.cos() - n.sin() * vw.sin() * i.cos());
+    let yh = r * (n.sin() * vw.cos() + n.cos() * vw.sin() * i.cos());
+    let zh = r * (vw.sin() * i.sin());
+    let mut longitude = yh.atan2(xh).to_degrees();
+    let mut latitude = zh.atan2((xh * xh + yh * yh).sqrt()).to_degrees();
+
+    // Main perturbations.
+    let ms = deg(356.0470 + 0.985_600_258_5 * d).to_radians();
+    let ls = ms + deg(282.9404 + 4.70935e-5 * d).to_radians();
+    let lm = m_rad + w.to_radians() + n;
+    let dm = lm - ls;
+    let f = lm - n;
+    longitude += -1.274 * (m_rad - 2.0 * dm).sin() + 0.658 * (2.0 * dm).sin()
+        - 0.186 * ms.sin()
+        - 0.059 * (2.0 * m_rad - 2.0 * dm).sin()
+        - 0.057 * (m_rad - 2.0 * dm + ms).sin()
+        + 0.053 * (m_rad + 2.0 * dm).sin()
+        + 0.046 * (2.0 * dm - ms).sin()
+        + 0.041 * (m_rad - ms).sin()
+        - 0.035 * dm.sin()
+        - 0.031 * (m_rad + ms).sin()
+        - 0.015 * (2.0 * f - 2.0 * dm).sin()
+        + 0.011 * (m_rad - 4.0 * dm).sin();
+    latitude += -0.173 * (f - 2.0 * dm).sin()
+        - 0.055 * (m_rad - f - 2.0 * dm).sin()
+        - 0.046 * (m_rad + f - 2.0 * dm).sin()
+        + 0.033 * (f + 2.0 * dm).sin()
+        + 0.017 * (2.0 * m_rad + f).sin();
+    let distance = r - 0.58 * (m_rad - 2.0 * dm).cos() - 0.46 * (2.0 * dm).cos();
+
+    let obliquity = (23.4393 - 3.563e-7 * d).to_radians();
+    let (lon_r, lat_r) = (longitude.to_radians(), latitude.to_radians());
+    let xe = lon_r.cos() * lat_r.cos();
+    let ye = lon_r.sin() * lat_r.cos() * obliquity.cos() - lat_r.sin() * obliquity.sin();
+    let ze = lon_r.sin() * lat_r.cos() * obliquity.sin() + lat_r.sin() * obliquity.cos();
+    let right_ascension = ye.atan2(xe).to_degrees().rem_euclid(360.0);
+    let declination = ze.atan2((xe * xe + ye * ye).sqrt()).to_degrees();
+    (right_ascension, declination, distance)
+}
+
+/// Topocentric altitude of the moon's centre in degrees.
+fn moon_altitude(lat: f64, lon: f64, at: i64) -> f64 {
+    let (right_ascension, declination, distance) = moon_coordinates(at);
+    let jd = julian_day(at);
+    let sidereal =
+        (280.460_618_37 + 360.985_647_366_29 * (jd - 2_451_545.0) + lon).rem_euclid(360.0);
+    let hour_angle = (sidereal - right_ascension).to_radians();
+    let (lat_r, decl_r) = (lat.to_radians(), declination.to_radians());
+    let altitude = (lat_r.sin() * decl_r.sin() + lat_r.cos() * decl_r.cos() * hour_angle.cos())
+        .asin()
+        .to_degrees();
+    let parallax = (1.0 / distance).asin().to_degrees();
+    altitude - parallax * altitude.to_radians().cos()
+}
+
+/// Moonrise and moonset during a local date, found by scanning the moon's
+/// altitude in ten-minute steps. Either may be missing on a given day.
+pub fn moon_times(lat: f64, lon: f64, date: NaiveDate, utc_offset: i32) -> MoonTimes {
+    // Upper limb on the horizon, with refraction.
+    const HORIZON: f64 = -0.583;
+    const STEP: i64 = 600;
+
+    let start = local_midnight(date, utc_offset);
+    let mut times = MoonTimes {
+        moonrise: None,
+        moonset: None,
+    };
+    let mut previous = moon_altitude(lat, lon, start) - HORIZON;
+    let mut t = start;
+    while t < start + 86_400 {
+        let next = moon_altitude(lat, lon, t + STEP) - HORIZON;
+        if previous.signum() != next.signum() {
+            let crossing = t + (STEP as f64 * previous / (previous - next)) as i64;
+            if previous < 0.0 {
+                times.moonrise.get_or_insert(crossing);
+            } else {
+                times.moonset.get_or_insert(crossing);
+            }
+        }
+        previous = next;
+        t += STEP;
+    }
+    times
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    /// The timestamp of `time` on `date` at UTC offset `utc_offset`.
+    fn at(date: NaiveDate, time: &str, utc_offset: i32) -> i64 {
+        let time = NaiveTime::parse_from_str(time, "%H:%M").unwrap();
+        date.and_time(time).and_utc().timestamp() - utc_offset as i64
+    }
+
+    fn assert_within_a_minute(actual: Option<i64>, expected: i64) {
+        let actual = actual.expect("an event that day");
+        assert!(
+            (actual - expected).abs() <= 60,
+            "{} is {} s from {}",
+            actual,
+            actual - expected,
+            expected
+        );
+    }
+
+    #[test]
+    fn sunrise_and_sunset_match_noaa() {
+        // London at the June solstice, in BST.
+        let day = date(2024, 6, 21);
+        let sun = sun_times(51.5074, -0.1278, day, 3600);
+        assert_within_a_minute(sun.sunrise, at(day, "04:43", 3600));
+        assert_within_a_minute(sun.sunset, at(day, "21:21", 3600));
+
+        // New York at the December solstice, in EST.
+        let day = date(2024, 12, 21);
+        let sun = sun_times(40.7128, -74.0060, day, -5 * 3600);
+        assert_within_a_minute(sun.sunrise, at(day, "07:17", -5 * 3600));
+        assert_within_a_minute(sun.sunset, at(day, "16:32", -5 * 3600));
+    }
+
+    #[test]
+    fn no_sunrise_or_sunset_in_polar_night_or_midnight_sun() {
+        // Tromsø.
+        let winter = sun_times(69.6492, 18.9553, date(2024, 12, 21), 3600);
+        assert_eq!((winter.sunrise, winter.sunset), (None, None));
+        // Civil twilight still comes and goes at midday.
+        assert!(winter.civil.dawn.is_some() && winter.civil.dusk.is_some());
+
+        let summer = sun_times(69.6492, 18.9553, date(2024, 6, 21), 7200);
+        assert_eq!((summer.sunrise, summer.sunset), (None, None));
+    }
+
+    #[test]
+    fn moon_phase_matches_known_full_and_new_moons() {
+        let full = moon_phase(at(date(2024, 1, 25), "17:54", 0));
+        assert_eq!(full.name, "Full Moon");
+        assert!((full.age - 0.5).abs() < 0.03, "{}", full.age);
+        assert!(full.illumination > 0.99);
+
+        // The total solar eclipse of April 2024.
+        let new = moon_phase(at(date(2024, 4, 8), "18:21", 0));
+        assert_eq!(new.name, "New Moon");
+        assert!(new.age.min(1.0 - new.age) < 0.03, "{}", new.age);
+        assert!(new.illumination < 0.01);
+    }
+
+    #[test]
+    fn full_moon_rises_around_sunset() {
+        let day = date(2024, 1, 25);
+        let moon = moon_times(51.5074, -0.1278, day, 0);
+        let sun = sun_times(51.5074, -0.1278, day, 0);
+        let (moonrise, sunset) = (moon.moonrise.unwrap(), sun.sunset.unwrap());
+        assert!(
+            (moonrise - sunset).abs() < 3600,
+            "{} vs {}",
+            moonrise,
+            sunset
+        );
+        assert!(moon.moonset.unwrap() < sun.sunrise.unwrap() + 3600);
+    }
+}
@@ -0,0 +1,59 @@
+//! Air density and the altitudes pilots and athletes reckon with, from the
+//! International Standard Atmosphere (ISA) of ICAO Doc 7488.
+
+/// ISA sea-level pressure, temperature and density.
+pub const SEA_LEVEL_PRESSURE_HPA: f64 = 1013.25;
+pub const SEA_LEVEL_TEMPERATURE_K: f64 = 288.15;
+pub const SEA_LEVEL_DENSITY: f64 = 1.225;
+
+/// ISA temperature lapse rate in K/m.
+const LAPSE_RATE: f64 = 0.0065;
+/// Specific gas constants of dry air and water vapour, in J/(kg·K).
+const R_DRY: f64 = 287.058;
+const R_VAPOUR: f64 = 461.495;
+/// g·M/(R·L), the exponent relating pressure to height in the ISA.
+const PRESSURE_EXPONENT: f64 = 5.255_88;
+/// The exponent relating density to height: `PRESSURE_EXPONENT - 1`.
+const DENSITY_EXPONENT: f64 = 4.255_88;
+
+/// Density of moist air in kg/m³ at `pressure_hpa` (the pressure where the
+/// air is, not reduced to sea level).
+pub fn air_density(pressure_hpa: f64, temperature_c: f64, humidity_percent: f64) -> f64 {
+    let temperature_k = temperature_c + 273.15;
+    // Tetens' saturation vapour pressure, in hPa.
+    let saturation = 6.1078 * (17.27 * temperature_c / (temperature_c + 237.3)).exp();
+    let vapour = saturation * humidity_percent / 100.0;
+    let dry = pressure_hpa - vapour;
+    (dry * 100.0 / (R_DRY * temperature_k)) + (vapour * 100.0 / (R_VAPOUR * temperature_k))
+}
+
+/// The height in metres at which the ISA has `pressure_hpa`: what an
+/// altimeter set to 1013.25 hPa reads.
+pub fn pressure_altitude_m(pressure_hpa: f64) -> f64 {
+    SEA_LEVEL_TEMPERATURE_K / LAPSE_RATE
+        * (1.0 - (pressure_hpa / SEA_LEVEL_PRESSURE_HPA).powf(1.0 / PRESSURE_EXPONENT))
+}
+
+/// The height in metres at which the ISA has `density` kg/m³: how high the
+/// air "feels" to a wing, an engine or a rider.
+pub fn density_altitude_m(density: f64) -> f64 {
+    SEA_LEVEL_TEMPERATURE_K / LAPSE_RATE
+        * (1.0 - (density / SEA_LEVEL_DENSITY).powf(1.0 / DENSITY_EXPONENT))
+}
+
+/// The pressure at `elevation_m` given the pressure reduced to sea level,
+/// assuming the ISA lapse rate below the station.
+pub fn station_pressure(sea_level_hpa: f64, elevation_m: f64, temperature_c: f64) -> f64 {
+    let temperature_k = temperature_c + 273.15;
+    sea_level_hpa
+        * (1.0 - LAPSE_RATE * elevation_m / (temperature_k + LAPSE_RATE * elevation_m))
+            .powf(PRESSURE_EXPONENT)
+}
+
+/// The elevation in metres at which `sea_level_hpa` reduces to
+/// `station_hpa`; the inverse of [`station_pressure`].
+pub fn elevation_m(sea_level_hpa: f64, station_hpa: f64, temperature_c: f64) -> f64 {
+    let temperature_k = temperature_c + 273.15;
+    let ratio = (station_hpa / sea_level_hpa).powf(1.0 / PRESSURE_EXPONENT);
+    temperature_k * (1.0 - ratio) / (LAPSE_RATE * ratio)
+}
@@ -0,0 +1,154 @@
+//! Geographic helpers.
+
+/// Great-circle distance in metres between two coordinates (haversine).
+pub fn distance_m(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    const EARTH_RADIUS_M: f64 = 6_371_000.0;
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let d_phi = (lat2 - lat1).to_radians();
+    let d_lambda = (lon2 - lon1).to_radians();
+    let a = (d_phi / 2.0).sin().powi(2) + phi1.cos() * phi2.cos() * (d_lambda / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_M * a.sqrt().asin()
+}
+
+/// Initial great-circle bearing from the first coordinate towards the
+/// second, in degrees from north.
+pub fn bearing_deg(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let d_lambda = (lon2 - lon1).to_radians();
+    let y = d_lambda.sin() * phi2.cos();
+    let x = phi1.cos() * phi2.sin() - phi1.sin() * phi2.cos() * d_lambda.cos();
+    y.atan2(x).to_degrees().rem_euclid(360.0)
+}
+
+/// Inverse-distance-weighted estimate at `(lat, lon)` from `(lat, lon,
+/// value)` samples, weighting each by 1/d². A sample within a metre of the
+/// point is returned as is. `None` without samples.
+pub fn idw(lat: f64, lon: f64, samples: &[(f64, f64, f64)]) -> Option<f64> {
+    let mut weighted = 0.0;
+    let mut total = 0.0;
+    for &(sample_lat, sample_lon, value) in samples {
+        let distance = distance_m(lat, lon, sample_lat, sample_lon);
+        if distance < 1.0 {
+            return Some(value);
+        }
+        let weight = 1.0 / (distance * distance);
+        weighted += weight * value;
+        total += weight;
+    }
+    (total > 0.0).then(|| weighted / total)
+}
+
+/// A polygon as rings of `(lat, lon)` vertices: the outline first, then any
+/// holes, as in GeoJSON.
+pub type Polygon = Vec<Vec<(f64, f64)>>;
+
+/// Whether `(lat, lon)` lies inside `polygon`, by ray casting. Edges are
+/// treated as straight in latitude and longitude, which is close enough for
+/// areas the size of a county; rings may cross the antimeridian.
+pub fn polygon_contains(polygon: &Polygon, lat: f64, lon: f64) -> bool {
+    let mut rings = polygon.iter();
+    let Some(outline) = rings.next() else {
+        return false;
+    };
+    ring_contains(outline, lat, lon) && !rings.any(|hole| ring_contains(hole, lat, lon))
+}
+
+/// Whether `(lat, lon)` lies inside any of `polygons`, as in a GeoJSON
+/// multi-polygon.
+pub fn polygons_contain(polygons: &[Polygon], lat: f64, lon: f64) -> bool {
+    polygons
+        .iter()
+        .any(|polygon| polygon_contains(polygon, lat, lon))
+}
+
+fn ring_contains(ring: &[(f64, f64)], lat: f64, lon: f64) -> bool {
+    // Each vertex's longitude is taken within 180° of the one before, so
+    // that a ring crossing the antimeridian stays in one piece, e.g. from
+    // 170° to 190° rather than 170° to -170°.
+    let mut vertices = Vec::with_capacity(ring.len());
+    for &(vertex_lat, vertex_lon) in ring {
+        let previous = vertices.last().map_or(vertex_lon, |&(_, lon)| lon);
+        vertices.push((vertex_lat, near(vertex_lon, previous)));
+    }
+    let Some(&(_, first_lon)) = vertices.first() else {
+        return false;
+    };
+    let lon = near(lon, first_lon);
+    let mut inside = false;
+    let mut previous = vertices[vertices.len() - 1];
+    for &vertex in &vertices {
+        let ((lat1, lon1), (lat2, lon2)) = (previous, vertex);
+        if (lat1 > lat) != (lat2 > lat) && lon < lon1 + (lat - lat1) / (lat2 - lat1) * (lon2 - lon1)
+        {
+            inside = !inside;
+        }
+        previous = vertex;
+    }
+    inside
+}
+
+/// `lon` moved by whole turns to within 180° of `reference`.
+fn near(lon: f64, reference: f64) -> f64 {
+    reference + (lon - reference + 180.0).rem_euclid(360.0) - 180.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A ring around `(lat, lon)` reaching `half` degrees each way.
+    fn square(lat: f64, lon: f64, half: f64) -> Vec<(f64, f64)> {
+        vec![
+            (lat - half, lon - half),
+            (lat - half, lon + half),
+            (lat + half, lon + half),
+            (lat + half, lon - half),
+        ]
+    }
+
+    #[test]
+    fn contains_points_inside_the_outline_only() {
+        let polygon = vec![square(40.0, -100.0, 1.0)];
+        assert!(polygon_contains(&polygon, 40.0, -100.0));
+        assert!(polygon_contains(&polygon, 40.9, -99.1));
+        assert!(!polygon_contains(&polygon, 41.5, -100.0));
+        assert!(!polygon_contains(&polygon, 40.0, -98.0));
+        assert!(!polygon_contains(&Vec::new(), 40.0, -100.0));
+    }
+
+    #[test]
+    fn excludes_points_in_a_hole() {
+        let polygon = vec![square(40.0, -100.0, 1.0), square(40.0, -100.0, 0.25)];
+        assert!(!polygon_contains(&polygon, 40.0, -100.0));
+        assert!(polygon_contains(&polygon, 40.5, -100.0));
+    }
+
+    #[test]
+    fn any_polygon_of_several_counts() {
+        let polygons = vec![
+            vec![square(40.0, -100.0, 1.0)],
+            vec![square(45.0, -90.0, 1.0)],
+        ];
+        assert!(polygons_contain(&polygons, 45.2, -90.3));
+        assert!(polygons_contain(&polygons, 40.0, -100.0));
+        assert!(!polygons_contain(&polygons, 43.0, -95.0));
+        assert!(!polygons_contain(&[], 40.0, -100.0));
+    }
+
+    #[test]
+    fn rings_may_cross_the_antimeridian() {
+        // Fiji's side of the date line: 178°E to 178°W.
+        let polygon = vec![vec![
+            (-18.0, 178.0),
+            (-18.0, -178.0),
+            (-16.0, -178.0),
+            (-16.0, 178.0),
+        ]];
+        assert!(polygon_contains(&polygon, -17.0, 179.5));
+        assert!(polygon_contains(&polygon, -17.0, -179.5));
+        assert!(polygon_contains(&polygon, -17.0, 180.0));
+        assert!(!polygon_contains(&polygon, -17.0, 0.0));
+        assert!(!polygon_contains(&polygon, -17.0, 177.0));
+        assert!(!polygon_contains(&polygon, -17.0, -177.0));
+    }
+}
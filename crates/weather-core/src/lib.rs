@@ -0,0 +1,19 @@
+//! Provider-agnostic core of the weather CLI: the report model, derived
+//! metrics computed from it, offline sun and moon calculations, the
+//! standard atmosphere and wind components.
+//!
+//! This crate follows semver strictly, because providers and third-party
+//! tools build on it. Adding fields to the model or calculators to the
+//! registry is a minor change; renaming, removing or changing the meaning
+//! of anything public is a major one. While the version is 0.y, those bump
+//! the patch and the minor version respectively. The model types are
+//! `#[non_exhaustive]` and built with their constructors, so that a new
+//! field breaks no one. The JSON schema version in
+//! [`model::SCHEMA_VERSION`] moves with the model.
+
+pub mod astro;
+pub mod atmosphere;
+pub mod geo;
+pub mod metrics;
+pub mod model;
+pub mod wind;
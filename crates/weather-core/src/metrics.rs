@@ -0,0 +1,236 @@
+//! Derived metrics computed from current conditions.
+//!
+//! Each metric is a calculator registered in [`CALCULATORS`]. Calculators run
+//! in registry order and can read the metrics computed before them, so
+//! composite indices build on simpler ones instead of repeating the maths.
+//! Which metrics appear in each output is chosen in the config file.
+
+use crate::atmosphere;
+use crate::model::Conditions;
+use std::collections::BTreeMap;
+
+/// How a metric's value is rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    /// A temperature in °C, converted for `--fahrenheit`.
+    Temperature,
+    /// A fixed unit symbol, e.g. "g/m³".
+    Fixed(&'static str),
+    /// A dimensionless number.
+    None,
+}
+
+pub struct Calculator {
+    pub key: &'static str,
+    pub label: &'static str,
+    pub icon: &'static str,
+    pub unit: Unit,
+    /// Decimal places shown.
+    pub precision: usize,
+    /// Returns `None` when the metric is undefined for the conditions, e.g.
+    /// wind chill on a warm day.
+    pub compute: fn(&Conditions, &Derived) -> Option<f64>,
+}
+
+/// Values computed so far, by calculator key.
+pub type Derived = BTreeMap<&'static str, f64>;
+
+pub const CALCULATORS: &[Calculator] = &[
+    Calculator {
+        key: "dew_point",
+        label: "Dew point",
+        icon: "💧",
+        unit: Unit::Temperature,
+        precision: 1,
+        compute: |current, _| {
+            Some(dew_point(
+                current.temperature_c,
+                current.humidity_percent as f64,
+            ))
+        },
+    },
+    Calculator {
+        key: "heat_index",
+        label: "Heat index",
+        icon: "🥵",
+        unit: Unit::Temperature,
+        precision: 1,
+        compute: |current, _| heat_index(current.temperature_c, current.humidity_percent as f64),
+    },
+    Calculator {
+        key: "wind_chill",
+        label: "Wind chill",
+        icon: "🥶",
+        unit: Unit::Temperature,
+        precision: 1,
+        compute: |current, _| wind_chill(current.temperature_c, current.wind_speed_ms),
+    },
+    Calculator {
+        key: "humidex",
+        label: "Humidex",
+        icon: "🌡️",
+        unit: Unit::None,
+        precision: 0,
+        compute: |current, derived| {
+            let dew = *derived.get("dew_point")?;
+            let vapour_pressure = 6.11 * (5417.7530 * (1.0 / 273.16 - 1.0 / (273.15 + dew))).exp();
+            Some(current.temperature_c + 0.5555 * (vapour_pressure - 10.0))
+        },
+    },
+    Calculator {
+        key: "absolute_humidity",
+        label: "Absolute humidity",
+        icon: "💦",
+        unit: Unit::Fixed("g/m³"),
+        precision: 1,
+        compute: |current, _| {
+            let t = current.temperature_c;
+            let saturation = 6.112 * (17.67 * t / (t + 243.5)).exp();
+            Some(saturation * current.humidity_percent as f64 * 2.1674 / (273.15 + t))
+        },
+    },
+    Calculator {
+        key: "cloud_base",
+        label: "Cloud base",
+        icon: "☁️",
+        unit: Unit::Fixed("m"),
+        precision: 0,
+        // About 125 m per degree of dew point depression.
+        compute: |current, derived| {
+            Some(125.0 * (current.temperature_c - derived.get("dew_point")?))
+        },
+    },
+    Calculator {
+        key: "air_density",
+        label: "Air density",
+        icon: "🌬️",
+        unit: Unit::Fixed("kg/m³"),
+        precision: 3,
+        // Sea-level pressure stands in when the station pressure is
+        // unknown, as if the place were at sea level.
+        compute: |current, _| {
+            Some(atmosphere::air_density(
+                station_pressure(current),
+                current.temperature_c,
+                current.humidity_percent as f64,
+            ))
+        },
+    },
+    Calculator {
+        key: "pressure_altitude",
+        label: "Pressure altitude",
+        icon: "🏔️",
+        unit: Unit::Fixed("m"),
+        precision: 0,
+        compute: |current, _| Some(atmosphere::pressure_altitude_m(station_pressure(current))),
+    },
+    Calculator {
+        key: "density_altitude",
+        label: "Density altitude",
+        icon: "🏔️",
+        unit: Unit::Fixed("m"),
+        precision: 0,
+        compute: |_, derived| Some(atmosphere::density_altitude_m(*derived.get("air_density")?)),
+    },
+    Calculator {
+        key: "beaufort",
+        label: "Beaufort force",
+        icon: "🌬️",
+        unit: Unit::None,
+        precision: 0,
+        compute: |current, _| Some(beaufort(current.wind_speed_ms) as f64),
+    },
+];
+
+pub fn lookup(key: &str) -> Option<&'static Calculator> {
+    CALCULATORS.iter().find(|c| c.key == key)
+}
+
+/// Checks that every name in `keys` is a registered metric.
+pub fn validate(keys: &[String]) -> Result<(), String> {
+    match keys.iter().find(|key| lookup(key).is_none()) {
+        Some(unknown) => Err(format!(
+            "Unknown metric '{}'. Available: {}",
+            unknown,
+            CALCULATORS
+                .iter()
+                .map(|c| c.key)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Runs every calculator and returns the requested metrics, in the order
+/// given, skipping those undefined for the current conditions.
+pub fn compute(current: &Conditions, keys: &[String]) -> Vec<(&'static Calculator, f64)> {
+    if keys.is_empty() {
+        return Vec::new();
+    }
+    let mut derived = Derived::new();
+    for calculator in CALCULATORS {
+        if let Some(value) = (calculator.compute)(current, &derived) {
+            derived.insert(calculator.key, value);
+        }
+    }
+    keys.iter()
+        .filter_map(|key| {
+            let calculator = lookup(key)?;
+            Some((calculator, *derived.get(calculator.key)?))
+        })
+        .collect()
+}
+
+/// Dew point in °C from temperature (°C) and relative humidity (%), using the
+/// Magnus approximation.
+pub fn dew_point(celsius: f64, humidity: f64) -> f64 {
+    let (b, c) = (17.62, 243.12);
+    let gamma = (humidity.max(1.0) / 100.0).ln() + b * celsius / (c + celsius);
+    c * gamma / (b - gamma)
+}
+
+/// The pressure where the place is, else the sea-level pressure.
+fn station_pressure(current: &Conditions) -> f64 {
+    current
+        .station_pressure_hpa
+        .unwrap_or(f64::from(current.pressure_hpa))
+}
+
+/// NWS Rothfusz regression; only meaningful from about 27 °C.
+fn heat_index(celsius: f64, humidity: f64) -> Option<f64> {
+    if celsius < 27.0 || humidity < 40.0 {
+        return None;
+    }
+    let t = celsius * 9.0 / 5.0 + 32.0;
+    let rh = humidity;
+    let fahrenheit = -42.379 + 2.049_015_23 * t + 10.143_331_27 * rh
+        - 0.224_755_41 * t * rh
+        - 0.006_837_83 * t * t
+        - 0.054_817_17 * rh * rh
+        + 0.001_228_74 * t * t * rh
+        + 0.000_852_82 * t * rh * rh
+        - 0.000_001_99 * t * t * rh * rh;
+    Some((fahrenheit - 32.0) * 5.0 / 9.0)
+}
+
+/// North American wind chill index; defined at or below 10 °C with wind
+/// above 4.8 km/h.
+fn wind_chill(celsius: f64, wind_ms: f64) -> Option<f64> {
+    let kmh = wind_ms * 3.6;
+    if celsius > 10.0 || kmh <= 4.8 {
+        return None;
+    }
+    let v = kmh.powf(0.16);
+    Some(13.12 + 0.6215 * celsius - 11.37 * v + 0.3965 * celsius * v)
+}
+
+fn beaufort(speed_ms: f64) -> u8 {
+    const LIMITS: [f64; 12] = [
+        0.5, 1.6, 3.4, 5.5, 8.0, 10.8, 13.9, 17.2, 20.8, 24.5, 28.5, 32.7,
+    ];
+    LIMITS
+        .iter()
+        .take_while(|limit| speed_ms >= **limit)
+        .count() as u8
+}
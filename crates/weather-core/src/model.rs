@@ -0,0 +1,436 @@
+//! Provider-agnostic weather model used for machine-readable output.
+//!
+//! The JSON produced from these types is a stable contract described by
+//! `schema/weather-report.v1.json`. Fields may be added in minor versions;
+//! renaming or removing a field requires bumping the major version.
+//!
+//! So that adding a field does not break code building these types in other
+//! crates, they are `#[non_exhaustive]`: build them with their constructors
+//! and set the remaining public fields.
+
+use crate::geo::{self, Polygon};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub const SCHEMA_VERSION: &str = "1.8";
+
+/// The JSON schema describing [`Report`].
+pub const SCHEMA: &str = include_str!("../schema/weather-report.v1.json");
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[non_exhaustive]
+pub struct Report {
+    pub schema_version: String,
+    pub source: Source,
+    pub location: Location,
+    pub observed_at: DateTime<Utc>,
+    pub current: Conditions,
+    /// Derived metrics selected in the config, by metric key (since 1.1).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub derived: BTreeMap<String, f64>,
+    /// Readings from the user's own weather station, when one is configured
+    /// (since 1.3).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local: Option<LocalObservation>,
+    /// Conditions interpolated for the exact coordinates from the
+    /// surrounding grid points, with `--hyperlocal` (since 1.4).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hyperlocal: Option<Hyperlocal>,
+    /// Alerts in force at the location, worst first, with `--alerts`
+    /// (since 1.5).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alerts: Vec<Alert>,
+}
+
+impl Report {
+    /// A report of the current schema version, without derived metrics,
+    /// station readings, hyperlocal conditions or alerts.
+    pub fn new(
+        source: Source,
+        location: Location,
+        observed_at: DateTime<Utc>,
+        current: Conditions,
+    ) -> Self {
+        Report {
+            schema_version: SCHEMA_VERSION.to_string(),
+            source,
+            location,
+            observed_at,
+            current,
+            derived: BTreeMap::new(),
+            local: None,
+            hyperlocal: None,
+            alerts: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[non_exhaustive]
+pub struct Source {
+    pub provider: String,
+    /// The provider's identifier of the station or grid cell the data comes
+    /// from (since 1.2).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub station: Option<String>,
+    /// The station's name, e.g. the city OpenWeatherMap matched (since 1.8).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Where the station or the centre of the grid cell is, which may be
+    /// some way from the location asked for (since 1.8).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latitude: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub longitude: Option<f64>,
+}
+
+impl Source {
+    /// Data from `provider`, without a station.
+    pub fn new(provider: impl Into<String>) -> Self {
+        Source {
+            provider: provider.into(),
+            station: None,
+            name: None,
+            latitude: None,
+            longitude: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[non_exhaustive]
+pub struct Location {
+    pub name: String,
+    pub native_name: Option<String>,
+    pub country: String,
+    pub state: Option<String>,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Offset of local time from UTC in seconds.
+    pub utc_offset_seconds: i32,
+}
+
+impl Location {
+    /// A place on UTC, without a native name, country or state.
+    pub fn new(name: impl Into<String>, latitude: f64, longitude: f64) -> Self {
+        Location {
+            name: name.into(),
+            native_name: None,
+            country: String::new(),
+            state: None,
+            latitude,
+            longitude,
+            utc_offset_seconds: 0,
+        }
+    }
+}
+
+/// Current conditions, always in metric units regardless of display flags.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[non_exhaustive]
+pub struct Conditions {
+    pub condition: Condition,
+    pub temperature_c: f64,
+    pub feels_like_c: f64,
+    pub temperature_min_c: f64,
+    pub temperature_max_c: f64,
+    pub humidity_percent: i32,
+    pub pressure_hpa: i32,
+    /// The pressure where the place is, not reduced to sea level, when the
+    /// provider reports it or the elevation is known (since 1.7).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub station_pressure_hpa: Option<f64>,
+    pub wind_speed_ms: f64,
+    pub wind_direction_deg: i32,
+    pub wind_gust_ms: Option<f64>,
+    pub visibility_m: i32,
+    pub cloud_cover_percent: i32,
+    /// Rain over the last hour, when any fell (since 1.8).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rain_mm: Option<f64>,
+    /// Snow over the last hour, as water, when any fell (since 1.8).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snow_mm: Option<f64>,
+    /// `None` when the sun does not rise or set that day, or the provider
+    /// does not say.
+    pub sunrise: Option<DateTime<Utc>>,
+    pub sunset: Option<DateTime<Utc>>,
+}
+
+impl Conditions {
+    /// `condition` at `temperature_c`, which it also feels like and which
+    /// is the day's range, in still, dry, clear air at standard pressure;
+    /// set the rest from what the provider reports.
+    pub fn new(condition: Condition, temperature_c: f64) -> Self {
+        Conditions {
+            condition,
+            temperature_c,
+            feels_like_c: temperature_c,
+            temperature_min_c: temperature_c,
+            temperature_max_c: temperature_c,
+            humidity_percent: 0,
+            pressure_hpa: 1013,
+            station_pressure_hpa: None,
+            wind_speed_ms: 0.0,
+            wind_direction_deg: 0,
+            wind_gust_ms: None,
+            visibility_m: 10_000,
+            cloud_cover_percent: 0,
+            rain_mm: None,
+            snow_mm: None,
+            sunrise: None,
+            sunset: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[non_exhaustive]
+pub struct Condition {
+    /// One of `clear`, `clouds`, `drizzle`, `rain`, `snow`, `thunderstorm`,
+    /// `fog` or `other`.
+    pub kind: String,
+    pub description: String,
+}
+
+impl Condition {
+    pub fn new(kind: impl Into<String>, description: impl Into<String>) -> Self {
+        Condition {
+            kind: kind.into(),
+            description: description.into(),
+        }
+    }
+}
+
+/// Temperature and precipitation at a point, inverse-distance-weighted
+/// from the provider's values at the grid points around it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[non_exhaustive]
+pub struct Hyperlocal {
+    pub temperature_c: f64,
+    /// Rain and snow over the last hour.
+    pub precipitation_mm: f64,
+    /// The grid points interpolated from.
+    pub points: Vec<GridPoint>,
+}
+
+impl Hyperlocal {
+    pub fn new(temperature_c: f64, precipitation_mm: f64, points: Vec<GridPoint>) -> Self {
+        Hyperlocal {
+            temperature_c,
+            precipitation_mm,
+            points,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[non_exhaustive]
+pub struct GridPoint {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Distance from the interpolated point.
+    pub distance_m: f64,
+    pub temperature_c: f64,
+    pub precipitation_mm: f64,
+}
+
+impl GridPoint {
+    pub fn new(
+        latitude: f64,
+        longitude: f64,
+        distance_m: f64,
+        temperature_c: f64,
+        precipitation_mm: f64,
+    ) -> Self {
+        GridPoint {
+            latitude,
+            longitude,
+            distance_m,
+            temperature_c,
+            precipitation_mm,
+        }
+    }
+}
+
+/// Current readings from a personal weather station, in metric units.
+/// Stations report different subsets, so every value is optional.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[non_exhaustive]
+pub struct LocalObservation {
+    /// Kind of station, e.g. `ecowitt` or `weewx`.
+    pub source: String,
+    pub observed_at: Option<DateTime<Utc>>,
+    pub temperature_c: Option<f64>,
+    pub humidity_percent: Option<f64>,
+    /// Sea-level (relative) pressure.
+    pub pressure_hpa: Option<f64>,
+    pub wind_speed_ms: Option<f64>,
+    pub wind_gust_ms: Option<f64>,
+    pub wind_direction_deg: Option<f64>,
+    pub rain_rate_mm_h: Option<f64>,
+    /// Fields of `current` that the station disagrees with by more than
+    /// the usual spread between a station and a model, e.g. `temperature_c`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub discrepancies: Vec<String>,
+}
+
+impl LocalObservation {
+    /// Readings from a station of kind `source`, none of them known yet.
+    pub fn new(source: impl Into<String>) -> Self {
+        LocalObservation {
+            source: source.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// A watch, warning or advisory, described in the terms of the Common
+/// Alerting Protocol whichever feed it came from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[non_exhaustive]
+pub struct Alert {
+    /// e.g. `Severe Thunderstorm Warning` or `Yellow wind warning`.
+    pub event: String,
+    pub severity: Severity,
+    pub urgency: Urgency,
+    pub certainty: Certainty,
+    pub headline: Option<String>,
+    /// The issuer's full text: what is expected, where, and what to do
+    /// (since 1.6).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The regions it was issued for, as the issuer names them.
+    pub area: String,
+    pub effective: Option<DateTime<Utc>>,
+    pub expires: Option<DateTime<Utc>>,
+    /// Feed it came from: `nws`, `meteoalarm`, `openweathermap` or `cap`, a
+    /// Common Alerting Protocol feed set in the config.
+    pub source: String,
+    /// Issuing agency, e.g. `NWS Sterling VA`.
+    pub sender: Option<String>,
+    /// The threatened area, when drawn more precisely than `area`.
+    #[serde(skip)]
+    pub polygons: Vec<Polygon>,
+}
+
+impl Alert {
+    /// `event` from the feed `source` over `area`, of unknown severity,
+    /// urgency and certainty and without times, text or polygons.
+    pub fn new(
+        event: impl Into<String>,
+        area: impl Into<String>,
+        source: impl Into<String>,
+    ) -> Self {
+        Alert {
+            event: event.into(),
+            severity: Severity::Unknown,
+            urgency: Urgency::Unknown,
+            certainty: Certainty::Unknown,
+            headline: None,
+            description: None,
+            area: area.into(),
+            effective: None,
+            expires: None,
+            source: source.into(),
+            sender: None,
+            polygons: Vec::new(),
+        }
+    }
+
+    /// Whether the alert applies at `(lat, lon)`: inside one of its
+    /// polygons, or anywhere when it has none.
+    pub fn covers(&self, lat: f64, lon: f64) -> bool {
+        self.polygons.is_empty() || geo::polygons_contain(&self.polygons, lat, lon)
+    }
+}
+
+/// CAP severity, most severe first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[non_exhaustive]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Extreme,
+    Severe,
+    Moderate,
+    Minor,
+    #[default]
+    Unknown,
+}
+
+/// CAP urgency: when to act.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[non_exhaustive]
+#[serde(rename_all = "lowercase")]
+pub enum Urgency {
+    Immediate,
+    Expected,
+    Future,
+    Past,
+    #[default]
+    Unknown,
+}
+
+/// CAP certainty: how likely the event is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[non_exhaustive]
+#[serde(rename_all = "lowercase")]
+pub enum Certainty {
+    Observed,
+    Likely,
+    Possible,
+    Unlikely,
+    #[default]
+    Unknown,
+}
+
+impl Severity {
+    /// From a CAP `severity` value such as `Severe`; anything else is
+    /// unknown.
+    pub fn from_cap(value: &str) -> Self {
+        match value.to_ascii_lowercase().as_str() {
+            "extreme" => Severity::Extreme,
+            "severe" => Severity::Severe,
+            "moderate" => Severity::Moderate,
+            "minor" => Severity::Minor,
+            _ => Severity::Unknown,
+        }
+    }
+}
+
+impl Urgency {
+    pub fn from_cap(value: &str) -> Self {
+        match value.to_ascii_lowercase().as_str() {
+            "immediate" => Urgency::Immediate,
+            "expected" => Urgency::Expected,
+            "future" => Urgency::Future,
+            "past" => Urgency::Past,
+            _ => Urgency::Unknown,
+        }
+    }
+}
+
+impl Certainty {
+    pub fn from_cap(value: &str) -> Self {
+        match value.to_ascii_lowercase().as_str() {
+            "observed" => Certainty::Observed,
+            "likely" => Certainty::Likely,
+            "possible" => Certainty::Possible,
+            "unlikely" => Certainty::Unlikely,
+            _ => Certainty::Unknown,
+        }
+    }
+}
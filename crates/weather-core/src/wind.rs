@@ -0,0 +1,29 @@
+//! Wind relative to a direction of travel or of fire.
+
+/// The wind split along and across `heading`, in the units of the speed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Components {
+    /// Against the heading; negative for a tailwind.
+    pub head: f64,
+    /// Across the heading; positive from the right, negative from the left.
+    pub cross: f64,
+}
+
+/// The components of a wind of `speed` blowing from `from_deg` for
+/// someone heading towards `heading_deg`, both in degrees from north.
+pub fn components(speed: f64, from_deg: f64, heading_deg: f64) -> Components {
+    let angle = (from_deg - heading_deg).to_radians();
+    Components {
+        head: speed * angle.cos(),
+        cross: speed * angle.sin(),
+    }
+}
+
+/// Where the wind comes from on a clock face with 12 o'clock ahead, as
+/// shooters call it: 3 is from the right, 9 from the left.
+pub fn clock(from_deg: f64, heading_deg: f64) -> u32 {
+    match ((from_deg - heading_deg).rem_euclid(360.0) / 30.0).round() as u32 % 12 {
+        0 => 12,
+        hour => hour,
+    }
+}
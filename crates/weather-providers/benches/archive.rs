@@ -0,0 +1,57 @@
+//! Parsing a month of Open-Meteo archive data, the unit of work of
+//! `weather db backfill`. Run with `cargo bench -p weather-providers`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::collections::HashMap;
+use weather_providers::geocode::Place;
+use weather_providers::openmeteo::{self, Archive};
+
+/// A 31-day hourly response shaped like the archive API's.
+fn month_json() -> Vec<u8> {
+    let hours = 31 * 24;
+    let series =
+        |value: &dyn Fn(usize) -> String| (0..hours).map(value).collect::<Vec<_>>().join(",");
+    format!(
+        r#"{{"latitude":52.52,"longitude":13.42,"utc_offset_seconds":0,"hourly":{{
+            "time":[{}],"temperature_2m":[{}],"relative_humidity_2m":[{}],
+            "apparent_temperature":[{}],"pressure_msl":[{}],"wind_speed_10m":[{}],
+            "wind_direction_10m":[{}],"wind_gusts_10m":[{}],"cloud_cover":[{}],
+            "weather_code":[{}]}}}}"#,
+        series(&|i| format!("\"2024-01-{:02}T{:02}:00\"", i / 24 + 1, i % 24)),
+        series(&|i| format!("{:.1}", (i % 24) as f64 / 2.0 - 3.0)),
+        series(&|i| (60 + i % 30).to_string()),
+        series(&|i| format!("{:.1}", (i % 24) as f64 / 2.0 - 5.0)),
+        series(&|i| format!("{:.1}", 1000.0 + (i % 40) as f64)),
+        series(&|i| format!("{:.1}", (i % 12) as f64)),
+        series(&|i| ((i * 7) % 360).to_string()),
+        series(&|i| format!("{:.1}", (i % 12) as f64 * 1.5)),
+        series(&|i| (i % 101).to_string()),
+        series(&|i| [0, 2, 3, 61, 71][i % 5].to_string()),
+    )
+    .into_bytes()
+}
+
+fn archive(c: &mut Criterion) {
+    let json = month_json();
+    let place = Place {
+        name: "Berlin".to_string(),
+        local_names: HashMap::new(),
+        lat: 52.52,
+        lon: 13.405,
+        country: "DE".to_string(),
+        state: None,
+    };
+
+    c.bench_function("parse month", |b| {
+        b.iter(|| serde_json::from_slice::<Archive>(black_box(&json)).unwrap())
+    });
+    c.bench_function("parse month to reports", |b| {
+        b.iter(|| {
+            let archive = serde_json::from_slice::<Archive>(black_box(&json)).unwrap();
+            openmeteo::reports(&place, &archive)
+        })
+    });
+}
+
+criterion_group!(benches, archive);
+criterion_main!(benches);
@@ -0,0 +1,109 @@
+//! Air quality for `weather air`, from the OpenWeatherMap Air Pollution API.
+
+use crate::error::WeatherError;
+use crate::retry;
+use serde::{Deserialize, Serialize};
+
+const AIR_POLLUTION_URL: &str = "https://api.openweathermap.org/data/2.5/air_pollution";
+
+/// Names of the index levels 1 to 5.
+pub const LEVELS: [&str; 5] = ["Good", "Fair", "Moderate", "Poor", "Very poor"];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AirPollution {
+    /// The current sample only.
+    pub list: Vec<Sample>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Sample {
+    pub dt: i64,
+    pub main: Index,
+    pub components: Components,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Index {
+    /// 1 (good) to 5 (very poor); see [`LEVELS`].
+    pub aqi: u8,
+}
+
+/// Concentrations in µg/m³.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Components {
+    pub co: f64,
+    pub no: f64,
+    pub no2: f64,
+    pub o3: f64,
+    pub so2: f64,
+    pub pm2_5: f64,
+    pub pm10: f64,
+    pub nh3: f64,
+}
+
+/// A pollutant the index is made of.
+#[derive(Debug, Clone, Copy)]
+pub struct Pollutant {
+    pub label: &'static str,
+    /// Lowest concentrations of levels 2 to 5.
+    pub bounds: [f64; 4],
+}
+
+impl Pollutant {
+    /// The index level, 1 to 5, that `concentration` falls in.
+    pub fn level(&self, concentration: f64) -> u8 {
+        1 + self
+            .bounds
+            .iter()
+            .filter(|&&bound| concentration >= bound)
+            .count() as u8
+    }
+}
+
+impl Components {
+    /// The pollutants that make up the index with their concentrations,
+    /// particulates first, banded as OpenWeatherMap documents the scale.
+    pub fn pollutants(&self) -> [(Pollutant, f64); 6] {
+        let pollutant = |label, bounds| Pollutant { label, bounds };
+        [
+            (pollutant("PM2.5", [10.0, 25.0, 50.0, 75.0]), self.pm2_5),
+            (pollutant("PM10", [20.0, 50.0, 100.0, 200.0]), self.pm10),
+            (pollutant("O₃", [60.0, 100.0, 140.0, 180.0]), self.o3),
+            (pollutant("NO₂", [40.0, 70.0, 150.0, 200.0]), self.no2),
+            (pollutant("SO₂", [20.0, 80.0, 250.0, 350.0]), self.so2),
+            (pollutant("CO", [4400.0, 9400.0, 12400.0, 15400.0]), self.co),
+        ]
+    }
+}
+
+/// Current air quality at a point.
+pub async fn current(
+    client: &reqwest::Client,
+    lat: f64,
+    lon: f64,
+    api_key: &str,
+) -> Result<Sample, Box<dyn std::error::Error>> {
+    let res = retry::send(air_pollution_request(client, lat, lon, api_key)).await?;
+    if !res.status().is_success() {
+        return Err(WeatherError::status("Air Pollution API", res.status()).into());
+    }
+    res.json::<AirPollution>()
+        .await?
+        .list
+        .into_iter()
+        .next()
+        .ok_or_else(|| "No air quality data for this location".into())
+}
+
+pub(crate) fn air_pollution_request(
+    client: &reqwest::Client,
+    lat: f64,
+    lon: f64,
+    api_key: &str,
+) -> reqwest::RequestBuilder {
+    client.get(AIR_POLLUTION_URL).query(&[
+        ("lat", lat.to_string().as_str()),
+        ("lon", lon.to_string().as_str()),
+        ("appid", api_key),
+    ])
+}
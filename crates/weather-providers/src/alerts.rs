@@ -0,0 +1,345 @@
+//! Watches, warnings and advisories in force at a place, normalized onto
+//! [`model::Alert`] from whichever feed covers it: the National Weather
+//! Service in the US, MeteoAlarm in Europe and OpenWeatherMap's One Call
+//! API elsewhere.
+//!
+//! Alerts are issued for whole counties, zones or regions. NWS storm and
+//! flash flood warnings also carry a polygon drawn around the threatened
+//! area, often a small part of the county, which [`model::Alert::covers`]
+//! tests. MeteoAlarm publishes a feed per country, whose warnings are
+//! matched to the place by region name, as are CAP feed alerts that carry
+//! no polygon; see [`cap_feed`].
+
+use crate::cap;
+use crate::error::WeatherError;
+use crate::geocode::Place;
+use crate::onecall;
+use crate::retry;
+use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+use serde::Deserialize;
+use weather_core::geo::Polygon;
+use weather_core::model::{self, Certainty, Severity, Urgency};
+
+const NWS_ALERTS_URL: &str = "https://api.weather.gov/alerts/active";
+const METEOALARM_URL: &str = "https://feeds.meteoalarm.org/api/v1/warnings";
+
+/// US states and territories the NWS issues alerts for.
+const NWS_COUNTRIES: [&str; 6] = ["US", "PR", "GU", "VI", "AS", "MP"];
+
+/// Alerts for the area around `place`, from the feed that covers it. NWS
+/// alerts whose polygon lies elsewhere in the county are included; see
+/// [`model::Alert::covers`]. OpenWeatherMap needs `api_key` with One Call
+/// 3.0 enabled.
+pub async fn for_place(
+    client: &reqwest::Client,
+    place: &Place,
+    api_key: &str,
+) -> Result<Vec<model::Alert>, Box<dyn std::error::Error>> {
+    if NWS_COUNTRIES.contains(&place.country.as_str()) {
+        nws(client, place.lat, place.lon).await
+    } else if let Some(feed) = meteoalarm_feed(&place.country) {
+        meteoalarm(client, feed, place).await
+    } else {
+        openweathermap(client, place.lat, place.lon, api_key).await
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct NwsAlerts {
+    features: Vec<NwsFeature>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NwsFeature {
+    /// `null` for alerts issued by zone alone.
+    geometry: Option<NwsGeometry>,
+    properties: NwsAlert,
+}
+
+/// GeoJSON geometry, with `[lon, lat]` positions.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", content = "coordinates")]
+enum NwsGeometry {
+    Polygon(Vec<Vec<[f64; 2]>>),
+    MultiPolygon(Vec<Vec<Vec<[f64; 2]>>>),
+    /// Not used for alerts; read as no polygon rather than failing.
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NwsAlert {
+    event: String,
+    area_desc: String,
+    #[serde(default)]
+    severity: String,
+    #[serde(default)]
+    urgency: String,
+    #[serde(default)]
+    certainty: String,
+    headline: Option<String>,
+    description: Option<String>,
+    effective: Option<DateTime<FixedOffset>>,
+    /// When the event ends; `expires` is only when the message does.
+    ends: Option<DateTime<FixedOffset>>,
+    expires: Option<DateTime<FixedOffset>>,
+    sender_name: Option<String>,
+}
+
+/// NWS alerts for the zones and county containing `(lat, lon)`.
+pub async fn nws(
+    client: &reqwest::Client,
+    lat: f64,
+    lon: f64,
+) -> Result<Vec<model::Alert>, Box<dyn std::error::Error>> {
+    let res = retry::send(nws_alerts_request(client, lat, lon)).await?;
+    if !res.status().is_success() {
+        return Err(WeatherError::status("NWS alerts", res.status()).into());
+    }
+    Ok(res
+        .json::<NwsAlerts>()
+        .await?
+        .features
+        .into_iter()
+        .map(from_nws)
+        .collect())
+}
+
+fn from_nws(feature: NwsFeature) -> model::Alert {
+    let rings = |rings: Vec<Vec<[f64; 2]>>| -> Polygon {
+        rings
+            .into_iter()
+            .map(|ring| ring.into_iter().map(|[lon, lat]| (lat, lon)).collect())
+            .collect()
+    };
+    let polygons = match feature.geometry {
+        Some(NwsGeometry::Polygon(polygon)) => vec![rings(polygon)],
+        Some(NwsGeometry::MultiPolygon(polygons)) => polygons.into_iter().map(rings).collect(),
+        Some(NwsGeometry::Other) | None => Vec::new(),
+    };
+    let alert = feature.properties;
+    let mut nws = model::Alert::new(alert.event, alert.area_desc, "nws");
+    nws.severity = Severity::from_cap(&alert.severity);
+    nws.urgency = Urgency::from_cap(&alert.urgency);
+    nws.certainty = Certainty::from_cap(&alert.certainty);
+    nws.headline = alert.headline;
+    nws.description = alert.description;
+    nws.effective = alert.effective.map(|t| t.with_timezone(&Utc));
+    nws.expires = alert.ends.or(alert.expires).map(|t| t.with_timezone(&Utc));
+    nws.sender = alert.sender_name;
+    nws.polygons = polygons;
+    nws
+}
+
+pub(crate) fn nws_alerts_request(
+    client: &reqwest::Client,
+    lat: f64,
+    lon: f64,
+) -> reqwest::RequestBuilder {
+    let point = format!("{:.4},{:.4}", lat, lon);
+    client
+        .get(NWS_ALERTS_URL)
+        .query(&[("point", point.as_str())])
+        // api.weather.gov rejects requests without a User-Agent.
+        .header(reqwest::header::USER_AGENT, "weather-cli")
+}
+
+/// MeteoAlarm's name for the feed of an ISO 3166-1 country code.
+fn meteoalarm_feed(country: &str) -> Option<&'static str> {
+    Some(match country {
+        "AT" => "austria",
+        "BA" => "bosnia-herzegovina",
+        "BE" => "belgium",
+        "BG" => "bulgaria",
+        "CH" => "switzerland",
+        "CY" => "cyprus",
+        "CZ" => "czechia",
+        "DE" => "germany",
+        "DK" => "denmark",
+        "EE" => "estonia",
+        "ES" => "spain",
+        "FI" => "finland",
+        "FR" => "france",
+        "GB" => "united-kingdom",
+        "GR" => "greece",
+        "HR" => "croatia",
+        "HU" => "hungary",
+        "IE" => "ireland",
+        "IL" => "israel",
+        "IS" => "iceland",
+        "IT" => "italy",
+        "LT" => "lithuania",
+        "LU" => "luxembourg",
+        "LV" => "latvia",
+        "MD" => "moldova",
+        "ME" => "montenegro",
+        "MK" => "republic-of-north-macedonia",
+        "MT" => "malta",
+        "NL" => "netherlands",
+        "NO" => "norway",
+        "PL" => "poland",
+        "PT" => "portugal",
+        "RO" => "romania",
+        "RS" => "serbia",
+        "SE" => "sweden",
+        "SI" => "slovenia",
+        "SK" => "slovakia",
+        "UA" => "ukraine",
+        _ => return None,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct MeteoAlarmFeed {
+    warnings: Vec<MeteoAlarmWarning>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MeteoAlarmWarning {
+    alert: MeteoAlarmAlert,
+}
+
+/// A CAP message as JSON.
+#[derive(Debug, Deserialize)]
+struct MeteoAlarmAlert {
+    /// The same warning in each language it was issued in.
+    info: Vec<MeteoAlarmInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MeteoAlarmInfo {
+    #[serde(default)]
+    language: String,
+    event: String,
+    #[serde(default)]
+    severity: String,
+    #[serde(default)]
+    urgency: String,
+    #[serde(default)]
+    certainty: String,
+    headline: Option<String>,
+    description: Option<String>,
+    effective: Option<DateTime<FixedOffset>>,
+    expires: Option<DateTime<FixedOffset>>,
+    sender_name: Option<String>,
+    #[serde(default)]
+    area: Vec<MeteoAlarmArea>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MeteoAlarmArea {
+    area_desc: String,
+}
+
+/// MeteoAlarm warnings for the regions named like `place` or its state.
+async fn meteoalarm(
+    client: &reqwest::Client,
+    feed: &str,
+    place: &Place,
+) -> Result<Vec<model::Alert>, Box<dyn std::error::Error>> {
+    let res = retry::send(meteoalarm_request(client, feed)).await?;
+    if !res.status().is_success() {
+        return Err(WeatherError::status("MeteoAlarm", res.status()).into());
+    }
+    Ok(res
+        .json::<MeteoAlarmFeed>()
+        .await?
+        .warnings
+        .into_iter()
+        .filter_map(|warning| from_meteoalarm(warning.alert))
+        .filter(|alert| names_place(&alert.area, place))
+        .collect())
+}
+
+/// Whether an alert's area description names `place` or its state.
+fn names_place(area: &str, place: &Place) -> bool {
+    let area = area.to_lowercase();
+    std::iter::once(&place.name)
+        .chain(&place.state)
+        .any(|name| area.contains(&name.to_lowercase()))
+}
+
+/// The English text of a MeteoAlarm warning, else its first language.
+fn from_meteoalarm(alert: MeteoAlarmAlert) -> Option<model::Alert> {
+    let mut info = alert.info;
+    let english = info
+        .iter()
+        .position(|info| info.language.starts_with("en"))
+        .unwrap_or(0);
+    if english >= info.len() {
+        return None;
+    }
+    let info = info.swap_remove(english);
+    let area = info
+        .area
+        .into_iter()
+        .map(|area| area.area_desc)
+        .collect::<Vec<_>>()
+        .join("; ");
+    let mut meteoalarm = model::Alert::new(info.event, area, "meteoalarm");
+    meteoalarm.severity = Severity::from_cap(&info.severity);
+    meteoalarm.urgency = Urgency::from_cap(&info.urgency);
+    meteoalarm.certainty = Certainty::from_cap(&info.certainty);
+    meteoalarm.headline = info.headline;
+    meteoalarm.description = info.description;
+    meteoalarm.effective = info.effective.map(|t| t.with_timezone(&Utc));
+    meteoalarm.expires = info.expires.map(|t| t.with_timezone(&Utc));
+    meteoalarm.sender = info.sender_name;
+    Some(meteoalarm)
+}
+
+/// Alerts from the CAP feed at `url` for the area around `place`: those
+/// whose polygon lies elsewhere in the same region are included, like NWS
+/// alerts, and those without one must name the place or its state.
+pub async fn cap_feed(
+    client: &reqwest::Client,
+    url: &str,
+    place: &Place,
+    now: DateTime<Utc>,
+) -> Result<Vec<model::Alert>, Box<dyn std::error::Error>> {
+    Ok(cap::fetch(client, url, now)
+        .await?
+        .into_iter()
+        .filter(|alert| !alert.polygons.is_empty() || names_place(&alert.area, place))
+        .collect())
+}
+
+pub(crate) fn meteoalarm_request(client: &reqwest::Client, feed: &str) -> reqwest::RequestBuilder {
+    client.get(format!("{}/feeds-{}", METEOALARM_URL, feed))
+}
+
+/// Alerts from national agencies that OpenWeatherMap relays. They carry no
+/// CAP classification, so severity, urgency and certainty are unknown.
+async fn openweathermap(
+    client: &reqwest::Client,
+    lat: f64,
+    lon: f64,
+    api_key: &str,
+) -> Result<Vec<model::Alert>, Box<dyn std::error::Error>> {
+    let res = retry::send(onecall::alerts_request(client, lat, lon, api_key)).await?;
+    if res.status().as_u16() == 401 {
+        return Err("One Call API 3.0 is not enabled for this API key".into());
+    }
+    if !res.status().is_success() {
+        return Err(WeatherError::status("One Call API", res.status()).into());
+    }
+    Ok(res
+        .json::<onecall::Alerts>()
+        .await?
+        .alerts
+        .into_iter()
+        .map(|alert| {
+            // Issued for the area around the point asked about.
+            let mut owm = model::Alert::new(alert.event, "", "openweathermap");
+            owm.headline = alert.description.lines().next().map(str::to_string);
+            owm.description = Some(alert.description).filter(|d| !d.trim().is_empty());
+            owm.effective = Utc.timestamp_opt(alert.start, 0).single();
+            owm.expires = Utc.timestamp_opt(alert.end, 0).single();
+            owm.sender = Some(alert.sender_name);
+            owm
+        })
+        .collect())
+}
@@ -0,0 +1,86 @@
+//! Ambient Weather Network accounts (requires `AMBIENT_API_KEY` and
+//! `AMBIENT_APPLICATION_KEY`).
+
+use crate::error::WeatherError;
+use crate::retry;
+use chrono::{TimeZone, Utc};
+use serde::Deserialize;
+use std::env;
+use weather_core::model::LocalObservation;
+
+const DEVICES_URL: &str = "https://rt.ambientweather.net/v1/devices";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Device {
+    mac_address: String,
+    last_data: Option<LastData>,
+}
+
+/// A device's latest upload, in the imperial units Ambient stores.
+#[derive(Debug, Deserialize)]
+struct LastData {
+    /// Unix milliseconds.
+    dateutc: Option<i64>,
+    tempf: Option<f64>,
+    humidity: Option<f64>,
+    baromrelin: Option<f64>,
+    windspeedmph: Option<f64>,
+    windgustmph: Option<f64>,
+    winddir: Option<f64>,
+    hourlyrainin: Option<f64>,
+}
+
+/// Current readings of the device with `mac`, or of the account's first
+/// device when `None`.
+pub async fn observe(
+    client: &reqwest::Client,
+    mac: Option<&str>,
+) -> Result<LocalObservation, Box<dyn std::error::Error>> {
+    let (Ok(api_key), Ok(application_key)) = (
+        env::var("AMBIENT_API_KEY"),
+        env::var("AMBIENT_APPLICATION_KEY"),
+    ) else {
+        return Err(
+            "Ambient Weather needs AMBIENT_API_KEY and AMBIENT_APPLICATION_KEY, \
+             from ambientweather.net/account"
+                .into(),
+        );
+    };
+    let res = retry::send(devices_request(client, &api_key, &application_key)).await?;
+    if !res.status().is_success() {
+        return Err(WeatherError::status("Ambient Weather API", res.status()).into());
+    }
+    let data = res
+        .json::<Vec<Device>>()
+        .await?
+        .into_iter()
+        .find(|d| mac.is_none_or(|mac| d.mac_address.eq_ignore_ascii_case(mac)))
+        .ok_or("No matching Ambient Weather device on this account")?
+        .last_data
+        .ok_or("The Ambient Weather device has not uploaded any data")?;
+
+    let mph = |v: f64| v * 0.44704;
+    let mut observation = LocalObservation::new("ambient");
+    observation.observed_at = data
+        .dateutc
+        .and_then(|t| Utc.timestamp_millis_opt(t).single());
+    observation.temperature_c = data.tempf.map(|f| (f - 32.0) * 5.0 / 9.0);
+    observation.humidity_percent = data.humidity;
+    observation.pressure_hpa = data.baromrelin.map(|p| p * 33.8639);
+    observation.wind_speed_ms = data.windspeedmph.map(mph);
+    observation.wind_gust_ms = data.windgustmph.map(mph);
+    observation.wind_direction_deg = data.winddir;
+    observation.rain_rate_mm_h = data.hourlyrainin.map(|r| r * 25.4);
+    Ok(observation)
+}
+
+pub(crate) fn devices_request(
+    client: &reqwest::Client,
+    api_key: &str,
+    application_key: &str,
+) -> reqwest::RequestBuilder {
+    client
+        .get(DEVICES_URL)
+        .query(&[("apiKey", api_key), ("applicationKey", application_key)])
+}
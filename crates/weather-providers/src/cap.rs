@@ -0,0 +1,214 @@
+//! Common Alerting Protocol (CAP 1.1 and 1.2) documents, and the Atom and
+//! RSS feeds national agencies publish them in, mapped onto
+//! [`model::Alert`]. This lets alerts come from any agency's feed, not only
+//! the providers in [`crate::alerts`].
+//!
+//! A feed either embeds its CAP messages or links to one per entry; linked
+//! messages are fetched, up to [`MAX_LINKED`] of them. Test messages,
+//! cancellations and expired alerts are left out.
+
+use crate::error::WeatherError;
+use crate::retry;
+use chrono::{DateTime, Utc};
+use futures_util::future::join_all;
+use roxmltree::{Document, Node};
+use weather_core::geo::Polygon;
+use weather_core::model::{self, Certainty, Severity, Urgency};
+
+/// Most linked CAP messages fetched from one feed.
+pub const MAX_LINKED: usize = 50;
+
+/// Vertices of the polygon a CAP circle is approximated by.
+const CIRCLE_VERTICES: usize = 32;
+
+/// What an XML document turned out to be.
+enum Parsed {
+    Alerts(Vec<model::Alert>),
+    /// A feed whose entries link to their CAP messages.
+    Links(Vec<String>),
+}
+
+/// The alerts in force from the CAP message or feed at `url`.
+pub async fn fetch(
+    client: &reqwest::Client,
+    url: &str,
+    now: DateTime<Utc>,
+) -> Result<Vec<model::Alert>, Box<dyn std::error::Error>> {
+    let (alerts, links) = match parse(&get(client, url).await?, now)? {
+        Parsed::Alerts(alerts) => (alerts, Vec::new()),
+        Parsed::Links(links) => (Vec::new(), links),
+    };
+    let linked = join_all(links.iter().take(MAX_LINKED).map(|link| async move {
+        // One unreadable message should not hide the rest of the feed.
+        match parse(&get(client, link).await.ok()?, now).ok()? {
+            Parsed::Alerts(alerts) => Some(alerts),
+            Parsed::Links(_) => None,
+        }
+    }))
+    .await;
+    Ok(alerts
+        .into_iter()
+        .chain(linked.into_iter().flatten().flatten())
+        .collect())
+}
+
+async fn get(client: &reqwest::Client, url: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let res = retry::send(
+        client
+            .get(url)
+            .header(reqwest::header::USER_AGENT, "weather-cli"),
+    )
+    .await?;
+    if !res.status().is_success() {
+        return Err(WeatherError::status("CAP feed", res.status()).into());
+    }
+    Ok(res.text().await?)
+}
+
+fn parse(xml: &str, now: DateTime<Utc>) -> Result<Parsed, String> {
+    let document = Document::parse(xml).map_err(|e| format!("Invalid XML: {}", e))?;
+    let root = document.root_element();
+    match root.tag_name().name() {
+        "alert" => Ok(Parsed::Alerts(from_cap(root, now).into_iter().collect())),
+        "feed" | "rss" => {
+            let entries: Vec<Node> = root
+                .descendants()
+                .filter(|node| matches!(node.tag_name().name(), "entry" | "item"))
+                .collect();
+            let embedded: Vec<Node> = entries
+                .iter()
+                .filter_map(|entry| entry.descendants().find(|node| is(*node, "alert")))
+                .collect();
+            if !embedded.is_empty() {
+                return Ok(Parsed::Alerts(
+                    embedded
+                        .into_iter()
+                        .filter_map(|alert| from_cap(alert, now))
+                        .collect(),
+                ));
+            }
+            Ok(Parsed::Links(
+                entries.iter().filter_map(|entry| link(*entry)).collect(),
+            ))
+        }
+        other => Err(format!(
+            "Expected a CAP alert or an Atom or RSS feed, found <{}>",
+            other
+        )),
+    }
+}
+
+/// Where an Atom entry or RSS item's CAP message is.
+fn link(entry: Node) -> Option<String> {
+    let links: Vec<Node> = entry.children().filter(|node| is(*node, "link")).collect();
+    // Atom names the target in `href`, preferring the CAP representation;
+    // RSS puts it in the element text.
+    let cap = links
+        .iter()
+        .find(|link| link.attribute("type") == Some("application/cap+xml"));
+    let link = cap.or(links.first())?;
+    link.attribute("href")
+        .map(str::to_string)
+        .or_else(|| link.text().map(|text| text.trim().to_string()))
+        .filter(|url| !url.is_empty())
+}
+
+/// The alert in a CAP message, from its English `info` block if it has one,
+/// unless the message is not an actual, current alert.
+fn from_cap(alert: Node, now: DateTime<Utc>) -> Option<model::Alert> {
+    if text(alert, "status").as_deref() != Some("Actual")
+        || text(alert, "msgType").as_deref() == Some("Cancel")
+    {
+        return None;
+    }
+    let infos: Vec<Node> = alert.children().filter(|node| is(*node, "info")).collect();
+    let info = infos
+        .iter()
+        .find(|info| text(**info, "language").is_some_and(|l| l.starts_with("en")))
+        .or(infos.first())?;
+
+    let expires = text(*info, "expires").and_then(|t| time(&t));
+    if expires.is_some_and(|expires| expires <= now) {
+        return None;
+    }
+    let areas: Vec<Node> = info.children().filter(|node| is(*node, "area")).collect();
+    let polygons = areas
+        .iter()
+        .flat_map(|area| area.children())
+        .filter_map(|node| match node.tag_name().name() {
+            "polygon" => node.text().and_then(polygon),
+            "circle" => node.text().and_then(circle),
+            _ => None,
+        })
+        .collect();
+    let area = areas
+        .iter()
+        .filter_map(|area| text(*area, "areaDesc"))
+        .collect::<Vec<_>>()
+        .join("; ");
+    let mut cap = model::Alert::new(text(*info, "event")?, area, "cap");
+    cap.severity = Severity::from_cap(&text(*info, "severity").unwrap_or_default());
+    cap.urgency = Urgency::from_cap(&text(*info, "urgency").unwrap_or_default());
+    cap.certainty = Certainty::from_cap(&text(*info, "certainty").unwrap_or_default());
+    cap.headline = text(*info, "headline");
+    cap.description = text(*info, "description");
+    cap.effective = text(*info, "onset")
+        .or_else(|| text(*info, "effective"))
+        .or_else(|| text(alert, "sent"))
+        .and_then(|t| time(&t));
+    cap.expires = expires;
+    cap.sender = text(*info, "senderName").or_else(|| text(alert, "sender"));
+    cap.polygons = polygons;
+    Some(cap)
+}
+
+/// A CAP polygon, `lat,lon` pairs separated by spaces.
+fn polygon(text: &str) -> Option<Polygon> {
+    let ring = text
+        .split_whitespace()
+        .map(|pair| {
+            let (lat, lon) = pair.split_once(',')?;
+            Some((lat.parse().ok()?, lon.parse().ok()?))
+        })
+        .collect::<Option<Vec<(f64, f64)>>>()?;
+    (ring.len() >= 3).then(|| vec![ring])
+}
+
+/// A CAP circle, `lat,lon radius` with the radius in kilometres, as a
+/// polygon.
+fn circle(text: &str) -> Option<Polygon> {
+    let (centre, radius) = text.trim().split_once(' ')?;
+    let (lat, lon) = centre.split_once(',')?;
+    let (lat, lon): (f64, f64) = (lat.parse().ok()?, lon.parse().ok()?);
+    let radius_km: f64 = radius.trim().parse().ok()?;
+    // Degrees of latitude are about 111 km; longitude shrinks with latitude.
+    let d_lat = radius_km / 111.32;
+    let d_lon = d_lat / lat.to_radians().cos().max(0.01);
+    let ring = (0..CIRCLE_VERTICES)
+        .map(|i| {
+            let angle = std::f64::consts::TAU * i as f64 / CIRCLE_VERTICES as f64;
+            (lat + d_lat * angle.sin(), lon + d_lon * angle.cos())
+        })
+        .collect();
+    Some(vec![ring])
+}
+
+fn time(text: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(text)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Whether `node` is an element named `name` in any namespace.
+fn is(node: Node, name: &str) -> bool {
+    node.is_element() && node.tag_name().name() == name
+}
+
+/// Trimmed text of the first child element named `name`.
+fn text(node: Node, name: &str) -> Option<String> {
+    node.children()
+        .find(|child| is(*child, name))?
+        .text()
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+}
@@ -0,0 +1,405 @@
+//! A current-weather client with a push-based update stream, for tools that
+//! keep weather on screen or in a file rather than looking it up once.
+//!
+//! Every call takes a [`Cancel`], so an embedding application can abort it
+//! on user action or shutdown, or bound it by a deadline. Requests pass
+//! through any [`Middleware`] added with [`WeatherClient::with_middleware`],
+//! whichever [`WeatherProvider`] answers them.
+
+use crate::error::WeatherError;
+use crate::geocode::Place;
+use crate::middleware::{Middleware, Transport};
+use crate::provider::{OpenWeatherMap, WeatherProvider};
+use futures_util::stream::{self, Stream};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+use weather_core::model::{Conditions, Report};
+
+pub use tokio_util::sync::CancellationToken;
+
+/// When to give up on a client call: once its token is cancelled or its
+/// deadline passes, whichever comes first. The default never gives up.
+#[derive(Debug, Clone, Default)]
+pub struct Cancel {
+    token: CancellationToken,
+    deadline: Option<Instant>,
+}
+
+impl Cancel {
+    /// Gives up when `token` (or a parent of it) is cancelled.
+    pub fn token(token: CancellationToken) -> Self {
+        Cancel {
+            token,
+            deadline: None,
+        }
+    }
+
+    /// Also gives up at `deadline`, or the existing deadline if earlier.
+    pub fn with_deadline(self, deadline: Instant) -> Self {
+        Cancel {
+            deadline: Some(self.deadline.map_or(deadline, |d| d.min(deadline))),
+            ..self
+        }
+    }
+
+    /// Also gives up `timeout` from now.
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    /// Whether the call should already have stopped.
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled() || self.deadline.is_some_and(|d| d <= Instant::now())
+    }
+
+    /// Runs `future` unless the call is cancelled or times out first.
+    pub async fn run<F: Future>(&self, future: F) -> Result<F::Output, Cancelled> {
+        let deadline = async {
+            match self.deadline {
+                Some(deadline) => tokio::time::sleep_until(deadline).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            biased;
+            _ = self.token.cancelled() => Err(Cancelled::Cancelled),
+            _ = deadline => Err(Cancelled::DeadlineExceeded),
+            output = future => Ok(output),
+        }
+    }
+}
+
+/// Why a client call stopped early.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cancelled {
+    Cancelled,
+    DeadlineExceeded,
+}
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Cancelled::Cancelled => write!(f, "Request cancelled"),
+            Cancelled::DeadlineExceeded => write!(f, "Request deadline exceeded"),
+        }
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Current weather and place lookups from a [`WeatherProvider`],
+/// OpenWeatherMap unless [`WeatherClient::with_provider`] picks another.
+#[derive(Clone)]
+pub struct WeatherClient {
+    http: reqwest::Client,
+    provider: Arc<dyn WeatherProvider>,
+    /// Outermost first.
+    middleware: Vec<Arc<dyn Middleware>>,
+}
+
+impl std::fmt::Debug for WeatherClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WeatherClient")
+            .field("http", &self.http)
+            .field("provider", &self.provider.name())
+            .field("middleware", &self.middleware.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// An update delivered by [`WeatherClient::watch`].
+#[derive(Debug)]
+pub enum WeatherUpdate {
+    /// A report that passed the change filter, with what changed since the
+    /// previous one delivered (empty for the first).
+    Report {
+        report: Box<Report>,
+        changes: Vec<Change>,
+    },
+    /// A refresh failed; the stream carries on at the next interval.
+    Error(String),
+}
+
+/// A difference between two consecutive delivered reports.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// The condition kind changed, e.g. from `clouds` to `rain`.
+    Condition { from: String, to: String },
+    /// A value of [`Conditions`] moved, by its JSON key.
+    Value {
+        field: &'static str,
+        from: f64,
+        to: f64,
+    },
+}
+
+/// Which refreshes a watch delivers.
+#[derive(Debug, Clone, Copy)]
+pub enum ChangeFilter {
+    /// Every new observation from the provider; polls that return the
+    /// observation already delivered are dropped.
+    NewObservation,
+    /// Only new observations where the condition changes or a value moves
+    /// by at least the given amount.
+    Significant(Sensitivity),
+}
+
+impl Default for ChangeFilter {
+    fn default() -> Self {
+        ChangeFilter::Significant(Sensitivity::default())
+    }
+}
+
+/// Smallest moves that count as a change.
+#[derive(Debug, Clone, Copy)]
+pub struct Sensitivity {
+    pub temperature_c: f64,
+    pub humidity_percent: f64,
+    pub pressure_hpa: f64,
+    pub wind_speed_ms: f64,
+    pub cloud_cover_percent: f64,
+}
+
+impl Default for Sensitivity {
+    /// Roughly the resolution at which people notice a difference.
+    fn default() -> Self {
+        Sensitivity {
+            temperature_c: 0.5,
+            humidity_percent: 5.0,
+            pressure_hpa: 1.0,
+            wind_speed_ms: 1.0,
+            cloud_cover_percent: 20.0,
+        }
+    }
+}
+
+impl WeatherClient {
+    /// An OpenWeatherMap client with its own HTTP connection pool and a 10
+    /// second timeout.
+    pub fn new(api_key: impl Into<String>) -> reqwest::Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()?;
+        Ok(Self::with_http(http, api_key))
+    }
+
+    /// An OpenWeatherMap client sharing an existing HTTP client.
+    pub fn with_http(http: reqwest::Client, api_key: impl Into<String>) -> Self {
+        WeatherClient {
+            http,
+            provider: Arc::new(OpenWeatherMap::new(api_key)),
+            middleware: Vec::new(),
+        }
+    }
+
+    /// Asks `provider` instead.
+    pub fn with_provider(mut self, provider: Arc<dyn WeatherProvider>) -> Self {
+        self.provider = provider;
+        self
+    }
+
+    /// The provider asked.
+    pub fn provider(&self) -> &dyn WeatherProvider {
+        &*self.provider
+    }
+
+    /// Adds `middleware` inside those added before, so the first one added
+    /// sees requests first and responses last.
+    pub fn with_middleware(mut self, middleware: impl Middleware) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// What the provider's requests go out through.
+    fn transport(&self) -> Transport<'_> {
+        Transport::new(&self.http, &self.middleware)
+    }
+
+    /// The best match for a place name, like
+    /// [`WeatherProvider::resolve_city`].
+    pub async fn resolve_city(&self, query: &str, cancel: &Cancel) -> Result<Place, WeatherError> {
+        cancel
+            .run(self.provider.resolve_city(self.transport(), query))
+            .await?
+    }
+
+    /// Up to `limit` places matching `query`, best first, like
+    /// [`WeatherProvider::search`].
+    pub async fn search(
+        &self,
+        query: &str,
+        limit: usize,
+        cancel: &Cancel,
+    ) -> Result<Vec<Place>, WeatherError> {
+        cancel
+            .run(self.provider.search(self.transport(), query, limit))
+            .await?
+    }
+
+    /// The place at a position, like [`WeatherProvider::reverse`].
+    pub async fn reverse(
+        &self,
+        lat: f64,
+        lon: f64,
+        cancel: &Cancel,
+    ) -> Result<Place, WeatherError> {
+        cancel
+            .run(self.provider.reverse(self.transport(), lat, lon))
+            .await?
+    }
+
+    /// The current weather at `place`.
+    pub async fn current(&self, place: &Place, cancel: &Cancel) -> Result<Report, WeatherError> {
+        cancel
+            .run(self.provider.current(self.transport(), place))
+            .await?
+    }
+
+    /// Refreshes the weather at `place` every `interval`, starting now, and
+    /// yields the reports that pass the default [`ChangeFilter`]. The stream
+    /// ends when `cancel` stops it, or when dropped.
+    pub fn watch(
+        &self,
+        place: Place,
+        interval: Duration,
+        cancel: Cancel,
+    ) -> impl Stream<Item = WeatherUpdate> {
+        self.watch_with(place, interval, ChangeFilter::default(), cancel)
+    }
+
+    /// [`WeatherClient::watch`] with an explicit change filter.
+    pub fn watch_with(
+        &self,
+        place: Place,
+        interval: Duration,
+        filter: ChangeFilter,
+        cancel: Cancel,
+    ) -> impl Stream<Item = WeatherUpdate> {
+        self.refresh(place, None, tokio::time::interval(interval), filter, cancel)
+    }
+
+    /// [`WeatherClient::watch_with`] continuing from `report`, already
+    /// delivered for `place`: the first refresh is `interval` from now, and
+    /// changes are measured from `report`.
+    pub fn watch_from(
+        &self,
+        place: Place,
+        report: Report,
+        interval: Duration,
+        filter: ChangeFilter,
+        cancel: Cancel,
+    ) -> impl Stream<Item = WeatherUpdate> {
+        let ticker = tokio::time::interval_at(Instant::now() + interval, interval);
+        self.refresh(place, Some(report), ticker, filter, cancel)
+    }
+
+    fn refresh(
+        &self,
+        place: Place,
+        last: Option<Report>,
+        ticker: tokio::time::Interval,
+        filter: ChangeFilter,
+        cancel: Cancel,
+    ) -> impl Stream<Item = WeatherUpdate> {
+        let state = (self.clone(), place, ticker, last);
+        stream::unfold(state, move |(client, place, mut ticker, mut last)| {
+            let cancel = cancel.clone();
+            async move {
+                loop {
+                    cancel.run(ticker.tick()).await.ok()?;
+                    let report = match client.current(&place, &cancel).await {
+                        Ok(report) => report,
+                        Err(_) if cancel.is_cancelled() => return None,
+                        Err(e) => {
+                            let update = WeatherUpdate::Error(e.to_string());
+                            return Some((update, (client, place, ticker, last)));
+                        }
+                    };
+                    let changes = match &last {
+                        Some(previous) if previous.observed_at == report.observed_at => continue,
+                        Some(previous) => changes(&previous.current, &report.current, filter),
+                        None => Vec::new(),
+                    };
+                    if last.is_some()
+                        && changes.is_empty()
+                        && matches!(filter, ChangeFilter::Significant(_))
+                    {
+                        continue;
+                    }
+                    // Measure the next change from what was delivered, so slow
+                    // drifts add up instead of never passing the filter.
+                    last = Some(report.clone());
+                    let update = WeatherUpdate::Report {
+                        report: Box::new(report),
+                        changes,
+                    };
+                    return Some((update, (client, place, ticker, last)));
+                }
+            }
+        })
+    }
+}
+
+/// What differs between two conditions. With [`ChangeFilter::NewObservation`]
+/// every difference is listed; otherwise only those reaching the sensitivity.
+pub fn changes(previous: &Conditions, current: &Conditions, filter: ChangeFilter) -> Vec<Change> {
+    let sensitivity = match filter {
+        ChangeFilter::Significant(sensitivity) => sensitivity,
+        ChangeFilter::NewObservation => Sensitivity {
+            temperature_c: 0.0,
+            humidity_percent: 0.0,
+            pressure_hpa: 0.0,
+            wind_speed_ms: 0.0,
+            cloud_cover_percent: 0.0,
+        },
+    };
+
+    let mut changes = Vec::new();
+    if previous.condition.kind != current.condition.kind {
+        changes.push(Change::Condition {
+            from: previous.condition.kind.clone(),
+            to: current.condition.kind.clone(),
+        });
+    }
+    let values = [
+        (
+            "temperature_c",
+            previous.temperature_c,
+            current.temperature_c,
+            sensitivity.temperature_c,
+        ),
+        (
+            "humidity_percent",
+            previous.humidity_percent as f64,
+            current.humidity_percent as f64,
+            sensitivity.humidity_percent,
+        ),
+        (
+            "pressure_hpa",
+            previous.pressure_hpa as f64,
+            current.pressure_hpa as f64,
+            sensitivity.pressure_hpa,
+        ),
+        (
+            "wind_speed_ms",
+            previous.wind_speed_ms,
+            current.wind_speed_ms,
+            sensitivity.wind_speed_ms,
+        ),
+        (
+            "cloud_cover_percent",
+            previous.cloud_cover_percent as f64,
+            current.cloud_cover_percent as f64,
+            sensitivity.cloud_cover_percent,
+        ),
+    ];
+    for (field, from, to, threshold) in values {
+        let moved = (to - from).abs();
+        if moved > 0.0 && moved >= threshold {
+            changes.push(Change::Value { field, from, to });
+        }
+    }
+    changes
+}
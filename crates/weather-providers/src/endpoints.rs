@@ -0,0 +1,286 @@
+//! Every remote endpoint the crate reads, with the shape its responses are
+//! expected to have. Providers change fields without notice; the
+//! integration tests check each endpoint against a recorded response, and
+//! with `--features live-tests` against the live API, so that shows up in a
+//! test run rather than as a parse error on a user's machine.
+//!
+//! Ecowitt gateways and WeeWX are on the user's network and have no public
+//! endpoint to check.
+
+use crate::geocode::Place;
+use crate::{
+    air, alerts, ambient, forecast, geocode, iplocation, marine, mountain, netatmo, onecall,
+    openmeteo, openweathermap, river, route, tides,
+};
+use chrono::{NaiveDate, Utc};
+use serde::de::DeserializeOwned;
+use std::env;
+
+/// Where live probes ask about: central London, or Washington, D.C. for the
+/// US-only services.
+const LONDON: (f64, f64) = (51.5074, -0.1278);
+const WASHINGTON: (f64, f64) = (38.9495, -77.1275);
+/// Fistral Beach, Newquay, for the marine forecast, which has no waves in
+/// London.
+const NEWQUAY: (f64, f64) = (50.4167, -5.1);
+/// Mont Blanc, for the forecast at elevations.
+const MONT_BLANC: (f64, f64) = (45.8326, 6.8652);
+/// Potomac River at Little Falls.
+const USGS_SITE: &str = "01646500";
+/// Thames at Kingston.
+const EA_STATION: &str = "3400TH";
+/// Washington, D.C. tide station.
+const NOAA_STATION: &str = "8594900";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endpoint {
+    OpenWeatherMapCurrent,
+    OpenWeatherMapForecast,
+    OpenWeatherMapOneCall,
+    OpenWeatherMapAlerts,
+    OpenWeatherMapAirPollution,
+    GeocodeDirect,
+    GeocodeReverse,
+    OpenMeteoArchive,
+    OpenMeteoCurrent,
+    OpenMeteoSearch,
+    OpenMeteoSolar,
+    OpenMeteoMarine,
+    OpenMeteoMarineWind,
+    OpenMeteoElevation,
+    OpenMeteoMountain,
+    OpenMeteoRoute,
+    UsgsLevels,
+    NwsAlerts,
+    MeteoAlarmWarnings,
+    EaStation,
+    EaReadings,
+    EaFloods,
+    NoaaStations,
+    NoaaPredictions,
+    WorldTides,
+    NetatmoStations,
+    AmbientDevices,
+    IpApiLocation,
+}
+
+impl Endpoint {
+    pub const ALL: [Endpoint; 28] = [
+        Endpoint::OpenWeatherMapCurrent,
+        Endpoint::OpenWeatherMapForecast,
+        Endpoint::OpenWeatherMapOneCall,
+        Endpoint::OpenWeatherMapAlerts,
+        Endpoint::OpenWeatherMapAirPollution,
+        Endpoint::GeocodeDirect,
+        Endpoint::GeocodeReverse,
+        Endpoint::OpenMeteoArchive,
+        Endpoint::OpenMeteoCurrent,
+        Endpoint::OpenMeteoSearch,
+        Endpoint::OpenMeteoSolar,
+        Endpoint::OpenMeteoMarine,
+        Endpoint::OpenMeteoMarineWind,
+        Endpoint::OpenMeteoElevation,
+        Endpoint::OpenMeteoMountain,
+        Endpoint::OpenMeteoRoute,
+        Endpoint::UsgsLevels,
+        Endpoint::NwsAlerts,
+        Endpoint::MeteoAlarmWarnings,
+        Endpoint::EaStation,
+        Endpoint::EaReadings,
+        Endpoint::EaFloods,
+        Endpoint::NoaaStations,
+        Endpoint::NoaaPredictions,
+        Endpoint::WorldTides,
+        Endpoint::NetatmoStations,
+        Endpoint::AmbientDevices,
+        Endpoint::IpApiLocation,
+    ];
+
+    /// Stable identifier, also the file name of the recorded response.
+    pub fn name(self) -> &'static str {
+        match self {
+            Endpoint::OpenWeatherMapCurrent => "openweathermap_current",
+            Endpoint::OpenWeatherMapForecast => "openweathermap_forecast",
+            Endpoint::OpenWeatherMapOneCall => "openweathermap_onecall",
+            Endpoint::OpenWeatherMapAlerts => "openweathermap_alerts",
+            Endpoint::OpenWeatherMapAirPollution => "openweathermap_air_pollution",
+            Endpoint::GeocodeDirect => "geocode_direct",
+            Endpoint::GeocodeReverse => "geocode_reverse",
+            Endpoint::OpenMeteoArchive => "openmeteo_archive",
+            Endpoint::OpenMeteoCurrent => "openmeteo_current",
+            Endpoint::OpenMeteoSearch => "openmeteo_search",
+            Endpoint::OpenMeteoSolar => "openmeteo_solar",
+            Endpoint::OpenMeteoMarine => "openmeteo_marine",
+            Endpoint::OpenMeteoMarineWind => "openmeteo_marine_wind",
+            Endpoint::OpenMeteoElevation => "openmeteo_elevation",
+            Endpoint::OpenMeteoMountain => "openmeteo_mountain",
+            Endpoint::OpenMeteoRoute => "openmeteo_route",
+            Endpoint::UsgsLevels => "usgs_levels",
+            Endpoint::NwsAlerts => "nws_alerts",
+            Endpoint::MeteoAlarmWarnings => "meteoalarm_warnings",
+            Endpoint::EaStation => "ea_station",
+            Endpoint::EaReadings => "ea_readings",
+            Endpoint::EaFloods => "ea_floods",
+            Endpoint::NoaaStations => "noaa_stations",
+            Endpoint::NoaaPredictions => "noaa_predictions",
+            Endpoint::WorldTides => "worldtides",
+            Endpoint::NetatmoStations => "netatmo_stations",
+            Endpoint::AmbientDevices => "ambient_devices",
+            Endpoint::IpApiLocation => "ipapi_location",
+        }
+    }
+
+    /// Environment variables a live request needs.
+    pub fn credentials(self) -> &'static [&'static str] {
+        match self {
+            Endpoint::OpenWeatherMapCurrent
+            | Endpoint::OpenWeatherMapForecast
+            | Endpoint::OpenWeatherMapOneCall
+            | Endpoint::OpenWeatherMapAlerts
+            | Endpoint::OpenWeatherMapAirPollution
+            | Endpoint::GeocodeDirect
+            | Endpoint::GeocodeReverse => &["OPEN_WEATHER_MAP_API"],
+            Endpoint::WorldTides => &["WORLDTIDES_API_KEY"],
+            Endpoint::NetatmoStations => &["NETATMO_ACCESS_TOKEN"],
+            Endpoint::AmbientDevices => &["AMBIENT_API_KEY", "AMBIENT_APPLICATION_KEY"],
+            Endpoint::OpenMeteoArchive
+            | Endpoint::OpenMeteoCurrent
+            | Endpoint::OpenMeteoSearch
+            | Endpoint::OpenMeteoSolar
+            | Endpoint::OpenMeteoMarine
+            | Endpoint::OpenMeteoMarineWind
+            | Endpoint::OpenMeteoElevation
+            | Endpoint::OpenMeteoMountain
+            | Endpoint::OpenMeteoRoute
+            | Endpoint::UsgsLevels
+            | Endpoint::NwsAlerts
+            | Endpoint::MeteoAlarmWarnings
+            | Endpoint::EaStation
+            | Endpoint::EaReadings
+            | Endpoint::EaFloods
+            | Endpoint::NoaaStations
+            | Endpoint::NoaaPredictions
+            | Endpoint::IpApiLocation => &[],
+        }
+    }
+
+    /// The request the provider sends, for a fixed location, with
+    /// credentials from the environment. Fails naming the first missing
+    /// variable.
+    pub fn probe(self, client: &reqwest::Client) -> Result<reqwest::RequestBuilder, String> {
+        let mut values = Vec::new();
+        for name in self.credentials() {
+            values.push(env::var(name).map_err(|_| format!("{} is not set", name))?);
+        }
+        let key = values.first().map(String::as_str).unwrap_or_default();
+        let (lat, lon) = LONDON;
+        let london = Place {
+            name: "London".to_string(),
+            local_names: Default::default(),
+            lat,
+            lon,
+            country: "GB".to_string(),
+            state: None,
+        };
+
+        Ok(match self {
+            Endpoint::OpenWeatherMapCurrent => {
+                openweathermap::weather_request(client, &london, key)
+            }
+            Endpoint::OpenWeatherMapForecast => forecast::forecast_request(client, lat, lon, key),
+            Endpoint::OpenWeatherMapOneCall => onecall::onecall_request(client, lat, lon, key),
+            Endpoint::OpenWeatherMapAlerts => onecall::alerts_request(client, lat, lon, key),
+            Endpoint::OpenWeatherMapAirPollution => {
+                air::air_pollution_request(client, lat, lon, key)
+            }
+            Endpoint::GeocodeDirect => geocode::direct_request(client, "London,GB", 1, key),
+            Endpoint::GeocodeReverse => geocode::reverse_request(client, lat, lon, key),
+            Endpoint::OpenMeteoArchive => {
+                let day = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap_or_default();
+                openmeteo::archive_request(client, lat, lon, day, day)
+            }
+            Endpoint::OpenMeteoCurrent => openmeteo::current_request(client, lat, lon),
+            Endpoint::OpenMeteoSearch => openmeteo::search_request(client, "London", Some("GB"), 1),
+            Endpoint::OpenMeteoSolar => openmeteo::solar_request(client, lat, lon, 1),
+            Endpoint::OpenMeteoMarine => marine::marine_request(client, NEWQUAY.0, NEWQUAY.1, 1),
+            Endpoint::OpenMeteoMarineWind => marine::wind_request(client, NEWQUAY.0, NEWQUAY.1, 1),
+            Endpoint::OpenMeteoElevation => mountain::elevation_request(client, &[MONT_BLANC]),
+            Endpoint::OpenMeteoMountain => mountain::forecast_request(
+                client,
+                MONT_BLANC.0,
+                MONT_BLANC.1,
+                &[4800.0, 2900.0, 1000.0],
+                1,
+            ),
+            Endpoint::OpenMeteoRoute => route::forecast_request(
+                client,
+                &[
+                    route::Point {
+                        lat,
+                        lon,
+                        elevation: Some(20.0),
+                    },
+                    route::Point {
+                        lat: MONT_BLANC.0,
+                        lon: MONT_BLANC.1,
+                        elevation: Some(4800.0),
+                    },
+                ],
+                1,
+            ),
+            Endpoint::UsgsLevels => river::usgs_request(client, USGS_SITE),
+            Endpoint::NwsAlerts => alerts::nws_alerts_request(client, WASHINGTON.0, WASHINGTON.1),
+            Endpoint::MeteoAlarmWarnings => alerts::meteoalarm_request(client, "united-kingdom"),
+            Endpoint::EaStation => river::ea_station_request(client, EA_STATION),
+            Endpoint::EaReadings => river::ea_readings_request(client, EA_STATION),
+            Endpoint::EaFloods => river::ea_floods_request(client, lat, lon),
+            Endpoint::NoaaStations => tides::noaa_stations_request(client),
+            Endpoint::NoaaPredictions => {
+                tides::noaa_predictions_request(client, NOAA_STATION, Utc::now())
+            }
+            Endpoint::WorldTides => tides::worldtides_request(client, lat, lon, Utc::now(), key),
+            Endpoint::NetatmoStations => netatmo::stations_request(client, key, None),
+            Endpoint::AmbientDevices => ambient::devices_request(client, key, &values[1]),
+            Endpoint::IpApiLocation => iplocation::locate_request(client),
+        })
+    }
+
+    /// Reads a successful response body the way the provider does.
+    pub fn check(self, body: &[u8]) -> Result<(), serde_json::Error> {
+        match self {
+            Endpoint::OpenWeatherMapCurrent => parse::<openweathermap::WeatherData>(body),
+            Endpoint::OpenWeatherMapForecast => parse::<forecast::Forecast>(body),
+            Endpoint::OpenWeatherMapOneCall => parse::<onecall::OneCall>(body),
+            Endpoint::OpenWeatherMapAlerts => parse::<onecall::Alerts>(body),
+            Endpoint::OpenWeatherMapAirPollution => parse::<air::AirPollution>(body),
+            Endpoint::GeocodeDirect | Endpoint::GeocodeReverse => parse::<Vec<Place>>(body),
+            Endpoint::OpenMeteoArchive => {
+                serde_json::from_slice::<openmeteo::Archive>(body).map(drop)
+            }
+            Endpoint::OpenMeteoCurrent => parse::<openmeteo::Current>(body),
+            Endpoint::OpenMeteoSearch => parse::<openmeteo::Search>(body),
+            Endpoint::OpenMeteoSolar => parse::<openmeteo::Solar>(body),
+            Endpoint::OpenMeteoMarine => parse::<marine::Marine>(body),
+            Endpoint::OpenMeteoMarineWind => parse::<marine::MarineWind>(body),
+            Endpoint::OpenMeteoElevation => parse::<mountain::Elevations>(body),
+            Endpoint::OpenMeteoMountain => parse::<Vec<mountain::Band>>(body),
+            Endpoint::OpenMeteoRoute => parse::<Vec<route::PointForecast>>(body),
+            Endpoint::UsgsLevels => parse::<river::UsgsResponse>(body),
+            Endpoint::NwsAlerts => parse::<alerts::NwsAlerts>(body),
+            Endpoint::MeteoAlarmWarnings => parse::<alerts::MeteoAlarmFeed>(body),
+            Endpoint::EaStation => parse::<river::EaStationResponse>(body),
+            Endpoint::EaReadings => parse::<river::EaReadings>(body),
+            Endpoint::EaFloods => parse::<river::EaFloods>(body),
+            Endpoint::NoaaStations => parse::<tides::NoaaStations>(body),
+            Endpoint::NoaaPredictions => parse::<tides::NoaaPredictions>(body),
+            Endpoint::WorldTides => parse::<tides::WorldTidesResponse>(body),
+            Endpoint::NetatmoStations => parse::<netatmo::StationsResponse>(body),
+            Endpoint::AmbientDevices => parse::<Vec<ambient::Device>>(body),
+            Endpoint::IpApiLocation => parse::<iplocation::IpLocation>(body),
+        }
+    }
+}
+
+fn parse<T: DeserializeOwned>(body: &[u8]) -> Result<(), serde_json::Error> {
+    serde_json::from_slice::<T>(body).map(drop)
+}
@@ -0,0 +1,112 @@
+//! Why a lookup failed, for callers that act on the kind of failure: ask
+//! for another name, another key, or try again later.
+
+use crate::client::Cancelled;
+use crate::middleware::BoxError;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum WeatherError {
+    /// No place matches the query.
+    CityNotFound(String),
+    /// The service refused the API key (HTTP 401).
+    InvalidApiKey { service: &'static str },
+    /// The service is limiting requests (HTTP 429).
+    RateLimited { service: &'static str },
+    /// Any other error status.
+    Status {
+        service: &'static str,
+        status: reqwest::StatusCode,
+    },
+    /// The place was found, but the service has no weather for it.
+    NoData(String),
+    /// The request could not be sent, or the response not received.
+    Network(reqwest::Error),
+    /// The response is not in the format the service documents.
+    Decode(reqwest::Error),
+    /// A [`crate::middleware::Middleware`] of a [`crate::WeatherClient`]
+    /// failed the request.
+    Middleware(BoxError),
+    /// A [`crate::WeatherClient`] call was cancelled or ran out of time.
+    Cancelled(Cancelled),
+}
+
+impl WeatherError {
+    /// The error for an unsuccessful `status` from `service`, e.g.
+    /// "Geocoding API".
+    pub fn status(service: &'static str, status: reqwest::StatusCode) -> Self {
+        match status.as_u16() {
+            401 => WeatherError::InvalidApiKey { service },
+            429 => WeatherError::RateLimited { service },
+            _ => WeatherError::Status { service, status },
+        }
+    }
+}
+
+impl fmt::Display for WeatherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WeatherError::CityNotFound(query) => write!(f, "City '{}' not found", query),
+            WeatherError::InvalidApiKey { service } => {
+                write!(
+                    f,
+                    "{} error: HTTP {}",
+                    service,
+                    reqwest::StatusCode::UNAUTHORIZED
+                )
+            }
+            WeatherError::RateLimited { service } => {
+                write!(
+                    f,
+                    "{} error: HTTP {}",
+                    service,
+                    reqwest::StatusCode::TOO_MANY_REQUESTS
+                )
+            }
+            WeatherError::Status { service, status } => {
+                write!(f, "{} error: HTTP {}", service, status)
+            }
+            WeatherError::NoData(place) => write!(f, "No weather data for '{}'", place),
+            WeatherError::Network(e) | WeatherError::Decode(e) => e.fmt(f),
+            WeatherError::Middleware(e) => e.fmt(f),
+            WeatherError::Cancelled(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for WeatherError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WeatherError::Network(e) | WeatherError::Decode(e) => Some(e),
+            WeatherError::Middleware(e) => Some(&**e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for WeatherError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_decode() {
+            WeatherError::Decode(e)
+        } else {
+            WeatherError::Network(e)
+        }
+    }
+}
+
+impl From<Cancelled> for WeatherError {
+    fn from(e: Cancelled) -> Self {
+        WeatherError::Cancelled(e)
+    }
+}
+
+/// `res` if its status is a success, else the error for it.
+pub(crate) fn check(
+    service: &'static str,
+    res: reqwest::Response,
+) -> Result<reqwest::Response, WeatherError> {
+    match res.status() {
+        status if status.is_success() => Ok(res),
+        status => Err(WeatherError::status(service, status)),
+    }
+}
@@ -0,0 +1,90 @@
+use crate::error::{self, WeatherError};
+use crate::openweathermap::{self, Clouds, Weather};
+use crate::retry;
+use serde::{Deserialize, Serialize};
+
+/// Response of the OpenWeatherMap 5 day / 3 hour forecast endpoint.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Forecast {
+    pub list: Vec<ForecastEntry>,
+    pub city: ForecastCity,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ForecastEntry {
+    pub dt: i64,
+    pub main: ForecastMain,
+    pub weather: Vec<Weather>,
+    pub clouds: Clouds,
+    pub wind: ForecastWind,
+    pub visibility: Option<i32>,
+    /// Probability of precipitation, 0.0 to 1.0.
+    #[serde(default)]
+    pub pop: f64,
+    pub rain: Option<Precipitation>,
+    pub snow: Option<Precipitation>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ForecastMain {
+    pub temp: f64,
+    pub feels_like: f64,
+    pub temp_min: f64,
+    pub temp_max: f64,
+    pub pressure: i32,
+    pub humidity: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ForecastWind {
+    pub speed: f64,
+    pub deg: i32,
+    pub gust: Option<f64>,
+}
+
+/// Precipitation volume in mm over the three hours of the entry.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Precipitation {
+    #[serde(rename = "3h", default)]
+    pub three_hours: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ForecastCity {
+    pub name: String,
+    pub country: String,
+    pub timezone: i32,
+    pub sunrise: i64,
+    pub sunset: i64,
+}
+
+pub async fn get_forecast(
+    client: &reqwest::Client,
+    lat: f64,
+    lon: f64,
+    api_key: &str,
+) -> Result<Forecast, WeatherError> {
+    let res = retry::send(forecast_request(client, lat, lon, api_key)).await?;
+    let res = error::check("Forecast API", res)?;
+
+    Ok(res.json::<Forecast>().await?)
+}
+
+/// The request [`get_forecast`] sends.
+pub(crate) fn forecast_request(
+    client: &reqwest::Client,
+    lat: f64,
+    lon: f64,
+    api_key: &str,
+) -> reqwest::RequestBuilder {
+    openweathermap::with_language(
+        client
+            .get("https://api.openweathermap.org/data/2.5/forecast")
+            .query(&[
+                ("lat", lat.to_string().as_str()),
+                ("lon", lon.to_string().as_str()),
+                ("appid", api_key),
+                ("units", "metric"),
+            ]),
+    )
+}
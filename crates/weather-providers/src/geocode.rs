@@ -0,0 +1,322 @@
+use crate::error::{self, WeatherError};
+use crate::middleware::Transport;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use weather_core::model::Location;
+
+/// A place returned by the OpenWeatherMap geocoding API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Place {
+    pub name: String,
+    #[serde(default)]
+    pub local_names: HashMap<String, String>,
+    pub lat: f64,
+    pub lon: f64,
+    pub country: String,
+    pub state: Option<String>,
+}
+
+impl Place {
+    /// The English name of the place, falling back to the default name.
+    pub fn english_name(&self) -> &str {
+        self.local_names
+            .get("en")
+            .map(String::as_str)
+            .unwrap_or(&self.name)
+    }
+
+    /// The state and country, e.g. "Maharashtra, IN", leaving out what is
+    /// unknown.
+    pub fn region(&self) -> String {
+        [self.state.as_deref(), Some(self.country.as_str())]
+            .into_iter()
+            .flatten()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// The place as a report's location, its clocks `utc_offset_seconds`
+    /// ahead of UTC.
+    pub fn location(&self, utc_offset_seconds: i32) -> Location {
+        let mut location = Location::new(self.english_name(), self.lat, self.lon);
+        location.native_name = self.native_name().map(str::to_string);
+        location.country = self.country.clone();
+        location.state = self.state.clone();
+        location.utc_offset_seconds = utc_offset_seconds;
+        location
+    }
+
+    /// The name in the main language of the place's country, when known and
+    /// different from the English name.
+    pub fn native_name(&self) -> Option<&str> {
+        let language = country_language(&self.country)?;
+        let native = self.local_names.get(language)?;
+        if native != self.english_name() {
+            Some(native)
+        } else {
+            None
+        }
+    }
+}
+
+/// Looks up `query` with the geocoding API, retrying with normalized and
+/// transliterated spellings ("Muenchen" → "München", "Kiev" → "Kyiv") when
+/// the name as typed yields no match.
+pub async fn resolve_city(
+    transport: Transport<'_>,
+    query: &str,
+    api_key: &str,
+) -> Result<Place, WeatherError> {
+    for candidate in query_variants(query) {
+        if let Some(place) = direct(transport, &candidate, api_key).await? {
+            return Ok(place);
+        }
+    }
+
+    Err(WeatherError::CityNotFound(query.to_string()))
+}
+
+/// Most places the geocoding API returns for one name.
+pub const MAX_MATCHES: usize = 5;
+
+/// Up to `limit` (at most [`MAX_MATCHES`]) places matching `query`, best
+/// first, from the first of its spellings that matches any; empty when
+/// none does. For choosing between places of the same name, such as the
+/// Springfields of the United States.
+pub async fn search(
+    transport: Transport<'_>,
+    query: &str,
+    limit: usize,
+    api_key: &str,
+) -> Result<Vec<Place>, WeatherError> {
+    for candidate in query_variants(query) {
+        let request = direct_request(
+            transport.http(),
+            &candidate,
+            limit.min(MAX_MATCHES),
+            api_key,
+        );
+        let places = parse_places(transport.send(request).await?).await?;
+        if !places.is_empty() {
+            return Ok(places);
+        }
+    }
+    Ok(Vec::new())
+}
+
+/// The best match for `name` exactly as given, if any.
+pub async fn direct(
+    transport: Transport<'_>,
+    name: &str,
+    api_key: &str,
+) -> Result<Option<Place>, WeatherError> {
+    let request = direct_request(transport.http(), name, 1, api_key);
+    parse_direct(transport.send(request).await?).await
+}
+
+/// The lookup request [`direct`] and [`search`] send, for up to `limit`
+/// places.
+pub fn direct_request(
+    client: &reqwest::Client,
+    name: &str,
+    limit: usize,
+    api_key: &str,
+) -> reqwest::RequestBuilder {
+    client
+        .get("https://api.openweathermap.org/geo/1.0/direct")
+        .query(&[
+            ("q", name),
+            ("limit", limit.to_string().as_str()),
+            ("appid", api_key),
+        ])
+}
+
+/// Reads the response to a [`direct_request`]: the best match, if any.
+pub async fn parse_direct(res: reqwest::Response) -> Result<Option<Place>, WeatherError> {
+    Ok(parse_places(res).await?.into_iter().next())
+}
+
+/// Reads the response to a [`direct_request`]: every match, best first.
+pub async fn parse_places(res: reqwest::Response) -> Result<Vec<Place>, WeatherError> {
+    let res = error::check("Geocoding API", res)?;
+    Ok(res.json::<Vec<Place>>().await?)
+}
+
+/// Finds the place nearest to the given coordinates. Positions without a
+/// named place nearby (e.g. at sea) get their coordinates as the name.
+pub async fn reverse(
+    transport: Transport<'_>,
+    lat: f64,
+    lon: f64,
+    api_key: &str,
+) -> Result<Place, WeatherError> {
+    let res = transport
+        .send(reverse_request(transport.http(), lat, lon, api_key))
+        .await?;
+    let res = error::check("Geocoding API", res)?;
+
+    let places = res.json::<Vec<Place>>().await?;
+    Ok(places.into_iter().next().map_or_else(
+        || Place {
+            name: format!("{:.4}, {:.4}", lat, lon),
+            local_names: HashMap::new(),
+            lat,
+            lon,
+            country: String::new(),
+            state: None,
+        },
+        // Keep the exact position rather than the place's centre.
+        |place| Place { lat, lon, ..place },
+    ))
+}
+
+/// The lookup request [`reverse`] sends.
+pub(crate) fn reverse_request(
+    client: &reqwest::Client,
+    lat: f64,
+    lon: f64,
+    api_key: &str,
+) -> reqwest::RequestBuilder {
+    client
+        .get("https://api.openweathermap.org/geo/1.0/reverse")
+        .query(&[
+            ("lat", lat.to_string().as_str()),
+            ("lon", lon.to_string().as_str()),
+            ("limit", "1"),
+            ("appid", api_key),
+        ])
+}
+
+/// Spellings to try for a user-supplied city name, most literal first.
+pub fn query_variants(query: &str) -> Vec<String> {
+    let trimmed = query.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut variants = vec![trimmed.clone()];
+
+    let (city, rest) = match trimmed.split_once(',') {
+        Some((city, rest)) => (city.trim().to_string(), format!(",{}", rest)),
+        None => (trimmed.clone(), String::new()),
+    };
+
+    if let Some(modern) = known_alias(&city) {
+        variants.push(format!("{}{}", modern, rest));
+    }
+    variants.push(format!("{}{}", fold_german_digraphs(&city), rest));
+    variants.push(format!("{}{}", strip_diacritics(&city), rest));
+
+    let mut unique = Vec::new();
+    for variant in variants {
+        if !unique.contains(&variant) {
+            unique.push(variant);
+        }
+    }
+    unique
+}
+
+/// Maps historical or anglicized exonyms to the name geocoders index today.
+fn known_alias(city: &str) -> Option<&'static str> {
+    let alias = match city.to_lowercase().as_str() {
+        "kiev" => "Kyiv",
+        "kharkov" => "Kharkiv",
+        "odessa" => "Odesa",
+        "bombay" => "Mumbai",
+        "calcutta" => "Kolkata",
+        "madras" => "Chennai",
+        "bangalore" => "Bengaluru",
+        "peking" => "Beijing",
+        "canton" => "Guangzhou",
+        "saigon" => "Ho Chi Minh City",
+        "rangoon" => "Yangon",
+        "constantinople" => "Istanbul",
+        "leningrad" => "Saint Petersburg",
+        "danzig" => "Gdansk",
+        "breslau" => "Wroclaw",
+        "pressburg" => "Bratislava",
+        "lemberg" => "Lviv",
+        _ => return None,
+    };
+    Some(alias)
+}
+
+/// Turns the ASCII transcriptions `ae`, `oe` and `ue` back into umlauts.
+fn fold_german_digraphs(city: &str) -> String {
+    city.replace("ae", "ä")
+        .replace("oe", "ö")
+        .replace("ue", "ü")
+        .replace("Ae", "Ä")
+        .replace("Oe", "Ö")
+        .replace("Ue", "Ü")
+}
+
+/// Replaces common accented Latin letters with their unaccented base letter.
+fn strip_diacritics(city: &str) -> String {
+    city.chars()
+        .map(|c| match c {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ą' => "a".to_string(),
+            'À' | 'Á' | 'Â' | 'Ã' | 'Ä' | 'Å' | 'Ā' | 'Ą' => "A".to_string(),
+            'ç' | 'ć' | 'č' => "c".to_string(),
+            'Ç' | 'Ć' | 'Č' => "C".to_string(),
+            'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ę' | 'ě' => "e".to_string(),
+            'È' | 'É' | 'Ê' | 'Ë' | 'Ē' | 'Ę' | 'Ě' => "E".to_string(),
+            'ì' | 'í' | 'î' | 'ï' | 'ī' | 'ı' => "i".to_string(),
+            'Ì' | 'Í' | 'Î' | 'Ï' | 'Ī' | 'İ' => "I".to_string(),
+            'ł' => "l".to_string(),
+            'Ł' => "L".to_string(),
+            'ñ' | 'ń' | 'ň' => "n".to_string(),
+            'Ñ' | 'Ń' | 'Ň' => "N".to_string(),
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ő' => "o".to_string(),
+            'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ö' | 'Ø' | 'Ō' | 'Ő' => "O".to_string(),
+            'ř' => "r".to_string(),
+            'Ř' => "R".to_string(),
+            'ś' | 'š' | 'ş' => "s".to_string(),
+            'Ś' | 'Š' | 'Ş' => "S".to_string(),
+            'ß' => "ss".to_string(),
+            'ț' | 'ţ' | 'ť' => "t".to_string(),
+            'Ț' | 'Ţ' | 'Ť' => "T".to_string(),
+            'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' => "u".to_string(),
+            'Ù' | 'Ú' | 'Û' | 'Ü' | 'Ū' | 'Ů' | 'Ű' => "U".to_string(),
+            'ý' | 'ÿ' => "y".to_string(),
+            'Ý' => "Y".to_string(),
+            'ź' | 'ż' | 'ž' => "z".to_string(),
+            'Ź' | 'Ż' | 'Ž' => "Z".to_string(),
+            other => other.to_string(),
+        })
+        .collect()
+}
+
+/// Main language (ISO 639-1) used for place names in a country (ISO 3166-1).
+fn country_language(country: &str) -> Option<&'static str> {
+    let language = match country {
+        "DE" | "AT" | "LI" => "de",
+        "FR" | "MC" => "fr",
+        "ES" | "MX" | "AR" | "CO" | "CL" | "PE" | "VE" => "es",
+        "IT" | "SM" => "it",
+        "PT" | "BR" => "pt",
+        "NL" => "nl",
+        "PL" => "pl",
+        "CZ" => "cs",
+        "SK" => "sk",
+        "HU" => "hu",
+        "RO" => "ro",
+        "BG" => "bg",
+        "GR" => "el",
+        "TR" => "tr",
+        "UA" => "uk",
+        "RU" | "BY" => "ru",
+        "SE" => "sv",
+        "NO" => "no",
+        "DK" => "da",
+        "FI" => "fi",
+        "JP" => "ja",
+        "CN" | "TW" => "zh",
+        "KR" => "ko",
+        "IN" => "hi",
+        "TH" => "th",
+        "VN" => "vi",
+        "IL" => "he",
+        "EG" | "SA" | "AE" | "MA" | "IQ" | "JO" => "ar",
+        "IR" => "fa",
+        _ => return None,
+    };
+    Some(language)
+}
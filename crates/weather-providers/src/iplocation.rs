@@ -0,0 +1,58 @@
+//! Approximate location of the public IP address, from ipapi.co. Good to
+//! the city at best, and wrong behind a VPN, so callers should say where
+//! they think the user is.
+
+use crate::error::WeatherError;
+use crate::retry;
+use serde::Deserialize;
+
+const IPAPI_URL: &str = "https://ipapi.co/json/";
+
+#[derive(Debug, Deserialize)]
+pub struct IpLocation {
+    pub city: Option<String>,
+    pub region: Option<String>,
+    /// ISO 3166 country code.
+    pub country_code: Option<String>,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl IpLocation {
+    /// "Berlin, Land Berlin, DE", leaving out what is unknown.
+    pub fn label(&self) -> String {
+        [&self.city, &self.region, &self.country_code]
+            .into_iter()
+            .flatten()
+            .filter(|part| !part.is_empty())
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// ipapi.co answers rate limiting and private addresses with HTTP 200
+/// and a reason instead of a position.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Response {
+    Failed { reason: String },
+    Located(IpLocation),
+}
+
+/// Where the address this request comes from appears to be.
+pub async fn locate(client: &reqwest::Client) -> Result<IpLocation, Box<dyn std::error::Error>> {
+    let res = retry::send(locate_request(client)).await?;
+    if !res.status().is_success() {
+        return Err(WeatherError::status("IP location", res.status()).into());
+    }
+    match res.json::<Response>().await? {
+        Response::Located(location) => Ok(location),
+        Response::Failed { reason } => Err(format!("IP location error: {}", reason).into()),
+    }
+}
+
+/// The request [`locate`] sends.
+pub(crate) fn locate_request(client: &reqwest::Client) -> reqwest::RequestBuilder {
+    client.get(IPAPI_URL)
+}
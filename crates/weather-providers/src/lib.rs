@@ -0,0 +1,38 @@
+//! Clients for the services the weather CLI reads from, usable without it.
+//!
+//! Each module speaks one API and returns its response types; current
+//! conditions are mapped onto [`weather_core::model`] so consumers can stay
+//! provider-agnostic. [`provider::WeatherProvider`] puts the sources of
+//! current conditions behind one interface. [`WeatherClient`] wraps a
+//! provider with middleware and cancellation, and a stream of changed
+//! reports for long-running consumers.
+//! Every lookup, the client's included, fails with a [`WeatherError`] that
+//! tells the kinds of failure apart.
+
+pub mod air;
+pub mod alerts;
+pub mod ambient;
+pub mod cap;
+pub mod client;
+pub mod endpoints;
+pub mod error;
+pub mod forecast;
+pub mod geocode;
+pub mod iplocation;
+pub mod marine;
+pub mod middleware;
+pub mod mountain;
+pub mod netatmo;
+pub mod onecall;
+pub mod openmeteo;
+pub mod openweathermap;
+pub mod provider;
+pub mod pws;
+pub mod retry;
+pub mod river;
+pub mod route;
+pub mod tides;
+
+pub use client::WeatherClient;
+pub use error::WeatherError;
+pub use provider::WeatherProvider;
@@ -0,0 +1,105 @@
+//! Open-Meteo's marine forecast for `weather surf`: waves and swell hour by
+//! hour, with the wind at 10 m from its weather forecast. Keyless, like the
+//! rest of Open-Meteo. Over land and enclosed water the wave values are
+//! missing.
+
+use crate::error::{self, WeatherError};
+use crate::{openmeteo, retry};
+use serde::Deserialize;
+
+const MARINE_URL: &str = "https://marine-api.open-meteo.com/v1/marine";
+
+const MARINE_HOURLY: &str = "wave_height,wave_direction,wave_period,swell_wave_height,\
+    swell_wave_direction,swell_wave_period";
+
+const WIND_HOURLY: &str = "wind_speed_10m,wind_direction_10m,wind_gusts_10m";
+
+/// Waves hour by hour. Times are Unix timestamps; heights are in metres,
+/// periods in seconds and directions in degrees the waves come from.
+#[derive(Debug, Deserialize)]
+pub struct Marine {
+    pub utc_offset_seconds: i32,
+    pub hourly: MarineHourly,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MarineHourly {
+    pub time: Vec<i64>,
+    /// Significant height of wind waves and swell together.
+    pub wave_height: Vec<Option<f64>>,
+    pub wave_direction: Vec<Option<f64>>,
+    pub wave_period: Vec<Option<f64>>,
+    pub swell_wave_height: Vec<Option<f64>>,
+    pub swell_wave_direction: Vec<Option<f64>>,
+    pub swell_wave_period: Vec<Option<f64>>,
+}
+
+/// The wind hour by hour, in m/s and degrees it blows from.
+#[derive(Debug, Deserialize)]
+pub struct MarineWind {
+    pub hourly: MarineWindHourly,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MarineWindHourly {
+    pub time: Vec<i64>,
+    pub wind_speed_10m: Vec<Option<f64>>,
+    pub wind_direction_10m: Vec<Option<f64>>,
+    pub wind_gusts_10m: Vec<Option<f64>>,
+}
+
+/// The waves and the wind at `lat`, `lon` for the next `days` days, today
+/// included.
+pub async fn forecast(
+    client: &reqwest::Client,
+    lat: f64,
+    lon: f64,
+    days: u32,
+) -> Result<(Marine, MarineWind), WeatherError> {
+    let marine = async {
+        let res = retry::send(marine_request(client, lat, lon, days)).await?;
+        let res = error::check("Open-Meteo Marine", res)?;
+        Ok::<_, WeatherError>(res.json::<Marine>().await?)
+    };
+    let wind = async {
+        let res = retry::send(wind_request(client, lat, lon, days)).await?;
+        let res = error::check("Open-Meteo", res)?;
+        Ok::<_, WeatherError>(res.json::<MarineWind>().await?)
+    };
+    tokio::try_join!(marine, wind)
+}
+
+/// The wave request [`forecast`] sends.
+pub(crate) fn marine_request(
+    client: &reqwest::Client,
+    lat: f64,
+    lon: f64,
+    days: u32,
+) -> reqwest::RequestBuilder {
+    client.get(MARINE_URL).query(&[
+        ("latitude", lat.to_string()),
+        ("longitude", lon.to_string()),
+        ("hourly", MARINE_HOURLY.to_string()),
+        ("forecast_days", days.to_string()),
+        ("timeformat", "unixtime".to_string()),
+        ("timezone", "auto".to_string()),
+    ])
+}
+
+/// The wind request [`forecast`] sends.
+pub(crate) fn wind_request(
+    client: &reqwest::Client,
+    lat: f64,
+    lon: f64,
+    days: u32,
+) -> reqwest::RequestBuilder {
+    client.get(openmeteo::FORECAST_URL).query(&[
+        ("latitude", lat.to_string()),
+        ("longitude", lon.to_string()),
+        ("hourly", WIND_HOURLY.to_string()),
+        ("wind_speed_unit", "ms".to_string()),
+        ("forecast_days", days.to_string()),
+        ("timeformat", "unixtime".to_string()),
+        ("timezone", "auto".to_string()),
+    ])
+}
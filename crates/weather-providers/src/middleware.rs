@@ -0,0 +1,120 @@
+//! Request/response interceptors for [`crate::client::WeatherClient`], in
+//! the style of tower layers: each middleware gets the outgoing request and
+//! the rest of the chain, and may change the request, call on, and inspect
+//! or replace the response.
+//!
+//! ```no_run
+//! use weather_providers::middleware::{BoxFuture, Middleware, MiddlewareResult, Next};
+//!
+//! struct LogRequests;
+//!
+//! impl Middleware for LogRequests {
+//!     fn handle<'a>(
+//!         &'a self,
+//!         request: reqwest::Request,
+//!         next: Next<'a>,
+//!     ) -> BoxFuture<'a, MiddlewareResult> {
+//!         Box::pin(async move {
+//!             let url = request.url().path().to_string();
+//!             let response = next.run(request).await?;
+//!             eprintln!("{} {}", url, response.status());
+//!             Ok(response)
+//!         })
+//!     }
+//! }
+//! ```
+
+use crate::error::WeatherError;
+use crate::retry;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Errors a middleware may fail a request with.
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+pub type MiddlewareResult = Result<reqwest::Response, BoxError>;
+
+/// An interceptor around every HTTP request a client makes. A response can
+/// be rewritten by building a new one from an `http::Response` with
+/// `reqwest::Response::from`.
+pub trait Middleware: Send + Sync + 'static {
+    fn handle<'a>(
+        &'a self,
+        request: reqwest::Request,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, MiddlewareResult>;
+}
+
+/// The remainder of the chain: later middleware, then the HTTP client.
+pub struct Next<'a> {
+    client: &'a reqwest::Client,
+    rest: &'a [Arc<dyn Middleware>],
+}
+
+impl<'a> Next<'a> {
+    fn new(client: &'a reqwest::Client, chain: &'a [Arc<dyn Middleware>]) -> Self {
+        Next {
+            client,
+            rest: chain,
+        }
+    }
+
+    /// Passes `request` on and resolves to the response.
+    pub fn run(self, request: reqwest::Request) -> BoxFuture<'a, MiddlewareResult> {
+        match self.rest.split_first() {
+            Some((middleware, rest)) => middleware.handle(
+                request,
+                Next {
+                    client: self.client,
+                    rest,
+                },
+            ),
+            None => Box::pin(async move { Ok(retry::execute(self.client, request).await?) }),
+        }
+    }
+}
+
+/// An HTTP client and the middleware its requests pass through, which
+/// [`crate::provider::WeatherProvider`]s send with. One made from a plain
+/// client has no middleware.
+#[derive(Clone, Copy)]
+pub struct Transport<'a> {
+    http: &'a reqwest::Client,
+    middleware: &'a [Arc<dyn Middleware>],
+}
+
+impl<'a> Transport<'a> {
+    pub(crate) fn new(http: &'a reqwest::Client, middleware: &'a [Arc<dyn Middleware>]) -> Self {
+        Transport { http, middleware }
+    }
+
+    /// The client to build requests with.
+    pub fn http(&self) -> &'a reqwest::Client {
+        self.http
+    }
+
+    /// Sends `request` through the middleware, then the client, retrying
+    /// transient failures. Error statuses are left to the caller's parser,
+    /// which knows the service to name.
+    pub async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, WeatherError> {
+        Next::new(self.http, self.middleware)
+            .run(request.build()?)
+            .await
+            .map_err(|e| match e.downcast::<reqwest::Error>() {
+                Ok(e) => WeatherError::from(*e),
+                Err(e) => WeatherError::Middleware(e),
+            })
+    }
+}
+
+impl<'a> From<&'a reqwest::Client> for Transport<'a> {
+    fn from(http: &'a reqwest::Client) -> Self {
+        Transport::new(http, &[])
+    }
+}
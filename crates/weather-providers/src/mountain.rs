@@ -0,0 +1,120 @@
+//! Open-Meteo's forecast at given elevations for `weather mountain`, and
+//! the terrain elevation those are picked from. The forecast is downscaled
+//! to each elevation from the model's own terrain height. Keyless, like the
+//! rest of Open-Meteo.
+
+use crate::error::{self, WeatherError};
+use crate::{openmeteo, retry};
+use serde::Deserialize;
+
+const ELEVATION_URL: &str = "https://api.open-meteo.com/v1/elevation";
+
+const DAILY: &str = "weather_code,temperature_2m_max,temperature_2m_min,precipitation_sum,\
+    snowfall_sum,wind_speed_10m_max,wind_gusts_10m_max,wind_direction_10m_dominant";
+
+/// Terrain elevations, in metres, from a 90 m digital elevation model.
+#[derive(Debug, Deserialize)]
+pub struct Elevations {
+    pub elevation: Vec<f64>,
+}
+
+/// The forecast at one elevation, by the place's local days, with the
+/// freezing level hour by hour. Times are Unix timestamps.
+#[derive(Debug, Deserialize)]
+pub struct Band {
+    /// The elevation forecast for, in metres.
+    pub elevation: f64,
+    pub utc_offset_seconds: i32,
+    pub daily: BandDaily,
+    pub hourly: BandHourly,
+}
+
+/// Temperatures in °C, precipitation in mm, snowfall in cm, wind in m/s
+/// and degrees it blows from.
+#[derive(Debug, Deserialize)]
+pub struct BandDaily {
+    pub time: Vec<i64>,
+    pub weather_code: Vec<Option<i32>>,
+    pub temperature_2m_max: Vec<Option<f64>>,
+    pub temperature_2m_min: Vec<Option<f64>>,
+    pub precipitation_sum: Vec<Option<f64>>,
+    pub snowfall_sum: Vec<Option<f64>>,
+    pub wind_speed_10m_max: Vec<Option<f64>>,
+    pub wind_gusts_10m_max: Vec<Option<f64>>,
+    pub wind_direction_10m_dominant: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BandHourly {
+    pub time: Vec<i64>,
+    /// Height above sea level of the 0 °C isotherm, in metres; the same at
+    /// every elevation.
+    pub freezing_level_height: Vec<Option<f64>>,
+}
+
+/// The terrain elevation at each of `points`, as (lat, lon), in order; at
+/// most 100 of them.
+pub async fn elevations(
+    client: &reqwest::Client,
+    points: &[(f64, f64)],
+) -> Result<Vec<f64>, WeatherError> {
+    let res = retry::send(elevation_request(client, points)).await?;
+    let res = error::check("Open-Meteo elevation", res)?;
+    Ok(res.json::<Elevations>().await?.elevation)
+}
+
+/// The forecast at `lat`, `lon` at each of `elevations`, in metres, for
+/// the next `days` days, today included; one [`Band`] per elevation, in
+/// order.
+pub async fn forecast(
+    client: &reqwest::Client,
+    lat: f64,
+    lon: f64,
+    elevations: &[f64],
+    days: u32,
+) -> Result<Vec<Band>, WeatherError> {
+    let res = retry::send(forecast_request(client, lat, lon, elevations, days)).await?;
+    let res = error::check("Open-Meteo", res)?;
+    Ok(res.json::<openmeteo::OneOrMany<Band>>().await?.into_vec())
+}
+
+/// The request [`elevations`] sends.
+pub(crate) fn elevation_request(
+    client: &reqwest::Client,
+    points: &[(f64, f64)],
+) -> reqwest::RequestBuilder {
+    let join = |values: &mut dyn Iterator<Item = f64>| {
+        values.map(|v| v.to_string()).collect::<Vec<_>>().join(",")
+    };
+    client.get(ELEVATION_URL).query(&[
+        ("latitude", join(&mut points.iter().map(|p| p.0))),
+        ("longitude", join(&mut points.iter().map(|p| p.1))),
+    ])
+}
+
+/// The request [`forecast`] sends: the same point once per elevation.
+pub(crate) fn forecast_request(
+    client: &reqwest::Client,
+    lat: f64,
+    lon: f64,
+    elevations: &[f64],
+    days: u32,
+) -> reqwest::RequestBuilder {
+    let repeat = |value: f64| vec![value.to_string(); elevations.len()].join(",");
+    let elevations = elevations
+        .iter()
+        .map(|m| format!("{:.0}", m))
+        .collect::<Vec<_>>()
+        .join(",");
+    client.get(openmeteo::FORECAST_URL).query(&[
+        ("latitude", repeat(lat)),
+        ("longitude", repeat(lon)),
+        ("elevation", elevations),
+        ("daily", DAILY.to_string()),
+        ("hourly", "freezing_level_height".to_string()),
+        ("wind_speed_unit", "ms".to_string()),
+        ("forecast_days", days.to_string()),
+        ("timeformat", "unixtime".to_string()),
+        ("timezone", "auto".to_string()),
+    ])
+}
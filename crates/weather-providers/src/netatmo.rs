@@ -0,0 +1,154 @@
+//! Netatmo weather station accounts: OAuth token refresh and the current
+//! readings of a station's outdoor, wind and rain modules.
+
+use crate::error::WeatherError;
+use crate::retry;
+use chrono::{TimeZone, Utc};
+use serde::Deserialize;
+use weather_core::model::LocalObservation;
+
+const TOKEN_URL: &str = "https://api.netatmo.com/oauth2/token";
+const STATIONS_URL: &str = "https://api.netatmo.com/api/getstationsdata";
+
+/// Tokens returned by the Netatmo OAuth server. Netatmo rotates the refresh
+/// token on every use, so the new one must be stored.
+#[derive(Debug, Deserialize)]
+pub struct Token {
+    pub access_token: String,
+    pub refresh_token: String,
+    /// Lifetime of the access token in seconds.
+    pub expires_in: i64,
+}
+
+/// Exchanges `refresh_token` for a new access token.
+pub async fn refresh(
+    client: &reqwest::Client,
+    client_id: &str,
+    client_secret: &str,
+    refresh_token: &str,
+) -> Result<Token, Box<dyn std::error::Error>> {
+    let res = retry::send(client.post(TOKEN_URL).form(&[
+        ("grant_type", "refresh_token"),
+        ("refresh_token", refresh_token),
+        ("client_id", client_id),
+        ("client_secret", client_secret),
+    ]))
+    .await?;
+    if !res.status().is_success() {
+        return Err(format!(
+            "Netatmo refused the refresh token (HTTP {}); authorise again with `weather auth netatmo`",
+            res.status()
+        )
+        .into());
+    }
+    Ok(res.json().await?)
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct StationsResponse {
+    body: StationsBody,
+}
+
+#[derive(Debug, Deserialize)]
+struct StationsBody {
+    devices: Vec<Device>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Device {
+    #[serde(rename = "_id")]
+    id: String,
+    dashboard_data: Option<Dashboard>,
+    #[serde(default)]
+    modules: Vec<Module>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Module {
+    #[serde(rename = "type")]
+    kind: String,
+    dashboard_data: Option<Dashboard>,
+}
+
+/// Latest values of a device or module; which ones are present depends on
+/// its type. Netatmo always reports metric units.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Dashboard {
+    #[serde(rename = "time_utc")]
+    time_utc: Option<i64>,
+    temperature: Option<f64>,
+    humidity: Option<f64>,
+    /// Sea-level pressure in mbar.
+    pressure: Option<f64>,
+    /// km/h.
+    wind_strength: Option<f64>,
+    gust_strength: Option<f64>,
+    wind_angle: Option<f64>,
+    /// Rain over the last hour in mm.
+    #[serde(rename = "sum_rain_1")]
+    sum_rain_1: Option<f64>,
+}
+
+/// Current readings of the station `device_id` (its MAC address), or of the
+/// account's first station when `None`.
+pub async fn observe(
+    client: &reqwest::Client,
+    access_token: &str,
+    device_id: Option<&str>,
+) -> Result<LocalObservation, Box<dyn std::error::Error>> {
+    let res = retry::send(stations_request(client, access_token, device_id)).await?;
+    if !res.status().is_success() {
+        return Err(WeatherError::status("Netatmo API", res.status()).into());
+    }
+    let device = res
+        .json::<StationsResponse>()
+        .await?
+        .body
+        .devices
+        .into_iter()
+        .find(|d| device_id.is_none_or(|id| d.id.eq_ignore_ascii_case(id)))
+        .ok_or("No Netatmo weather station on this account")?;
+
+    // NAModule1 is the outdoor sensor, NAModule2 the anemometer and
+    // NAModule3 the rain gauge.
+    let module = |kind: &str| {
+        device
+            .modules
+            .iter()
+            .find(|m| m.kind == kind)
+            .and_then(|m| m.dashboard_data.as_ref())
+    };
+    let (outdoor, wind, rain) = (
+        module("NAModule1"),
+        module("NAModule2"),
+        module("NAModule3"),
+    );
+    let indoor = device.dashboard_data.as_ref();
+
+    let mut observation = LocalObservation::new("netatmo");
+    observation.observed_at = outdoor
+        .or(indoor)
+        .and_then(|d| d.time_utc)
+        .and_then(|t| Utc.timestamp_opt(t, 0).single());
+    observation.temperature_c = outdoor.and_then(|d| d.temperature);
+    observation.humidity_percent = outdoor.and_then(|d| d.humidity);
+    observation.pressure_hpa = indoor.and_then(|d| d.pressure);
+    observation.wind_speed_ms = wind.and_then(|d| d.wind_strength).map(|v| v / 3.6);
+    observation.wind_gust_ms = wind.and_then(|d| d.gust_strength).map(|v| v / 3.6);
+    observation.wind_direction_deg = wind.and_then(|d| d.wind_angle).filter(|a| *a >= 0.0);
+    observation.rain_rate_mm_h = rain.and_then(|d| d.sum_rain_1);
+    Ok(observation)
+}
+
+pub(crate) fn stations_request(
+    client: &reqwest::Client,
+    access_token: &str,
+    device_id: Option<&str>,
+) -> reqwest::RequestBuilder {
+    let request = client.get(STATIONS_URL).bearer_auth(access_token);
+    match device_id {
+        Some(device_id) => request.query(&[("device_id", device_id)]),
+        None => request,
+    }
+}
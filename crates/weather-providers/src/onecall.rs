@@ -0,0 +1,114 @@
+use crate::error::WeatherError;
+use crate::openweathermap::{self, RecentPrecipitation, Weather};
+use crate::retry;
+use serde::{Deserialize, Serialize};
+
+/// Response of the OpenWeatherMap One Call 3.0 endpoint (minutely nowcast and
+/// hourly forecast; the other blocks are excluded).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OneCall {
+    pub timezone_offset: i32,
+    /// Next hour in one-minute steps; missing where no nowcast is available.
+    #[serde(default)]
+    pub minutely: Vec<Minutely>,
+    #[serde(default)]
+    pub hourly: Vec<Hourly>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Minutely {
+    pub dt: i64,
+    /// Precipitation rate in mm/h.
+    pub precipitation: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Hourly {
+    pub dt: i64,
+    /// Temperature in °C.
+    pub temp: f64,
+    /// Wind speed in m/s, and the direction it blows from in degrees.
+    pub wind_speed: f64,
+    pub wind_deg: i32,
+    pub wind_gust: Option<f64>,
+    pub weather: Vec<Weather>,
+    /// Probability of precipitation, 0.0 to 1.0.
+    #[serde(default)]
+    pub pop: f64,
+    pub rain: Option<RecentPrecipitation>,
+}
+
+pub async fn get_onecall(
+    client: &reqwest::Client,
+    lat: f64,
+    lon: f64,
+    api_key: &str,
+) -> Result<OneCall, Box<dyn std::error::Error>> {
+    let res = retry::send(onecall_request(client, lat, lon, api_key)).await?;
+
+    if res.status().as_u16() == 401 {
+        return Err("One Call API 3.0 is not enabled for this API key".into());
+    }
+    if !res.status().is_success() {
+        return Err(WeatherError::status("One Call API", res.status()).into());
+    }
+
+    Ok(res.json::<OneCall>().await?)
+}
+
+/// The `alerts` block of a One Call response, requested on its own.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Alerts {
+    /// Missing when no alerts are in force.
+    #[serde(default)]
+    pub alerts: Vec<Alert>,
+}
+
+/// An alert relayed from a national agency.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Alert {
+    pub sender_name: String,
+    pub event: String,
+    pub start: i64,
+    pub end: i64,
+    pub description: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// The request [`get_onecall`] sends.
+pub(crate) fn onecall_request(
+    client: &reqwest::Client,
+    lat: f64,
+    lon: f64,
+    api_key: &str,
+) -> reqwest::RequestBuilder {
+    openweathermap::with_language(
+        client
+            .get("https://api.openweathermap.org/data/3.0/onecall")
+            .query(&[
+                ("lat", lat.to_string().as_str()),
+                ("lon", lon.to_string().as_str()),
+                ("appid", api_key),
+                ("units", "metric"),
+                ("exclude", "current,daily,alerts"),
+            ]),
+    )
+}
+
+/// A One Call request for the alerts block alone.
+pub(crate) fn alerts_request(
+    client: &reqwest::Client,
+    lat: f64,
+    lon: f64,
+    api_key: &str,
+) -> reqwest::RequestBuilder {
+    client
+        .get("https://api.openweathermap.org/data/3.0/onecall")
+        .query(&[
+            ("lat", lat.to_string().as_str()),
+            ("lon", lon.to_string().as_str()),
+            ("appid", api_key),
+            ("exclude", "current,minutely,hourly,daily"),
+        ])
+}
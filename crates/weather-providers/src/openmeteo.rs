@@ -0,0 +1,503 @@
+//! Open-Meteo: current conditions and place search for `--provider
+//! open-meteo`, the solar radiation forecast for `weather solar`, and
+//! historical weather (ERA5 reanalysis) used to backfill the observation
+//! log. Free and keyless, but rate limited per address.
+
+use crate::error::{self, WeatherError};
+use crate::geocode::{self, Place};
+use crate::middleware::Transport;
+use crate::retry;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use weather_core::model::{Condition, Conditions, Report, Source};
+
+const ARCHIVE_URL: &str = "https://archive-api.open-meteo.com/v1/archive";
+pub(crate) const FORECAST_URL: &str = "https://api.open-meteo.com/v1/forecast";
+const GEOCODING_URL: &str = "https://geocoding-api.open-meteo.com/v1/search";
+
+const CURRENT: &str = "temperature_2m,relative_humidity_2m,apparent_temperature,pressure_msl,\
+    surface_pressure,wind_speed_10m,wind_direction_10m,wind_gusts_10m,cloud_cover,weather_code,\
+    visibility,rain,showers,snowfall";
+
+const DAILY: &str = "temperature_2m_max,temperature_2m_min,sunrise,sunset";
+
+const SOLAR_HOURLY: &str =
+    "shortwave_radiation,direct_normal_irradiance,diffuse_radiation,sunshine_duration,is_day";
+
+const SOLAR_DAILY: &str = "sunshine_duration,daylight_duration,shortwave_radiation_sum";
+
+const HOURLY: &str = "temperature_2m,relative_humidity_2m,apparent_temperature,pressure_msl,\
+    wind_speed_10m,wind_direction_10m,wind_gusts_10m,cloud_cover,weather_code";
+
+/// An archive response as received; [`ArchiveBody::parse`] reads it into an
+/// [`Archive`] that borrows from it.
+///
+/// Only the archive is read this way. Its timestamps are the one sizeable
+/// set of strings in any Open-Meteo response, hundreds per month of
+/// backfill; the forecast requests ask for `timeformat=unixtime`, so their
+/// series are numbers and there is nothing to borrow.
+#[derive(Debug)]
+pub struct ArchiveBody(bytes::Bytes);
+
+impl ArchiveBody {
+    pub fn parse(&self) -> serde_json::Result<Archive<'_>> {
+        serde_json::from_slice(&self.0)
+    }
+}
+
+/// Hourly series of an archive response. Hours the reanalysis hasn't reached
+/// yet are `null`.
+#[derive(Debug, Deserialize)]
+pub struct Archive<'a> {
+    /// Centre of the grid cell the series is for.
+    pub latitude: f64,
+    pub longitude: f64,
+    pub utc_offset_seconds: i32,
+    #[serde(borrow)]
+    pub hourly: Hourly<'a>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Hourly<'a> {
+    /// UTC, e.g. `2024-01-01T00:00`. Borrowed from the response: a month
+    /// has over 700 of them.
+    #[serde(borrow)]
+    pub time: Vec<Cow<'a, str>>,
+    pub temperature_2m: Vec<Option<f64>>,
+    pub relative_humidity_2m: Vec<Option<f64>>,
+    pub apparent_temperature: Vec<Option<f64>>,
+    pub pressure_msl: Vec<Option<f64>>,
+    pub wind_speed_10m: Vec<Option<f64>>,
+    pub wind_direction_10m: Vec<Option<f64>>,
+    pub wind_gusts_10m: Vec<Option<f64>>,
+    pub cloud_cover: Vec<Option<f64>>,
+    pub weather_code: Vec<Option<i32>>,
+}
+
+/// Error from the archive, telling a rate limit apart so callers can back
+/// off and retry.
+#[derive(Debug)]
+pub enum ArchiveError {
+    RateLimited,
+    Other(Box<dyn std::error::Error>),
+}
+
+impl std::fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArchiveError::RateLimited => write!(f, "Open-Meteo rate limit reached"),
+            ArchiveError::Other(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ArchiveError {}
+
+impl From<reqwest::Error> for ArchiveError {
+    fn from(e: reqwest::Error) -> Self {
+        ArchiveError::Other(e.into())
+    }
+}
+
+/// Hourly history for `start..=end` (UTC dates).
+pub async fn archive(
+    client: &reqwest::Client,
+    lat: f64,
+    lon: f64,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<ArchiveBody, ArchiveError> {
+    // Not retried: the backfill paces itself after a 429.
+    let res = archive_request(client, lat, lon, start, end).send().await?;
+
+    match res.status().as_u16() {
+        429 => Err(ArchiveError::RateLimited),
+        200 => Ok(ArchiveBody(res.bytes().await?)),
+        _ => {
+            let status = res.status();
+            let body: serde_json::Value = res.json().await.unwrap_or_default();
+            let reason = body["reason"].as_str().unwrap_or_default().to_string();
+            Err(ArchiveError::Other(
+                format!("Open-Meteo archive error: HTTP {} {}", status, reason).into(),
+            ))
+        }
+    }
+}
+
+/// The request [`archive`] sends.
+pub(crate) fn archive_request(
+    client: &reqwest::Client,
+    lat: f64,
+    lon: f64,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> reqwest::RequestBuilder {
+    client.get(ARCHIVE_URL).query(&[
+        ("latitude", lat.to_string()),
+        ("longitude", lon.to_string()),
+        ("start_date", start.to_string()),
+        ("end_date", end.to_string()),
+        ("hourly", HOURLY.to_string()),
+        ("wind_speed_unit", "ms".to_string()),
+        ("timezone", "GMT".to_string()),
+    ])
+}
+
+/// One report per complete hour of `archive`, oldest first.
+pub fn reports(place: &Place, archive: &Archive) -> Vec<Report> {
+    let h = &archive.hourly;
+    (0..h.time.len())
+        .filter_map(|i| {
+            let observed_at = NaiveDateTime::parse_from_str(&h.time[i], "%Y-%m-%dT%H:%M")
+                .ok()?
+                .and_utc();
+            let code = h.weather_code.get(i).copied().flatten()?;
+            let (kind, description) = condition(code);
+            let temperature = h.temperature_2m.get(i).copied().flatten()?;
+            let mut source = Source::new("open-meteo-archive");
+            source.station = Some(format!("{:.2},{:.2}", archive.latitude, archive.longitude));
+            let mut current = Conditions::new(Condition::new(kind, description), temperature);
+            current.feels_like_c = h.apparent_temperature[i].unwrap_or(temperature);
+            current.humidity_percent = h.relative_humidity_2m[i]?.round() as i32;
+            current.pressure_hpa = h.pressure_msl[i]?.round() as i32;
+            current.wind_speed_ms = h.wind_speed_10m[i]?;
+            current.wind_direction_deg = h.wind_direction_10m[i].unwrap_or(0.0).round() as i32;
+            current.wind_gust_ms = h.wind_gusts_10m[i];
+            // ERA5 has no visibility; it is left unrestricted, as
+            // OpenWeatherMap reports it beyond 10 km.
+            current.cloud_cover_percent = h.cloud_cover[i]?.round() as i32;
+            Some(Report::new(
+                source,
+                place.location(archive.utc_offset_seconds),
+                observed_at,
+                current,
+            ))
+        })
+        .collect()
+}
+
+/// A forecast response for several locations at once: one location comes
+/// back as an object, several as an array.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum OneOrMany<T> {
+    Many(Vec<T>),
+    One(Box<T>),
+}
+
+impl<T> OneOrMany<T> {
+    pub(crate) fn into_vec(self) -> Vec<T> {
+        match self {
+            OneOrMany::Many(all) => all,
+            OneOrMany::One(one) => vec![*one],
+        }
+    }
+}
+
+/// A forecast response carrying the current conditions, with the day's
+/// range and sun times. Times are Unix timestamps.
+#[derive(Debug, Deserialize)]
+pub(crate) struct Current {
+    latitude: f64,
+    longitude: f64,
+    utc_offset_seconds: i32,
+    current: CurrentValues,
+    daily: Daily,
+}
+
+#[derive(Debug, Deserialize)]
+struct CurrentValues {
+    time: i64,
+    /// Length in seconds of the step the precipitation sums cover.
+    interval: i64,
+    temperature_2m: f64,
+    relative_humidity_2m: f64,
+    apparent_temperature: f64,
+    pressure_msl: f64,
+    surface_pressure: Option<f64>,
+    wind_speed_10m: f64,
+    wind_direction_10m: f64,
+    wind_gusts_10m: Option<f64>,
+    cloud_cover: f64,
+    weather_code: i32,
+    visibility: Option<f64>,
+    rain: f64,
+    showers: f64,
+    /// In cm of snow.
+    snowfall: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Daily {
+    temperature_2m_max: Vec<Option<f64>>,
+    temperature_2m_min: Vec<Option<f64>>,
+    sunrise: Vec<Option<i64>>,
+    sunset: Vec<Option<i64>>,
+}
+
+/// Current conditions at `place`.
+pub async fn current(transport: Transport<'_>, place: &Place) -> Result<Report, WeatherError> {
+    let res = transport
+        .send(current_request(transport.http(), place.lat, place.lon))
+        .await?;
+    parse_current(res, place).await
+}
+
+/// The request [`current`] sends.
+pub(crate) fn current_request(
+    client: &reqwest::Client,
+    lat: f64,
+    lon: f64,
+) -> reqwest::RequestBuilder {
+    client.get(FORECAST_URL).query(&[
+        ("latitude", lat.to_string()),
+        ("longitude", lon.to_string()),
+        ("current", CURRENT.to_string()),
+        ("daily", DAILY.to_string()),
+        ("forecast_days", "1".to_string()),
+        ("wind_speed_unit", "ms".to_string()),
+        ("timeformat", "unixtime".to_string()),
+        ("timezone", "auto".to_string()),
+    ])
+}
+
+/// Reads the response to a [`current_request`].
+pub(crate) async fn parse_current(
+    res: reqwest::Response,
+    place: &Place,
+) -> Result<Report, WeatherError> {
+    let res = error::check("Open-Meteo", res)?;
+    Ok(report(place, res.json::<Current>().await?))
+}
+
+fn report(place: &Place, response: Current) -> Report {
+    let now = response.current;
+    let (kind, description) = condition(now.weather_code);
+    // Sums over the last step, scaled to an hour.
+    let per_hour = 3600.0 / now.interval.max(1) as f64;
+    let rain = (now.rain + now.showers) * per_hour;
+    // Open-Meteo's rule of thumb: 7 cm of snow is 10 mm of water.
+    let snow = now.snowfall * 10.0 / 7.0 * per_hour;
+    let first = |values: &[Option<f64>]| values.first().copied().flatten();
+    // Days without a sunrise or sunset have none in the series.
+    let time = |times: &[Option<i64>]| {
+        times
+            .first()
+            .copied()
+            .flatten()
+            .and_then(|t| DateTime::from_timestamp(t, 0))
+    };
+
+    let mut source = Source::new("open-meteo");
+    // The model grid cell the values are for.
+    source.station = Some(format!(
+        "{:.2},{:.2}",
+        response.latitude, response.longitude
+    ));
+    source.latitude = Some(response.latitude);
+    source.longitude = Some(response.longitude);
+
+    let mut current = Conditions::new(Condition::new(kind, description), now.temperature_2m);
+    current.feels_like_c = now.apparent_temperature;
+    current.temperature_min_c =
+        first(&response.daily.temperature_2m_min).unwrap_or(now.temperature_2m);
+    current.temperature_max_c =
+        first(&response.daily.temperature_2m_max).unwrap_or(now.temperature_2m);
+    current.humidity_percent = now.relative_humidity_2m.round() as i32;
+    current.pressure_hpa = now.pressure_msl.round() as i32;
+    current.station_pressure_hpa = now.surface_pressure;
+    current.wind_speed_ms = now.wind_speed_10m;
+    current.wind_direction_deg = now.wind_direction_10m.round() as i32;
+    current.wind_gust_ms = now.wind_gusts_10m;
+    // Capped at 10 km, as OpenWeatherMap reports it; unrestricted when the
+    // model has none.
+    if let Some(visibility) = now.visibility {
+        current.visibility_m = visibility.min(10_000.0).round() as i32;
+    }
+    current.cloud_cover_percent = now.cloud_cover.round() as i32;
+    current.rain_mm = Some(rain).filter(|mm| *mm > 0.0);
+    current.snow_mm = Some(snow).filter(|mm| *mm > 0.0);
+    current.sunrise = time(&response.daily.sunrise);
+    current.sunset = time(&response.daily.sunset);
+    Report::new(
+        source,
+        place.location(response.utc_offset_seconds),
+        DateTime::from_timestamp(now.time, 0).unwrap_or_else(Utc::now),
+        current,
+    )
+}
+
+/// Results of a place search.
+#[derive(Debug, Deserialize)]
+pub(crate) struct Search {
+    /// Absent when nothing matched.
+    #[serde(default)]
+    results: Vec<SearchResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResult {
+    name: String,
+    latitude: f64,
+    longitude: f64,
+    #[serde(default)]
+    country_code: String,
+    admin1: Option<String>,
+}
+
+/// A solar radiation forecast, hour by hour and summed by day, in the
+/// place's local days. Times are Unix timestamps; values are missing where
+/// the models have none.
+#[derive(Debug, Deserialize)]
+pub struct Solar {
+    pub utc_offset_seconds: i32,
+    pub hourly: SolarHourly,
+    pub daily: SolarDaily,
+}
+
+/// Radiation values are means over the hour before `time`, in W/m².
+#[derive(Debug, Deserialize)]
+pub struct SolarHourly {
+    pub time: Vec<i64>,
+    /// Global horizontal irradiance (GHI).
+    pub shortwave_radiation: Vec<Option<f64>>,
+    /// Direct normal irradiance (DNI).
+    pub direct_normal_irradiance: Vec<Option<f64>>,
+    pub diffuse_radiation: Vec<Option<f64>>,
+    /// Seconds of the hour with direct sunshine above 120 W/m².
+    pub sunshine_duration: Vec<Option<f64>>,
+    /// 1 while the sun is up, else 0.
+    pub is_day: Vec<Option<u8>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SolarDaily {
+    /// Local midnight of each day.
+    pub time: Vec<i64>,
+    /// Seconds of direct sunshine.
+    pub sunshine_duration: Vec<Option<f64>>,
+    /// Seconds from sunrise to sunset.
+    pub daylight_duration: Vec<Option<f64>>,
+    /// Global radiation over the day, in MJ/m².
+    pub shortwave_radiation_sum: Vec<Option<f64>>,
+}
+
+/// The solar radiation forecast for the next `days` days, today included.
+pub async fn solar(
+    client: &reqwest::Client,
+    lat: f64,
+    lon: f64,
+    days: u32,
+) -> Result<Solar, WeatherError> {
+    let res = retry::send(solar_request(client, lat, lon, days)).await?;
+    let res = error::check("Open-Meteo", res)?;
+    Ok(res.json::<Solar>().await?)
+}
+
+/// The request [`solar`] sends.
+pub(crate) fn solar_request(
+    client: &reqwest::Client,
+    lat: f64,
+    lon: f64,
+    days: u32,
+) -> reqwest::RequestBuilder {
+    client.get(FORECAST_URL).query(&[
+        ("latitude", lat.to_string()),
+        ("longitude", lon.to_string()),
+        ("hourly", SOLAR_HOURLY.to_string()),
+        ("daily", SOLAR_DAILY.to_string()),
+        ("forecast_days", days.to_string()),
+        ("timeformat", "unixtime".to_string()),
+        ("timezone", "auto".to_string()),
+    ])
+}
+
+/// Looks up `query` with Open-Meteo's place search, trying the same
+/// spellings as [`geocode::resolve_city`]. A trailing two-letter country
+/// code, as in `London,GB`, narrows the search to that country.
+pub async fn resolve_city(transport: Transport<'_>, query: &str) -> Result<Place, WeatherError> {
+    search(transport, query, 1)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| WeatherError::CityNotFound(query.to_string()))
+}
+
+/// Up to `limit` places matching `query`, best first, like
+/// [`geocode::search`].
+pub async fn search(
+    transport: Transport<'_>,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<Place>, WeatherError> {
+    for candidate in geocode::query_variants(query) {
+        let (name, country) = match candidate.rsplit_once(',') {
+            Some((name, code)) if code.trim().len() == 2 => (name.trim(), Some(code.trim())),
+            _ => (candidate.as_str(), None),
+        };
+        let res = transport
+            .send(search_request(transport.http(), name, country, limit))
+            .await?;
+        let places: Vec<Place> = error::check("Open-Meteo place search", res)?
+            .json::<Search>()
+            .await?
+            .results
+            .into_iter()
+            .map(|found| Place {
+                name: found.name,
+                local_names: HashMap::new(),
+                lat: found.latitude,
+                lon: found.longitude,
+                country: found.country_code,
+                state: found.admin1,
+            })
+            .collect();
+        if !places.is_empty() {
+            return Ok(places);
+        }
+    }
+    Ok(Vec::new())
+}
+
+/// The request [`search`] sends for one spelling.
+pub(crate) fn search_request(
+    client: &reqwest::Client,
+    name: &str,
+    country: Option<&str>,
+    limit: usize,
+) -> reqwest::RequestBuilder {
+    let request = client.get(GEOCODING_URL).query(&[
+        ("name", name),
+        ("count", limit.to_string().as_str()),
+        ("language", "en"),
+    ]);
+    match country {
+        Some(country) => request.query(&[("countryCode", country.to_uppercase())]),
+        None => request,
+    }
+}
+
+/// The description of a WMO weather code, e.g. `snow showers`.
+pub fn describe(code: i32) -> &'static str {
+    condition(code).1
+}
+
+/// Condition kind and description of a WMO weather interpretation code.
+fn condition(code: i32) -> (&'static str, &'static str) {
+    match code {
+        0 => ("clear", "clear sky"),
+        1 => ("clear", "mainly clear"),
+        2 => ("clouds", "partly cloudy"),
+        3 => ("clouds", "overcast"),
+        45 | 48 => ("fog", "fog"),
+        51..=57 => ("drizzle", "drizzle"),
+        61..=67 => ("rain", "rain"),
+        80..=82 => ("rain", "rain showers"),
+        71..=77 => ("snow", "snow"),
+        85 | 86 => ("snow", "snow showers"),
+        95..=99 => ("thunderstorm", "thunderstorm"),
+        _ => ("other", "unknown"),
+    }
+}
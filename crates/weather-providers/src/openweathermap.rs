@@ -0,0 +1,209 @@
+//! OpenWeatherMap current weather: the response types and their mapping onto
+//! the core report model.
+
+use crate::error::{self, WeatherError};
+use crate::geocode::Place;
+use crate::middleware::Transport;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use weather_core::model::{Condition, Conditions, Report, Source};
+
+static LANGUAGE: OnceLock<String> = OnceLock::new();
+
+/// Sets the language of condition descriptions in OpenWeatherMap responses,
+/// current, forecast and One Call alike, for the rest of the process: one
+/// of its `lang` codes, e.g. `de` or `zh_cn`. Only the first call has an
+/// effect; without one, they are in English.
+pub fn set_language(language: &str) {
+    LANGUAGE.set(language.to_string()).ok();
+}
+
+/// `request` asking for descriptions in the language [`set_language`] set.
+pub(crate) fn with_language(request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    match LANGUAGE.get() {
+        Some(language) => request.query(&[("lang", language)]),
+        None => request,
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WeatherData {
+    pub coord: Coord,
+    pub weather: Vec<Weather>,
+    pub base: String,
+    pub main: Main,
+    pub visibility: i32,
+    pub wind: Wind,
+    pub clouds: Clouds,
+    pub dt: i64,
+    pub sys: Sys,
+    /// Only present when it has rained or snowed recently.
+    pub rain: Option<RecentPrecipitation>,
+    pub snow: Option<RecentPrecipitation>,
+    pub timezone: i32,
+    pub id: i64,
+    pub name: String,
+    pub cod: i32,
+}
+
+/// Precipitation volume in mm.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecentPrecipitation {
+    #[serde(rename = "1h")]
+    pub one_hour: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Coord {
+    pub lon: f64,
+    pub lat: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Weather {
+    pub id: i32,
+    pub main: String,
+    pub description: String,
+    pub icon: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Main {
+    pub temp: f64,
+    pub feels_like: f64,
+    pub temp_min: f64,
+    pub temp_max: f64,
+    pub pressure: i32,
+    pub humidity: i32,
+    pub sea_level: Option<i32>,
+    pub grnd_level: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Wind {
+    pub speed: f64,
+    pub deg: i32,
+    pub gust: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Clouds {
+    pub all: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Sys {
+    pub country: String,
+    pub sunrise: i64,
+    pub sunset: i64,
+}
+
+pub async fn fetch_weather(
+    transport: Transport<'_>,
+    place: &Place,
+    api_key: &str,
+) -> Result<WeatherData, WeatherError> {
+    let res = transport
+        .send(weather_request(transport.http(), place, api_key))
+        .await?;
+    parse_weather(res, place).await
+}
+
+/// The current weather request [`fetch_weather`] sends.
+pub fn weather_request(
+    client: &reqwest::Client,
+    place: &Place,
+    api_key: &str,
+) -> reqwest::RequestBuilder {
+    with_language(
+        client
+            .get("https://api.openweathermap.org/data/2.5/weather")
+            .query(&[
+                ("lat", place.lat.to_string().as_str()),
+                ("lon", place.lon.to_string().as_str()),
+                ("appid", api_key),
+                ("units", "metric"),
+            ]),
+    )
+}
+
+/// Reads the response to a [`weather_request`].
+pub async fn parse_weather(
+    res: reqwest::Response,
+    place: &Place,
+) -> Result<WeatherData, WeatherError> {
+    if res.status().as_u16() == 404 {
+        return Err(WeatherError::NoData(place.name.clone()));
+    }
+    let res = error::check("OpenWeatherMap API", res)?;
+
+    let weather_data = res.json::<WeatherData>().await?;
+    Ok(weather_data)
+}
+
+/// Maps a current weather response onto the provider-agnostic report.
+pub fn report(place: &Place, weather: &WeatherData) -> Report {
+    let mut source = Source::new("openweathermap");
+    source.station = Some(weather.id.to_string());
+    source.name = Some(weather.name.clone()).filter(|name| !name.is_empty());
+    source.latitude = Some(weather.coord.lat);
+    source.longitude = Some(weather.coord.lon);
+    Report::new(
+        source,
+        place.location(weather.timezone),
+        utc(weather.dt).unwrap_or_else(Utc::now),
+        conditions(weather),
+    )
+}
+
+/// The current conditions of a response, in core units.
+pub fn conditions(weather: &WeatherData) -> Conditions {
+    let condition = weather.weather.first();
+    let mut conditions = Conditions::new(
+        Condition::new(
+            condition_kind(condition.map_or("", |c| c.main.as_str())),
+            condition.map_or(String::new(), |c| c.description.clone()),
+        ),
+        weather.main.temp,
+    );
+    conditions.feels_like_c = weather.main.feels_like;
+    conditions.temperature_min_c = weather.main.temp_min;
+    conditions.temperature_max_c = weather.main.temp_max;
+    conditions.humidity_percent = weather.main.humidity;
+    conditions.pressure_hpa = weather.main.pressure;
+    conditions.station_pressure_hpa = weather.main.grnd_level.map(f64::from);
+    conditions.wind_speed_ms = weather.wind.speed;
+    conditions.wind_direction_deg = weather.wind.deg;
+    conditions.wind_gust_ms = weather.wind.gust;
+    conditions.visibility_m = weather.visibility;
+    conditions.cloud_cover_percent = weather.clouds.all;
+    conditions.rain_mm = weather.rain.as_ref().and_then(|rain| rain.one_hour);
+    conditions.snow_mm = weather.snow.as_ref().and_then(|snow| snow.one_hour);
+    conditions.sunrise = utc(weather.sys.sunrise);
+    conditions.sunset = utc(weather.sys.sunset);
+    conditions
+}
+
+/// OpenWeatherMap gives 0 for times it has none, such as sunrise in the
+/// polar night.
+fn utc(timestamp: i64) -> Option<DateTime<Utc>> {
+    if timestamp == 0 {
+        return None;
+    }
+    Utc.timestamp_opt(timestamp, 0).single()
+}
+
+/// The core condition kind of an OpenWeatherMap condition group (`main`).
+pub fn condition_kind(main: &str) -> &'static str {
+    match main.to_lowercase().as_str() {
+        "clear" => "clear",
+        "clouds" => "clouds",
+        "drizzle" => "drizzle",
+        "rain" => "rain",
+        "snow" => "snow",
+        "thunderstorm" => "thunderstorm",
+        "mist" | "smoke" | "haze" | "dust" | "fog" | "sand" | "ash" => "fog",
+        _ => "other",
+    }
+}
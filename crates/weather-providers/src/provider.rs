@@ -0,0 +1,175 @@
+//! Sources of current conditions behind one trait, so that consumers can
+//! switch between them. Every provider answers with a [`Report`], holding
+//! only what the provider actually gave. Requests go out through a
+//! [`Transport`]: a plain client, or a [`crate::WeatherClient`]'s with its
+//! middleware.
+//!
+//! ```no_run
+//! use weather_providers::provider::{OpenMeteo, WeatherProvider};
+//!
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = reqwest::Client::new();
+//! let provider: Box<dyn WeatherProvider> = Box::new(OpenMeteo);
+//! let place = provider.resolve_city((&client).into(), "Innsbruck").await?;
+//! let report = provider.current((&client).into(), &place).await?;
+//! println!("{}", report.current.temperature_c);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::WeatherError;
+use crate::geocode::{self, Place};
+use crate::middleware::{BoxFuture, Transport};
+use crate::openmeteo;
+use crate::openweathermap;
+use std::collections::HashMap;
+use weather_core::model::Report;
+
+pub type ProviderFuture<'a, T> = BoxFuture<'a, Result<T, WeatherError>>;
+
+pub trait WeatherProvider: Send + Sync {
+    /// Identifier, as given to `--provider` and recorded in reports.
+    fn name(&self) -> &'static str;
+
+    /// The best match for a place name such as `London` or `London,GB`.
+    fn resolve_city<'a>(
+        &'a self,
+        transport: Transport<'a>,
+        query: &'a str,
+    ) -> ProviderFuture<'a, Place>;
+
+    /// Up to `limit` places matching `query`, best first, for choosing
+    /// between places of the same name. Empty when nothing matches.
+    fn search<'a>(
+        &'a self,
+        transport: Transport<'a>,
+        query: &'a str,
+        limit: usize,
+    ) -> ProviderFuture<'a, Vec<Place>>;
+
+    /// The place at a position, e.g. the device's.
+    fn reverse<'a>(
+        &'a self,
+        transport: Transport<'a>,
+        lat: f64,
+        lon: f64,
+    ) -> ProviderFuture<'a, Place>;
+
+    /// Current conditions at `place`.
+    fn current<'a>(
+        &'a self,
+        transport: Transport<'a>,
+        place: &'a Place,
+    ) -> ProviderFuture<'a, Report>;
+}
+
+/// OpenWeatherMap, with its geocoding API. Needs an API key.
+pub struct OpenWeatherMap {
+    api_key: String,
+}
+
+impl OpenWeatherMap {
+    pub fn new(api_key: impl Into<String>) -> Self {
+        OpenWeatherMap {
+            api_key: api_key.into(),
+        }
+    }
+}
+
+impl WeatherProvider for OpenWeatherMap {
+    fn name(&self) -> &'static str {
+        "openweathermap"
+    }
+
+    fn resolve_city<'a>(
+        &'a self,
+        transport: Transport<'a>,
+        query: &'a str,
+    ) -> ProviderFuture<'a, Place> {
+        Box::pin(geocode::resolve_city(transport, query, &self.api_key))
+    }
+
+    /// At most [`geocode::MAX_MATCHES`].
+    fn search<'a>(
+        &'a self,
+        transport: Transport<'a>,
+        query: &'a str,
+        limit: usize,
+    ) -> ProviderFuture<'a, Vec<Place>> {
+        Box::pin(geocode::search(transport, query, limit, &self.api_key))
+    }
+
+    fn reverse<'a>(
+        &'a self,
+        transport: Transport<'a>,
+        lat: f64,
+        lon: f64,
+    ) -> ProviderFuture<'a, Place> {
+        Box::pin(geocode::reverse(transport, lat, lon, &self.api_key))
+    }
+
+    fn current<'a>(
+        &'a self,
+        transport: Transport<'a>,
+        place: &'a Place,
+    ) -> ProviderFuture<'a, Report> {
+        Box::pin(async move {
+            let weather = openweathermap::fetch_weather(transport, place, &self.api_key).await?;
+            Ok(openweathermap::report(place, &weather))
+        })
+    }
+}
+
+/// Open-Meteo's forecast models and place search. Keyless.
+pub struct OpenMeteo;
+
+impl WeatherProvider for OpenMeteo {
+    fn name(&self) -> &'static str {
+        "open-meteo"
+    }
+
+    fn resolve_city<'a>(
+        &'a self,
+        transport: Transport<'a>,
+        query: &'a str,
+    ) -> ProviderFuture<'a, Place> {
+        Box::pin(openmeteo::resolve_city(transport, query))
+    }
+
+    fn search<'a>(
+        &'a self,
+        transport: Transport<'a>,
+        query: &'a str,
+        limit: usize,
+    ) -> ProviderFuture<'a, Vec<Place>> {
+        Box::pin(openmeteo::search(transport, query, limit))
+    }
+
+    /// Open-Meteo has no reverse geocoding; the place is named after its
+    /// coordinates.
+    fn reverse<'a>(
+        &'a self,
+        _transport: Transport<'a>,
+        lat: f64,
+        lon: f64,
+    ) -> ProviderFuture<'a, Place> {
+        Box::pin(async move {
+            Ok(Place {
+                name: format!("{:.4}, {:.4}", lat, lon),
+                local_names: HashMap::new(),
+                lat,
+                lon,
+                country: String::new(),
+                state: None,
+            })
+        })
+    }
+
+    fn current<'a>(
+        &'a self,
+        transport: Transport<'a>,
+        place: &'a Place,
+    ) -> ProviderFuture<'a, Report> {
+        Box::pin(openmeteo::current(transport, place))
+    }
+}
@@ -0,0 +1,415 @@
+//! Readings from a personal weather station: on the local network an
+//! Ecowitt gateway's live data page, or WeeWX's current conditions as JSON
+//! over HTTP or from its MQTT extension; in the cloud a Netatmo or Ambient
+//! Weather account.
+
+use crate::error::WeatherError;
+use crate::retry;
+use crate::{ambient, netatmo};
+use chrono::{TimeZone, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use weather_core::model::LocalObservation;
+
+const MQTT_DEFAULT_PORT: u16 = 1883;
+/// WeeWX publishes every loop packet, but a broker without a retained
+/// message makes us wait for the next one.
+const MQTT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A personal weather station to read from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Station {
+    /// An Ecowitt gateway or console at this host, e.g. `192.168.1.50`.
+    Ecowitt(String),
+    /// A URL serving WeeWX loop data as flat JSON.
+    WeeWxHttp(String),
+    /// A WeeWX MQTT topic on a broker at `host:port`.
+    WeeWxMqtt { addr: String, topic: String },
+    /// A Netatmo station by MAC address, or the account's first one.
+    Netatmo(Option<String>),
+    /// An Ambient Weather device by MAC address, or the account's first one.
+    Ambient(Option<String>),
+}
+
+impl Station {
+    /// Whether reading the station needs a Netatmo access token.
+    pub fn needs_netatmo_token(&self) -> bool {
+        matches!(self, Station::Netatmo(_))
+    }
+}
+
+impl FromStr for Station {
+    type Err = String;
+
+    /// Accepts `ecowitt:<host>`, `weewx:<http url>`,
+    /// `weewx:mqtt://<host>[:<port>]/<topic>`, `netatmo[:<mac>]` or
+    /// `ambient[:<mac>]`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let unknown = || {
+            format!(
+                "Unknown weather station '{}': expected ecowitt:<host>, weewx:<url>, \
+                 weewx:mqtt://<host>[:<port>]/<topic>, netatmo[:<mac>] or ambient[:<mac>]",
+                value
+            )
+        };
+        let device = |mac: &str| (!mac.is_empty()).then(|| mac.to_string());
+        match value.split_once(':') {
+            None if value == "netatmo" => Ok(Station::Netatmo(None)),
+            None if value == "ambient" => Ok(Station::Ambient(None)),
+            Some(("netatmo", mac)) => Ok(Station::Netatmo(device(mac))),
+            Some(("ambient", mac)) => Ok(Station::Ambient(device(mac))),
+            Some(("ecowitt", host)) if !host.is_empty() => Ok(Station::Ecowitt(host.to_string())),
+            Some(("weewx", target)) => {
+                if let Some(rest) = target.strip_prefix("mqtt://") {
+                    let (host, topic) = rest.split_once('/').ok_or_else(unknown)?;
+                    if host.is_empty() || topic.is_empty() {
+                        return Err(unknown());
+                    }
+                    let addr = if host.contains(':') {
+                        host.to_string()
+                    } else {
+                        format!("{}:{}", host, MQTT_DEFAULT_PORT)
+                    };
+                    Ok(Station::WeeWxMqtt {
+                        addr,
+                        topic: topic.to_string(),
+                    })
+                } else if target.starts_with("http://") || target.starts_with("https://") {
+                    Ok(Station::WeeWxHttp(target.to_string()))
+                } else {
+                    Err(unknown())
+                }
+            }
+            _ => Err(unknown()),
+        }
+    }
+}
+
+/// Reads the station's current values. `netatmo_token` is the OAuth access
+/// token for Netatmo stations, see [`Station::needs_netatmo_token`].
+pub async fn observe(
+    client: &reqwest::Client,
+    station: &Station,
+    netatmo_token: Option<&str>,
+) -> Result<LocalObservation, Box<dyn std::error::Error>> {
+    match station {
+        Station::Netatmo(device) => {
+            let token = netatmo_token.ok_or("Netatmo needs an access token")?;
+            netatmo::observe(client, token, device.as_deref()).await
+        }
+        Station::Ambient(mac) => ambient::observe(client, mac.as_deref()).await,
+        Station::Ecowitt(host) => ecowitt_observe(client, host).await,
+        Station::WeeWxHttp(url) => {
+            let res = retry::send(client.get(url)).await?;
+            if !res.status().is_success() {
+                return Err(WeatherError::status("WeeWX", res.status()).into());
+            }
+            Ok(weewx_observation(&res.json().await?))
+        }
+        Station::WeeWxMqtt { addr, topic } => {
+            let payload = match tokio::time::timeout(MQTT_TIMEOUT, mqtt_next(addr, topic)).await {
+                Ok(payload) => payload?,
+                Err(_) => {
+                    return Err(format!(
+                        "no message on '{}' within {}s",
+                        topic,
+                        MQTT_TIMEOUT.as_secs()
+                    )
+                    .into())
+                }
+            };
+            Ok(weewx_observation(&serde_json::from_slice(&payload)?))
+        }
+    }
+}
+
+fn fahrenheit_to_celsius(f: f64) -> f64 {
+    (f - 32.0) * 5.0 / 9.0
+}
+
+/// The number at the start of a value such as `"3.2 m/s"` or `"62%"`, and
+/// the unit after it.
+fn split_unit(text: &str) -> Option<(f64, &str)> {
+    let text = text.trim();
+    let end = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+        .unwrap_or(text.len());
+    Some((text[..end].parse().ok()?, text[end..].trim()))
+}
+
+fn wind_ms(value: f64, unit: &str) -> Option<f64> {
+    match unit.to_ascii_lowercase().as_str() {
+        "m/s" | "" => Some(value),
+        "km/h" => Some(value / 3.6),
+        "mph" => Some(value * 0.44704),
+        "knots" | "knot" | "kn" => Some(value * 0.514444),
+        "ft/s" => Some(value * 0.3048),
+        _ => None,
+    }
+}
+
+fn pressure_hpa(value: f64, unit: &str) -> Option<f64> {
+    match unit.to_ascii_lowercase().as_str() {
+        "hpa" | "mbar" | "" => Some(value),
+        "inhg" => Some(value * 33.8639),
+        "mmhg" => Some(value * 1.33322),
+        _ => None,
+    }
+}
+
+fn rain_rate_mm_h(value: f64, unit: &str) -> Option<f64> {
+    match unit.to_ascii_lowercase().as_str() {
+        "mm/hr" | "mm/h" | "" => Some(value),
+        "in/hr" | "in/h" => Some(value * 25.4),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct EcowittLiveData {
+    #[serde(default)]
+    common_list: Vec<EcowittValue>,
+    #[serde(default)]
+    wh25: Vec<EcowittIndoor>,
+    #[serde(default)]
+    rain: Vec<EcowittValue>,
+    #[serde(default, rename = "piezoRain")]
+    piezo_rain: Vec<EcowittValue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EcowittValue {
+    id: String,
+    val: String,
+    unit: Option<String>,
+}
+
+/// The gateway's indoor sensor, which also carries the barometer.
+#[derive(Debug, Deserialize)]
+struct EcowittIndoor {
+    rel: Option<String>,
+}
+
+async fn ecowitt_observe(
+    client: &reqwest::Client,
+    host: &str,
+) -> Result<LocalObservation, Box<dyn std::error::Error>> {
+    let res = retry::send(client.get(format!("http://{}/get_livedata_info", host))).await?;
+    if !res.status().is_success() {
+        return Err(WeatherError::status("Ecowitt gateway", res.status()).into());
+    }
+    let data = res.json::<EcowittLiveData>().await?;
+
+    let common = |id: &str| {
+        let value = data.common_list.iter().find(|v| v.id == id)?;
+        let (number, unit) = split_unit(&value.val)?;
+        Some((number, value.unit.as_deref().unwrap_or(unit)))
+    };
+    let rain_rate = data
+        .rain
+        .iter()
+        .chain(&data.piezo_rain)
+        .find(|v| v.id == "0x0E")
+        .and_then(|v| split_unit(&v.val))
+        .and_then(|(value, unit)| rain_rate_mm_h(value, unit));
+
+    // Ecowitt ids: 0x02 outdoor temperature, 0x07 outdoor humidity,
+    // 0x0A wind direction, 0x0B wind speed, 0x0C gust.
+    // The gateway serves live values without a timestamp.
+    let mut observation = LocalObservation::new("ecowitt");
+    observation.temperature_c = common("0x02").map(|(value, unit)| match unit {
+        "F" | "℉" => fahrenheit_to_celsius(value),
+        _ => value,
+    });
+    observation.humidity_percent = common("0x07").map(|(value, _)| value);
+    observation.pressure_hpa = data
+        .wh25
+        .first()
+        .and_then(|indoor| split_unit(indoor.rel.as_deref()?))
+        .and_then(|(value, unit)| pressure_hpa(value, unit));
+    observation.wind_speed_ms = common("0x0B").and_then(|(value, unit)| wind_ms(value, unit));
+    observation.wind_gust_ms = common("0x0C").and_then(|(value, unit)| wind_ms(value, unit));
+    observation.wind_direction_deg = common("0x0A").map(|(value, _)| value);
+    observation.rain_rate_mm_h = rain_rate;
+    Ok(observation)
+}
+
+/// A WeeWX key suffix naming a unit and the conversion to metric from it.
+type Suffix = (&'static str, fn(f64) -> f64);
+
+/// WeeWX `usUnits` values.
+const WEEWX_US: i64 = 1;
+const WEEWX_METRICWX: i64 = 17;
+
+/// Maps WeeWX loop data onto the model. The MQTT extension names values
+/// with a unit suffix (`outTemp_C`, `windSpeed_kph`); plain loop packets
+/// use bare names in the unit system given by `usUnits`.
+fn weewx_observation(packet: &HashMap<String, Value>) -> LocalObservation {
+    let number = |key: &str| match packet.get(key)? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    };
+    let system = number("usUnits").map(|units| units as i64);
+    // The first suffixed key present, else the bare key in `usUnits`.
+    let value = |name: &str, suffixes: &[Suffix], bare: fn(i64, f64) -> f64| {
+        suffixes
+            .iter()
+            .find_map(|(suffix, convert)| number(&format!("{}_{}", name, suffix)).map(convert))
+            .or_else(|| Some(bare(system?, number(name)?)))
+    };
+
+    let temperature = |name: &str| {
+        value(
+            name,
+            &[("C", |c| c), ("F", fahrenheit_to_celsius)],
+            |system, t| match system {
+                WEEWX_US => fahrenheit_to_celsius(t),
+                _ => t,
+            },
+        )
+    };
+    let wind = |name: &str| {
+        value(
+            name,
+            &[
+                ("mps", |v| v),
+                ("meter_per_second", |v| v),
+                ("kph", |v| v / 3.6),
+                ("km_per_hour", |v| v / 3.6),
+                ("mph", |v| v * 0.44704),
+                ("mile_per_hour", |v| v * 0.44704),
+                ("knot", |v| v * 0.514444),
+            ],
+            |system, v| match system {
+                WEEWX_US => v * 0.44704,
+                WEEWX_METRICWX => v,
+                _ => v / 3.6,
+            },
+        )
+    };
+
+    let mut observation = LocalObservation::new("weewx");
+    observation.observed_at =
+        number("dateTime").and_then(|t| Utc.timestamp_opt(t as i64, 0).single());
+    observation.temperature_c = temperature("outTemp");
+    observation.humidity_percent = number("outHumidity");
+    observation.pressure_hpa = value(
+        "barometer",
+        &[
+            ("mbar", |p| p),
+            ("hPa", |p| p),
+            ("inHg", |p| p * 33.8639),
+            ("mmHg", |p| p * 1.33322),
+        ],
+        |system, p| match system {
+            WEEWX_US => p * 33.8639,
+            _ => p,
+        },
+    );
+    observation.wind_speed_ms = wind("windSpeed");
+    observation.wind_gust_ms = wind("windGust");
+    observation.wind_direction_deg = number("windDir");
+    observation.rain_rate_mm_h = value(
+        "rainRate",
+        &[
+            ("mm_per_hour", |r| r),
+            ("cm_per_hour", |r| r * 10.0),
+            ("inch_per_hour", |r| r * 25.4),
+        ],
+        |system, r| match system {
+            WEEWX_US => r * 25.4,
+            WEEWX_METRICWX => r,
+            _ => r * 10.0,
+        },
+    );
+    observation
+}
+
+/// Appends an MQTT string: a two-byte length and the UTF-8 bytes.
+fn mqtt_string(packet: &mut Vec<u8>, text: &str) {
+    packet.extend_from_slice(&(text.len() as u16).to_be_bytes());
+    packet.extend_from_slice(text.as_bytes());
+}
+
+/// Frames an MQTT control packet with its variable-length remaining length.
+fn mqtt_packet(header: u8, body: &[u8]) -> Vec<u8> {
+    let mut packet = vec![header];
+    let mut length = body.len();
+    loop {
+        let mut byte = (length % 128) as u8;
+        length /= 128;
+        if length > 0 {
+            byte |= 0x80;
+        }
+        packet.push(byte);
+        if length == 0 {
+            break;
+        }
+    }
+    packet.extend_from_slice(body);
+    packet
+}
+
+/// Reads one MQTT control packet and returns its header byte and body.
+async fn mqtt_read(stream: &mut TcpStream) -> std::io::Result<(u8, Vec<u8>)> {
+    let header = stream.read_u8().await?;
+    let (mut length, mut shift) = (0usize, 0);
+    loop {
+        let byte = stream.read_u8().await?;
+        length |= ((byte & 0x7f) as usize) << shift;
+        if byte & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+    }
+    let mut body = vec![0; length];
+    stream.read_exact(&mut body).await?;
+    Ok((header, body))
+}
+
+/// Subscribes to `topic` with MQTT 3.1.1 and returns the payload of the
+/// first message published to it.
+async fn mqtt_next(addr: &str, topic: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut stream = TcpStream::connect(addr).await?;
+
+    // CONNECT: protocol "MQTT" level 4, clean session, 60s keep-alive.
+    let mut connect = Vec::new();
+    mqtt_string(&mut connect, "MQTT");
+    connect.extend_from_slice(&[4, 0x02, 0, 60]);
+    mqtt_string(&mut connect, &format!("weather-cli-{}", std::process::id()));
+    stream.write_all(&mqtt_packet(0x10, &connect)).await?;
+    match mqtt_read(&mut stream).await? {
+        (0x20, body) if body.get(1) == Some(&0) => {}
+        (0x20, body) => {
+            return Err(format!(
+                "MQTT broker refused the connection (code {:?})",
+                body.get(1)
+            )
+            .into())
+        }
+        _ => return Err("unexpected reply from the MQTT broker".into()),
+    }
+
+    // SUBSCRIBE: packet id 1, QoS 0.
+    let mut subscribe = vec![0, 1];
+    mqtt_string(&mut subscribe, topic);
+    subscribe.push(0);
+    stream.write_all(&mqtt_packet(0x82, &subscribe)).await?;
+
+    loop {
+        let (header, body) = mqtt_read(&mut stream).await?;
+        let (3, [high, low, ..]) = (header >> 4, body.as_slice()) else {
+            continue;
+        };
+        let topic_length = u16::from_be_bytes([*high, *low]) as usize;
+        let qos = (header >> 1) & 0x03;
+        let start = 2 + topic_length + if qos > 0 { 2 } else { 0 };
+        return Ok(body.get(start..).unwrap_or_default().to_vec());
+    }
+}
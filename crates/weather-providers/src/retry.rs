@@ -0,0 +1,182 @@
+//! Retries of requests that failed for a passing reason: a timeout, a
+//! refused connection, or a 429 or 5xx answer. Only idempotent requests
+//! are retried, after a delay that doubles with each attempt, with jitter
+//! so that clients failing together do not retry together.
+//!
+//! Every request the provider modules and [`crate::WeatherClient`] send
+//! goes through [`send`] or [`execute`], which follow the policy set with
+//! [`set_policy`].
+
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Longest wait honoured from a `Retry-After` header; a server asking for
+/// longer gets no retry.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+static POLICY: OnceLock<Policy> = OnceLock::new();
+
+/// How transient failures are retried.
+#[derive(Debug, Clone, Copy)]
+pub struct Policy {
+    /// Retries after the first attempt; 0 disables retrying.
+    pub retries: u32,
+    /// Delay before the first retry, doubled for each one after.
+    pub base_delay: Duration,
+    /// Wait a random 50–100% of each delay.
+    pub jitter: bool,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Policy {
+            retries: 2,
+            base_delay: Duration::from_millis(500),
+            jitter: true,
+        }
+    }
+}
+
+impl Policy {
+    /// The wait before retry number `retry`, counting from 0.
+    fn delay(&self, retry: u32) -> Duration {
+        let delay = self.base_delay.saturating_mul(1 << retry.min(16));
+        if self.jitter {
+            delay.mul_f64(0.5 + fastrand::f64() / 2.0)
+        } else {
+            delay
+        }
+    }
+}
+
+/// Sets the policy for the rest of the process. Only the first call has
+/// an effect; without one, [`Policy::default`] applies.
+pub fn set_policy(policy: Policy) {
+    POLICY.set(policy).ok();
+}
+
+fn policy() -> &'static Policy {
+    POLICY.get_or_init(Policy::default)
+}
+
+/// Sends `request` like [`reqwest::RequestBuilder::send`], retrying
+/// transient failures.
+pub async fn send(request: reqwest::RequestBuilder) -> reqwest::Result<reqwest::Response> {
+    let (client, request) = request.build_split();
+    execute(&client, request?).await
+}
+
+/// Executes `request` with `client`, retrying transient failures.
+pub async fn execute(
+    client: &reqwest::Client,
+    request: reqwest::Request,
+) -> reqwest::Result<reqwest::Response> {
+    let policy = policy();
+    let idempotent = request.method().is_idempotent();
+    let mut retry = 0;
+    loop {
+        // A request with a streamed body cannot be sent twice.
+        let attempt = match request.try_clone() {
+            Some(attempt) if idempotent && retry < policy.retries => attempt,
+            _ => return client.execute(request).await,
+        };
+        let wait = match client.execute(attempt).await {
+            Ok(res) if is_transient(res.status()) => match retry_after(res.headers()) {
+                // Asked to back off longer than is worth waiting: give up.
+                Some(wait) if wait > MAX_RETRY_AFTER => return Ok(res),
+                Some(wait) => wait,
+                None => policy.delay(retry),
+            },
+            Ok(res) => return Ok(res),
+            Err(e) if e.is_timeout() || e.is_connect() => policy.delay(retry),
+            Err(e) => return Err(e),
+        };
+        tokio::time::sleep(wait).await;
+        retry += 1;
+    }
+}
+
+fn is_transient(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// The wait a `Retry-After` header in seconds asks for.
+fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let seconds = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+    use reqwest::StatusCode;
+
+    fn fixed(base_ms: u64) -> Policy {
+        Policy {
+            retries: 2,
+            base_delay: Duration::from_millis(base_ms),
+            jitter: false,
+        }
+    }
+
+    #[test]
+    fn delay_doubles_and_caps_the_exponent() {
+        let policy = fixed(500);
+        assert_eq!(policy.delay(0), Duration::from_millis(500));
+        assert_eq!(policy.delay(1), Duration::from_secs(1));
+        assert_eq!(policy.delay(3), Duration::from_secs(4));
+        assert_eq!(policy.delay(16), policy.delay(40));
+        assert_eq!(
+            Policy {
+                base_delay: Duration::MAX,
+                ..policy
+            }
+            .delay(3),
+            Duration::MAX
+        );
+    }
+
+    #[test]
+    fn jitter_waits_half_to_all_of_the_delay() {
+        let policy = Policy {
+            jitter: true,
+            ..fixed(1000)
+        };
+        for _ in 0..100 {
+            let wait = policy.delay(0);
+            assert!(wait >= Duration::from_millis(500) && wait <= Duration::from_secs(1));
+        }
+    }
+
+    #[test]
+    fn rate_limits_and_server_errors_are_transient() {
+        assert!(is_transient(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_transient(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(is_transient(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!is_transient(StatusCode::OK));
+        assert!(!is_transient(StatusCode::NOT_FOUND));
+        assert!(!is_transient(StatusCode::UNAUTHORIZED));
+    }
+
+    #[test]
+    fn retry_after_reads_seconds() {
+        let header = |value: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(RETRY_AFTER, HeaderValue::from_static(value));
+            headers
+        };
+        assert_eq!(retry_after(&header("5")), Some(Duration::from_secs(5)));
+        assert_eq!(retry_after(&header("600")), Some(Duration::from_secs(600)));
+        // HTTP dates are not honoured; the policy's delay applies.
+        assert_eq!(retry_after(&header("Wed, 21 Oct 2026 07:28:00 GMT")), None);
+        assert_eq!(retry_after(&header("-1")), None);
+        assert_eq!(retry_after(&HeaderMap::new()), None);
+    }
+}
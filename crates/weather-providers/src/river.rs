@@ -0,0 +1,355 @@
+//! River gauge levels and flood warnings for a configured station, from the
+//! USGS Water Services (US) or the Environment Agency flood monitoring API
+//! (England).
+
+use crate::alerts;
+use crate::error::WeatherError;
+use crate::retry;
+use serde::Deserialize;
+use std::str::FromStr;
+
+const USGS_IV_URL: &str = "https://waterservices.usgs.gov/nwis/iv/";
+const EA_BASE_URL: &str = "https://environment.data.gov.uk/flood-monitoring";
+
+/// USGS parameter code for gage height.
+const USGS_GAGE_HEIGHT: &str = "00065";
+/// Radius around an Environment Agency station searched for flood warnings.
+const EA_WARNING_RADIUS_KM: &str = "10";
+
+/// A river gauge to report on.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Station {
+    /// A USGS site number, e.g. `01646500`.
+    Usgs(String),
+    /// An Environment Agency station reference, e.g. `1491TH`.
+    EnvironmentAgency(String),
+}
+
+impl FromStr for Station {
+    type Err = String;
+
+    /// Accepts `usgs:<site>` or `ea:<station>`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.split_once(':') {
+            Some(("usgs", site)) if !site.is_empty() => Ok(Station::Usgs(site.to_string())),
+            Some(("ea", id)) if !id.is_empty() => Ok(Station::EnvironmentAgency(id.to_string())),
+            _ => Err(format!(
+                "Unknown river station '{}': expected usgs:<site> or ea:<station>",
+                value
+            )),
+        }
+    }
+}
+
+/// Latest level at a gauge plus any flood warnings in force nearby.
+#[derive(Debug)]
+pub struct RiverReport {
+    pub station: String,
+    pub river: Option<String>,
+    /// Most recent stage reading.
+    pub level: Option<f64>,
+    pub unit: String,
+    /// Unix timestamp of the latest reading.
+    pub observed_at: Option<i64>,
+    /// Change in level over the readings fetched (about two hours).
+    pub trend: Option<f64>,
+    /// Top of the usual range, when the provider publishes one.
+    pub typical_high: Option<f64>,
+    pub warnings: Vec<FloodWarning>,
+}
+
+#[derive(Debug)]
+pub struct FloodWarning {
+    /// e.g. "Flood Warning" or "Flood Watch".
+    pub severity: String,
+    pub area: String,
+}
+
+pub async fn report(
+    client: &reqwest::Client,
+    station: &Station,
+) -> Result<RiverReport, Box<dyn std::error::Error>> {
+    match station {
+        Station::Usgs(site) => usgs_report(client, site).await,
+        Station::EnvironmentAgency(id) => ea_report(client, id).await,
+    }
+}
+
+/// Change between the oldest and the newest of `readings`.
+fn trend(readings: &[(i64, f64)]) -> Option<f64> {
+    let oldest = readings.iter().min_by_key(|r| r.0)?;
+    let newest = readings.iter().max_by_key(|r| r.0)?;
+    (newest.0 > oldest.0).then_some(newest.1 - oldest.1)
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct UsgsResponse {
+    value: UsgsValue,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UsgsValue {
+    time_series: Vec<UsgsTimeSeries>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UsgsTimeSeries {
+    source_info: UsgsSourceInfo,
+    variable: UsgsVariable,
+    values: Vec<UsgsValues>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UsgsSourceInfo {
+    site_name: String,
+    geo_location: UsgsGeoLocation,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UsgsGeoLocation {
+    geog_location: UsgsCoordinates,
+}
+
+#[derive(Debug, Deserialize)]
+struct UsgsCoordinates {
+    latitude: f64,
+    longitude: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UsgsVariable {
+    unit: UsgsUnit,
+    no_data_value: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UsgsUnit {
+    unit_code: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct UsgsValues {
+    value: Vec<UsgsReading>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UsgsReading {
+    value: String,
+    date_time: chrono::DateTime<chrono::FixedOffset>,
+}
+
+async fn usgs_report(
+    client: &reqwest::Client,
+    site: &str,
+) -> Result<RiverReport, Box<dyn std::error::Error>> {
+    let res = retry::send(usgs_request(client, site)).await?;
+    if !res.status().is_success() {
+        return Err(WeatherError::status("USGS API", res.status()).into());
+    }
+    let series = res
+        .json::<UsgsResponse>()
+        .await?
+        .value
+        .time_series
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("USGS site {} reports no gage height", site))?;
+
+    let no_data = series.variable.no_data_value;
+    let readings: Vec<(i64, f64)> = series
+        .values
+        .iter()
+        .flat_map(|v| &v.value)
+        .filter_map(|r| Some((r.date_time.timestamp(), r.value.parse::<f64>().ok()?)))
+        .filter(|(_, value)| Some(*value) != no_data)
+        .collect();
+    let latest = readings.iter().max_by_key(|r| r.0);
+
+    let coordinates = &series.source_info.geo_location.geog_location;
+    let warnings = nws_flood_warnings(client, coordinates.latitude, coordinates.longitude)
+        .await
+        .unwrap_or_default();
+
+    Ok(RiverReport {
+        station: series.source_info.site_name,
+        river: None,
+        level: latest.map(|r| r.1),
+        unit: series.variable.unit.unit_code,
+        observed_at: latest.map(|r| r.0),
+        trend: trend(&readings),
+        typical_high: None,
+        warnings,
+    })
+}
+
+/// Recent gage heights at a USGS site.
+pub(crate) fn usgs_request(client: &reqwest::Client, site: &str) -> reqwest::RequestBuilder {
+    client.get(USGS_IV_URL).query(&[
+        ("format", "json"),
+        ("sites", site),
+        ("parameterCd", USGS_GAGE_HEIGHT),
+        ("period", "PT2H"),
+        ("siteStatus", "all"),
+    ])
+}
+
+/// Active National Weather Service flood products covering a point.
+async fn nws_flood_warnings(
+    client: &reqwest::Client,
+    lat: f64,
+    lon: f64,
+) -> Result<Vec<FloodWarning>, Box<dyn std::error::Error>> {
+    Ok(alerts::nws(client, lat, lon)
+        .await?
+        .into_iter()
+        .filter(|alert| alert.event.contains("Flood") && alert.covers(lat, lon))
+        .map(|alert| FloodWarning {
+            severity: alert.event,
+            area: alert.area,
+        })
+        .collect())
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct EaStationResponse {
+    items: EaStation,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EaStation {
+    label: String,
+    river_name: Option<String>,
+    // A few stations list several positions, and the stage scale is only
+    // embedded in some responses (otherwise it's a link), so these are
+    // picked apart loosely.
+    #[serde(default)]
+    lat: serde_json::Value,
+    #[serde(default)]
+    long: serde_json::Value,
+    #[serde(default)]
+    stage_scale: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct EaReadings {
+    items: Vec<EaReading>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EaReading {
+    date_time: chrono::DateTime<chrono::Utc>,
+    value: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct EaFloods {
+    items: Vec<EaFlood>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EaFlood {
+    description: String,
+    severity: String,
+    /// 1 (severe) to 4 (no longer in force).
+    severity_level: u8,
+}
+
+async fn ea_report(
+    client: &reqwest::Client,
+    id: &str,
+) -> Result<RiverReport, Box<dyn std::error::Error>> {
+    let res = retry::send(ea_station_request(client, id)).await?;
+    if res.status().as_u16() == 404 {
+        return Err(format!("Unknown Environment Agency station '{}'", id).into());
+    }
+    if !res.status().is_success() {
+        return Err(WeatherError::status("Environment Agency API", res.status()).into());
+    }
+    let station = res.json::<EaStationResponse>().await?.items;
+
+    let res = retry::send(ea_readings_request(client, id)).await?;
+    if !res.status().is_success() {
+        return Err(WeatherError::status("Environment Agency API", res.status()).into());
+    }
+    let readings: Vec<(i64, f64)> = res
+        .json::<EaReadings>()
+        .await?
+        .items
+        .iter()
+        .map(|r| (r.date_time.timestamp(), r.value))
+        .collect();
+    let latest = readings.iter().max_by_key(|r| r.0);
+
+    let warnings = match (station.lat.as_f64(), station.long.as_f64()) {
+        (Some(lat), Some(long)) => ea_flood_warnings(client, lat, long)
+            .await
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    };
+
+    Ok(RiverReport {
+        station: station.label,
+        river: station.river_name,
+        level: latest.map(|r| r.1),
+        unit: "m".to_string(),
+        observed_at: latest.map(|r| r.0),
+        trend: trend(&readings),
+        typical_high: station.stage_scale["typicalRangeHigh"].as_f64(),
+        warnings,
+    })
+}
+
+pub(crate) fn ea_station_request(client: &reqwest::Client, id: &str) -> reqwest::RequestBuilder {
+    client.get(format!("{}/id/stations/{}", EA_BASE_URL, id))
+}
+
+pub(crate) fn ea_readings_request(client: &reqwest::Client, id: &str) -> reqwest::RequestBuilder {
+    // Level readings arrive every 15 minutes; eight cover about two hours.
+    client
+        .get(format!("{}/id/stations/{}/readings", EA_BASE_URL, id))
+        .query(&[("parameter", "level"), ("_sorted", ""), ("_limit", "8")])
+}
+
+async fn ea_flood_warnings(
+    client: &reqwest::Client,
+    lat: f64,
+    long: f64,
+) -> Result<Vec<FloodWarning>, Box<dyn std::error::Error>> {
+    let res = retry::send(ea_floods_request(client, lat, long)).await?;
+    if !res.status().is_success() {
+        return Err(WeatherError::status("Environment Agency floods", res.status()).into());
+    }
+    Ok(res
+        .json::<EaFloods>()
+        .await?
+        .items
+        .into_iter()
+        .filter(|flood| flood.severity_level < 4)
+        .map(|flood| FloodWarning {
+            severity: flood.severity,
+            area: flood.description,
+        })
+        .collect())
+}
+
+pub(crate) fn ea_floods_request(
+    client: &reqwest::Client,
+    lat: f64,
+    long: f64,
+) -> reqwest::RequestBuilder {
+    client.get(format!("{}/id/floods", EA_BASE_URL)).query(&[
+        ("lat", lat.to_string().as_str()),
+        ("long", long.to_string().as_str()),
+        ("dist", EA_WARNING_RADIUS_KM),
+    ])
+}
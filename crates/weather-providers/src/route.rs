@@ -0,0 +1,88 @@
+//! Open-Meteo's hourly forecast at points along a route for `weather gpx`,
+//! each point at its own elevation where the route gives one. Keyless,
+//! like the rest of Open-Meteo.
+
+use crate::error::{self, WeatherError};
+use crate::{openmeteo, retry};
+use serde::Deserialize;
+
+const HOURLY: &str = "temperature_2m,precipitation_probability,precipitation,weather_code,\
+    wind_speed_10m,wind_direction_10m,wind_gusts_10m";
+
+/// The most points one request takes.
+pub const MAX_POINTS: usize = 100;
+
+/// The most days the forecast reaches, today included.
+pub const MAX_DAYS: u32 = 16;
+
+/// A point to forecast for, with its elevation in metres when known.
+#[derive(Debug, Clone, Copy)]
+pub struct Point {
+    pub lat: f64,
+    pub lon: f64,
+    pub elevation: Option<f64>,
+}
+
+/// The forecast at one point. Times are Unix timestamps.
+#[derive(Debug, Deserialize)]
+pub struct PointForecast {
+    /// The elevation forecast for, in metres.
+    pub elevation: f64,
+    pub utc_offset_seconds: i32,
+    pub hourly: PointHourly,
+}
+
+/// Temperatures in °C, precipitation in mm, its probability in percent,
+/// wind in m/s and degrees it blows from.
+#[derive(Debug, Deserialize)]
+pub struct PointHourly {
+    pub time: Vec<i64>,
+    pub temperature_2m: Vec<Option<f64>>,
+    pub precipitation_probability: Vec<Option<f64>>,
+    pub precipitation: Vec<Option<f64>>,
+    pub weather_code: Vec<Option<i32>>,
+    pub wind_speed_10m: Vec<Option<f64>>,
+    pub wind_direction_10m: Vec<Option<f64>>,
+    pub wind_gusts_10m: Vec<Option<f64>>,
+}
+
+/// The forecast at each of `points`, at most [`MAX_POINTS`] of them, in
+/// order, for the next `days` days, today included.
+pub async fn forecast(
+    client: &reqwest::Client,
+    points: &[Point],
+    days: u32,
+) -> Result<Vec<PointForecast>, WeatherError> {
+    let res = retry::send(forecast_request(client, points, days)).await?;
+    let res = error::check("Open-Meteo", res)?;
+    Ok(res
+        .json::<openmeteo::OneOrMany<PointForecast>>()
+        .await?
+        .into_vec())
+}
+
+/// The request [`forecast`] sends. Unless every point has an elevation,
+/// each is forecast at the terrain elevation there.
+pub(crate) fn forecast_request(
+    client: &reqwest::Client,
+    points: &[Point],
+    days: u32,
+) -> reqwest::RequestBuilder {
+    let join =
+        |value: &dyn Fn(&Point) -> String| points.iter().map(value).collect::<Vec<_>>().join(",");
+    let request = client.get(openmeteo::FORECAST_URL).query(&[
+        ("latitude", join(&|point| point.lat.to_string())),
+        ("longitude", join(&|point| point.lon.to_string())),
+        ("hourly", HOURLY.to_string()),
+        ("wind_speed_unit", "ms".to_string()),
+        ("forecast_days", days.to_string()),
+        ("timeformat", "unixtime".to_string()),
+        ("timezone", "auto".to_string()),
+    ]);
+    if points.iter().all(|point| point.elevation.is_some()) {
+        let elevation = |point: &Point| format!("{:.0}", point.elevation.unwrap_or_default());
+        request.query(&[("elevation", join(&elevation))])
+    } else {
+        request
+    }
+}
@@ -0,0 +1,274 @@
+//! Tide predictions for `weather tides`: NOAA CO-OPS for US coasts, WorldTides
+//! everywhere else (requires `WORLDTIDES_API_KEY`).
+
+use crate::error::WeatherError;
+use crate::geocode::Place;
+use crate::retry;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::env;
+use weather_core::geo;
+
+const NOAA_STATIONS_URL: &str =
+    "https://api.tidesandcurrents.noaa.gov/mdapi/prod/webapi/stations.json";
+const NOAA_PREDICTIONS_URL: &str = "https://api.tidesandcurrents.noaa.gov/api/prod/datagetter";
+const WORLDTIDES_URL: &str = "https://www.worldtides.info/api/v3";
+
+/// NOAA stations further away than this are not representative of the coast
+/// being asked about.
+const MAX_STATION_DISTANCE_M: f64 = 100_000.0;
+/// How far ahead to predict.
+const RANGE_HOURS: i64 = 48;
+
+/// A predicted high or low water.
+#[derive(Debug, Serialize)]
+pub struct Tide {
+    /// Unix timestamp.
+    pub time: i64,
+    /// Height in metres above the datum.
+    pub height: f64,
+    pub high: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TidePrediction {
+    pub provider: &'static str,
+    pub station: String,
+    /// Distance from the requested location to the station, when known.
+    pub distance_m: Option<f64>,
+    /// Reference level the heights are measured from, e.g. MLLW.
+    pub datum: String,
+    pub tides: Vec<Tide>,
+}
+
+/// Predicts the high and low tides near `place` for the next two days.
+pub async fn predict(
+    client: &reqwest::Client,
+    place: &Place,
+    now: DateTime<Utc>,
+) -> Result<TidePrediction, Box<dyn std::error::Error>> {
+    let worldtides_key = env::var("WORLDTIDES_API_KEY").ok();
+
+    if place.country == "US" {
+        match noaa_prediction(client, place, now).await {
+            Ok(prediction) => return Ok(prediction),
+            // Alaska and Pacific territories can be far from a CO-OPS
+            // station; WorldTides covers them if a key is configured.
+            Err(e) if worldtides_key.is_none() => return Err(e),
+            Err(_) => {}
+        }
+    }
+
+    match worldtides_key {
+        Some(key) => worldtides_prediction(client, place, now, &key).await,
+        None => Err(
+            "Tide predictions outside the US need a WorldTides key: set \
+             WORLDTIDES_API_KEY (see https://www.worldtides.info/developer)"
+                .into(),
+        ),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct NoaaStations {
+    stations: Vec<NoaaStation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NoaaStation {
+    id: String,
+    name: String,
+    lat: f64,
+    lng: f64,
+    state: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct NoaaPredictions {
+    predictions: Option<Vec<NoaaPrediction>>,
+    error: Option<NoaaError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NoaaPrediction {
+    /// "YYYY-MM-DD HH:MM" in GMT, as requested.
+    t: String,
+    v: String,
+    /// "H" or "L".
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct NoaaError {
+    message: String,
+}
+
+async fn noaa_prediction(
+    client: &reqwest::Client,
+    place: &Place,
+    now: DateTime<Utc>,
+) -> Result<TidePrediction, Box<dyn std::error::Error>> {
+    let res = retry::send(noaa_stations_request(client)).await?;
+    if !res.status().is_success() {
+        return Err(WeatherError::status("NOAA station list", res.status()).into());
+    }
+    let stations = res.json::<NoaaStations>().await?.stations;
+
+    let (station, distance) = stations
+        .iter()
+        .map(|s| (s, geo::distance_m(place.lat, place.lon, s.lat, s.lng)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .filter(|(_, distance)| *distance <= MAX_STATION_DISTANCE_M)
+        .ok_or_else(|| {
+            format!(
+                "No NOAA tide station within {} km of {}",
+                MAX_STATION_DISTANCE_M / 1000.0,
+                place.name
+            )
+        })?;
+
+    let res = retry::send(noaa_predictions_request(client, &station.id, now)).await?;
+    if !res.status().is_success() {
+        return Err(WeatherError::status("NOAA predictions", res.status()).into());
+    }
+    let body = res.json::<NoaaPredictions>().await?;
+    if let Some(error) = body.error {
+        return Err(format!("NOAA predictions error: {}", error.message).into());
+    }
+
+    let mut tides = Vec::new();
+    for prediction in body.predictions.unwrap_or_default() {
+        let time = NaiveDateTime::parse_from_str(&prediction.t, "%Y-%m-%d %H:%M")?
+            .and_utc()
+            .timestamp();
+        tides.push(Tide {
+            time,
+            height: prediction.v.trim().parse()?,
+            high: prediction.kind == "H",
+        });
+    }
+
+    Ok(TidePrediction {
+        provider: "NOAA CO-OPS",
+        station: match &station.state {
+            Some(state) if !state.is_empty() => format!("{}, {}", station.name, state),
+            _ => station.name.clone(),
+        },
+        distance_m: Some(distance),
+        datum: "MLLW".to_string(),
+        tides,
+    })
+}
+
+pub(crate) fn noaa_stations_request(client: &reqwest::Client) -> reqwest::RequestBuilder {
+    client
+        .get(NOAA_STATIONS_URL)
+        .query(&[("type", "tidepredictions")])
+}
+
+/// High and low waters at `station` over the next [`RANGE_HOURS`].
+pub(crate) fn noaa_predictions_request(
+    client: &reqwest::Client,
+    station: &str,
+    now: DateTime<Utc>,
+) -> reqwest::RequestBuilder {
+    let begin = now.format("%Y%m%d %H:%M").to_string();
+    let range = RANGE_HOURS.to_string();
+    client.get(NOAA_PREDICTIONS_URL).query(&[
+        ("product", "predictions"),
+        ("station", station),
+        ("begin_date", begin.as_str()),
+        ("range", range.as_str()),
+        ("datum", "MLLW"),
+        ("interval", "hilo"),
+        ("units", "metric"),
+        ("time_zone", "gmt"),
+        ("format", "json"),
+        ("application", "weather-cli"),
+    ])
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorldTidesResponse {
+    status: u16,
+    error: Option<String>,
+    station: Option<String>,
+    atlas: Option<String>,
+    response_lat: Option<f64>,
+    response_lon: Option<f64>,
+    response_datum: Option<String>,
+    #[serde(default)]
+    extremes: Vec<WorldTidesExtreme>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WorldTidesExtreme {
+    dt: i64,
+    height: f64,
+    /// "High" or "Low".
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+async fn worldtides_prediction(
+    client: &reqwest::Client,
+    place: &Place,
+    now: DateTime<Utc>,
+    key: &str,
+) -> Result<TidePrediction, Box<dyn std::error::Error>> {
+    let res = retry::send(worldtides_request(client, place.lat, place.lon, now, key)).await?;
+    let body = res.json::<WorldTidesResponse>().await?;
+    if body.status != 200 {
+        return Err(format!(
+            "WorldTides error: {}",
+            body.error
+                .unwrap_or_else(|| format!("HTTP {}", body.status))
+        )
+        .into());
+    }
+
+    let distance_m = match (body.response_lat, body.response_lon) {
+        (Some(lat), Some(lon)) => Some(geo::distance_m(place.lat, place.lon, lat, lon)),
+        _ => None,
+    };
+    Ok(TidePrediction {
+        provider: "WorldTides",
+        station: body
+            .station
+            .or(body.atlas.map(|atlas| format!("{} model", atlas)))
+            .unwrap_or_else(|| place.name.clone()),
+        distance_m,
+        datum: body.response_datum.unwrap_or_else(|| "LAT".to_string()),
+        tides: body
+            .extremes
+            .into_iter()
+            .map(|e| Tide {
+                time: e.dt,
+                height: e.height,
+                high: e.kind == "High",
+            })
+            .collect(),
+    })
+}
+
+pub(crate) fn worldtides_request(
+    client: &reqwest::Client,
+    lat: f64,
+    lon: f64,
+    now: DateTime<Utc>,
+    key: &str,
+) -> reqwest::RequestBuilder {
+    let start = now.timestamp().to_string();
+    let length = (RANGE_HOURS * 3600).to_string();
+    client.get(WORLDTIDES_URL).query(&[
+        ("extremes", ""),
+        ("lat", lat.to_string().as_str()),
+        ("lon", lon.to_string().as_str()),
+        ("start", start.as_str()),
+        ("length", length.as_str()),
+        ("datum", "LAT"),
+        ("key", key),
+    ])
+}
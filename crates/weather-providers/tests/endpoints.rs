@@ -0,0 +1,130 @@
+//! Provider response shapes, one test per endpoint: against the recorded
+//! responses in `tests/cassettes` on every run, and against the live APIs
+//! with `cargo test -p weather-providers --features live-tests`. Live
+//! endpoints whose credentials are not in the environment are skipped; set
+//! `RECORD_CASSETTES=1` on a live run to re-record the responses received.
+
+use std::path::PathBuf;
+use weather_providers::endpoints::Endpoint;
+
+fn cassette(endpoint: Endpoint) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/cassettes")
+        .join(format!("{}.json", endpoint.name()))
+}
+
+fn replay(endpoint: Endpoint) {
+    let path = cassette(endpoint);
+    let body = std::fs::read(&path)
+        .unwrap_or_else(|e| panic!("No recorded response {}: {}", path.display(), e));
+    if let Err(e) = endpoint.check(&body) {
+        panic!("{} no longer matches: {}", path.display(), e);
+    }
+}
+
+#[cfg(feature = "live-tests")]
+async fn live(endpoint: Endpoint) {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .build()
+        .unwrap();
+    let request = match endpoint.probe(&client) {
+        Ok(request) => request,
+        Err(missing) => {
+            eprintln!("skipping live {}: {}", endpoint.name(), missing);
+            return;
+        }
+    };
+    let response = request.send().await.unwrap();
+    let status = response.status();
+    let body = response.bytes().await.unwrap();
+    assert!(
+        status.is_success(),
+        "{}: HTTP {}: {}",
+        endpoint.name(),
+        status,
+        String::from_utf8_lossy(&body)
+    );
+    if let Err(e) = endpoint.check(&body) {
+        panic!("{} changed shape: {}", endpoint.name(), e);
+    }
+    if std::env::var_os("RECORD_CASSETTES").is_some() {
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        std::fs::write(
+            cassette(endpoint),
+            serde_json::to_string_pretty(&json).unwrap() + "\n",
+        )
+        .unwrap();
+    }
+}
+
+/// Generates the replay and live test of each endpoint. Adding an
+/// [`Endpoint`] without listing it here fails `matrix_covers_every_endpoint`.
+macro_rules! matrix {
+    ($($test:ident => $endpoint:ident,)*) => {
+        const MATRIX: &[Endpoint] = &[$(Endpoint::$endpoint),*];
+
+        mod replay {
+            use super::*;
+            $(
+                #[test]
+                fn $test() {
+                    replay(Endpoint::$endpoint);
+                }
+            )*
+        }
+
+        #[cfg(feature = "live-tests")]
+        mod live {
+            use super::*;
+            $(
+                #[tokio::test]
+                async fn $test() {
+                    live(Endpoint::$endpoint).await;
+                }
+            )*
+        }
+    };
+}
+
+matrix! {
+    openweathermap_current => OpenWeatherMapCurrent,
+    openweathermap_forecast => OpenWeatherMapForecast,
+    openweathermap_onecall => OpenWeatherMapOneCall,
+    openweathermap_alerts => OpenWeatherMapAlerts,
+    openweathermap_air_pollution => OpenWeatherMapAirPollution,
+    geocode_direct => GeocodeDirect,
+    geocode_reverse => GeocodeReverse,
+    openmeteo_archive => OpenMeteoArchive,
+    openmeteo_current => OpenMeteoCurrent,
+    openmeteo_search => OpenMeteoSearch,
+    openmeteo_solar => OpenMeteoSolar,
+    openmeteo_marine => OpenMeteoMarine,
+    openmeteo_marine_wind => OpenMeteoMarineWind,
+    openmeteo_elevation => OpenMeteoElevation,
+    openmeteo_mountain => OpenMeteoMountain,
+    openmeteo_route => OpenMeteoRoute,
+    usgs_levels => UsgsLevels,
+    nws_alerts => NwsAlerts,
+    meteoalarm_warnings => MeteoAlarmWarnings,
+    ea_station => EaStation,
+    ea_readings => EaReadings,
+    ea_floods => EaFloods,
+    noaa_stations => NoaaStations,
+    noaa_predictions => NoaaPredictions,
+    worldtides => WorldTides,
+    netatmo_stations => NetatmoStations,
+    ambient_devices => AmbientDevices,
+    ipapi_location => IpApiLocation,
+}
+
+#[test]
+fn matrix_covers_every_endpoint() {
+    for endpoint in Endpoint::ALL {
+        assert!(
+            MATRIX.contains(&endpoint),
+            "{:?} has no tests in the matrix",
+            endpoint
+        );
+    }
+}